@@ -1,3 +1,19 @@
+from dimble_rs.dimble_rs import (
+    CorruptFieldError,
+    CorruptFileError,
+    DimbleError,
+    DictionaryWarning,
+    DimbleSequence,
+    InvalidPathError,
+    MissingFieldError,
+    PersonName,
+    PersonNameComponents,
+    PixelArray,
+    UnsupportedDicomError,
+    UnsupportedValueError,
+    UnsupportedVersionError,
+)
+
 from .dimble import (
     _create_temp_dir,
     dicom_to_dimble,
@@ -5,7 +21,9 @@ from .dimble import (
     dimble_to_nifti,
     load_dimble,
     nifti_to_dimble,
+    pixel_array_info,
     rglob_dicom,
+    verify,
 )
 
 rglob_dicom
@@ -16,6 +34,21 @@ __all__ = [
     "load_dimble",
     "nifti_to_dimble",
     "dimble_to_nifti",
+    "pixel_array_info",
     "_create_temp_dir",
     "rglob_dicom",
+    "verify",
+    "PersonName",
+    "PersonNameComponents",
+    "PixelArray",
+    "DimbleSequence",
+    "DimbleError",
+    "DictionaryWarning",
+    "CorruptFileError",
+    "CorruptFieldError",
+    "InvalidPathError",
+    "MissingFieldError",
+    "UnsupportedDicomError",
+    "UnsupportedValueError",
+    "UnsupportedVersionError",
 ]
@@ -8,6 +8,12 @@ import SimpleITK as sitk
 from dimble_rs import dimble_rs
 from safetensors.numpy import save_file
 
+# NIfTI header keys such as "dim[0]" are not DICOM tags, so they are kept together as JSON in a
+# private element
+NIFTI_PRIVATE_CREATOR_TAG = "00090010"
+NIFTI_PRIVATE_CREATOR = "DIMBLE NIFTI"
+NIFTI_METADATA_TAG = "00091001"
+
 
 def _create_temp_dir() -> Path:
     temp_dir = Path(tempfile.gettempdir())
@@ -15,9 +21,7 @@ def _create_temp_dir() -> Path:
     return temp_dir
 
 
-def _dicom_to_ir(
-    dicom_path: Path, output_name: str, dtype=np.float32
-) -> dict[str, Path]:
+def _dicom_to_ir(dicom_path: Path, output_name: str, dtype=None) -> dict[str, Path]:
     ds = pydicom.dcmread(dicom_path)
     output_json = Path(output_name + ".json")
     output_pixel_array = Path(output_name + ".safetensors")
@@ -25,23 +29,28 @@ def _dicom_to_ir(
         ds_json_dict = ds.to_json_dict()
         json.dump(ds_json_dict, f, sort_keys=True, indent=4)
 
-    pixel_array = ds.pixel_array.astype(dtype)
-    save_file({"pixel_array": pixel_array}, output_pixel_array)
+    # the stored values in their own dtype, rescale and windowing are applied on load
+    pixel_array = ds.pixel_array if dtype is None else ds.pixel_array.astype(dtype)
+    # like pixel_array, only pixel data of more than one frame has a frame axis
+    frames = int(ds.get("NumberOfFrames", 1) or 1)
+    metadata = {"frames": str(frames)} if frames > 1 else None
+    save_file({"pixel_array": pixel_array}, output_pixel_array, metadata=metadata)
     return {"json": output_json, "pixel_array": output_pixel_array}
 
 
-def _nifti_to_ir(
-    image_path: Path, output_name: str, dtype=np.float32
-) -> dict[str, Path]:
+def _nifti_to_ir(image_path: Path, output_name: str, dtype=None) -> dict[str, Path]:
     # code adapted from https://stackoverflow.com/a/64012212
     itk_image = sitk.ReadImage(image_path)
+    metadata = {k: itk_image.GetMetaData(k) for k in itk_image.GetMetaDataKeys()}
     ds_json_dict = {}
-    for k in itk_image.GetMetaDataKeys():
-        entry = {
-            "vr": "CS",
-            "Value": [itk_image.GetMetaData(k)],
-        }
-        ds_json_dict[k] = entry
+    ds_json_dict[NIFTI_PRIVATE_CREATOR_TAG] = {
+        "vr": "LO",
+        "Value": [NIFTI_PRIVATE_CREATOR],
+    }
+    ds_json_dict[NIFTI_METADATA_TAG] = {
+        "vr": "UT",
+        "Value": [json.dumps(metadata, sort_keys=True)],
+    }
     ds_json_dict["7FE00010"] = {
         "vr": "OW",
         "Value": None,
@@ -52,87 +61,157 @@ def _nifti_to_ir(
     with open(output_json, "w") as f:
         json.dump(ds_json_dict, f, sort_keys=True, indent=4)
 
-    pixel_array = sitk.GetArrayFromImage(itk_image).astype(dtype)
+    pixel_array = sitk.GetArrayFromImage(itk_image)
+    if dtype is not None:
+        pixel_array = pixel_array.astype(dtype)
     save_file({"pixel_array": pixel_array}, output_pixel_array)
     return {"json": output_json, "pixel_array": output_pixel_array}
 
 
-def _ir_to_dimble(json_path: Path, pixel_path: Path, output_path: Path) -> None:
-    dimble_rs.dicom_json_to_dimble(str(json_path), str(output_path), str(pixel_path))
-
-
-def _dimble_to_ir(dimble_path: Path, output_path: Path) -> None:
-    dimble_rs.dimble_to_dicom_json(str(dimble_path), str(output_path))
+def _ir_to_dimble(
+    json_path: Path,
+    pixel_path: Path,
+    output_path: Path,
+    chunk_shape=None,
+    codec=None,
+    pixel_codec=None,
+    pixel_alignment=4096,
+) -> None:
+    dimble_rs.dicom_json_to_dimble(
+        str(json_path),
+        str(output_path),
+        str(pixel_path),
+        chunk_shape,
+        codec,
+        pixel_codec,
+        pixel_alignment,
+    )
 
 
-def dicom_to_dimble(dicom_path: Path, output_path: Path, dtype=np.float32) -> None:
+def dicom_to_dimble(
+    dicom_path: Path,
+    output_path: Path,
+    dtype=None,
+    chunk_shape=None,
+    codec=None,
+    pixel_codec=None,
+    keep_encapsulated=False,
+    pixel_alignment=4096,
+) -> None:
     dicom_path = Path(dicom_path)
+    dtype_name = None if dtype is None else np.dtype(dtype).name
+    try:
+        dimble_rs.dicom_to_dimble(
+            str(dicom_path),
+            str(output_path),
+            dtype_name,
+            chunk_shape,
+            codec,
+            pixel_codec,
+            keep_encapsulated,
+            pixel_alignment,
+        )
+        return
+    except dimble_rs.UnsupportedDicomError:
+        # compressed pixel data still needs pydicom and its image handlers
+        pass
     ir_paths = _dicom_to_ir(
         dicom_path, str(_create_temp_dir() / (dicom_path.stem + ".ir")), dtype=dtype
     )
     try:
-        _ir_to_dimble(ir_paths["json"], ir_paths["pixel_array"], output_path)
+        _ir_to_dimble(
+            ir_paths["json"],
+            ir_paths["pixel_array"],
+            output_path,
+            chunk_shape,
+            codec,
+            pixel_codec,
+            pixel_alignment,
+        )
     finally:
         for path in ir_paths.values():
             path.unlink(missing_ok=True)
 
 
-def nifti_to_dimble(image_path: Path, output_path: Path, dtype=np.float32) -> None:
+def nifti_to_dimble(
+    image_path: Path,
+    output_path: Path,
+    dtype=None,
+    chunk_shape=None,
+    codec=None,
+    pixel_codec=None,
+    pixel_alignment=4096,
+) -> None:
     image_path = Path(image_path)
     ir_paths = _nifti_to_ir(
         image_path, str(_create_temp_dir() / (image_path.stem + ".ir")), dtype=dtype
     )
     try:
-        _ir_to_dimble(ir_paths["json"], ir_paths["pixel_array"], output_path)
+        _ir_to_dimble(
+            ir_paths["json"],
+            ir_paths["pixel_array"],
+            output_path,
+            chunk_shape,
+            codec,
+            pixel_codec,
+            pixel_alignment,
+        )
     finally:
         for path in ir_paths.values():
             path.unlink(missing_ok=True)
 
 
-def load_dimble(path: Path, fields: list[str], device="cpu", slices=None):
-    return dimble_rs.load_dimble(str(path), fields, device, slices)
+def load_dimble(
+    path: Path,
+    fields: list[str],
+    device="cpu",
+    slices=None,
+    keywords=False,
+    typed=False,
+    backend="torch",
+    dtype_fallback="widen",
+    values="stored",
+    frames=None,
+):
+    return dimble_rs.load_dimble(
+        str(path),
+        fields,
+        device,
+        slices,
+        keywords,
+        typed,
+        backend,
+        dtype_fallback,
+        values,
+        frames,
+    )
+
 
+def pixel_array_info(path: Path) -> dict:
+    return dimble_rs.pixel_array_info(str(path))
 
-def dimble_to_dicom(dimble_path: Path, output_path: Path) -> None:
-    dimble_path = Path(dimble_path)
-    ir_path = _create_temp_dir() / (dimble_path.stem + ".ir.json")
-    dimble_ds = load_dimble(dimble_path, ["7FE00010"])
 
-    pixel_data = dimble_ds["7FE00010"].numpy()
-    from pydicom.uid import RLELossless
+def verify(path: Path) -> list[str]:
+    return dimble_rs.verify(str(path))
 
-    try:
-        _dimble_to_ir(dimble_path, ir_path)
-        with open(ir_path) as f:
-            ds: pydicom.Dataset = pydicom.Dataset.from_json(f.read())
-        # TODO this code makes lots of unvalidated assumptions that should not be made.
-        ds.BitsAllocated = 16
-        ds.PixelRepresentation = 0
-        ds.compress(RLELossless, pixel_data.astype(np.uint16))
-        ds.save_as(output_path, write_like_original=False)
-    finally:
-        ir_path.unlink(missing_ok=True)
+
+def dimble_to_dicom(dimble_path: Path, output_path: Path) -> None:
+    dimble_rs.dimble_to_dicom(str(dimble_path), str(output_path))
 
 
 def dimble_to_nifti(dimble_path: Path, output_path: Path) -> None:
-    dimble_path = Path(dimble_path)
-    ir_path = _create_temp_dir() / (dimble_path.stem + ".ir.json")
-    dimble_ds = load_dimble(dimble_path, ["7FE00010"])
+    pixel_data = load_dimble(dimble_path, ["7FE00010"], backend="numpy")["7FE00010"]
 
-    pixel_data = dimble_ds["7FE00010"].numpy()
+    # files written before the header keys became tags keep the NIfTI header keys as they are
+    metadata = dimble_rs.load_legacy_fields(str(dimble_path))
+    if not metadata:
+        metadata_json = load_dimble(dimble_path, [NIFTI_METADATA_TAG])[NIFTI_METADATA_TAG]
+        metadata = json.loads(metadata_json)
 
     itk_image = sitk.GetImageFromArray(pixel_data)
-
-    try:
-        _dimble_to_ir(dimble_path, ir_path)
-        with open(ir_path) as f:
-            json_ds = json.load(f)
-            for k in json_ds:
-                if k != "7FE00010":
-                    itk_image.SetMetaData(k, json_ds[k]["Value"][0])
-        sitk.WriteImage(itk_image, output_path)
-    finally:
-        ir_path.unlink(missing_ok=True)
+    for k, v in metadata.items():
+        itk_image.SetMetaData(k, str(v))
+    sitk.WriteImage(itk_image, output_path)
 
 def rglob_dicom(path: Path) -> list[Path]:
     dicom_extensions = [".dcm", ".dicom", ".DCM", ".DICOM"]
@@ -0,0 +1,63 @@
+"""Prints the entries of src/dictionary.rs from the DocBook source of DICOM PS3.6.
+
+Download part06.xml from https://dicom.nema.org/medical/dicom/current/source/docbook/part06/
+and run `python scripts/generate_dictionary.py part06.xml`. Elements of the overlay repeating
+groups are printed under group 6000; other repeating groups and elements without a VR, like the
+item delimiters, are skipped.
+"""
+import re
+import sys
+import xml.etree.ElementTree as ET
+
+import plac
+
+NAMESPACES = {
+    "db": "http://docbook.org/ns/docbook",
+    "xml": "http://www.w3.org/XML/1998/namespace",
+}
+REGISTRY_TABLES = ["table_6-1", "table_7-1", "table_8-1"]
+TAG_PATTERN = re.compile(r"\(([0-9A-Fx]{4}),([0-9A-Fx]{4})\)")
+
+
+def cell_text(cell: ET.Element) -> str:
+    text = "".join(cell.itertext())
+    return " ".join(text.replace("\u200b", "").split())
+
+
+def registry_rows(root: ET.Element):
+    for table_id in REGISTRY_TABLES:
+        table = root.find(f".//db:table[@xml:id='{table_id}']", NAMESPACES)
+        if table is None:
+            sys.exit(f"{table_id} is not in the document")
+        for row in table.iterfind("db:tbody/db:tr", NAMESPACES):
+            yield [cell_text(cell) for cell in row.iterfind("db:td", NAMESPACES)]
+
+
+def parse_tag(text: str) -> int | None:
+    match = TAG_PATTERN.fullmatch(text)
+    if match is None:
+        return None
+    group, element = match.groups()
+    if group == "60xx":
+        group = "6000"
+    if "x" in group or "x" in element:
+        return None
+    return int(group + element, 16)
+
+
+def main(part06: str):
+    root = ET.parse(part06).getroot()
+    entries = {}
+    for tag_text, _name, keyword, vr, vm, *note in registry_rows(root):
+        tag = parse_tag(tag_text)
+        if tag is None or not keyword or not vr or vr == "See Note":
+            continue
+        retired = "true" if note and "RET" in note[0] else "false"
+        hex_tag = f"0x{tag >> 16:04X}_{tag & 0xFFFF:04X}"
+        entries[tag] = f'entry({hex_tag}, "{keyword}", "{vr}", "{vm}", {retired}),'
+    for tag in sorted(entries):
+        print("    " + entries[tag])
+
+
+if __name__ == "__main__":
+    plac.call(main)
@@ -0,0 +1,141 @@
+//! The DICOM Specific Character Set (0008,0005) and decoding text values with it.
+
+use encoding_rs::Encoding;
+
+/// The Specific Character Set attribute
+pub(crate) const SPECIFIC_CHARACTER_SET: u32 = 0x0008_0005;
+
+/// The defined term for UTF-8, the only character set dimble writes DICOM text in
+pub(crate) const ISO_IR_192: &str = "ISO_IR 192";
+
+const ESC: u8 = 0x1B;
+
+/// The character set that text VRs (SH, LO, ST, LT, UC, UT and PN) of a dataset are encoded in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct CharacterSet {
+    /// The encoding, or `None` for the default repertoire
+    encoding: Option<&'static Encoding>,
+    /// The escape sequence that designates the character set when code extensions are used
+    escape: Option<&'static [u8]>,
+}
+
+/// The defined terms along with their encoding and code extension escape sequence, if any
+const DEFINED_TERMS: &[(&str, &Encoding, Option<&[u8]>)] = &[
+    ("ISO_IR 100", encoding_rs::WINDOWS_1252, None),
+    ("ISO_IR 101", encoding_rs::ISO_8859_2, None),
+    ("ISO_IR 109", encoding_rs::ISO_8859_3, None),
+    ("ISO_IR 110", encoding_rs::ISO_8859_4, None),
+    ("ISO_IR 144", encoding_rs::ISO_8859_5, None),
+    ("ISO_IR 127", encoding_rs::ISO_8859_6, None),
+    ("ISO_IR 126", encoding_rs::ISO_8859_7, None),
+    ("ISO_IR 138", encoding_rs::ISO_8859_8, None),
+    ("ISO_IR 148", encoding_rs::WINDOWS_1254, None),
+    ("ISO_IR 203", encoding_rs::ISO_8859_15, None),
+    ("ISO_IR 166", encoding_rs::WINDOWS_874, None),
+    ("ISO_IR 13", encoding_rs::SHIFT_JIS, None),
+    (ISO_IR_192, encoding_rs::UTF_8, None),
+    ("GB18030", encoding_rs::GB18030, None),
+    ("GBK", encoding_rs::GBK, None),
+    (
+        "ISO 2022 IR 100",
+        encoding_rs::WINDOWS_1252,
+        Some(b"\x1B-A"),
+    ),
+    ("ISO 2022 IR 101", encoding_rs::ISO_8859_2, Some(b"\x1B-B")),
+    ("ISO 2022 IR 109", encoding_rs::ISO_8859_3, Some(b"\x1B-C")),
+    ("ISO 2022 IR 110", encoding_rs::ISO_8859_4, Some(b"\x1B-D")),
+    ("ISO 2022 IR 144", encoding_rs::ISO_8859_5, Some(b"\x1B-L")),
+    ("ISO 2022 IR 127", encoding_rs::ISO_8859_6, Some(b"\x1B-G")),
+    ("ISO 2022 IR 126", encoding_rs::ISO_8859_7, Some(b"\x1B-F")),
+    ("ISO 2022 IR 138", encoding_rs::ISO_8859_8, Some(b"\x1B-H")),
+    (
+        "ISO 2022 IR 148",
+        encoding_rs::WINDOWS_1254,
+        Some(b"\x1B-M"),
+    ),
+    ("ISO 2022 IR 203", encoding_rs::ISO_8859_15, Some(b"\x1B-b")),
+    ("ISO 2022 IR 166", encoding_rs::WINDOWS_874, Some(b"\x1B-T")),
+    ("ISO 2022 IR 13", encoding_rs::SHIFT_JIS, Some(b"\x1B)I")),
+    // the ISO-2022-JP decoder follows the escape sequences itself
+    ("ISO 2022 IR 87", encoding_rs::ISO_2022_JP, None),
+    ("ISO 2022 IR 149", encoding_rs::EUC_KR, Some(b"\x1B$)C")),
+    ("ISO 2022 IR 58", encoding_rs::GBK, Some(b"\x1B$)A")),
+];
+
+impl CharacterSet {
+    /// The character set named by the values of a Specific Character Set attribute, or `None` if
+    /// it is not supported.
+    ///
+    /// Several values are code extensions. Only the default repertoire extended with a single
+    /// other character set is supported, which covers the usual Japanese, Korean and Chinese
+    /// datasets.
+    pub(crate) fn from_terms<'a>(terms: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        let mut terms: Vec<&str> = terms.into_iter().map(str::trim).collect();
+        if terms.len() > 1 && matches!(terms[0], "" | "ISO 2022 IR 6") {
+            terms.remove(0);
+        }
+        match terms.as_slice() {
+            [] | [""] | ["ISO_IR 6"] | ["ISO 2022 IR 6"] => Some(Self::default()),
+            [term] => DEFINED_TERMS
+                .iter()
+                .find(|(defined, ..)| defined == term)
+                .map(|&(_, encoding, escape)| CharacterSet {
+                    encoding: Some(encoding),
+                    escape,
+                }),
+            _ => None,
+        }
+    }
+
+    /// Decodes a text value, or returns `None` if it is not valid in this character set or uses
+    /// code extensions other than the designated one.
+    pub(crate) fn decode(&self, bytes: &[u8]) -> Option<String> {
+        let Some(encoding) = self.encoding else {
+            return Some(decode_default(bytes));
+        };
+        if encoding == encoding_rs::ISO_2022_JP {
+            return encoding
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .map(String::from);
+        }
+        let stripped;
+        let bytes = match self.escape {
+            Some(escape) if bytes.contains(&ESC) => {
+                stripped = strip_escape(bytes, escape);
+                stripped.as_slice()
+            }
+            _ => bytes,
+        };
+        if bytes.contains(&ESC) {
+            return None;
+        }
+        encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(String::from)
+    }
+}
+
+/// Decodes text in the default repertoire, tolerating the UTF-8 and Latin-1 text that files
+/// without a Specific Character Set often hold anyway
+pub(crate) fn decode_default(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        // fall back to latin-1, the most common non UTF-8 character set
+        Err(_) => bytes.iter().map(|&b| char::from(b)).collect(),
+    }
+}
+
+/// Removes every occurrence of `escape` from `bytes`
+fn strip_escape(bytes: &[u8], escape: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        if rest.starts_with(escape) {
+            rest = &rest[escape.len()..];
+        } else {
+            out.push(rest[0]);
+            rest = &rest[1..];
+        }
+    }
+    out
+}
@@ -0,0 +1,224 @@
+//! Checksums of the header and of every deferred field, written after the data section of files
+//! whose preamble has the [`CHECKSUMS`](crate::format::CHECKSUMS) flag.
+//!
+//! ```text
+//! N × 24   entries sorted by offset: u64 LE data offset, u64 LE stored length, u64 LE checksum
+//! u64 LE   checksum of the preamble, header length and header
+//! u64 LE   number of entries N
+//! ```
+//!
+//! Checksums are XXH3 64 of the stored bytes, so a compressed field's checksum is of its
+//! compressed bytes. Readers that do not know the flag never read past the fields they look up,
+//! so the trailer does not get in their way.
+
+use snafu::prelude::*;
+use std::{io::Write, ops::Range};
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+use crate::ir_to_dimble::{HeaderField, HeaderFieldMap};
+
+const WORD_LENGTH: usize = std::mem::size_of::<u64>();
+const ENTRY_LENGTH: usize = 3 * WORD_LENGTH;
+const FOOTER_LENGTH: usize = 2 * WORD_LENGTH;
+
+pub(crate) fn checksum(bytes: &[u8]) -> u64 {
+    xxh3_64(bytes)
+}
+
+/// Checksums and counts everything written through it, for fields streamed into a file
+pub(crate) struct ChecksumWriter<W> {
+    inner: W,
+    hasher: Xxh3,
+    length: u64,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Xxh3::new(),
+            length: 0,
+        }
+    }
+
+    /// The checksum of everything written so far
+    pub(crate) fn checksum(&self) -> u64 {
+        self.hasher.digest()
+    }
+
+    /// The number of bytes written so far
+    pub(crate) fn length(&self) -> u64 {
+        self.length
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let length = self.inner.write(buf)?;
+        self.hasher.update(&buf[..length]);
+        self.length += length as u64;
+        Ok(length)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The location and checksum of the stored bytes of every deferred field in `header_fields`,
+/// including those nested in sequences, as the entries [`encode`] takes
+pub(crate) fn field_checksums(header_fields: &HeaderFieldMap, data_bytes: &[u8]) -> Vec<[u64; 3]> {
+    let mut entries = Vec::new();
+    add_field_checksums(header_fields, data_bytes, &mut entries);
+    entries
+}
+
+fn add_field_checksums(header_fields: &HeaderFieldMap, data_bytes: &[u8], out: &mut Vec<[u64; 3]>) {
+    for header_field in header_fields.values() {
+        match header_field {
+            HeaderField::Deffered(offset, length, _)
+            | HeaderField::Compressed(offset, length, ..) => {
+                // fields past the end of the data have nothing to checksum, and verify as corrupt
+                let location = *offset as usize..offset.saturating_add(*length) as usize;
+                if let Some(bytes) = data_bytes.get(location) {
+                    out.push([*offset, *length, checksum(bytes)]);
+                }
+            }
+            HeaderField::SQ(items) => {
+                for item in items {
+                    add_field_checksums(item, data_bytes, out);
+                }
+            }
+            HeaderField::Empty(_) | HeaderField::BulkDataURI(..) => {}
+        }
+    }
+}
+
+/// Encodes the trailer for a file whose preamble, header length and header are `head`, and whose
+/// fields have the location and checksum `entries`
+pub(crate) fn encode(head: &[u8], mut entries: Vec<[u64; 3]>) -> Vec<u8> {
+    entries.sort_unstable();
+    entries.dedup();
+
+    let mut trailer = Vec::with_capacity(entries.len() * ENTRY_LENGTH + FOOTER_LENGTH);
+    for entry in &entries {
+        for word in entry {
+            trailer.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+    trailer.extend_from_slice(&checksum(head).to_le_bytes());
+    trailer.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+    trailer
+}
+
+/// The checksums read from the end of a file
+#[derive(Debug)]
+pub(crate) struct Checksums {
+    pub(crate) header: u64,
+    /// Sorted by offset and then length
+    fields: Vec<[u64; 3]>,
+    /// Where the trailer starts within the file
+    pub(crate) start: usize,
+}
+
+impl Checksums {
+    /// Reads the trailer at the end of `buffer`, whose data section starts at `data_offset`
+    pub(crate) fn read(buffer: &[u8], data_offset: usize) -> Result<Self, ChecksumError> {
+        let word = |start: usize| {
+            u64::from_le_bytes(
+                buffer[start..start + WORD_LENGTH]
+                    .try_into()
+                    .expect("slice has the length of a u64"),
+            )
+        };
+        let truncated = TruncatedSnafu {
+            length: buffer.len(),
+        };
+        let footer_start = buffer
+            .len()
+            .checked_sub(FOOTER_LENGTH)
+            .filter(|&start| start >= data_offset)
+            .context(truncated)?;
+        let header = word(footer_start);
+        let count = word(footer_start + WORD_LENGTH) as usize;
+        let start = count
+            .checked_mul(ENTRY_LENGTH)
+            .and_then(|length| footer_start.checked_sub(length))
+            .filter(|&start| start >= data_offset)
+            .context(truncated)?;
+        let fields = (0..count)
+            .map(|i| {
+                let entry = start + i * ENTRY_LENGTH;
+                [0, 1, 2].map(|j| word(entry + j * WORD_LENGTH))
+            })
+            .collect();
+        Ok(Self {
+            header,
+            fields,
+            start,
+        })
+    }
+
+    /// The checksum recorded for the stored bytes at `location` within the data section
+    pub(crate) fn field(&self, location: Range<u64>) -> Option<u64> {
+        let key = [location.start, location.end - location.start];
+        self.fields
+            .binary_search_by(|entry| entry[..2].cmp(&key))
+            .ok()
+            .map(|position| self.fields[position][2])
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum ChecksumError {
+    #[snafu(display("File of {length} bytes is too short for its checksums"))]
+    Truncated { length: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::Tag;
+
+    #[test]
+    fn test_trailer_round_trip() {
+        let data_bytes = b"abcdefgh";
+        let item = HeaderFieldMap::from([(
+            Tag::new(0x0008, 0x0100),
+            HeaderField::Deffered(4, 4, *b"SH"),
+        )]);
+        let header_fields = HeaderFieldMap::from([
+            (
+                Tag::new(0x0008, 0x0060),
+                HeaderField::Deffered(0, 2, *b"CS"),
+            ),
+            (Tag::new(0x0008, 0x9215), HeaderField::SQ(vec![item])),
+            (Tag::new(0x0010, 0x0010), HeaderField::Empty(*b"PN")),
+        ]);
+        let head = b"head";
+        let mut file = data_bytes.to_vec();
+        file.extend(encode(head, field_checksums(&header_fields, data_bytes)));
+
+        let checksums = Checksums::read(&file, 0).unwrap();
+        assert_eq!(checksums.start, data_bytes.len());
+        assert_eq!(checksums.header, checksum(head));
+        assert_eq!(checksums.field(0..2), Some(checksum(b"ab")));
+        assert_eq!(checksums.field(4..8), Some(checksum(b"efgh")));
+        assert_eq!(checksums.field(0..3), None);
+
+        // the trailer cannot start inside the data section
+        assert!(Checksums::read(&file, 9).is_err());
+        assert!(Checksums::read(&file[..file.len() - 1], 0).is_err());
+    }
+
+    #[test]
+    fn test_checksum_writer() {
+        let mut written = Vec::new();
+        let mut writer = ChecksumWriter::new(&mut written);
+        writer.write_all(b"abc").unwrap();
+        writer.write_all(b"defgh").unwrap();
+        assert_eq!(writer.length(), 8);
+        assert_eq!(writer.checksum(), checksum(b"abcdefgh"));
+        assert_eq!(written, b"abcdefgh");
+    }
+}
@@ -0,0 +1,395 @@
+//! Optional compression of the stored bytes of a field.
+//!
+//! Uncompressed fields are read straight out of the memory map. A compressed field is recorded as
+//! [`HeaderField::Compressed`](crate::HeaderField::Compressed) with its codec and the length it
+//! decompresses to, and is decompressed into memory whenever it is read. Pixel data is compressed a
+//! tensor at a time instead, as the `compressed` module of [`pixel_array`](crate::pixel_array)
+//! lays out.
+
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+use std::{
+    io::{self, Read, Write},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Codec {
+    Zstd,
+    /// Faster than zstd to compress and decompress, but compresses less
+    Lz4,
+    /// Lossless image codec for pixel data. Each row of every tensor is replaced by the
+    /// differences between neighbouring elements, like the TIFF horizontal predictor, then
+    /// compressed with zstd. Smooth images leave mostly small differences, which compress far
+    /// better than the pixels themselves.
+    DeltaZstd,
+}
+
+impl Codec {
+    /// The name Python and the command line know the codec by
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::Zstd => "zstd",
+            Codec::Lz4 => "lz4",
+            Codec::DeltaZstd => "delta-zstd",
+        }
+    }
+}
+
+impl FromStr for Codec {
+    type Err = CodecError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [Codec::Zstd, Codec::Lz4, Codec::DeltaZstd]
+            .into_iter()
+            .find(|codec| codec.name() == name)
+            .context(UnknownCodecSnafu { name })
+    }
+}
+
+/// The codecs that compress the values of fields and the pixel data. Neither is compressed by
+/// default, so that every field can be read without a copy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Compression {
+    pub fields: Option<Codec>,
+    pub pixel_data: Option<Codec>,
+}
+
+impl Compression {
+    pub fn is_none(&self) -> bool {
+        self.fields.is_none() && self.pixel_data.is_none()
+    }
+}
+
+/// Compresses the value of a field other than the pixel data
+pub(crate) fn compress(codec: Codec, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+    let mut compressed = Vec::new();
+    match codec {
+        Codec::Zstd => zstd_stream(bytes, bytes.len() as u64, &mut compressed)?,
+        Codec::Lz4 => lz4_stream(bytes, bytes.len() as u64, &mut compressed)?,
+        Codec::DeltaZstd => return NotPixelDataSnafu.fail(),
+    }
+    Ok(compressed)
+}
+
+/// Compresses the `length` bytes of a tensor's data read from `data` into `out`, returning the
+/// length of the compressed bytes. [`Codec::DeltaZstd`] delta encodes the tensor's rows of
+/// `row_length` bytes of elements of `item_size` bytes first. Only a row of the tensor is in memory
+/// at a time.
+pub(crate) fn compress_tensor(
+    codec: Codec,
+    data: impl Read,
+    length: u64,
+    item_size: usize,
+    row_length: usize,
+    out: impl Write,
+) -> Result<u64, CodecError> {
+    let mut out = CountingWriter {
+        inner: out,
+        count: 0,
+    };
+    match codec {
+        Codec::Zstd => zstd_stream(data, length, &mut out)?,
+        Codec::Lz4 => lz4_stream(data, length, &mut out)?,
+        Codec::DeltaZstd => {
+            let data = DeltaEncoder::new(data, item_size, row_length);
+            zstd_stream(data, length, &mut out)?
+        }
+    }
+    Ok(out.count)
+}
+
+/// Compresses the `length` bytes of `bytes` into a single zstd frame that records that length, as
+/// [`zstd::bulk::compress`] does
+fn zstd_stream(mut bytes: impl Read, length: u64, out: impl Write) -> Result<(), CodecError> {
+    let mut encoder =
+        zstd::stream::Encoder::new(out, zstd::DEFAULT_COMPRESSION_LEVEL).context(ZstdSnafu)?;
+    encoder
+        .set_pledged_src_size(Some(length))
+        .context(ZstdSnafu)?;
+    io::copy(&mut bytes, &mut encoder).context(StreamSnafu)?;
+    encoder.finish().context(ZstdSnafu)?;
+    Ok(())
+}
+
+/// Compresses the `length` bytes of `bytes` into a single lz4 frame that records that length
+fn lz4_stream(mut bytes: impl Read, length: u64, out: impl Write) -> Result<(), CodecError> {
+    let frame_info = lz4_flex::frame::FrameInfo::new().content_size(Some(length));
+    let mut encoder = lz4_flex::frame::FrameEncoder::with_frame_info(frame_info, out);
+    io::copy(&mut bytes, &mut encoder).context(StreamSnafu)?;
+    encoder.finish().context(Lz4Snafu)?;
+    Ok(())
+}
+
+/// Counts the bytes written through it
+struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Delta encodes the data of a tensor a row at a time as it is read
+struct DeltaEncoder<R> {
+    inner: R,
+    item_size: usize,
+    row_length: usize,
+    /// The row being read, and how much of it has been read
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> DeltaEncoder<R> {
+    fn new(inner: R, item_size: usize, row_length: usize) -> Self {
+        Self {
+            inner,
+            item_size,
+            row_length,
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for DeltaEncoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.buffer.len() {
+            self.buffer.clear();
+            self.position = 0;
+            // rows of zero length still have to read to the end of the tensor
+            (&mut self.inner)
+                .take(self.row_length.max(1) as u64)
+                .read_to_end(&mut self.buffer)?;
+            // a row cut short is left as it is, as the stream it came from was too short
+            if self.buffer.len() == self.row_length {
+                delta_encode(&mut self.buffer, self.item_size, self.row_length);
+            }
+        }
+        let length = buf.len().min(self.buffer.len() - self.position);
+        buf[..length].copy_from_slice(&self.buffer[self.position..][..length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// Decompresses the stored bytes of a field other than the pixel data, which must decompress to
+/// exactly the `length` bytes recorded for it. No more than that is ever decompressed, whatever
+/// length the bytes claim.
+pub(crate) fn decompress(codec: Codec, bytes: &[u8], length: u64) -> Result<Vec<u8>, CodecError> {
+    let mut decompressed = Vec::new();
+    let limit = length.saturating_add(1);
+    match codec {
+        Codec::Zstd => zstd::stream::Decoder::with_buffer(bytes)
+            .context(ZstdSnafu)?
+            .take(limit)
+            .read_to_end(&mut decompressed)
+            .context(ZstdSnafu)?,
+        Codec::Lz4 => lz4_flex::frame::FrameDecoder::new(bytes)
+            .take(limit)
+            .read_to_end(&mut decompressed)
+            .context(StreamSnafu)?,
+        Codec::DeltaZstd => return NotPixelDataSnafu.fail(),
+    };
+    ensure!(
+        decompressed.len() as u64 == length,
+        DecompressedLengthSnafu {
+            expected_length: length,
+            length: decompressed.len() as u64,
+        }
+    );
+    Ok(decompressed)
+}
+
+/// Decompresses the data of a tensor that [`compress_tensor`] compressed, which must decompress to
+/// exactly the `length` bytes its shape and dtype give
+pub(crate) fn decompress_tensor(
+    codec: Codec,
+    bytes: &[u8],
+    length: usize,
+    item_size: usize,
+    row_length: usize,
+) -> Result<Vec<u8>, CodecError> {
+    match codec {
+        Codec::Zstd | Codec::Lz4 => decompress(codec, bytes, length as u64),
+        Codec::DeltaZstd => {
+            let mut data = decompress(Codec::Zstd, bytes, length as u64)?;
+            delta_decode(&mut data, item_size, row_length);
+            Ok(data)
+        }
+    }
+}
+
+/// Reads a little endian element of up to 8 bytes
+fn read_word(element: &[u8]) -> u64 {
+    let mut word = [0; 8];
+    word[..element.len()].copy_from_slice(element);
+    u64::from_le_bytes(word)
+}
+
+/// Replaces every element by its wrapping difference from the element before it in its row. The
+/// elements are treated as unsigned integers of their width, so any dtype round trips exactly.
+fn delta_encode(data: &mut [u8], item_size: usize, row_length: usize) {
+    // complex128 elements are left as they are
+    if item_size > 8 || row_length == 0 {
+        return;
+    }
+    for row in data.chunks_exact_mut(row_length) {
+        let mut previous = 0u64;
+        for element in row.chunks_exact_mut(item_size) {
+            let value = read_word(element);
+            element.copy_from_slice(&value.wrapping_sub(previous).to_le_bytes()[..item_size]);
+            previous = value;
+        }
+    }
+}
+
+/// Inverts [`delta_encode`]
+fn delta_decode(data: &mut [u8], item_size: usize, row_length: usize) {
+    if item_size > 8 || row_length == 0 {
+        return;
+    }
+    for row in data.chunks_exact_mut(row_length) {
+        let mut previous = 0u64;
+        for element in row.chunks_exact_mut(item_size) {
+            previous = previous.wrapping_add(read_word(element));
+            element.copy_from_slice(&previous.to_le_bytes()[..item_size]);
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum CodecError {
+    #[snafu(display("Unknown codec {name:?}, expected zstd, lz4 or delta-zstd"))]
+    UnknownCodec { name: String },
+
+    #[snafu(display("Could not compress or decompress with zstd"))]
+    Zstd { source: std::io::Error },
+
+    #[snafu(display("Could not stream the bytes through the compressor"))]
+    Stream { source: std::io::Error },
+
+    #[snafu(display("Could not compress or decompress with lz4"))]
+    Lz4 { source: lz4_flex::frame::Error },
+
+    #[snafu(display(
+        "Compressed bytes should decompress to exactly {expected_length} bytes, not {length}"
+    ))]
+    DecompressedLength { expected_length: u64, length: u64 },
+
+    #[snafu(display("The delta-zstd codec only compresses pixel data"))]
+    NotPixelData,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = b"ISO_IR 100 ".repeat(8);
+        for codec in [Codec::Zstd, Codec::Lz4] {
+            let compressed = compress(codec, &bytes).unwrap();
+            assert!(compressed.len() < bytes.len());
+            assert_eq!(
+                decompress(codec, &compressed, bytes.len() as u64).unwrap(),
+                bytes
+            );
+        }
+        assert!(matches!(
+            compress(Codec::DeltaZstd, &bytes),
+            Err(CodecError::NotPixelData)
+        ));
+        assert!(decompress(Codec::Zstd, &bytes, bytes.len() as u64).is_err());
+    }
+
+    #[test]
+    fn test_decompress_exact_length() {
+        let bytes = vec![0; 1 << 20];
+        for codec in [Codec::Zstd, Codec::Lz4] {
+            let compressed = compress(codec, &bytes).unwrap();
+            // a frame cut short
+            assert!(decompress(codec, &compressed[..compressed.len() / 2], 1 << 20).is_err());
+            // a frame longer than recorded is only decompressed to one byte past the length
+            assert!(matches!(
+                decompress(codec, &compressed, 1024),
+                Err(CodecError::DecompressedLength {
+                    expected_length: 1024,
+                    length: 1025
+                })
+            ));
+            assert!(matches!(
+                decompress(codec, &compressed, 2 << 20),
+                Err(CodecError::DecompressedLength { .. })
+            ));
+        }
+
+        // frames written without their length are read up to the recorded length too
+        let mut encoder = zstd::stream::Encoder::new(Vec::new(), 0).unwrap();
+        encoder.include_contentsize(false).unwrap();
+        std::io::Write::write_all(&mut encoder, &bytes).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(
+            decompress(Codec::Zstd, &compressed, 1 << 20).unwrap(),
+            bytes
+        );
+        assert!(decompress(Codec::Zstd, &compressed, 16).is_err());
+    }
+
+    #[test]
+    fn test_delta_zstd() {
+        // a ramp with a wrap around, which the differences store as small values
+        let pixels: Vec<u8> = (0..64u16)
+            .map(|i| i.wrapping_mul(1000).wrapping_sub(3))
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let compress = |codec| {
+            let mut compressed = Vec::new();
+            compress_tensor(codec, &pixels[..], 128, 2, 32, &mut compressed).unwrap();
+            compressed
+        };
+        let compressed = compress(Codec::DeltaZstd);
+        assert!(compressed.len() < compress(Codec::Zstd).len());
+        assert_eq!(
+            decompress_tensor(Codec::DeltaZstd, &compressed, 128, 2, 32).unwrap(),
+            pixels
+        );
+
+        let mut row = [1, 0, 3, 0, 2, 0, 0, 255];
+        delta_encode(&mut row, 2, 8);
+        assert_eq!(row, [1, 0, 2, 0, 255, 255, 254, 254]);
+        delta_decode(&mut row, 2, 8);
+        assert_eq!(row, [1, 0, 3, 0, 2, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_compress_tensor_length() {
+        let data = vec![7; 1000];
+        for codec in [Codec::Zstd, Codec::Lz4, Codec::DeltaZstd] {
+            let mut compressed = vec![1, 2, 3];
+            let length = compress_tensor(codec, &data[..], 1000, 1, 100, &mut compressed).unwrap();
+            assert_eq!(length as usize, compressed.len() - 3);
+            assert_eq!(
+                decompress_tensor(codec, &compressed[3..], 1000, 1, 100).unwrap(),
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn test_codec_names() {
+        for codec in [Codec::Zstd, Codec::Lz4, Codec::DeltaZstd] {
+            assert_eq!(codec.name().parse::<Codec>().unwrap(), codec);
+        }
+        assert!("gzip".parse::<Codec>().is_err());
+    }
+}
@@ -1,24 +1,23 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::ir_to_dimble::VR;
+use crate::person_name::PersonName;
+use crate::tag::Tag;
 
-pub type DicomJsonData = HashMap<String, DicomField>;
-
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-pub struct Alphabetic {
-    #[serde(rename = "Alphabetic")]
-    pub alphabetic: String, // TODO support Ideographic and Phonetic
-}
+pub type DicomJsonData = BTreeMap<Tag, DicomField>;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum DicomValue {
     Integer(i64),
+    /// SV/UV values beyond the range of an i64
+    UnsignedInteger(u64),
     Float(f64),
     String(String),
-    Alphabetic(Alphabetic),
+    // an empty `{}` is read as an empty sequence item, even for PN
     SeqField(DicomJsonData),
+    PersonName(PersonName),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -26,11 +25,21 @@ pub struct DicomField {
     #[serde(rename = "Value")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<Vec<DicomValue>>,
-    #[serde(with = "vr_serialization")]
-    pub vr: VR,
+    /// Missing VRs are filled in from the data dictionary when converting
+    #[serde(default, with = "vr_serialization")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vr: Option<VR>,
     #[serde(rename = "InlineBinary")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub inline_binary: Option<String>,
+    #[serde(rename = "BulkDataURI")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bulk_data_uri: Option<String>,
+}
+
+/// VRs whose values are carried as `InlineBinary` in the JSON model
+pub(crate) fn is_inline_binary_vr(vr: &VR) -> bool {
+    matches!(vr, b"OB" | b"OD" | b"OF" | b"OL" | b"OV" | b"OW" | b"UN")
 }
 
 mod vr_serialization {
@@ -41,19 +50,25 @@ mod vr_serialization {
 
     use super::VR;
 
-    pub fn serialize<S>(value: &VR, serializer: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(value: &Option<VR>, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let value = std::str::from_utf8(value).map_err(S::Error::custom)?;
+        let value = value
+            .as_ref()
+            .map(|vr| std::str::from_utf8(vr))
+            .transpose()
+            .map_err(S::Error::custom)?;
         value.serialize(serializer)
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<VR, D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<VR>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let value = <Cow<'de, str>>::deserialize(deserializer)?;
-        value.as_bytes().try_into().map_err(D::Error::custom)
+        let value = <Option<Cow<'de, str>>>::deserialize(deserializer)?;
+        value
+            .map(|vr| vr.as_bytes().try_into().map_err(D::Error::custom))
+            .transpose()
     }
 }
@@ -0,0 +1,1857 @@
+//! Parses DICOM Part 10 files straight into the dimble IR, without a pydicom/JSON round trip.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use memmap2::MmapOptions;
+use snafu::prelude::*;
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use crate::character_set::{self, CharacterSet, SPECIFIC_CHARACTER_SET};
+use crate::codec::Compression;
+use crate::dicom_json::*;
+use crate::dictionary::{self, DictionaryEntry};
+use crate::ir_to_dimble::{
+    self, DictionaryMismatch, HeaderField, PixelStream, SerialiseFieldsError, VR,
+};
+use crate::person_name::PersonName;
+use crate::pixel_array::{self, Dtype, EncapsulatedPixelData, RLE_LOSSLESS};
+use crate::tag::Tag;
+
+pub(crate) const FILE_PREAMBLE_LENGTH: usize = 128;
+pub(crate) const DICM_PREFIX: &[u8; 4] = b"DICM";
+
+pub(crate) const ITEM: u32 = 0xFFFE_E000;
+const ITEM_DELIMITATION_ITEM: u32 = 0xFFFE_E00D;
+pub(crate) const SEQUENCE_DELIMITATION_ITEM: u32 = 0xFFFE_E0DD;
+pub(crate) const UNDEFINED_LENGTH: u32 = 0xFFFF_FFFF;
+
+/// Length of the tag and length of an item header
+pub(crate) const ITEM_HEADER_LENGTH: usize = 8;
+
+/// The deepest sequences may nest, far deeper than any real dataset, so that a corrupt or
+/// malicious file fails rather than overflowing the stack
+const MAX_SEQUENCE_DEPTH: usize = 64;
+
+pub(crate) const TRANSFER_SYNTAX_UID: u32 = 0x0002_0010;
+pub(crate) const PIXEL_DATA: u32 = 0x7FE0_0010;
+
+const IMPLICIT_VR_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2";
+pub(crate) const EXPLICIT_VR_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2.1";
+const DEFLATED_EXPLICIT_VR_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2.1.99";
+const EXPLICIT_VR_BIG_ENDIAN: &str = "1.2.840.10008.1.2.2";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Encoding {
+    explicit_vr: bool,
+    little_endian: bool,
+}
+
+impl Encoding {
+    const EXPLICIT_LE: Encoding = Encoding {
+        explicit_vr: true,
+        little_endian: true,
+    };
+    const IMPLICIT_LE: Encoding = Encoding {
+        explicit_vr: false,
+        little_endian: true,
+    };
+    const EXPLICIT_BE: Encoding = Encoding {
+        explicit_vr: true,
+        little_endian: false,
+    };
+}
+
+/// VRs whose explicit encoding has 2 reserved bytes followed by a 4 byte length
+pub(crate) fn has_long_length(vr: &VR) -> bool {
+    matches!(
+        vr,
+        b"OB"
+            | b"OD"
+            | b"OF"
+            | b"OL"
+            | b"OV"
+            | b"OW"
+            | b"SQ"
+            | b"SV"
+            | b"UC"
+            | b"UN"
+            | b"UR"
+            | b"UT"
+            | b"UV"
+    )
+}
+
+/// Text VRs that may hold several backslash separated values
+fn is_multi_valued_text(vr: &VR) -> bool {
+    matches!(
+        vr,
+        b"AE" | b"AS" | b"CS" | b"DA" | b"DT" | b"LO" | b"SH" | b"TM" | b"UC" | b"UI"
+    )
+}
+
+struct ElementHeader {
+    tag: u32,
+    vr: VR,
+    length: u32,
+    offset: usize,
+}
+
+/// The items of encapsulated pixel data, as they are in the file
+#[derive(Debug, PartialEq)]
+pub(crate) struct Fragments<'a> {
+    /// The value of the basic offset table item, empty if there is no offset table
+    pub(crate) offset_table: &'a [u8],
+    pub(crate) fragments: Vec<&'a [u8]>,
+}
+
+/// The parsed dataset along with the raw native or encapsulated pixel data, if any
+pub(crate) struct ParsedDicom<'a> {
+    pub(crate) dataset: DicomJsonData,
+    pub(crate) transfer_syntax: String,
+    pub(crate) little_endian: bool,
+    pub(crate) pixel_data: Option<&'a [u8]>,
+    pub(crate) encapsulated: Option<Fragments<'a>>,
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    pixel_data: Option<&'a [u8]>,
+    encapsulated: Option<Fragments<'a>>,
+    /// The character set of the dataset or item being parsed
+    character_set: CharacterSet,
+    /// The number of sequences the element being parsed is nested in
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn take(&mut self, length: usize) -> InnerResult<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + length)
+            .context(TruncatedSnafu {
+                offset: self.pos,
+                length,
+            })?;
+        self.pos += length;
+        Ok(bytes)
+    }
+
+    fn read_u16(&mut self, encoding: Encoding) -> InnerResult<u16> {
+        let bytes = self.take(2)?.try_into().expect("took 2 bytes");
+        Ok(if encoding.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_u32(&mut self, encoding: Encoding) -> InnerResult<u32> {
+        let bytes = self.take(4)?.try_into().expect("took 4 bytes");
+        Ok(if encoding.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_tag(&mut self, encoding: Encoding) -> InnerResult<u32> {
+        let group = self.read_u16(encoding)?;
+        let element = self.read_u16(encoding)?;
+        Ok((u32::from(group) << 16) | u32::from(element))
+    }
+
+    fn read_element_header(&mut self, encoding: Encoding) -> InnerResult<ElementHeader> {
+        let offset = self.pos;
+        let tag = self.read_tag(encoding)?;
+        if tag >> 16 == 0xFFFE {
+            // items and delimiters never have a VR
+            let length = self.read_u32(encoding)?;
+            return Ok(ElementHeader {
+                tag,
+                vr: *b"NA",
+                length,
+                offset,
+            });
+        }
+        if encoding.explicit_vr {
+            let vr: VR = self.take(2)?.try_into().expect("took 2 bytes");
+            let length = if has_long_length(&vr) {
+                self.take(2)?;
+                self.read_u32(encoding)?
+            } else {
+                u32::from(self.read_u16(encoding)?)
+            };
+            Ok(ElementHeader {
+                tag,
+                vr,
+                length,
+                offset,
+            })
+        } else {
+            let length = self.read_u32(encoding)?;
+            let vr = implicit_vr(tag).unwrap_or(if length == UNDEFINED_LENGTH {
+                *b"SQ"
+            } else {
+                *b"UN"
+            });
+            Ok(ElementHeader {
+                tag,
+                vr,
+                length,
+                offset,
+            })
+        }
+    }
+
+    /// Parses elements until `end` (if given), the end of the buffer, or an item delimiter
+    fn parse_dataset(
+        &mut self,
+        encoding: Encoding,
+        end: Option<usize>,
+        top_level: bool,
+    ) -> InnerResult<DicomJsonData> {
+        let mut dataset = DicomJsonData::new();
+        let end = end.unwrap_or(self.bytes.len());
+        // an item may have its own character set, which ends with the item
+        let parent_character_set = self.character_set;
+        while self.pos < end {
+            let header = self.read_element_header(encoding)?;
+            if header.tag == ITEM_DELIMITATION_ITEM {
+                break;
+            }
+            if top_level && header.tag >> 16 == 0x0002 {
+                // file meta elements are not part of the dataset
+                self.take(header.length as usize)?;
+                continue;
+            }
+            let field = self.parse_element(&header, encoding, top_level)?;
+            if header.tag == SPECIFIC_CHARACTER_SET {
+                self.character_set = character_set(&field)?;
+            }
+            dataset.insert(Tag::from(header.tag), field);
+        }
+        self.character_set = parent_character_set;
+        Ok(dataset)
+    }
+
+    fn parse_element(
+        &mut self,
+        header: &ElementHeader,
+        encoding: Encoding,
+        top_level: bool,
+    ) -> InnerResult<DicomField> {
+        let vr = header.vr;
+        if vr == *b"SQ" || (vr == *b"UN" && header.length == UNDEFINED_LENGTH) {
+            // UN with an undefined length is a sequence encoded as implicit VR little endian
+            let encoding = if vr == *b"UN" {
+                Encoding::IMPLICIT_LE
+            } else {
+                encoding
+            };
+            let items = self.parse_sequence(header, encoding)?;
+            return Ok(DicomField {
+                value: Some(items),
+                vr: Some(*b"SQ"),
+                inline_binary: None,
+                bulk_data_uri: None,
+            });
+        }
+        if header.tag == PIXEL_DATA && top_level {
+            if header.length == UNDEFINED_LENGTH {
+                self.encapsulated = Some(self.parse_fragments(header, encoding)?);
+            } else {
+                self.pixel_data = Some(self.take(header.length as usize)?);
+            }
+            return Ok(DicomField {
+                value: None,
+                vr: Some(vr),
+                // the pixel data is stored as a safetensors object instead
+                inline_binary: Some(String::new()),
+                bulk_data_uri: None,
+            });
+        }
+        ensure!(
+            header.length != UNDEFINED_LENGTH,
+            UndefinedLengthSnafu {
+                tag: header.tag,
+                offset: header.offset
+            }
+        );
+        let bytes = self.take(header.length as usize)?;
+        decode_value(header, bytes, encoding, self.character_set)
+    }
+
+    /// Reads the items of encapsulated pixel data up to its sequence delimiter
+    fn parse_fragments(
+        &mut self,
+        header: &ElementHeader,
+        encoding: Encoding,
+    ) -> InnerResult<Fragments<'a>> {
+        let mut items = Vec::new();
+        loop {
+            let item = self.read_element_header(encoding)?;
+            match item.tag {
+                SEQUENCE_DELIMITATION_ITEM => break,
+                ITEM => {
+                    ensure!(
+                        item.length != UNDEFINED_LENGTH,
+                        UndefinedLengthSnafu {
+                            tag: item.tag,
+                            offset: item.offset
+                        }
+                    );
+                    items.push(self.take(item.length as usize)?);
+                }
+                tag => UnexpectedSequenceElementSnafu {
+                    tag,
+                    offset: item.offset,
+                }
+                .fail()?,
+            }
+        }
+        let (&offset_table, fragments) = items.split_first().context(MissingOffsetTableSnafu {
+            offset: header.offset,
+        })?;
+        Ok(Fragments {
+            offset_table,
+            fragments: fragments.to_vec(),
+        })
+    }
+
+    fn parse_sequence(
+        &mut self,
+        header: &ElementHeader,
+        encoding: Encoding,
+    ) -> InnerResult<Vec<DicomValue>> {
+        ensure!(
+            self.depth < MAX_SEQUENCE_DEPTH,
+            SequenceTooDeepSnafu {
+                tag: header.tag,
+                offset: header.offset
+            }
+        );
+        self.depth += 1;
+        let items = self.parse_items(header, encoding);
+        self.depth -= 1;
+        items
+    }
+
+    fn parse_items(
+        &mut self,
+        header: &ElementHeader,
+        encoding: Encoding,
+    ) -> InnerResult<Vec<DicomValue>> {
+        let end = if header.length == UNDEFINED_LENGTH {
+            None
+        } else {
+            Some(self.pos + header.length as usize)
+        };
+        let mut items = Vec::new();
+        while end.is_none_or(|end| self.pos < end) {
+            let item = self.read_element_header(encoding)?;
+            match item.tag {
+                SEQUENCE_DELIMITATION_ITEM => break,
+                ITEM => {
+                    let item_end = if item.length == UNDEFINED_LENGTH {
+                        None
+                    } else {
+                        Some(self.pos + item.length as usize)
+                    };
+                    let dataset = self.parse_dataset(encoding, item_end, false)?;
+                    items.push(DicomValue::SeqField(dataset));
+                }
+                tag => UnexpectedSequenceElementSnafu {
+                    tag,
+                    offset: item.offset,
+                }
+                .fail()?,
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// The character set named by a Specific Character Set field
+fn character_set(field: &DicomField) -> InnerResult<CharacterSet> {
+    let terms: Vec<&str> = field
+        .value
+        .iter()
+        .flatten()
+        .filter_map(|value| match value {
+            DicomValue::String(term) => Some(term.as_str()),
+            _ => None,
+        })
+        .collect();
+    CharacterSet::from_terms(terms.iter().copied()).context(UnsupportedCharacterSetSnafu {
+        character_set: terms.join("\\"),
+    })
+}
+
+fn decode_value(
+    header: &ElementHeader,
+    bytes: &[u8],
+    encoding: Encoding,
+    character_set: CharacterSet,
+) -> InnerResult<DicomField> {
+    let vr = header.vr;
+    // only these VRs use the Specific Character Set, the others are in the default repertoire
+    let decode_text = |bytes: &[u8]| -> InnerResult<String> {
+        if matches!(&vr, b"SH" | b"LO" | b"ST" | b"LT" | b"UC" | b"UT" | b"PN") {
+            character_set.decode(bytes).context(UndecodableTextSnafu {
+                tag: header.tag,
+                offset: header.offset,
+            })
+        } else {
+            Ok(character_set::decode_default(bytes))
+        }
+    };
+    let field = |value: Vec<DicomValue>| DicomField {
+        value: if value.is_empty() { None } else { Some(value) },
+        vr: Some(vr),
+        inline_binary: None,
+        bulk_data_uri: None,
+    };
+    if bytes.is_empty() {
+        return Ok(field(vec![]));
+    }
+
+    macro_rules! numbers {
+        ($ty:ty, $variant:ident, $as:ty) => {{
+            const SIZE: usize = std::mem::size_of::<$ty>();
+            ensure!(
+                bytes.len() % SIZE == 0,
+                InvalidValueLengthSnafu {
+                    tag: header.tag,
+                    offset: header.offset,
+                    length: bytes.len()
+                }
+            );
+            bytes
+                .chunks_exact(SIZE)
+                .map(|chunk| {
+                    let chunk = chunk.try_into().expect("chunk has the size of the type");
+                    let value = if encoding.little_endian {
+                        <$ty>::from_le_bytes(chunk)
+                    } else {
+                        <$ty>::from_be_bytes(chunk)
+                    };
+                    DicomValue::$variant(value as $as)
+                })
+                .collect()
+        }};
+    }
+
+    let values = match &vr {
+        b"US" => numbers!(u16, Integer, i64),
+        b"SS" => numbers!(i16, Integer, i64),
+        b"UL" => numbers!(u32, Integer, i64),
+        b"SL" => numbers!(i32, Integer, i64),
+        b"SV" => numbers!(i64, Integer, i64),
+        b"UV" => numbers!(u64, UnsignedInteger, u64),
+        b"FL" => numbers!(f32, Float, f64),
+        b"FD" => numbers!(f64, Float, f64),
+        b"AT" => {
+            // a tag is a group and an element, so a trailing group on its own is invalid
+            ensure!(
+                bytes.len().is_multiple_of(4),
+                InvalidValueLengthSnafu {
+                    tag: header.tag,
+                    offset: header.offset,
+                    length: bytes.len()
+                }
+            );
+            let tags: Vec<DicomValue> = numbers!(u16, Integer, i64);
+            tags.chunks_exact(2)
+                .map(|pair| match pair {
+                    [DicomValue::Integer(group), DicomValue::Integer(element)] => {
+                        DicomValue::String(format!("{group:04X}{element:04X}"))
+                    }
+                    _ => unreachable!("AT values are pairs of integers"),
+                })
+                .collect()
+        }
+        b"OB" | b"OD" | b"OF" | b"OL" | b"OV" | b"OW" | b"UN" => {
+            return Ok(DicomField {
+                value: None,
+                vr: Some(vr),
+                inline_binary: Some(STANDARD.encode(bytes)),
+                bulk_data_uri: None,
+            });
+        }
+        b"PN" => decode_text(bytes)?
+            .trim_end_matches([' ', '\0'])
+            .split('\\')
+            .map(|name| DicomValue::PersonName(PersonName::parse(name)))
+            .collect(),
+        b"DS" => decode_text(bytes)?
+            .trim_end_matches([' ', '\0'])
+            .split('\\')
+            .map(|v| {
+                let v = v.trim();
+                v.parse::<f64>()
+                    .map(DicomValue::Float)
+                    .unwrap_or_else(|_| DicomValue::String(v.to_string()))
+            })
+            .collect(),
+        b"IS" => decode_text(bytes)?
+            .trim_end_matches([' ', '\0'])
+            .split('\\')
+            .map(|v| {
+                let v = v.trim();
+                v.parse::<i64>()
+                    .map(DicomValue::Integer)
+                    .unwrap_or_else(|_| DicomValue::String(v.to_string()))
+            })
+            .collect(),
+        vr if is_multi_valued_text(vr) => decode_text(bytes)?
+            .trim_end_matches([' ', '\0'])
+            .split('\\')
+            .map(|v| DicomValue::String(v.trim().to_string()))
+            .collect(),
+        // LT, ST, UT, UR and anything else is a single text value
+        _ => vec![DicomValue::String(
+            decode_text(bytes)?
+                .trim_end_matches([' ', '\0'])
+                .to_string(),
+        )],
+    };
+    Ok(field(values))
+}
+
+/// Parses a DICOM Part 10 file, with or without the 128 byte preamble.
+pub(crate) fn parse_dicom(bytes: &[u8]) -> InnerResult<ParsedDicom<'_>> {
+    let has_preamble = bytes.get(FILE_PREAMBLE_LENGTH..FILE_PREAMBLE_LENGTH + DICM_PREFIX.len())
+        == Some(DICM_PREFIX.as_slice());
+    let mut parser = Parser {
+        bytes,
+        pos: if has_preamble {
+            FILE_PREAMBLE_LENGTH + DICM_PREFIX.len()
+        } else {
+            0
+        },
+        pixel_data: None,
+        encapsulated: None,
+        character_set: CharacterSet::default(),
+        depth: 0,
+    };
+
+    // the file meta group is always explicit VR little endian
+    let mut transfer_syntax = None;
+    while parser.bytes.get(parser.pos..parser.pos + 2) == Some(&[0x02, 0x00]) {
+        let header = parser.read_element_header(Encoding::EXPLICIT_LE)?;
+        let value = parser.take(header.length as usize)?;
+        if header.tag == TRANSFER_SYNTAX_UID {
+            transfer_syntax = Some(
+                character_set::decode_default(value)
+                    .trim_end_matches(['\0', ' '])
+                    .to_string(),
+            );
+        }
+    }
+    let transfer_syntax = match transfer_syntax {
+        Some(transfer_syntax) => transfer_syntax,
+        None if has_preamble => MissingTransferSyntaxSnafu.fail()?,
+        // bare datasets without a file meta group are implicit VR little endian
+        None => IMPLICIT_VR_LITTLE_ENDIAN.to_string(),
+    };
+
+    let encoding = match transfer_syntax.as_str() {
+        IMPLICIT_VR_LITTLE_ENDIAN => Encoding::IMPLICIT_LE,
+        EXPLICIT_VR_LITTLE_ENDIAN => Encoding::EXPLICIT_LE,
+        EXPLICIT_VR_BIG_ENDIAN => Encoding::EXPLICIT_BE,
+        DEFLATED_EXPLICIT_VR_LITTLE_ENDIAN => UnsupportedTransferSyntaxSnafu {
+            transfer_syntax: transfer_syntax.clone(),
+        }
+        .fail()?,
+        // every other transfer syntax, including the encapsulated ones, is explicit VR little endian
+        _ => Encoding::EXPLICIT_LE,
+    };
+    let dataset = parser.parse_dataset(encoding, None, true)?;
+
+    Ok(ParsedDicom {
+        dataset,
+        transfer_syntax,
+        little_endian: encoding.little_endian,
+        pixel_data: parser.pixel_data,
+        encapsulated: parser.encapsulated,
+    })
+}
+
+fn integer_field(dataset: &DicomJsonData, tag: Tag) -> Option<i64> {
+    match dataset.get(&tag)?.value.as_deref()? {
+        [DicomValue::Integer(i), ..] => Some(*i),
+        [DicomValue::String(s), ..] => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn required_integer_field(dataset: &DicomJsonData, tag: Tag) -> InnerResult<usize> {
+    let value = integer_field(dataset, tag).context(MissingPixelAttributeSnafu { tag })?;
+    usize::try_from(value)
+        .ok()
+        .context(MissingPixelAttributeSnafu { tag })
+}
+
+/// The image pixel attributes describing how the pixel data is stored
+struct ImagePixel {
+    rows: usize,
+    columns: usize,
+    samples_per_pixel: usize,
+    frames: usize,
+    dtype: Dtype,
+    bits_stored: usize,
+    planar_configuration: i64,
+}
+
+impl ImagePixel {
+    fn new(dataset: &DicomJsonData) -> InnerResult<Self> {
+        let bits_allocated = required_integer_field(dataset, Tag::BITS_ALLOCATED)?;
+        let signed = integer_field(dataset, Tag::PIXEL_REPRESENTATION) == Some(1);
+        let dtype = match (bits_allocated, signed) {
+            (8, false) => Dtype::U8,
+            (8, true) => Dtype::I8,
+            (16, false) => Dtype::U16,
+            (16, true) => Dtype::I16,
+            (32, false) => Dtype::U32,
+            (32, true) => Dtype::I32,
+            _ => UnsupportedBitsAllocatedSnafu { bits_allocated }.fail()?,
+        };
+        Ok(Self {
+            rows: required_integer_field(dataset, Tag::ROWS)?,
+            columns: required_integer_field(dataset, Tag::COLUMNS)?,
+            samples_per_pixel: usize::try_from(
+                integer_field(dataset, Tag::SAMPLES_PER_PIXEL).unwrap_or(1),
+            )
+            .ok()
+            .context(MissingPixelAttributeSnafu {
+                tag: Tag::SAMPLES_PER_PIXEL,
+            })?,
+            frames: usize::try_from(integer_field(dataset, Tag::NUMBER_OF_FRAMES).unwrap_or(1))
+                .unwrap_or(0)
+                .max(1),
+            dtype,
+            bits_stored: integer_field(dataset, Tag::BITS_STORED)
+                .map_or(bits_allocated, |b| b as usize),
+            planar_configuration: integer_field(dataset, Tag::PLANAR_CONFIGURATION).unwrap_or(0),
+        })
+    }
+
+    /// The shape of pydicom's `pixel_array`, which only has a frame axis for more than one frame
+    fn shape(&self) -> Vec<usize> {
+        let mut shape = Vec::with_capacity(4);
+        if self.frames > 1 {
+            shape.push(self.frames);
+        }
+        shape.extend([self.rows, self.columns]);
+        if self.samples_per_pixel > 1 {
+            shape.push(self.samples_per_pixel);
+        }
+        shape
+    }
+
+    /// The length in bytes of each stored frame, `None` if it overflows
+    fn frame_length(&self) -> Option<usize> {
+        self.rows
+            .checked_mul(self.columns)?
+            .checked_mul(self.samples_per_pixel)?
+            .checked_mul(self.dtype.size())
+    }
+
+    /// The length in bytes of every stored frame, `None` if it overflows
+    fn length(&self) -> Option<usize> {
+        self.frame_length()?.checked_mul(self.frames)
+    }
+
+    /// See [`PixelArray::frames`](pixel_array::PixelArray::frames)
+    fn frames(&self) -> Option<usize> {
+        (self.frames > 1).then_some(self.frames)
+    }
+}
+
+/// Decodes native pixel data into the little endian bytes pydicom's `pixel_array` would have, cast
+/// to a dtype, a frame at a time so that streaming it holds only one frame in memory
+struct NativeFrames<'a> {
+    image: ImagePixel,
+    little_endian: bool,
+    /// The stored bytes of every frame
+    pixel_data: &'a [u8],
+    dtype: Dtype,
+    /// The frame after the one being read, and how much of it has been read
+    next_frame: usize,
+    frame: Vec<u8>,
+    position: usize,
+}
+
+impl<'a> NativeFrames<'a> {
+    /// Checks that the pixel data is long enough for its image pixel attributes and can be cast to
+    /// `dtype`, which defaults to the dtype it is stored in
+    fn new(parsed: &ParsedDicom, pixel_data: &'a [u8], dtype: Option<Dtype>) -> InnerResult<Self> {
+        let image = ImagePixel::new(&parsed.dataset)?;
+        // no pixel data is long enough for a length that overflows
+        let expected_length = image.length().unwrap_or(usize::MAX);
+        ensure!(
+            pixel_data.len() >= expected_length,
+            PixelDataTooShortSnafu {
+                expected_length,
+                length: pixel_data.len()
+            }
+        );
+        let dtype = dtype.unwrap_or(image.dtype);
+        // fail now rather than part way through the frames
+        pixel_array::cast(&vec![0; image.dtype.size()], image.dtype, dtype)
+            .context(PixelArraySnafu)?;
+        Ok(Self {
+            image,
+            little_endian: parsed.little_endian,
+            pixel_data: &pixel_data[..expected_length],
+            dtype,
+            next_frame: 0,
+            frame: Vec::new(),
+            position: 0,
+        })
+    }
+
+    fn decode_frame(&self, frame: usize) -> InnerResult<Vec<u8>> {
+        let image = &self.image;
+        let item_size = image.dtype.size();
+        let frame_length = self.pixel_data.len() / image.frames;
+        let mut data = self.pixel_data[frame * frame_length..(frame + 1) * frame_length].to_vec();
+
+        if !self.little_endian {
+            for element in data.chunks_exact_mut(item_size) {
+                element.reverse();
+            }
+        }
+
+        if matches!(image.dtype, Dtype::I8 | Dtype::I16 | Dtype::I32) {
+            pixel_array::sign_extend(&mut data, item_size, image.bits_stored);
+        }
+
+        if image.samples_per_pixel > 1 && image.planar_configuration == 1 {
+            // colour-by-plane to colour-by-pixel
+            let samples_per_pixel = image.samples_per_pixel;
+            let plane_len = image.rows * image.columns;
+            let mut interleaved = vec![0u8; data.len()];
+            for sample in 0..samples_per_pixel {
+                for pixel in 0..plane_len {
+                    let src = (sample * plane_len + pixel) * item_size;
+                    let dst = (pixel * samples_per_pixel + sample) * item_size;
+                    interleaved[dst..dst + item_size].copy_from_slice(&data[src..src + item_size]);
+                }
+            }
+            data = interleaved;
+        }
+
+        if self.dtype == image.dtype {
+            Ok(data)
+        } else {
+            pixel_array::cast(&data, image.dtype, self.dtype).context(PixelArraySnafu)
+        }
+    }
+}
+
+impl Read for NativeFrames<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.frame.len() {
+            if self.next_frame == self.image.frames {
+                return Ok(0);
+            }
+            self.frame = self
+                .decode_frame(self.next_frame)
+                .map_err(io::Error::other)?;
+            self.next_frame += 1;
+            self.position = 0;
+        }
+        let length = buf.len().min(self.frame.len() - self.position);
+        buf[..length].copy_from_slice(&self.frame[self.position..][..length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// The number of consecutive fragments holding each of `frames` frames. A single frame is all of
+/// the fragments, and as many fragments as frames are a fragment each. Otherwise the basic offset
+/// table must give the offset of each frame's first fragment item from the first fragment item.
+fn frame_fragments(fragments: &Fragments, frames: usize) -> InnerResult<Vec<usize>> {
+    let count = fragments.fragments.len();
+    if frames == 1 {
+        return Ok(vec![count]);
+    }
+    if count == frames {
+        return Ok(vec![1; frames]);
+    }
+    let unknown = UnknownFrameFragmentsSnafu {
+        frames,
+        fragments: count,
+    };
+    let starts: Vec<usize> = fragments
+        .fragments
+        .iter()
+        .scan(0, |start, fragment| {
+            let item_start = *start;
+            *start += ITEM_HEADER_LENGTH + fragment.len();
+            Some(item_start)
+        })
+        .collect();
+    let offsets: Vec<usize> = fragments
+        .offset_table
+        .chunks_exact(4)
+        .map(|offset| u32::from_le_bytes(offset.try_into().expect("4 bytes")) as usize)
+        .collect();
+    ensure!(
+        offsets.len() == frames
+            && offsets.first() == Some(&0)
+            && offsets.windows(2).all(|pair| pair[0] < pair[1])
+            && offsets.iter().all(|offset| starts.contains(offset)),
+        unknown
+    );
+    let mut counts = vec![0; frames];
+    for start in starts {
+        counts[offsets.partition_point(|&offset| offset <= start) - 1] += 1;
+    }
+    Ok(counts)
+}
+
+/// Describes encapsulated pixel data by the image pixel attributes of the dataset
+fn encapsulated_pixel_data<'a>(
+    parsed: &ParsedDicom,
+    fragments: &Fragments<'a>,
+) -> InnerResult<EncapsulatedPixelData<'a>> {
+    let image = ImagePixel::new(&parsed.dataset)?;
+    Ok(EncapsulatedPixelData {
+        transfer_syntax: parsed.transfer_syntax.clone(),
+        dtype: image.dtype,
+        shape: image.shape(),
+        frames: image.frames(),
+        bits_stored: image.bits_stored,
+        offset_table: fragments.offset_table,
+        fragments: fragments.fragments.clone(),
+        frame_fragments: frame_fragments(fragments, image.frames)?,
+    })
+}
+
+/// Casts decoded pixels to `dtype` and serialises them as a safetensors object
+fn decoded_to_safetensors(
+    parsed: &ParsedDicom,
+    (native_dtype, shape, data): (Dtype, Vec<usize>, Vec<u8>),
+    dtype: Option<Dtype>,
+    chunk_shape: Option<&[usize]>,
+) -> InnerResult<Vec<u8>> {
+    let dtype = dtype.unwrap_or(native_dtype);
+    let data = pixel_array::cast(&data, native_dtype, dtype).context(PixelArraySnafu)?;
+    // like pydicom, only pixel data of more than one frame has a frame axis
+    let frames = integer_field(&parsed.dataset, Tag::NUMBER_OF_FRAMES)
+        .filter(|&frames| frames > 1)
+        .map(|frames| frames as usize);
+    pixel_array::pixel_array_to_safetensors(dtype, &shape, frames, chunk_shape, &data)
+        .context(PixelArraySnafu)
+}
+
+/// Converts a DICOM Part 10 file to dimble, optionally casting the pixel data to `dtype`, storing it
+/// in chunks of `chunk_shape`, compressing fields as `compression` says and aligning the pixel
+/// data's tensor data within the file to `pixel_alignment` bytes. Returns the attributes that
+/// disagree with the data dictionary.
+///
+/// Encapsulated pixel data in RLE Lossless is decoded, and in any other transfer syntax is
+/// unsupported, unless `keep_encapsulated` keeps the offset table and fragments as they are so that
+/// [`dimble_to_dicom`](crate::dimble_to_dicom) writes them back out unchanged. Kept pixel data
+/// cannot be cast or chunked.
+///
+/// Native pixel data is decoded a frame at a time as it is written, so it is never held in memory
+/// as a whole. Decoded and kept encapsulated pixel data, and every other field, are converted in
+/// memory.
+pub fn dicom_to_dimble(
+    dicom_path: &str,
+    dimble_path: &str,
+    dtype: Option<Dtype>,
+    chunk_shape: Option<&[usize]>,
+    compression: Compression,
+    keep_encapsulated: bool,
+    pixel_alignment: usize,
+) -> Result<Vec<DictionaryMismatch>> {
+    let file = fs::File::open(dicom_path).context(CouldNotOpenSnafu { dicom_path })?;
+    // mapped rather than read, so that the pixel data streams through without being held in memory
+    let bytes =
+        unsafe { MmapOptions::new().map(&file) }.context(CouldNotOpenSnafu { dicom_path })?;
+    let parsed = parse_dicom(&bytes)?;
+
+    // native pixel data is decoded a frame at a time as it is written, and chunked and compressed as
+    // it goes. Encapsulated pixel data is converted in memory.
+    let mut native_frames = None;
+    let pixel_array_safetensors = match (parsed.pixel_data, &parsed.encapsulated) {
+        (Some(pixel_data), _) => {
+            native_frames = Some(NativeFrames::new(&parsed, pixel_data, dtype)?);
+            // a placeholder the streamed pixel data replaces
+            Some(Vec::new())
+        }
+        (None, Some(fragments)) => {
+            let encapsulated = encapsulated_pixel_data(&parsed, fragments)?;
+            if keep_encapsulated {
+                ensure!(
+                    dtype.is_none() && chunk_shape.is_none(),
+                    KeptEncapsulatedOptionsSnafu
+                );
+                Some(
+                    pixel_array::encapsulated_to_safetensors(&encapsulated)
+                        .context(PixelArraySnafu)?,
+                )
+            } else {
+                ensure!(
+                    encapsulated.transfer_syntax == RLE_LOSSLESS,
+                    EncapsulatedPixelDataSnafu {
+                        transfer_syntax: &parsed.transfer_syntax
+                    }
+                );
+                let decoded = encapsulated.slice(&[]).context(PixelArraySnafu)?;
+                Some(decoded_to_safetensors(
+                    &parsed,
+                    (decoded.dtype, decoded.shape, decoded.data),
+                    dtype,
+                    chunk_shape,
+                )?)
+            }
+        }
+        (None, None) => None,
+    };
+
+    let (header_fields, data_bytes, mismatches) =
+        ir_to_dimble::prepare_dicom_fields_for_serialisation(
+            parsed.dataset,
+            pixel_array_safetensors.as_deref(),
+        )
+        .context(PrepareFieldsSnafu)?;
+    let pixel_stream = match (native_frames, header_fields.get(&Tag::PIXEL_DATA)) {
+        (Some(frames), Some(HeaderField::Deffered(_, _, vr))) => {
+            let (dtype, shape, frame_count) =
+                (frames.dtype, frames.image.shape(), frames.image.frames());
+            let (header, data_length, data) = pixel_array::pixel_array_to_safetensors_stream(
+                dtype,
+                &shape,
+                frame_count,
+                chunk_shape,
+                frames,
+            )
+            .context(PixelArraySnafu)?;
+            Some(PixelStream {
+                header: header.into(),
+                data,
+                data_length,
+                vr: *vr,
+            })
+        }
+        _ => None,
+    };
+    ir_to_dimble::serialise_dimble_fields(
+        &header_fields,
+        &data_bytes,
+        pixel_stream,
+        dimble_path,
+        compression,
+        pixel_alignment,
+    )
+    .context(SerialiseFieldsSnafu)?;
+    Ok(mismatches)
+}
+
+#[derive(Debug, Snafu)]
+pub enum InnerError {
+    #[snafu(display("Could not open the path {dicom_path}"))]
+    CouldNotOpen {
+        source: std::io::Error,
+        dicom_path: String,
+    },
+
+    #[snafu(display("File ended at byte offset {offset} while reading {length} bytes"))]
+    Truncated { offset: usize, length: usize },
+
+    #[snafu(display("File has a preamble but no transfer syntax in its meta group"))]
+    MissingTransferSyntax,
+
+    #[snafu(display("Transfer syntax {transfer_syntax} is not supported"))]
+    UnsupportedTransferSyntax { transfer_syntax: String },
+
+    #[snafu(display(
+        "Pixel data encapsulated in transfer syntax {transfer_syntax} can only be kept encapsulated"
+    ))]
+    EncapsulatedPixelData { transfer_syntax: String },
+
+    #[snafu(display("Encapsulated pixel data at byte offset {offset} has no offset table item"))]
+    MissingOffsetTable { offset: usize },
+
+    #[snafu(display("Cannot tell which of {fragments} fragments hold each of {frames} frames"))]
+    UnknownFrameFragments { frames: usize, fragments: usize },
+
+    #[snafu(display("Pixel data kept encapsulated cannot be cast or chunked"))]
+    KeptEncapsulatedOptions,
+
+    #[snafu(display("Element {tag:08X} at byte offset {offset} has an undefined length"))]
+    UndefinedLength { tag: u32, offset: usize },
+
+    #[snafu(display("Element {tag:08X} at byte offset {offset} found inside a sequence"))]
+    UnexpectedSequenceElement { tag: u32, offset: usize },
+
+    #[snafu(display(
+        "Sequence {tag:08X} at byte offset {offset} is nested more than {MAX_SEQUENCE_DEPTH} sequences deep"
+    ))]
+    SequenceTooDeep { tag: u32, offset: usize },
+
+    #[snafu(display(
+        "Element {tag:08X} at byte offset {offset} has a value length {length} that does not fit its VR"
+    ))]
+    InvalidValueLength {
+        tag: u32,
+        offset: usize,
+        length: usize,
+    },
+
+    #[snafu(display("Specific character set {character_set} is not supported"))]
+    UnsupportedCharacterSet { character_set: String },
+
+    #[snafu(display(
+        "Element {tag:08X} at byte offset {offset} is not valid text in the specific character set"
+    ))]
+    UndecodableText { tag: u32, offset: usize },
+
+    #[snafu(display("Pixel data requires the {tag} attribute"))]
+    MissingPixelAttribute { tag: Tag },
+
+    #[snafu(display("Pixel data with {bits_allocated} bits allocated is not supported"))]
+    UnsupportedBitsAllocated { bits_allocated: usize },
+
+    #[snafu(display("Pixel data should have {expected_length} bytes, has {length}"))]
+    PixelDataTooShort {
+        expected_length: usize,
+        length: usize,
+    },
+
+    #[snafu(display("Could not build the pixel array"))]
+    PixelArray { source: pixel_array::Error },
+
+    #[snafu(display("Could not prepare the fields"))]
+    PrepareFields { source: ir_to_dimble::InnerError },
+
+    #[snafu(display("Could not serialize the fields"))]
+    SerialiseFields { source: SerialiseFieldsError },
+}
+
+type InnerResult<T, E = InnerError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub struct Error(InnerError);
+
+impl Error {
+    /// Whether the file uses DICOM features that only the pydicom based conversion handles
+    pub fn is_unsupported(&self) -> bool {
+        matches!(
+            self.0,
+            InnerError::UnsupportedTransferSyntax { .. }
+                | InnerError::UnsupportedCharacterSet { .. }
+                | InnerError::UndecodableText { .. }
+                | InnerError::EncapsulatedPixelData { .. }
+                | InnerError::UnsupportedBitsAllocated { .. }
+        )
+    }
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The VR of an attribute in a dataset encoded with implicit VR
+fn implicit_vr(tag: u32) -> Option<VR> {
+    let tag = Tag::from(tag);
+    if tag.is_group_length() {
+        return Some(*b"UL");
+    }
+    if tag.group % 2 == 1 {
+        // private attributes have no known VR
+        return None;
+    }
+    dictionary::lookup(tag).map(DictionaryEntry::implicit_vr)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::codec::Codec;
+    use crate::ir_to_dimble::DEFAULT_PIXEL_ALIGNMENT;
+    use crate::pixel_array::{PixelData, Slice};
+    use crate::reader::{self, DimbleReader, FieldValue};
+
+    /// Builds DICOM element bytes for tests
+    pub(crate) struct DicomBuilder {
+        pub(crate) bytes: Vec<u8>,
+        encoding: Encoding,
+    }
+
+    impl DicomBuilder {
+        /// Starts a Part 10 file with a preamble and a meta group holding `transfer_syntax`
+        pub(crate) fn part10(transfer_syntax: &str) -> Self {
+            let mut bytes = vec![0; FILE_PREAMBLE_LENGTH];
+            bytes.extend_from_slice(DICM_PREFIX);
+            let mut meta = DicomBuilder {
+                bytes,
+                encoding: Encoding::EXPLICIT_LE,
+            };
+            let mut uid = transfer_syntax.as_bytes().to_vec();
+            if uid.len() % 2 == 1 {
+                uid.push(0);
+            }
+            meta.element(TRANSFER_SYNTAX_UID, b"UI", &uid);
+            let encoding = match transfer_syntax {
+                IMPLICIT_VR_LITTLE_ENDIAN => Encoding::IMPLICIT_LE,
+                EXPLICIT_VR_BIG_ENDIAN => Encoding::EXPLICIT_BE,
+                _ => Encoding::EXPLICIT_LE,
+            };
+            DicomBuilder {
+                bytes: meta.bytes,
+                encoding,
+            }
+        }
+
+        fn u16(&mut self, v: u16) {
+            if self.encoding.little_endian {
+                self.bytes.extend(v.to_le_bytes())
+            } else {
+                self.bytes.extend(v.to_be_bytes())
+            }
+        }
+
+        fn u32(&mut self, v: u32) {
+            if self.encoding.little_endian {
+                self.bytes.extend(v.to_le_bytes())
+            } else {
+                self.bytes.extend(v.to_be_bytes())
+            }
+        }
+
+        fn header(&mut self, tag: u32, vr: &VR, length: u32) {
+            self.u16((tag >> 16) as u16);
+            self.u16(tag as u16);
+            if self.encoding.explicit_vr {
+                self.bytes.extend_from_slice(vr);
+                if has_long_length(vr) {
+                    self.bytes.extend([0, 0]);
+                    self.u32(length);
+                } else {
+                    self.u16(length as u16);
+                }
+            } else {
+                self.u32(length);
+            }
+        }
+
+        pub(crate) fn element(&mut self, tag: u32, vr: &VR, value: &[u8]) -> &mut Self {
+            self.header(tag, vr, value.len() as u32);
+            self.bytes.extend_from_slice(value);
+            self
+        }
+
+        pub(crate) fn us(&mut self, tag: u32, value: u16) -> &mut Self {
+            let value = if self.encoding.little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            };
+            self.element(tag, b"US", &value)
+        }
+
+        /// Writes an undefined length sequence whose items are built by `items`
+        pub(crate) fn sequence(
+            &mut self,
+            tag: u32,
+            items: &[&dyn Fn(&mut DicomBuilder)],
+        ) -> &mut Self {
+            self.header(tag, b"SQ", UNDEFINED_LENGTH);
+            for item in items {
+                self.u16(0xFFFE);
+                self.u16(0xE000);
+                self.u32(UNDEFINED_LENGTH);
+                item(self);
+                self.u16(0xFFFE);
+                self.u16(0xE00D);
+                self.u32(0);
+            }
+            self.u16(0xFFFE);
+            self.u16(0xE0DD);
+            self.u32(0);
+            self
+        }
+
+        pub(crate) fn pixel_data(&mut self, vr: &VR, value: &[u8]) -> &mut Self {
+            self.element(PIXEL_DATA, vr, value)
+        }
+
+        /// Writes encapsulated pixel data of the offset table and fragment items
+        pub(crate) fn encapsulated_pixel_data(
+            &mut self,
+            offset_table: &[u8],
+            fragments: &[&[u8]],
+        ) -> &mut Self {
+            self.header(PIXEL_DATA, b"OB", UNDEFINED_LENGTH);
+            for item in std::iter::once(offset_table).chain(fragments.iter().copied()) {
+                self.u16(0xFFFE);
+                self.u16(0xE000);
+                self.u32(item.len() as u32);
+                self.bytes.extend_from_slice(item);
+            }
+            self.u16(0xFFFE);
+            self.u16(0xE0DD);
+            self.u32(0);
+            self
+        }
+    }
+
+    /// PackBits encodes a segment, with repeat runs for repeated bytes and literal runs otherwise
+    fn pack_bits(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            let repeats = bytes[i..]
+                .iter()
+                .take(128)
+                .take_while(|&&byte| byte == bytes[i])
+                .count();
+            if repeats > 1 {
+                out.extend([(1 - repeats as i16) as u8, bytes[i]]);
+                i += repeats;
+            } else {
+                let literal = (i..bytes.len())
+                    .take(128)
+                    .take_while(|&j| j + 1 == bytes.len() || bytes[j] != bytes[j + 1])
+                    .count()
+                    .max(1);
+                out.push((literal - 1) as u8);
+                out.extend_from_slice(&bytes[i..i + literal]);
+                i += literal;
+            }
+        }
+        if out.len() % 2 == 1 {
+            // a no-op run pads the segment to an even length
+            out.push(0x80);
+        }
+        out
+    }
+
+    /// RLE Lossless encodes a frame of little endian, colour-by-pixel elements
+    pub(crate) fn rle_frame(data: &[u8], samples: usize, item_size: usize) -> Vec<u8> {
+        let segments = samples * item_size;
+        let mut header = vec![0u8; 64];
+        header[..4].copy_from_slice(&(segments as u32).to_le_bytes());
+        let mut body = Vec::new();
+        for segment in 0..segments {
+            let offset = (64 + body.len()) as u32;
+            header[4 + segment * 4..][..4].copy_from_slice(&offset.to_le_bytes());
+            let (sample, byte) = (segment / item_size, item_size - 1 - segment % item_size);
+            let bytes: Vec<u8> = data
+                .chunks_exact(item_size)
+                .skip(sample)
+                .step_by(samples)
+                .map(|element| element[byte])
+                .collect();
+            body.extend(pack_bits(&bytes));
+        }
+        [header, body].concat()
+    }
+
+    /// A signed, 12 bits stored image of `frames` 2x3 frames, encoded as RLE Lossless with an
+    /// offset table. Returns the builder and the decoded pixels.
+    pub(crate) fn rle_image(frames: usize) -> (DicomBuilder, Vec<u8>) {
+        let mut builder = DicomBuilder::part10(pixel_array::RLE_LOSSLESS);
+        builder
+            .element(0x0008_0016, b"UI", b"1.2.840.10008.5.1.4.1.1.2\0")
+            .element(0x0008_0018, b"UI", b"1.2.3.4\0")
+            .us(0x0028_0002, 1)
+            .element(0x0028_0008, b"IS", format!("{frames} ").as_bytes())
+            .us(0x0028_0010, 2)
+            .us(0x0028_0011, 3)
+            .us(0x0028_0100, 16)
+            .us(0x0028_0101, 12)
+            .us(0x0028_0102, 11)
+            .us(0x0028_0103, 1);
+        let pixels: Vec<i16> = (0..frames as i16 * 6)
+            .map(|i| [-2048, -1, 0, 0, 0, 2000][i as usize % 6] + i / 6)
+            .collect();
+        let decoded: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+        // the stored values are the low 12 bits
+        let stored: Vec<u8> = pixels
+            .iter()
+            .flat_map(|&p| (p as u16 & 0x0FFF).to_le_bytes())
+            .collect();
+        let fragments: Vec<Vec<u8>> = stored
+            .chunks_exact(12)
+            .map(|frame| rle_frame(frame, 1, 2))
+            .collect();
+        let mut offset_table = Vec::new();
+        let mut offset = 0;
+        for fragment in &fragments {
+            offset_table.extend((offset as u32).to_le_bytes());
+            offset += ITEM_HEADER_LENGTH + fragment.len();
+        }
+        let fragments: Vec<&[u8]> = fragments.iter().map(Vec::as_slice).collect();
+        builder.encapsulated_pixel_data(&offset_table, &fragments);
+        (builder, decoded)
+    }
+
+    fn image_builder(transfer_syntax: &str, pixels: &[u16]) -> DicomBuilder {
+        let mut builder = DicomBuilder::part10(transfer_syntax);
+        builder
+            .element(0x0008_0060, b"CS", b"CT")
+            .element(0x0010_0010, b"PN", b"Doe^John")
+            .element(0x0020_0032, b"DS", b"-1.5\\2\\3.25 ")
+            .us(0x0028_0002, 1)
+            .us(0x0028_0010, 2)
+            .us(0x0028_0011, 2)
+            .us(0x0028_0100, 16)
+            .us(0x0028_0101, 12)
+            .us(0x0028_0103, 0);
+        let pixel_bytes: Vec<u8> = pixels
+            .iter()
+            .flat_map(|p| {
+                if builder.encoding.little_endian {
+                    p.to_le_bytes()
+                } else {
+                    p.to_be_bytes()
+                }
+            })
+            .collect();
+        builder.pixel_data(b"OW", &pixel_bytes);
+        builder
+    }
+
+    fn convert(
+        bytes: &[u8],
+        name: &str,
+        dtype: Option<Dtype>,
+        keep_encapsulated: bool,
+    ) -> Result<DimbleReader> {
+        let dicom_path = format!("/tmp/{name}.dcm");
+        let dimble_path = format!("/tmp/{name}.dimble");
+        fs::write(&dicom_path, bytes).unwrap();
+        dicom_to_dimble(
+            &dicom_path,
+            &dimble_path,
+            dtype,
+            None,
+            Compression::default(),
+            keep_encapsulated,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+        Ok(DimbleReader::open(&dimble_path).unwrap())
+    }
+
+    fn round_trip(bytes: &[u8], name: &str, dtype: Option<Dtype>) -> DimbleReader {
+        convert(bytes, name, dtype, false).unwrap()
+    }
+
+    #[test]
+    fn test_explicit_vr_little_endian() {
+        let pixels = [0u16, 1, 4095, 2];
+        let builder = image_builder(EXPLICIT_VR_LITTLE_ENDIAN, &pixels);
+        let reader = round_trip(&builder.bytes, "native_explicit_le", None);
+
+        assert_eq!(
+            reader.element(Tag::new(0x0008, 0x0060)).unwrap().value,
+            FieldValue::String("CT".to_string())
+        );
+        assert_eq!(
+            reader.element(Tag::new(0x0010, 0x0010)).unwrap().value,
+            FieldValue::PersonName(PersonName::parse("Doe^John"))
+        );
+        assert_eq!(
+            reader.element(Tag::new(0x0020, 0x0032)).unwrap().value,
+            FieldValue::Multiple(vec![
+                FieldValue::Float(-1.5),
+                FieldValue::Float(2.0),
+                FieldValue::Float(3.25)
+            ])
+        );
+        assert_eq!(
+            reader.element(Tag::ROWS).unwrap().value,
+            FieldValue::Integer(2)
+        );
+        assert!(
+            reader.element(Tag::new(0x0002, 0x0010)).is_err(),
+            "file meta is not part of the dataset"
+        );
+
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.dtype, Dtype::U16);
+        assert_eq!(pixel_array.shape, [2, 2]);
+        let expected: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+        assert_eq!(pixel_array.data, expected);
+    }
+
+    #[test]
+    fn test_64_bit_integers() {
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        let uv = [u64::MAX, 1].map(u64::to_le_bytes).concat();
+        let sv = [i64::MIN, -1].map(i64::to_le_bytes).concat();
+        builder
+            .element(0x0009_1001, b"UV", &uv)
+            .element(0x0009_1002, b"SV", &sv);
+        let reader = round_trip(&builder.bytes, "64_bit_integers", None);
+
+        assert_eq!(
+            reader.element(Tag::new(0x0009, 0x1001)).unwrap().value,
+            FieldValue::Multiple(vec![
+                FieldValue::UnsignedInteger(u64::MAX),
+                FieldValue::Integer(1)
+            ])
+        );
+        assert_eq!(
+            reader.element(Tag::new(0x0009, 0x1002)).unwrap().value,
+            FieldValue::Multiple(vec![FieldValue::Integer(i64::MIN), FieldValue::Integer(-1)])
+        );
+    }
+
+    #[test]
+    fn test_multi_frame() {
+        let pixels: Vec<u16> = (0..12).collect();
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder
+            .us(0x0028_0002, 1)
+            .element(0x0028_0008, b"IS", b"3 ")
+            .us(0x0028_0010, 2)
+            .us(0x0028_0011, 2)
+            .us(0x0028_0100, 16)
+            .us(0x0028_0101, 16)
+            .us(0x0028_0103, 0);
+        let pixel_bytes: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+        builder.pixel_data(b"OW", &pixel_bytes);
+        let reader = round_trip(&builder.bytes, "native_multi_frame", None);
+
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.shape, [3, 2, 2]);
+        assert_eq!(pixel_array.frames, Some(3));
+        assert_eq!(pixel_array.frame(2).unwrap().data, &pixel_bytes[16..]);
+    }
+
+    #[test]
+    fn test_corrupt_image_pixel_attributes() {
+        let image = |samples_per_pixel: &[u8], frames: &[u8]| {
+            let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+            builder
+                .element(0x0028_0002, b"US", samples_per_pixel)
+                .element(0x0028_0008, b"IS", frames)
+                .element(0x0028_0010, b"US", &u16::MAX.to_le_bytes())
+                .element(0x0028_0011, b"US", &u16::MAX.to_le_bytes())
+                .us(0x0028_0100, 32)
+                .us(0x0028_0103, 0);
+            builder.pixel_data(b"OW", &[0; 8]);
+            builder.bytes
+        };
+
+        // the length of the frames overflows, rather than passing the length check
+        let error = convert(
+            &image(&1u16.to_le_bytes(), b"9999999999999 "),
+            "overflow",
+            None,
+            false,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            error.0,
+            InnerError::PixelDataTooShort { length: 8, .. }
+        ));
+
+        let error = convert(
+            &image(&1u16.to_le_bytes(), b"-3"),
+            "negative_frames",
+            None,
+            false,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            error.0,
+            InnerError::PixelDataTooShort { length: 8, .. }
+        ));
+
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder
+            .element(0x0028_0002, b"SS", &(-1i16).to_le_bytes())
+            .us(0x0028_0010, 1)
+            .us(0x0028_0011, 1)
+            .us(0x0028_0100, 8);
+        builder.pixel_data(b"OB", &[0; 2]);
+        let error = convert(&builder.bytes, "negative_samples", None, false)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.0,
+            InnerError::MissingPixelAttribute {
+                tag: Tag::SAMPLES_PER_PIXEL
+            }
+        ));
+    }
+
+    #[test]
+    fn test_streamed_frames() {
+        let pixels: Vec<i16> = (-6..6).collect();
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_BIG_ENDIAN);
+        builder
+            .us(0x0028_0002, 1)
+            .element(0x0028_0008, b"IS", b"3 ")
+            .us(0x0028_0010, 2)
+            .us(0x0028_0011, 2)
+            .us(0x0028_0100, 16)
+            .us(0x0028_0101, 16)
+            .us(0x0028_0103, 1);
+        let pixel_bytes: Vec<u8> = pixels.iter().flat_map(|p| p.to_be_bytes()).collect();
+        builder.pixel_data(b"OW", &pixel_bytes);
+        let expected: Vec<u8> = pixels
+            .iter()
+            .flat_map(|p| f32::from(*p).to_le_bytes())
+            .collect();
+
+        // reads that straddle frames see the same bytes as decoding them all at once
+        let parsed = parse_dicom(&builder.bytes).unwrap();
+        let mut frames =
+            NativeFrames::new(&parsed, parsed.pixel_data.unwrap(), Some(Dtype::F32)).unwrap();
+        let mut streamed = Vec::new();
+        let mut buf = [0; 3];
+        loop {
+            let length = frames.read(&mut buf).unwrap();
+            if length == 0 {
+                break;
+            }
+            streamed.extend_from_slice(&buf[..length]);
+        }
+        assert_eq!(streamed, expected);
+
+        // the pixel data streams in the same whether it is chunked or compressed as it goes
+        let dicom_path = "/tmp/native_streamed.dcm";
+        fs::write(dicom_path, &builder.bytes).unwrap();
+        for (name, chunk_shape, pixel_codec) in [
+            ("streamed", None, None),
+            ("zstd", None, Some(Codec::Zstd)),
+            ("chunked", Some(&[2, 1, 2][..]), Some(Codec::DeltaZstd)),
+        ] {
+            let dimble_path = format!("/tmp/native_{name}.dimble");
+            let compression = Compression {
+                fields: None,
+                pixel_data: pixel_codec,
+            };
+            dicom_to_dimble(
+                dicom_path,
+                &dimble_path,
+                Some(Dtype::F32),
+                chunk_shape,
+                compression,
+                false,
+                DEFAULT_PIXEL_ALIGNMENT,
+            )
+            .unwrap();
+            let reader = DimbleReader::open(&dimble_path).unwrap();
+            let pixel_array = match reader.pixel_data().unwrap() {
+                PixelData::Contiguous(pixel_array) => pixel_array.slice(&[]).unwrap(),
+                PixelData::Chunked(chunked) => chunked.slice(&[]).unwrap(),
+                PixelData::Encapsulated(_) => panic!("pixel data should be decoded"),
+            };
+            assert_eq!(pixel_array.shape, [3, 2, 2]);
+            assert_eq!(pixel_array.frames, Some(3));
+            assert_eq!(pixel_array.data, expected);
+            assert!(reader.verify().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_implicit_vr_little_endian() {
+        let pixels = [7u16, 8, 9, 10];
+        let builder = image_builder(IMPLICIT_VR_LITTLE_ENDIAN, &pixels);
+        let reader = round_trip(&builder.bytes, "native_implicit_le", Some(Dtype::F32));
+
+        assert_eq!(reader.element(Tag::new(0x0008, 0x0060)).unwrap().vr, *b"CS");
+        assert_eq!(
+            reader.element(Tag::COLUMNS).unwrap().value,
+            FieldValue::Integer(2)
+        );
+
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.dtype, Dtype::F32);
+        let expected: Vec<u8> = pixels
+            .iter()
+            .flat_map(|p| f32::from(*p).to_le_bytes())
+            .collect();
+        assert_eq!(pixel_array.data, expected);
+    }
+
+    #[test]
+    fn test_explicit_vr_big_endian() {
+        let pixels = [0x0102u16, 0x0304, 0x0506, 0x0708];
+        let builder = image_builder(EXPLICIT_VR_BIG_ENDIAN, &pixels);
+        let reader = round_trip(&builder.bytes, "native_explicit_be", None);
+
+        assert_eq!(
+            reader.element(Tag::BITS_ALLOCATED).unwrap().value,
+            FieldValue::Integer(16)
+        );
+        let pixel_array = reader.pixel_array().unwrap();
+        let expected: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+        assert_eq!(pixel_array.data, expected);
+    }
+
+    #[test]
+    fn test_undefined_length_sequence() {
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder.sequence(
+            0x0008_9215,
+            &[&|item: &mut DicomBuilder| {
+                item.element(0x0008_0100, b"SH", b"121327")
+                    .element(0x0008_0102, b"SH", b"DCM ");
+            }],
+        );
+        builder.element(0x0010_0020, b"LO", b"12345 ");
+        let parsed = parse_dicom(&builder.bytes).unwrap();
+
+        let sequence = parsed.dataset.get(&Tag::new(0x0008, 0x9215)).unwrap();
+        assert_eq!(sequence.vr, Some(*b"SQ"));
+        match sequence.value.as_deref() {
+            Some([DicomValue::SeqField(item)]) => {
+                assert_eq!(
+                    item.get(&Tag::new(0x0008, 0x0102)).unwrap().value,
+                    Some(vec![DicomValue::String("DCM".to_string())])
+                );
+            }
+            other => panic!("expected a single item, got {other:?}"),
+        }
+        assert!(parsed.dataset.contains_key(&Tag::new(0x0010, 0x0020)));
+    }
+
+    #[test]
+    fn test_sequence_depth_limit() {
+        // sequences of undefined length, each the only element of an item of the one before
+        let nested = |depth: usize| {
+            let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+            let sequence = [
+                &[0x08, 0x00, 0x15, 0x11, b'S', b'Q', 0, 0][..],
+                &UNDEFINED_LENGTH.to_le_bytes(),
+                &[0xFE, 0xFF, 0x00, 0xE0],
+                &UNDEFINED_LENGTH.to_le_bytes(),
+            ]
+            .concat();
+            let delimiters = [
+                &[0xFE, 0xFF, 0x0D, 0xE0, 0, 0, 0, 0][..],
+                &[0xFE, 0xFF, 0xDD, 0xE0, 0, 0, 0, 0],
+            ]
+            .concat();
+            builder.bytes.extend(sequence.repeat(depth));
+            builder.bytes.extend(delimiters.repeat(depth));
+            parse_dicom(&builder.bytes).map(|parsed| parsed.dataset.len())
+        };
+        assert_eq!(nested(MAX_SEQUENCE_DEPTH).unwrap(), 1);
+        assert!(matches!(
+            nested(MAX_SEQUENCE_DEPTH + 1),
+            Err(InnerError::SequenceTooDeep { .. })
+        ));
+    }
+
+    #[test]
+    fn test_attribute_tag_length() {
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder.element(0x0020_9165, b"AT", &[0x20, 0x00, 0x32, 0x00, 0x20, 0x00]);
+        assert!(matches!(
+            parse_dicom(&builder.bytes),
+            Err(InnerError::InvalidValueLength {
+                tag: 0x0020_9165,
+                length: 6,
+                ..
+            })
+        ));
+
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder.element(0x0020_9165, b"AT", &[0x20, 0x00, 0x32, 0x00]);
+        let parsed = parse_dicom(&builder.bytes).unwrap();
+        assert_eq!(
+            parsed.dataset.get(&Tag::new(0x0020, 0x9165)).unwrap().value,
+            Some(vec![DicomValue::String("00200032".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_specific_character_set() {
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder
+            .element(0x0008_0005, b"CS", b"ISO_IR 100")
+            .element(0x0008_0090, b"PN", b"M\xFCller^Hans")
+            .sequence(
+                0x0008_1111,
+                &[&|item: &mut DicomBuilder| {
+                    item.element(0x0008_0005, b"CS", b"GB18030 ")
+                        .element(0x0008_1150, b"UI", b"1.2.3\0")
+                        .element(0x0010_0020, b"LO", b"\xD6\xD0\xCE\xC4");
+                }],
+            )
+            .element(0x0008_1030, b"LO", b"\xC9tude ")
+            .element(0x0010_0010, b"PN", b"\xC9mile^Zola");
+        let reader = round_trip(&builder.bytes, "specific_character_set", None);
+
+        assert_eq!(
+            reader.element(Tag::new(0x0008, 0x0090)).unwrap().value,
+            FieldValue::PersonName(PersonName::parse("Müller^Hans"))
+        );
+        match reader.element(Tag::new(0x0008, 0x1111)).unwrap().value {
+            FieldValue::Sequence(items) => assert_eq!(
+                items[0][&Tag::new(0x0010, 0x0020)].value,
+                FieldValue::String("中文".to_string())
+            ),
+            other => panic!("expected a sequence, got {other:?}"),
+        }
+        // the item's character set ends with the item
+        assert_eq!(
+            reader.element(Tag::new(0x0008, 0x1030)).unwrap().value,
+            FieldValue::String("Étude".to_string())
+        );
+    }
+
+    #[test]
+    fn test_iso_2022_person_name() {
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder
+            .element(0x0008_0005, b"CS", b"\\ISO 2022 IR 87 ")
+            .element(
+                0x0010_0010,
+                b"PN",
+                b"Yamada^Tarou=\x1B$B;3ED\x1B(B^\x1B$BB@O:\x1B(B=\x1B$B$d$^$@\x1B(B^\x1B$B$?$m$&\x1B(B ",
+            );
+        let parsed = parse_dicom(&builder.bytes).unwrap();
+        assert_eq!(
+            parsed.dataset.get(&Tag::new(0x0010, 0x0010)).unwrap().value,
+            Some(vec![DicomValue::PersonName(PersonName::parse(
+                "Yamada^Tarou=山田^太郎=やまだ^たろう"
+            ))])
+        );
+
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder
+            .element(0x0008_0005, b"CS", b"ISO 2022 IR 13\\ISO 2022 IR 87 ")
+            .element(0x0010_0010, b"PN", b"\xD4\xCF\xC0\xDE^\xC0\xDB\xB3 ");
+        let error = convert(&builder.bytes, "unsupported_character_set", None, false)
+            .err()
+            .unwrap();
+        assert!(error.is_unsupported());
+    }
+
+    #[test]
+    fn test_rle_lossless() {
+        let (builder, decoded) = rle_image(2);
+        let reader = round_trip(&builder.bytes, "rle_lossless", None);
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.dtype, Dtype::I16);
+        assert_eq!(pixel_array.shape, [2, 2, 3]);
+        assert_eq!(pixel_array.frames, Some(2));
+        assert_eq!(pixel_array.data, decoded);
+    }
+
+    #[test]
+    fn test_keep_encapsulated() {
+        let (builder, decoded) = rle_image(3);
+        let parsed = parse_dicom(&builder.bytes).unwrap();
+        let reader = convert(&builder.bytes, "rle_kept", None, true).unwrap();
+        assert!(matches!(
+            reader.pixel_array().err().unwrap(),
+            reader::Error::InvalidPixelArray {
+                source: pixel_array::Error::Encapsulated { .. },
+                ..
+            }
+        ));
+        let PixelData::Encapsulated(encapsulated) = reader.pixel_data().unwrap() else {
+            panic!("pixel data should be encapsulated");
+        };
+        assert_eq!(encapsulated.transfer_syntax, pixel_array::RLE_LOSSLESS);
+        assert_eq!(encapsulated.shape, [3, 2, 3]);
+        assert_eq!(encapsulated.frame_fragments, [1, 1, 1]);
+        let fragments = parsed.encapsulated.unwrap();
+        assert_eq!(encapsulated.offset_table, fragments.offset_table);
+        assert_eq!(encapsulated.fragments, fragments.fragments);
+
+        let pixel_data = reader.pixel_data().unwrap();
+        assert_eq!(pixel_data.to_owned_array().unwrap().data, decoded);
+        assert_eq!(pixel_data.gather_frames(&[2]).unwrap().data, &decoded[24..]);
+        let slices = [
+            Slice {
+                start: Some(1),
+                stop: None,
+                step: 1,
+            },
+            Slice::FULL,
+            Slice {
+                start: Some(2),
+                stop: None,
+                step: 1,
+            },
+        ];
+        let sliced = pixel_data.slice(&slices).unwrap();
+        assert_eq!(sliced.shape, [2, 2, 1]);
+        assert_eq!(
+            sliced.data,
+            [
+                &decoded[16..18],
+                &decoded[22..24],
+                &decoded[28..30],
+                &decoded[34..36]
+            ]
+            .concat()
+        );
+
+        assert!(matches!(
+            convert(&builder.bytes, "rle_kept_cast", Some(Dtype::F32), true)
+                .err()
+                .unwrap()
+                .0,
+            InnerError::KeptEncapsulatedOptions
+        ));
+    }
+
+    #[test]
+    fn test_frame_fragments() {
+        let fragments = Fragments {
+            // frames start at the first and third fragment items
+            offset_table: &[0, 0, 0, 0, 28, 0, 0, 0],
+            fragments: vec![&[0; 4], &[0; 8], &[0; 2]],
+        };
+        assert_eq!(frame_fragments(&fragments, 2).unwrap(), [2, 1]);
+        assert_eq!(frame_fragments(&fragments, 1).unwrap(), [3]);
+        assert_eq!(frame_fragments(&fragments, 3).unwrap(), [1, 1, 1]);
+
+        let fragments = Fragments {
+            offset_table: &[0, 0, 0, 0, 16, 0, 0, 0],
+            ..fragments
+        };
+        assert!(frame_fragments(&fragments, 2).is_err());
+        let fragments = Fragments {
+            offset_table: &[],
+            ..fragments
+        };
+        assert!(frame_fragments(&fragments, 2).is_err());
+    }
+
+    #[test]
+    fn test_encapsulated_pixel_data_is_unsupported() {
+        // JPEG 2000 can only be kept encapsulated
+        let mut builder = DicomBuilder::part10("1.2.840.10008.1.2.4.90");
+        builder
+            .element(0x0008_0016, b"UI", b"1.2.840.10008.5.1.4.1.1.2\0")
+            .element(0x0008_0018, b"UI", b"1.2.3.4\0")
+            .us(0x0028_0010, 2)
+            .us(0x0028_0011, 2)
+            .us(0x0028_0100, 8)
+            .encapsulated_pixel_data(&[], &[b"\xFF\x4F\xFF\x51", b"\xFF\xD9"]);
+        let error = convert(&builder.bytes, "jpeg_2000", None, false)
+            .err()
+            .unwrap();
+        assert!(error.is_unsupported());
+
+        let reader = convert(&builder.bytes, "jpeg_2000_kept", None, true).unwrap();
+        let pixel_data = reader.pixel_data().unwrap();
+        assert_eq!(pixel_data.transfer_syntax(), Some("1.2.840.10008.1.2.4.90"));
+        assert_eq!(pixel_data.shape(), [2, 2]);
+        assert!(matches!(
+            pixel_data.to_owned_array(),
+            Err(pixel_array::Error::Undecodable { .. })
+        ));
+    }
+}
@@ -0,0 +1,2093 @@
+//! The DICOM data dictionary, compiled in.
+//!
+//! [`ENTRIES`] holds the commonly used part of the PS3.6 registry of data elements, including
+//! the file meta and directory groups. `scripts/generate_dictionary.py` prints every entry of
+//! the registry in the same form from the DocBook source of PS3.6, to refresh or extend the
+//! table. Of the repeating groups only the overlays, `(60xx,eeee)`, are listed, under group 6000.
+
+use std::{collections::HashMap, fmt, sync::OnceLock};
+
+use crate::ir_to_dimble::VR;
+use crate::tag::Tag;
+
+/// A data element of the DICOM standard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DictionaryEntry {
+    pub tag: Tag,
+    pub keyword: &'static str,
+    /// The VR, or VRs like `US or SS` when it depends on the context
+    pub vr: &'static str,
+    /// The value multiplicity, like `1`, `1-n` or `2-2n`
+    pub vm: &'static str,
+    pub retired: bool,
+}
+
+impl DictionaryEntry {
+    /// Every VR the element may have
+    pub fn vrs(&self) -> impl Iterator<Item = VR> + '_ {
+        self.vr.split(" or ").map(|vr| {
+            vr.as_bytes()
+                .try_into()
+                .expect("dictionary VRs have 2 characters")
+        })
+    }
+
+    pub fn allows_vr(&self, vr: &VR) -> bool {
+        self.vrs().any(|allowed| allowed == *vr)
+    }
+
+    /// The VR to read the element with when the encoding does not say, as in implicit VR
+    /// datasets. Pixel data and the other `OB or OW` elements are OW in implicit VR.
+    pub fn implicit_vr(&self) -> VR {
+        if self.vr == "OB or OW" {
+            *b"OW"
+        } else {
+            self.vrs().next().expect("dictionary entries have a VR")
+        }
+    }
+
+    pub fn multiplicity(&self) -> Multiplicity {
+        self.vm
+            .parse()
+            .expect("dictionary entries have a valid value multiplicity")
+    }
+}
+
+/// A parsed value multiplicity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Multiplicity {
+    pub min: usize,
+    /// `None` when any number of values, in steps of `step`, may follow
+    pub max: Option<usize>,
+    pub step: usize,
+}
+
+impl Multiplicity {
+    /// Whether an element may have `count` values
+    pub fn allows(&self, count: usize) -> bool {
+        count >= self.min
+            && self.max.is_none_or(|max| count <= max)
+            && (count - self.min).is_multiple_of(self.step)
+    }
+}
+
+impl std::str::FromStr for Multiplicity {
+    type Err = ();
+
+    /// Parses `1`, `1-3`, `1-n` and `2-2n`
+    fn from_str(vm: &str) -> Result<Self, Self::Err> {
+        let number = |s: &str| s.parse::<usize>().map_err(|_| ());
+        let Some((min, max)) = vm.split_once('-') else {
+            let count = number(vm)?;
+            return Ok(Self {
+                min: count,
+                max: Some(count),
+                step: 1,
+            });
+        };
+        let min = number(min)?;
+        match max.strip_suffix('n') {
+            Some("") => Ok(Self {
+                min,
+                max: None,
+                step: 1,
+            }),
+            Some(step) => Ok(Self {
+                min,
+                max: None,
+                step: number(step)?,
+            }),
+            None => Ok(Self {
+                min,
+                max: Some(number(max)?),
+                step: 1,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for DictionaryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.tag, self.keyword)
+    }
+}
+
+/// Looks up the standard element with this tag. Overlay elements match in every group from
+/// 6000 to 601E.
+pub fn lookup(tag: Tag) -> Option<&'static DictionaryEntry> {
+    let tag = if tag.group & 0xFFE1 == 0x6000 {
+        Tag::new(0x6000, tag.element)
+    } else {
+        tag
+    };
+    ENTRIES
+        .binary_search_by_key(&tag, |entry| entry.tag)
+        .ok()
+        .map(|i| &ENTRIES[i])
+}
+
+/// Looks up the standard element with this keyword, such as `PixelData`
+pub fn lookup_keyword(keyword: &str) -> Option<&'static DictionaryEntry> {
+    static KEYWORDS: OnceLock<HashMap<&'static str, &'static DictionaryEntry>> = OnceLock::new();
+    KEYWORDS
+        .get_or_init(|| ENTRIES.iter().map(|entry| (entry.keyword, entry)).collect())
+        .get(keyword)
+        .copied()
+}
+
+const fn entry(
+    tag: u32,
+    keyword: &'static str,
+    vr: &'static str,
+    vm: &'static str,
+    retired: bool,
+) -> DictionaryEntry {
+    DictionaryEntry {
+        tag: Tag::new((tag >> 16) as u16, tag as u16),
+        keyword,
+        vr,
+        vm,
+        retired,
+    }
+}
+
+/// The elements, sorted by tag
+#[rustfmt::skip]
+pub static ENTRIES: &[DictionaryEntry] = &[
+    entry(0x0002_0000, "FileMetaInformationGroupLength", "UL", "1", false),
+    entry(0x0002_0001, "FileMetaInformationVersion", "OB", "1", false),
+    entry(0x0002_0002, "MediaStorageSOPClassUID", "UI", "1", false),
+    entry(0x0002_0003, "MediaStorageSOPInstanceUID", "UI", "1", false),
+    entry(0x0002_0010, "TransferSyntaxUID", "UI", "1", false),
+    entry(0x0002_0012, "ImplementationClassUID", "UI", "1", false),
+    entry(0x0002_0013, "ImplementationVersionName", "SH", "1", false),
+    entry(0x0002_0016, "SourceApplicationEntityTitle", "AE", "1", false),
+    entry(0x0002_0017, "SendingApplicationEntityTitle", "AE", "1", false),
+    entry(0x0002_0018, "ReceivingApplicationEntityTitle", "AE", "1", false),
+    entry(0x0002_0100, "PrivateInformationCreatorUID", "UI", "1", false),
+    entry(0x0002_0102, "PrivateInformation", "OB", "1", false),
+    entry(0x0004_1130, "FileSetID", "CS", "1", false),
+    entry(0x0004_1141, "FileSetDescriptorFileID", "CS", "1-8", false),
+    entry(0x0004_1142, "SpecificCharacterSetOfFileSetDescriptorFile", "CS", "1", false),
+    entry(0x0004_1200, "OffsetOfTheFirstDirectoryRecordOfTheRootDirectoryEntity", "UL", "1", false),
+    entry(0x0004_1202, "OffsetOfTheLastDirectoryRecordOfTheRootDirectoryEntity", "UL", "1", false),
+    entry(0x0004_1212, "FileSetConsistencyFlag", "US", "1", false),
+    entry(0x0004_1220, "DirectoryRecordSequence", "SQ", "1", false),
+    entry(0x0004_1400, "OffsetOfTheNextDirectoryRecord", "UL", "1", false),
+    entry(0x0004_1410, "RecordInUseFlag", "US", "1", false),
+    entry(0x0004_1420, "OffsetOfReferencedLowerLevelDirectoryEntity", "UL", "1", false),
+    entry(0x0004_1430, "DirectoryRecordType", "CS", "1", false),
+    entry(0x0004_1432, "PrivateRecordUID", "UI", "1", false),
+    entry(0x0004_1500, "ReferencedFileID", "CS", "1-8", false),
+    entry(0x0004_1504, "MRDRDirectoryRecordOffset", "UL", "1", true),
+    entry(0x0004_1510, "ReferencedSOPClassUIDInFile", "UI", "1", false),
+    entry(0x0004_1511, "ReferencedSOPInstanceUIDInFile", "UI", "1", false),
+    entry(0x0004_1512, "ReferencedTransferSyntaxUIDInFile", "UI", "1", false),
+    entry(0x0004_151A, "ReferencedRelatedGeneralSOPClassUIDInFile", "UI", "1-n", false),
+    entry(0x0004_1600, "NumberOfReferences", "UL", "1", true),
+    entry(0x0008_0001, "LengthToEnd", "UL", "1", true),
+    entry(0x0008_0005, "SpecificCharacterSet", "CS", "1-n", false),
+    entry(0x0008_0006, "LanguageCodeSequence", "SQ", "1", false),
+    entry(0x0008_0008, "ImageType", "CS", "2-n", false),
+    entry(0x0008_0010, "RecognitionCode", "SH", "1", true),
+    entry(0x0008_0012, "InstanceCreationDate", "DA", "1", false),
+    entry(0x0008_0013, "InstanceCreationTime", "TM", "1", false),
+    entry(0x0008_0014, "InstanceCreatorUID", "UI", "1", false),
+    entry(0x0008_0015, "InstanceCoercionDateTime", "DT", "1", false),
+    entry(0x0008_0016, "SOPClassUID", "UI", "1", false),
+    entry(0x0008_0018, "SOPInstanceUID", "UI", "1", false),
+    entry(0x0008_001A, "RelatedGeneralSOPClassUID", "UI", "1-n", false),
+    entry(0x0008_001B, "OriginalSpecializedSOPClassUID", "UI", "1", false),
+    entry(0x0008_0020, "StudyDate", "DA", "1", false),
+    entry(0x0008_0021, "SeriesDate", "DA", "1", false),
+    entry(0x0008_0022, "AcquisitionDate", "DA", "1", false),
+    entry(0x0008_0023, "ContentDate", "DA", "1", false),
+    entry(0x0008_0024, "OverlayDate", "DA", "1", true),
+    entry(0x0008_0025, "CurveDate", "DA", "1", true),
+    entry(0x0008_002A, "AcquisitionDateTime", "DT", "1", false),
+    entry(0x0008_0030, "StudyTime", "TM", "1", false),
+    entry(0x0008_0031, "SeriesTime", "TM", "1", false),
+    entry(0x0008_0032, "AcquisitionTime", "TM", "1", false),
+    entry(0x0008_0033, "ContentTime", "TM", "1", false),
+    entry(0x0008_0034, "OverlayTime", "TM", "1", true),
+    entry(0x0008_0035, "CurveTime", "TM", "1", true),
+    entry(0x0008_0040, "DataSetType", "US", "1", true),
+    entry(0x0008_0041, "DataSetSubtype", "LO", "1", true),
+    entry(0x0008_0042, "NuclearMedicineSeriesType", "CS", "1", true),
+    entry(0x0008_0050, "AccessionNumber", "SH", "1", false),
+    entry(0x0008_0051, "IssuerOfAccessionNumberSequence", "SQ", "1", false),
+    entry(0x0008_0052, "QueryRetrieveLevel", "CS", "1", false),
+    entry(0x0008_0053, "QueryRetrieveView", "CS", "1", false),
+    entry(0x0008_0054, "RetrieveAETitle", "AE", "1-n", false),
+    entry(0x0008_0055, "StationAETitle", "AE", "1", false),
+    entry(0x0008_0056, "InstanceAvailability", "CS", "1", false),
+    entry(0x0008_0058, "FailedSOPInstanceUIDList", "UI", "1-n", false),
+    entry(0x0008_0060, "Modality", "CS", "1", false),
+    entry(0x0008_0061, "ModalitiesInStudy", "CS", "1-n", false),
+    entry(0x0008_0062, "SOPClassesInStudy", "UI", "1-n", false),
+    entry(0x0008_0064, "ConversionType", "CS", "1", false),
+    entry(0x0008_0068, "PresentationIntentType", "CS", "1", false),
+    entry(0x0008_0070, "Manufacturer", "LO", "1", false),
+    entry(0x0008_0080, "InstitutionName", "LO", "1", false),
+    entry(0x0008_0081, "InstitutionAddress", "ST", "1", false),
+    entry(0x0008_0082, "InstitutionCodeSequence", "SQ", "1", false),
+    entry(0x0008_0090, "ReferringPhysicianName", "PN", "1", false),
+    entry(0x0008_0092, "ReferringPhysicianAddress", "ST", "1", false),
+    entry(0x0008_0094, "ReferringPhysicianTelephoneNumbers", "SH", "1-n", false),
+    entry(0x0008_0096, "ReferringPhysicianIdentificationSequence", "SQ", "1", false),
+    entry(0x0008_0100, "CodeValue", "SH", "1", false),
+    entry(0x0008_0101, "ExtendedCodeValue", "LO", "1", false),
+    entry(0x0008_0102, "CodingSchemeDesignator", "SH", "1", false),
+    entry(0x0008_0103, "CodingSchemeVersion", "SH", "1", false),
+    entry(0x0008_0104, "CodeMeaning", "LO", "1", false),
+    entry(0x0008_0105, "MappingResource", "CS", "1", false),
+    entry(0x0008_0106, "ContextGroupVersion", "DT", "1", false),
+    entry(0x0008_0107, "ContextGroupLocalVersion", "DT", "1", false),
+    entry(0x0008_0108, "ExtendedCodeMeaning", "LT", "1", false),
+    entry(0x0008_010B, "ContextGroupExtensionFlag", "CS", "1", false),
+    entry(0x0008_010C, "CodingSchemeUID", "UI", "1", false),
+    entry(0x0008_010D, "ContextGroupExtensionCreatorUID", "UI", "1", false),
+    entry(0x0008_010F, "ContextIdentifier", "CS", "1", false),
+    entry(0x0008_0110, "CodingSchemeIdentificationSequence", "SQ", "1", false),
+    entry(0x0008_0112, "CodingSchemeRegistry", "LO", "1", false),
+    entry(0x0008_0114, "CodingSchemeExternalID", "ST", "1", false),
+    entry(0x0008_0115, "CodingSchemeName", "ST", "1", false),
+    entry(0x0008_0116, "CodingSchemeResponsibleOrganization", "ST", "1", false),
+    entry(0x0008_0117, "ContextUID", "UI", "1", false),
+    entry(0x0008_0118, "MappingResourceUID", "UI", "1", false),
+    entry(0x0008_0119, "LongCodeValue", "UC", "1", false),
+    entry(0x0008_0120, "URNCodeValue", "UR", "1", false),
+    entry(0x0008_0121, "EquivalentCodeSequence", "SQ", "1", false),
+    entry(0x0008_0122, "MappingResourceName", "LO", "1", false),
+    entry(0x0008_0123, "ContextGroupIdentificationSequence", "SQ", "1", false),
+    entry(0x0008_0124, "MappingResourceIdentificationSequence", "SQ", "1", false),
+    entry(0x0008_0201, "TimezoneOffsetFromUTC", "SH", "1", false),
+    entry(0x0008_0220, "ResponsibleGroupCodeSequence", "SQ", "1", false),
+    entry(0x0008_1000, "NetworkID", "AE", "1", true),
+    entry(0x0008_1010, "StationName", "SH", "1", false),
+    entry(0x0008_1030, "StudyDescription", "LO", "1", false),
+    entry(0x0008_1032, "ProcedureCodeSequence", "SQ", "1", false),
+    entry(0x0008_103E, "SeriesDescription", "LO", "1", false),
+    entry(0x0008_103F, "SeriesDescriptionCodeSequence", "SQ", "1", false),
+    entry(0x0008_1040, "InstitutionalDepartmentName", "LO", "1", false),
+    entry(0x0008_1041, "InstitutionalDepartmentTypeCodeSequence", "SQ", "1", false),
+    entry(0x0008_1048, "PhysiciansOfRecord", "PN", "1-n", false),
+    entry(0x0008_1049, "PhysiciansOfRecordIdentificationSequence", "SQ", "1", false),
+    entry(0x0008_1050, "PerformingPhysicianName", "PN", "1-n", false),
+    entry(0x0008_1052, "PerformingPhysicianIdentificationSequence", "SQ", "1", false),
+    entry(0x0008_1060, "NameOfPhysiciansReadingStudy", "PN", "1-n", false),
+    entry(0x0008_1062, "PhysiciansReadingStudyIdentificationSequence", "SQ", "1", false),
+    entry(0x0008_1070, "OperatorsName", "PN", "1-n", false),
+    entry(0x0008_1072, "OperatorIdentificationSequence", "SQ", "1", false),
+    entry(0x0008_1080, "AdmittingDiagnosesDescription", "LO", "1-n", false),
+    entry(0x0008_1084, "AdmittingDiagnosesCodeSequence", "SQ", "1", false),
+    entry(0x0008_1090, "ManufacturerModelName", "LO", "1", false),
+    entry(0x0008_1100, "ReferencedResultsSequence", "SQ", "1", true),
+    entry(0x0008_1110, "ReferencedStudySequence", "SQ", "1", false),
+    entry(0x0008_1111, "ReferencedPerformedProcedureStepSequence", "SQ", "1", false),
+    entry(0x0008_1112, "ReferencedInstancesBySOPClassSequence", "SQ", "1", false),
+    entry(0x0008_1115, "ReferencedSeriesSequence", "SQ", "1", false),
+    entry(0x0008_1120, "ReferencedPatientSequence", "SQ", "1", false),
+    entry(0x0008_1125, "ReferencedVisitSequence", "SQ", "1", false),
+    entry(0x0008_1130, "ReferencedOverlaySequence", "SQ", "1", true),
+    entry(0x0008_1134, "ReferencedStereometricInstanceSequence", "SQ", "1", false),
+    entry(0x0008_113A, "ReferencedWaveformSequence", "SQ", "1", false),
+    entry(0x0008_1140, "ReferencedImageSequence", "SQ", "1", false),
+    entry(0x0008_1145, "ReferencedCurveSequence", "SQ", "1", true),
+    entry(0x0008_114A, "ReferencedInstanceSequence", "SQ", "1", false),
+    entry(0x0008_114B, "ReferencedRealWorldValueMappingInstanceSequence", "SQ", "1", false),
+    entry(0x0008_1150, "ReferencedSOPClassUID", "UI", "1", false),
+    entry(0x0008_1155, "ReferencedSOPInstanceUID", "UI", "1", false),
+    entry(0x0008_1156, "DefinitionSourceSequence", "SQ", "1", false),
+    entry(0x0008_115A, "SOPClassesSupported", "UI", "1-n", false),
+    entry(0x0008_1160, "ReferencedFrameNumber", "IS", "1-n", false),
+    entry(0x0008_1161, "SimpleFrameList", "UL", "1-n", false),
+    entry(0x0008_1162, "CalculatedFrameList", "UL", "3-3n", false),
+    entry(0x0008_1163, "TimeRange", "FD", "2", false),
+    entry(0x0008_1164, "FrameExtractionSequence", "SQ", "1", false),
+    entry(0x0008_1190, "RetrieveURL", "UR", "1", false),
+    entry(0x0008_1195, "TransactionUID", "UI", "1", false),
+    entry(0x0008_1196, "WarningReason", "US", "1", false),
+    entry(0x0008_1197, "FailureReason", "US", "1", false),
+    entry(0x0008_1198, "FailedSOPSequence", "SQ", "1", false),
+    entry(0x0008_1199, "ReferencedSOPSequence", "SQ", "1", false),
+    entry(0x0008_119A, "OtherFailuresSequence", "SQ", "1", false),
+    entry(0x0008_1200, "StudiesContainingOtherReferencedInstancesSequence", "SQ", "1", false),
+    entry(0x0008_1250, "RelatedSeriesSequence", "SQ", "1", false),
+    entry(0x0008_2110, "LossyImageCompressionRetired", "CS", "1", true),
+    entry(0x0008_2111, "DerivationDescription", "ST", "1", false),
+    entry(0x0008_2112, "SourceImageSequence", "SQ", "1", false),
+    entry(0x0008_2120, "StageName", "SH", "1", false),
+    entry(0x0008_2122, "StageNumber", "IS", "1", false),
+    entry(0x0008_2124, "NumberOfStages", "IS", "1", false),
+    entry(0x0008_2127, "ViewName", "SH", "1", false),
+    entry(0x0008_2128, "ViewNumber", "IS", "1", false),
+    entry(0x0008_2129, "NumberOfEventTimers", "IS", "1", false),
+    entry(0x0008_212A, "NumberOfViewsInStage", "IS", "1", false),
+    entry(0x0008_2130, "EventElapsedTimes", "DS", "1-n", false),
+    entry(0x0008_2132, "EventTimerNames", "LO", "1-n", false),
+    entry(0x0008_2133, "EventTimerSequence", "SQ", "1", false),
+    entry(0x0008_2134, "EventTimeOffset", "FD", "1", false),
+    entry(0x0008_2135, "EventCodeSequence", "SQ", "1", false),
+    entry(0x0008_2142, "StartTrim", "IS", "1", false),
+    entry(0x0008_2143, "StopTrim", "IS", "1", false),
+    entry(0x0008_2144, "RecommendedDisplayFrameRate", "IS", "1", false),
+    entry(0x0008_2200, "TransducerPosition", "CS", "1", true),
+    entry(0x0008_2204, "TransducerOrientation", "CS", "1", true),
+    entry(0x0008_2208, "AnatomicStructure", "CS", "1", true),
+    entry(0x0008_2218, "AnatomicRegionSequence", "SQ", "1", false),
+    entry(0x0008_2220, "AnatomicRegionModifierSequence", "SQ", "1", false),
+    entry(0x0008_2228, "PrimaryAnatomicStructureSequence", "SQ", "1", false),
+    entry(0x0008_2229, "AnatomicStructureSpaceOrRegionSequence", "SQ", "1", true),
+    entry(0x0008_2230, "PrimaryAnatomicStructureModifierSequence", "SQ", "1", false),
+    entry(0x0008_3001, "AlternateRepresentationSequence", "SQ", "1", false),
+    entry(0x0008_3010, "IrradiationEventUID", "UI", "1-n", false),
+    entry(0x0008_3011, "SourceIrradiationEventSequence", "SQ", "1", false),
+    entry(0x0008_3012, "RadiopharmaceuticalAdministrationEventUID", "UI", "1", false),
+    entry(0x0008_4000, "IdentifyingComments", "LT", "1", true),
+    entry(0x0008_9007, "FrameType", "CS", "4", false),
+    entry(0x0008_9092, "ReferencedImageEvidenceSequence", "SQ", "1", false),
+    entry(0x0008_9121, "ReferencedRawDataSequence", "SQ", "1", false),
+    entry(0x0008_9123, "CreatorVersionUID", "UI", "1", false),
+    entry(0x0008_9124, "DerivationImageSequence", "SQ", "1", false),
+    entry(0x0008_9154, "SourceImageEvidenceSequence", "SQ", "1", false),
+    entry(0x0008_9205, "PixelPresentation", "CS", "1", false),
+    entry(0x0008_9206, "VolumetricProperties", "CS", "1", false),
+    entry(0x0008_9207, "VolumeBasedCalculationTechnique", "CS", "1", false),
+    entry(0x0008_9208, "ComplexImageComponent", "CS", "1", false),
+    entry(0x0008_9209, "AcquisitionContrast", "CS", "1", false),
+    entry(0x0008_9215, "DerivationCodeSequence", "SQ", "1", false),
+    entry(0x0008_9237, "ReferencedPresentationStateSequence", "SQ", "1", false),
+    entry(0x0008_9410, "ReferencedOtherPlaneSequence", "SQ", "1", false),
+    entry(0x0008_9458, "FrameDisplaySequence", "SQ", "1", false),
+    entry(0x0008_9459, "RecommendedDisplayFrameRateInFloat", "FL", "1", false),
+    entry(0x0008_9460, "SkipFrameRangeFlag", "CS", "1", false),
+    entry(0x0010_0010, "PatientName", "PN", "1", false),
+    entry(0x0010_0020, "PatientID", "LO", "1", false),
+    entry(0x0010_0021, "IssuerOfPatientID", "LO", "1", false),
+    entry(0x0010_0022, "TypeOfPatientID", "CS", "1", false),
+    entry(0x0010_0024, "IssuerOfPatientIDQualifiersSequence", "SQ", "1", false),
+    entry(0x0010_0030, "PatientBirthDate", "DA", "1", false),
+    entry(0x0010_0032, "PatientBirthTime", "TM", "1", false),
+    entry(0x0010_0040, "PatientSex", "CS", "1", false),
+    entry(0x0010_0050, "PatientInsurancePlanCodeSequence", "SQ", "1", false),
+    entry(0x0010_0101, "PatientPrimaryLanguageCodeSequence", "SQ", "1", false),
+    entry(0x0010_0102, "PatientPrimaryLanguageModifierCodeSequence", "SQ", "1", false),
+    entry(0x0010_0200, "QualityControlSubject", "CS", "1", false),
+    entry(0x0010_0201, "QualityControlSubjectTypeCodeSequence", "SQ", "1", false),
+    entry(0x0010_1000, "OtherPatientIDs", "LO", "1-n", true),
+    entry(0x0010_1001, "OtherPatientNames", "PN", "1-n", false),
+    entry(0x0010_1002, "OtherPatientIDsSequence", "SQ", "1", false),
+    entry(0x0010_1005, "PatientBirthName", "PN", "1", false),
+    entry(0x0010_1010, "PatientAge", "AS", "1", false),
+    entry(0x0010_1020, "PatientSize", "DS", "1", false),
+    entry(0x0010_1021, "PatientSizeCodeSequence", "SQ", "1", false),
+    entry(0x0010_1022, "PatientBodyMassIndex", "DS", "1", false),
+    entry(0x0010_1023, "MeasuredAPDimension", "DS", "1", false),
+    entry(0x0010_1024, "MeasuredLateralDimension", "DS", "1", false),
+    entry(0x0010_1030, "PatientWeight", "DS", "1", false),
+    entry(0x0010_1040, "PatientAddress", "LO", "1", false),
+    entry(0x0010_1050, "InsurancePlanIdentification", "LO", "1-n", true),
+    entry(0x0010_1060, "PatientMotherBirthName", "PN", "1", false),
+    entry(0x0010_1080, "MilitaryRank", "LO", "1", false),
+    entry(0x0010_1081, "BranchOfService", "LO", "1", false),
+    entry(0x0010_1090, "MedicalRecordLocator", "LO", "1", true),
+    entry(0x0010_1100, "ReferencedPatientPhotoSequence", "SQ", "1", false),
+    entry(0x0010_2000, "MedicalAlerts", "LO", "1-n", false),
+    entry(0x0010_2110, "Allergies", "LO", "1-n", false),
+    entry(0x0010_2150, "CountryOfResidence", "LO", "1", false),
+    entry(0x0010_2152, "RegionOfResidence", "LO", "1", false),
+    entry(0x0010_2154, "PatientTelephoneNumbers", "SH", "1-n", false),
+    entry(0x0010_2155, "PatientTelecomInformation", "LT", "1", false),
+    entry(0x0010_2160, "EthnicGroup", "SH", "1", false),
+    entry(0x0010_2180, "Occupation", "SH", "1", false),
+    entry(0x0010_21A0, "SmokingStatus", "CS", "1", false),
+    entry(0x0010_21B0, "AdditionalPatientHistory", "LT", "1", false),
+    entry(0x0010_21C0, "PregnancyStatus", "US", "1", false),
+    entry(0x0010_21D0, "LastMenstrualDate", "DA", "1", false),
+    entry(0x0010_21F0, "PatientReligiousPreference", "LO", "1", false),
+    entry(0x0010_2201, "PatientSpeciesDescription", "LO", "1", false),
+    entry(0x0010_2202, "PatientSpeciesCodeSequence", "SQ", "1", false),
+    entry(0x0010_2203, "PatientSexNeutered", "CS", "1", false),
+    entry(0x0010_2210, "AnatomicalOrientationType", "CS", "1", false),
+    entry(0x0010_2292, "PatientBreedDescription", "LO", "1", false),
+    entry(0x0010_2293, "PatientBreedCodeSequence", "SQ", "1", false),
+    entry(0x0010_2294, "BreedRegistrationSequence", "SQ", "1", false),
+    entry(0x0010_2295, "BreedRegistrationNumber", "LO", "1", false),
+    entry(0x0010_2296, "BreedRegistryCodeSequence", "SQ", "1", false),
+    entry(0x0010_2297, "ResponsiblePerson", "PN", "1", false),
+    entry(0x0010_2298, "ResponsiblePersonRole", "CS", "1", false),
+    entry(0x0010_2299, "ResponsibleOrganization", "LO", "1", false),
+    entry(0x0010_4000, "PatientComments", "LT", "1", false),
+    entry(0x0010_9431, "ExaminedBodyThickness", "FL", "1", false),
+    entry(0x0012_0010, "ClinicalTrialSponsorName", "LO", "1", false),
+    entry(0x0012_0020, "ClinicalTrialProtocolID", "LO", "1", false),
+    entry(0x0012_0021, "ClinicalTrialProtocolName", "LO", "1", false),
+    entry(0x0012_0030, "ClinicalTrialSiteID", "LO", "1", false),
+    entry(0x0012_0031, "ClinicalTrialSiteName", "LO", "1", false),
+    entry(0x0012_0040, "ClinicalTrialSubjectID", "LO", "1", false),
+    entry(0x0012_0042, "ClinicalTrialSubjectReadingID", "LO", "1", false),
+    entry(0x0012_0050, "ClinicalTrialTimePointID", "LO", "1", false),
+    entry(0x0012_0051, "ClinicalTrialTimePointDescription", "ST", "1", false),
+    entry(0x0012_0060, "ClinicalTrialCoordinatingCenterName", "LO", "1", false),
+    entry(0x0012_0062, "PatientIdentityRemoved", "CS", "1", false),
+    entry(0x0012_0063, "DeidentificationMethod", "LO", "1-n", false),
+    entry(0x0012_0064, "DeidentificationMethodCodeSequence", "SQ", "1", false),
+    entry(0x0012_0071, "ClinicalTrialSeriesID", "LO", "1", false),
+    entry(0x0012_0072, "ClinicalTrialSeriesDescription", "LO", "1", false),
+    entry(0x0012_0081, "ClinicalTrialProtocolEthicsCommitteeName", "LO", "1", false),
+    entry(0x0012_0082, "ClinicalTrialProtocolEthicsCommitteeApprovalNumber", "LO", "1", false),
+    entry(0x0012_0083, "ConsentForClinicalTrialUseSequence", "SQ", "1", false),
+    entry(0x0012_0084, "DistributionType", "CS", "1", false),
+    entry(0x0012_0085, "ConsentForDistributionFlag", "CS", "1", false),
+    entry(0x0018_0010, "ContrastBolusAgent", "LO", "1", false),
+    entry(0x0018_0012, "ContrastBolusAgentSequence", "SQ", "1", false),
+    entry(0x0018_0013, "ContrastBolusT1Relaxivity", "FL", "1", false),
+    entry(0x0018_0014, "ContrastBolusAdministrationRouteSequence", "SQ", "1", false),
+    entry(0x0018_0015, "BodyPartExamined", "CS", "1", false),
+    entry(0x0018_0020, "ScanningSequence", "CS", "1-n", false),
+    entry(0x0018_0021, "SequenceVariant", "CS", "1-n", false),
+    entry(0x0018_0022, "ScanOptions", "CS", "1-n", false),
+    entry(0x0018_0023, "MRAcquisitionType", "CS", "1", false),
+    entry(0x0018_0024, "SequenceName", "SH", "1", false),
+    entry(0x0018_0025, "AngioFlag", "CS", "1", false),
+    entry(0x0018_0026, "InterventionDrugInformationSequence", "SQ", "1", false),
+    entry(0x0018_0027, "InterventionDrugStopTime", "TM", "1", false),
+    entry(0x0018_0028, "InterventionDrugDose", "DS", "1", false),
+    entry(0x0018_0029, "InterventionDrugCodeSequence", "SQ", "1", false),
+    entry(0x0018_002A, "AdditionalDrugSequence", "SQ", "1", false),
+    entry(0x0018_0030, "Radionuclide", "LO", "1-n", true),
+    entry(0x0018_0031, "Radiopharmaceutical", "LO", "1", false),
+    entry(0x0018_0032, "EnergyWindowCenterline", "DS", "1", true),
+    entry(0x0018_0033, "EnergyWindowTotalWidth", "DS", "1-n", true),
+    entry(0x0018_0034, "InterventionDrugName", "LO", "1", false),
+    entry(0x0018_0035, "InterventionDrugStartTime", "TM", "1", false),
+    entry(0x0018_0036, "InterventionSequence", "SQ", "1", false),
+    entry(0x0018_0037, "TherapyType", "CS", "1", true),
+    entry(0x0018_0038, "InterventionStatus", "CS", "1", false),
+    entry(0x0018_0039, "TherapyDescription", "CS", "1", true),
+    entry(0x0018_003A, "InterventionDescription", "ST", "1", false),
+    entry(0x0018_0040, "CineRate", "IS", "1", false),
+    entry(0x0018_0042, "InitialCineRunState", "CS", "1", false),
+    entry(0x0018_0050, "SliceThickness", "DS", "1", false),
+    entry(0x0018_0060, "KVP", "DS", "1", false),
+    entry(0x0018_0070, "CountsAccumulated", "IS", "1", false),
+    entry(0x0018_0071, "AcquisitionTerminationCondition", "CS", "1", false),
+    entry(0x0018_0072, "EffectiveDuration", "DS", "1", false),
+    entry(0x0018_0073, "AcquisitionStartCondition", "CS", "1", false),
+    entry(0x0018_0074, "AcquisitionStartConditionData", "IS", "1", false),
+    entry(0x0018_0075, "AcquisitionTerminationConditionData", "IS", "1", false),
+    entry(0x0018_0080, "RepetitionTime", "DS", "1", false),
+    entry(0x0018_0081, "EchoTime", "DS", "1", false),
+    entry(0x0018_0082, "InversionTime", "DS", "1", false),
+    entry(0x0018_0083, "NumberOfAverages", "DS", "1", false),
+    entry(0x0018_0084, "ImagingFrequency", "DS", "1", false),
+    entry(0x0018_0085, "ImagedNucleus", "SH", "1", false),
+    entry(0x0018_0086, "EchoNumbers", "IS", "1-n", false),
+    entry(0x0018_0087, "MagneticFieldStrength", "DS", "1", false),
+    entry(0x0018_0088, "SpacingBetweenSlices", "DS", "1", false),
+    entry(0x0018_0089, "NumberOfPhaseEncodingSteps", "IS", "1", false),
+    entry(0x0018_0090, "DataCollectionDiameter", "DS", "1", false),
+    entry(0x0018_0091, "EchoTrainLength", "IS", "1", false),
+    entry(0x0018_0093, "PercentSampling", "DS", "1", false),
+    entry(0x0018_0094, "PercentPhaseFieldOfView", "DS", "1", false),
+    entry(0x0018_0095, "PixelBandwidth", "DS", "1", false),
+    entry(0x0018_1000, "DeviceSerialNumber", "LO", "1", false),
+    entry(0x0018_1002, "DeviceUID", "UI", "1", false),
+    entry(0x0018_1003, "DeviceID", "LO", "1", false),
+    entry(0x0018_1004, "PlateID", "LO", "1", false),
+    entry(0x0018_1005, "GeneratorID", "LO", "1", false),
+    entry(0x0018_1006, "GridID", "LO", "1", false),
+    entry(0x0018_1007, "CassetteID", "LO", "1", false),
+    entry(0x0018_1008, "GantryID", "LO", "1", false),
+    entry(0x0018_1010, "SecondaryCaptureDeviceID", "LO", "1", false),
+    entry(0x0018_1011, "HardcopyCreationDeviceID", "LO", "1", true),
+    entry(0x0018_1012, "DateOfSecondaryCapture", "DA", "1", false),
+    entry(0x0018_1014, "TimeOfSecondaryCapture", "TM", "1", false),
+    entry(0x0018_1016, "SecondaryCaptureDeviceManufacturer", "LO", "1", false),
+    entry(0x0018_1017, "HardcopyDeviceManufacturer", "LO", "1", true),
+    entry(0x0018_1018, "SecondaryCaptureDeviceManufacturerModelName", "LO", "1", false),
+    entry(0x0018_1019, "SecondaryCaptureDeviceSoftwareVersions", "LO", "1-n", false),
+    entry(0x0018_101A, "HardcopyDeviceSoftwareVersion", "LO", "1-n", true),
+    entry(0x0018_101B, "HardcopyDeviceManufacturerModelName", "LO", "1", true),
+    entry(0x0018_1020, "SoftwareVersions", "LO", "1-n", false),
+    entry(0x0018_1022, "VideoImageFormatAcquired", "SH", "1", false),
+    entry(0x0018_1023, "DigitalImageFormatAcquired", "LO", "1", false),
+    entry(0x0018_1030, "ProtocolName", "LO", "1", false),
+    entry(0x0018_1040, "ContrastBolusRoute", "LO", "1", false),
+    entry(0x0018_1041, "ContrastBolusVolume", "DS", "1", false),
+    entry(0x0018_1042, "ContrastBolusStartTime", "TM", "1", false),
+    entry(0x0018_1043, "ContrastBolusStopTime", "TM", "1", false),
+    entry(0x0018_1044, "ContrastBolusTotalDose", "DS", "1", false),
+    entry(0x0018_1045, "SyringeCounts", "IS", "1", false),
+    entry(0x0018_1046, "ContrastFlowRate", "DS", "1-n", false),
+    entry(0x0018_1047, "ContrastFlowDuration", "DS", "1-n", false),
+    entry(0x0018_1048, "ContrastBolusIngredient", "CS", "1", false),
+    entry(0x0018_1049, "ContrastBolusIngredientConcentration", "DS", "1", false),
+    entry(0x0018_1050, "SpatialResolution", "DS", "1", false),
+    entry(0x0018_1060, "TriggerTime", "DS", "1", false),
+    entry(0x0018_1061, "TriggerSourceOrType", "LO", "1", false),
+    entry(0x0018_1062, "NominalInterval", "IS", "1", false),
+    entry(0x0018_1063, "FrameTime", "DS", "1", false),
+    entry(0x0018_1064, "CardiacFramingType", "LO", "1", false),
+    entry(0x0018_1065, "FrameTimeVector", "DS", "1-n", false),
+    entry(0x0018_1066, "FrameDelay", "DS", "1", false),
+    entry(0x0018_1067, "ImageTriggerDelay", "DS", "1", false),
+    entry(0x0018_1068, "MultiplexGroupTimeOffset", "DS", "1", false),
+    entry(0x0018_1069, "TriggerTimeOffset", "DS", "1", false),
+    entry(0x0018_106A, "SynchronizationTrigger", "CS", "1", false),
+    entry(0x0018_106C, "SynchronizationChannel", "US", "2", false),
+    entry(0x0018_106E, "TriggerSamplePosition", "UL", "1", false),
+    entry(0x0018_1070, "RadiopharmaceuticalRoute", "LO", "1", false),
+    entry(0x0018_1071, "RadiopharmaceuticalVolume", "DS", "1", false),
+    entry(0x0018_1072, "RadiopharmaceuticalStartTime", "TM", "1", false),
+    entry(0x0018_1073, "RadiopharmaceuticalStopTime", "TM", "1", false),
+    entry(0x0018_1074, "RadionuclideTotalDose", "DS", "1", false),
+    entry(0x0018_1075, "RadionuclideHalfLife", "DS", "1", false),
+    entry(0x0018_1076, "RadionuclidePositronFraction", "DS", "1", false),
+    entry(0x0018_1077, "RadiopharmaceuticalSpecificActivity", "DS", "1", false),
+    entry(0x0018_1078, "RadiopharmaceuticalStartDateTime", "DT", "1", false),
+    entry(0x0018_1079, "RadiopharmaceuticalStopDateTime", "DT", "1", false),
+    entry(0x0018_1080, "BeatRejectionFlag", "CS", "1", false),
+    entry(0x0018_1081, "LowRRValue", "IS", "1", false),
+    entry(0x0018_1082, "HighRRValue", "IS", "1", false),
+    entry(0x0018_1083, "IntervalsAcquired", "IS", "1", false),
+    entry(0x0018_1084, "IntervalsRejected", "IS", "1", false),
+    entry(0x0018_1085, "PVCRejection", "LO", "1", false),
+    entry(0x0018_1086, "SkipBeats", "IS", "1", false),
+    entry(0x0018_1088, "HeartRate", "IS", "1", false),
+    entry(0x0018_1090, "CardiacNumberOfImages", "IS", "1", false),
+    entry(0x0018_1094, "TriggerWindow", "IS", "1", false),
+    entry(0x0018_1100, "ReconstructionDiameter", "DS", "1", false),
+    entry(0x0018_1110, "DistanceSourceToDetector", "DS", "1", false),
+    entry(0x0018_1111, "DistanceSourceToPatient", "DS", "1", false),
+    entry(0x0018_1114, "EstimatedRadiographicMagnificationFactor", "DS", "1", false),
+    entry(0x0018_1120, "GantryDetectorTilt", "DS", "1", false),
+    entry(0x0018_1121, "GantryDetectorSlew", "DS", "1", false),
+    entry(0x0018_1130, "TableHeight", "DS", "1", false),
+    entry(0x0018_1131, "TableTraverse", "DS", "1", false),
+    entry(0x0018_1134, "TableMotion", "CS", "1", false),
+    entry(0x0018_1135, "TableVerticalIncrement", "DS", "1-n", false),
+    entry(0x0018_1136, "TableLateralIncrement", "DS", "1-n", false),
+    entry(0x0018_1137, "TableLongitudinalIncrement", "DS", "1-n", false),
+    entry(0x0018_1138, "TableAngle", "DS", "1", false),
+    entry(0x0018_113A, "TableType", "CS", "1", false),
+    entry(0x0018_1140, "RotationDirection", "CS", "1", false),
+    entry(0x0018_1141, "AngularPosition", "DS", "1", true),
+    entry(0x0018_1142, "RadialPosition", "DS", "1-n", false),
+    entry(0x0018_1143, "ScanArc", "DS", "1", false),
+    entry(0x0018_1144, "AngularStep", "DS", "1", false),
+    entry(0x0018_1145, "CenterOfRotationOffset", "DS", "1", false),
+    entry(0x0018_1146, "RotationOffset", "DS", "1-n", true),
+    entry(0x0018_1147, "FieldOfViewShape", "CS", "1", false),
+    entry(0x0018_1149, "FieldOfViewDimensions", "IS", "1-2", false),
+    entry(0x0018_1150, "ExposureTime", "IS", "1", false),
+    entry(0x0018_1151, "XRayTubeCurrent", "IS", "1", false),
+    entry(0x0018_1152, "Exposure", "IS", "1", false),
+    entry(0x0018_1153, "ExposureInuAs", "IS", "1", false),
+    entry(0x0018_1154, "AveragePulseWidth", "DS", "1", false),
+    entry(0x0018_1155, "RadiationSetting", "CS", "1", false),
+    entry(0x0018_1156, "RectificationType", "CS", "1", false),
+    entry(0x0018_115A, "RadiationMode", "CS", "1", false),
+    entry(0x0018_115E, "ImageAndFluoroscopyAreaDoseProduct", "DS", "1", false),
+    entry(0x0018_1160, "FilterType", "SH", "1", false),
+    entry(0x0018_1161, "TypeOfFilters", "LO", "1-n", false),
+    entry(0x0018_1162, "IntensifierSize", "DS", "1", false),
+    entry(0x0018_1164, "ImagerPixelSpacing", "DS", "2", false),
+    entry(0x0018_1166, "Grid", "CS", "1-n", false),
+    entry(0x0018_1170, "GeneratorPower", "IS", "1", false),
+    entry(0x0018_1180, "CollimatorGridName", "SH", "1", false),
+    entry(0x0018_1181, "CollimatorType", "CS", "1", false),
+    entry(0x0018_1182, "FocalDistance", "IS", "1-2", false),
+    entry(0x0018_1183, "XFocusCenter", "DS", "1-2", false),
+    entry(0x0018_1184, "YFocusCenter", "DS", "1-2", false),
+    entry(0x0018_1190, "FocalSpots", "DS", "1-n", false),
+    entry(0x0018_1191, "AnodeTargetMaterial", "CS", "1", false),
+    entry(0x0018_11A0, "BodyPartThickness", "DS", "1", false),
+    entry(0x0018_11A2, "CompressionForce", "DS", "1", false),
+    entry(0x0018_1200, "DateOfLastCalibration", "DA", "1-n", false),
+    entry(0x0018_1201, "TimeOfLastCalibration", "TM", "1-n", false),
+    entry(0x0018_1210, "ConvolutionKernel", "SH", "1-n", false),
+    entry(0x0018_1242, "ActualFrameDuration", "IS", "1", false),
+    entry(0x0018_1243, "CountRate", "IS", "1", false),
+    entry(0x0018_1250, "ReceiveCoilName", "SH", "1", false),
+    entry(0x0018_1251, "TransmitCoilName", "SH", "1", false),
+    entry(0x0018_1260, "PlateType", "SH", "1", false),
+    entry(0x0018_1261, "PhosphorType", "LO", "1", false),
+    entry(0x0018_1300, "ScanVelocity", "DS", "1", false),
+    entry(0x0018_1301, "WholeBodyTechnique", "CS", "1-n", false),
+    entry(0x0018_1302, "ScanLength", "IS", "1", false),
+    entry(0x0018_1310, "AcquisitionMatrix", "US", "4", false),
+    entry(0x0018_1312, "InPlanePhaseEncodingDirection", "CS", "1", false),
+    entry(0x0018_1314, "FlipAngle", "DS", "1", false),
+    entry(0x0018_1315, "VariableFlipAngleFlag", "CS", "1", false),
+    entry(0x0018_1316, "SAR", "DS", "1", false),
+    entry(0x0018_1318, "dBdt", "DS", "1", false),
+    entry(0x0018_1400, "AcquisitionDeviceProcessingDescription", "LO", "1", false),
+    entry(0x0018_1401, "AcquisitionDeviceProcessingCode", "LO", "1", false),
+    entry(0x0018_1402, "CassetteOrientation", "CS", "1", false),
+    entry(0x0018_1403, "CassetteSize", "CS", "1", false),
+    entry(0x0018_1404, "ExposuresOnPlate", "US", "1", false),
+    entry(0x0018_1405, "RelativeXRayExposure", "IS", "1", false),
+    entry(0x0018_1411, "ExposureIndex", "DS", "1", false),
+    entry(0x0018_1412, "TargetExposureIndex", "DS", "1", false),
+    entry(0x0018_1413, "DeviationIndex", "DS", "1", false),
+    entry(0x0018_1450, "ColumnAngulation", "DS", "1", false),
+    entry(0x0018_1460, "TomoLayerHeight", "DS", "1", false),
+    entry(0x0018_1470, "TomoAngle", "DS", "1", false),
+    entry(0x0018_1480, "TomoTime", "DS", "1", false),
+    entry(0x0018_1490, "TomoType", "CS", "1", false),
+    entry(0x0018_1491, "TomoClass", "CS", "1", false),
+    entry(0x0018_1495, "NumberOfTomosynthesisSourceImages", "IS", "1", false),
+    entry(0x0018_1500, "PositionerMotion", "CS", "1", false),
+    entry(0x0018_1508, "PositionerType", "CS", "1", false),
+    entry(0x0018_1510, "PositionerPrimaryAngle", "DS", "1", false),
+    entry(0x0018_1511, "PositionerSecondaryAngle", "DS", "1", false),
+    entry(0x0018_1520, "PositionerPrimaryAngleIncrement", "DS", "1-n", false),
+    entry(0x0018_1521, "PositionerSecondaryAngleIncrement", "DS", "1-n", false),
+    entry(0x0018_1530, "DetectorPrimaryAngle", "DS", "1", false),
+    entry(0x0018_1531, "DetectorSecondaryAngle", "DS", "1", false),
+    entry(0x0018_1600, "ShutterShape", "CS", "1-3", false),
+    entry(0x0018_1602, "ShutterLeftVerticalEdge", "IS", "1", false),
+    entry(0x0018_1604, "ShutterRightVerticalEdge", "IS", "1", false),
+    entry(0x0018_1606, "ShutterUpperHorizontalEdge", "IS", "1", false),
+    entry(0x0018_1608, "ShutterLowerHorizontalEdge", "IS", "1", false),
+    entry(0x0018_1610, "CenterOfCircularShutter", "IS", "2", false),
+    entry(0x0018_1612, "RadiusOfCircularShutter", "IS", "1", false),
+    entry(0x0018_1620, "VerticesOfThePolygonalShutter", "IS", "2-2n", false),
+    entry(0x0018_1622, "ShutterPresentationValue", "US", "1", false),
+    entry(0x0018_1623, "ShutterOverlayGroup", "US", "1", false),
+    entry(0x0018_1624, "ShutterPresentationColorCIELabValue", "US", "3", false),
+    entry(0x0018_1700, "CollimatorShape", "CS", "1-3", false),
+    entry(0x0018_1702, "CollimatorLeftVerticalEdge", "IS", "1", false),
+    entry(0x0018_1704, "CollimatorRightVerticalEdge", "IS", "1", false),
+    entry(0x0018_1706, "CollimatorUpperHorizontalEdge", "IS", "1", false),
+    entry(0x0018_1708, "CollimatorLowerHorizontalEdge", "IS", "1", false),
+    entry(0x0018_1710, "CenterOfCircularCollimator", "IS", "2", false),
+    entry(0x0018_1712, "RadiusOfCircularCollimator", "IS", "1", false),
+    entry(0x0018_1720, "VerticesOfThePolygonalCollimator", "IS", "2-2n", false),
+    entry(0x0018_1800, "AcquisitionTimeSynchronized", "CS", "1", false),
+    entry(0x0018_1801, "TimeSource", "SH", "1", false),
+    entry(0x0018_1802, "TimeDistributionProtocol", "CS", "1", false),
+    entry(0x0018_1803, "NTPSourceAddress", "LO", "1", false),
+    entry(0x0018_2001, "PageNumberVector", "IS", "1-n", false),
+    entry(0x0018_2002, "FrameLabelVector", "SH", "1-n", false),
+    entry(0x0018_2003, "FramePrimaryAngleVector", "DS", "1-n", false),
+    entry(0x0018_2004, "FrameSecondaryAngleVector", "DS", "1-n", false),
+    entry(0x0018_2005, "SliceLocationVector", "DS", "1-n", false),
+    entry(0x0018_2006, "DisplayWindowLabelVector", "SH", "1-n", false),
+    entry(0x0018_2010, "NominalScannedPixelSpacing", "DS", "2", false),
+    entry(0x0018_2020, "DigitizingDeviceTransportDirection", "CS", "1", false),
+    entry(0x0018_2030, "RotationOfScannedFilm", "DS", "1", false),
+    entry(0x0018_3100, "IVUSAcquisition", "CS", "1", false),
+    entry(0x0018_3101, "IVUSPullbackRate", "DS", "1", false),
+    entry(0x0018_3102, "IVUSGatedRate", "DS", "1", false),
+    entry(0x0018_3103, "IVUSPullbackStartFrameNumber", "IS", "1", false),
+    entry(0x0018_3104, "IVUSPullbackStopFrameNumber", "IS", "1", false),
+    entry(0x0018_3105, "LesionNumber", "IS", "1-n", false),
+    entry(0x0018_5000, "OutputPower", "SH", "1-n", false),
+    entry(0x0018_5010, "TransducerData", "LO", "1-n", false),
+    entry(0x0018_5012, "FocusDepth", "DS", "1", false),
+    entry(0x0018_5020, "ProcessingFunction", "LO", "1", false),
+    entry(0x0018_5021, "PostprocessingFunction", "LO", "1", true),
+    entry(0x0018_5022, "MechanicalIndex", "DS", "1", false),
+    entry(0x0018_5024, "BoneThermalIndex", "DS", "1", false),
+    entry(0x0018_5026, "CranialThermalIndex", "DS", "1", false),
+    entry(0x0018_5027, "SoftTissueThermalIndex", "DS", "1", false),
+    entry(0x0018_5028, "SoftTissueFocusThermalIndex", "DS", "1", false),
+    entry(0x0018_5029, "SoftTissueSurfaceThermalIndex", "DS", "1", false),
+    entry(0x0018_5050, "DepthOfScanField", "IS", "1", false),
+    entry(0x0018_5100, "PatientPosition", "CS", "1", false),
+    entry(0x0018_5101, "ViewPosition", "CS", "1", false),
+    entry(0x0018_5104, "ProjectionEponymousNameCodeSequence", "SQ", "1", false),
+    entry(0x0018_6000, "Sensitivity", "DS", "1", false),
+    entry(0x0018_6011, "SequenceOfUltrasoundRegions", "SQ", "1", false),
+    entry(0x0018_6012, "RegionSpatialFormat", "US", "1", false),
+    entry(0x0018_6014, "RegionDataType", "US", "1", false),
+    entry(0x0018_6016, "RegionFlags", "UL", "1", false),
+    entry(0x0018_6018, "RegionLocationMinX0", "UL", "1", false),
+    entry(0x0018_601A, "RegionLocationMinY0", "UL", "1", false),
+    entry(0x0018_601C, "RegionLocationMaxX1", "UL", "1", false),
+    entry(0x0018_601E, "RegionLocationMaxY1", "UL", "1", false),
+    entry(0x0018_6020, "ReferencePixelX0", "SL", "1", false),
+    entry(0x0018_6022, "ReferencePixelY0", "SL", "1", false),
+    entry(0x0018_6024, "PhysicalUnitsXDirection", "US", "1", false),
+    entry(0x0018_6026, "PhysicalUnitsYDirection", "US", "1", false),
+    entry(0x0018_6028, "ReferencePixelPhysicalValueX", "FD", "1", false),
+    entry(0x0018_602A, "ReferencePixelPhysicalValueY", "FD", "1", false),
+    entry(0x0018_602C, "PhysicalDeltaX", "FD", "1", false),
+    entry(0x0018_602E, "PhysicalDeltaY", "FD", "1", false),
+    entry(0x0018_6030, "TransducerFrequency", "UL", "1", false),
+    entry(0x0018_6031, "TransducerType", "CS", "1", false),
+    entry(0x0018_6032, "PulseRepetitionFrequency", "UL", "1", false),
+    entry(0x0018_6034, "DopplerCorrectionAngle", "FD", "1", false),
+    entry(0x0018_6036, "SteeringAngle", "FD", "1", false),
+    entry(0x0018_6039, "DopplerSampleVolumeXPosition", "SL", "1", false),
+    entry(0x0018_603B, "DopplerSampleVolumeYPosition", "SL", "1", false),
+    entry(0x0018_603D, "TMLinePositionX0", "SL", "1", false),
+    entry(0x0018_603F, "TMLinePositionY0", "SL", "1", false),
+    entry(0x0018_6041, "TMLinePositionX1", "SL", "1", false),
+    entry(0x0018_6043, "TMLinePositionY1", "SL", "1", false),
+    entry(0x0018_6044, "PixelComponentOrganization", "US", "1", false),
+    entry(0x0018_6046, "PixelComponentMask", "UL", "1", false),
+    entry(0x0018_6048, "PixelComponentRangeStart", "UL", "1", false),
+    entry(0x0018_604A, "PixelComponentRangeStop", "UL", "1", false),
+    entry(0x0018_604C, "PixelComponentPhysicalUnits", "US", "1", false),
+    entry(0x0018_604E, "PixelComponentDataType", "US", "1", false),
+    entry(0x0018_6050, "NumberOfTableBreakPoints", "UL", "1", false),
+    entry(0x0018_6052, "TableOfXBreakPoints", "UL", "1-n", false),
+    entry(0x0018_6054, "TableOfYBreakPoints", "FD", "1-n", false),
+    entry(0x0018_6056, "NumberOfTableEntries", "UL", "1", false),
+    entry(0x0018_6058, "TableOfPixelValues", "UL", "1-n", false),
+    entry(0x0018_605A, "TableOfParameterValues", "FL", "1-n", false),
+    entry(0x0018_6060, "RWaveTimeVector", "FL", "1-n", false),
+    entry(0x0018_7000, "DetectorConditionsNominalFlag", "CS", "1", false),
+    entry(0x0018_7001, "DetectorTemperature", "DS", "1", false),
+    entry(0x0018_7004, "DetectorType", "CS", "1", false),
+    entry(0x0018_7005, "DetectorConfiguration", "CS", "1", false),
+    entry(0x0018_7006, "DetectorDescription", "LT", "1", false),
+    entry(0x0018_7008, "DetectorMode", "LT", "1", false),
+    entry(0x0018_700A, "DetectorID", "SH", "1", false),
+    entry(0x0018_700C, "DateOfLastDetectorCalibration", "DA", "1", false),
+    entry(0x0018_700E, "TimeOfLastDetectorCalibration", "TM", "1", false),
+    entry(0x0018_7010, "ExposuresOnDetectorSinceLastCalibration", "IS", "1", false),
+    entry(0x0018_7011, "ExposuresOnDetectorSinceManufactured", "IS", "1", false),
+    entry(0x0018_7012, "DetectorTimeSinceLastExposure", "DS", "1", false),
+    entry(0x0018_7014, "DetectorActiveTime", "DS", "1", false),
+    entry(0x0018_7016, "DetectorActivationOffsetFromExposure", "DS", "1", false),
+    entry(0x0018_701A, "DetectorBinning", "DS", "2", false),
+    entry(0x0018_7020, "DetectorElementPhysicalSize", "DS", "2", false),
+    entry(0x0018_7022, "DetectorElementSpacing", "DS", "2", false),
+    entry(0x0018_7024, "DetectorActiveShape", "CS", "1", false),
+    entry(0x0018_7026, "DetectorActiveDimensions", "DS", "1-2", false),
+    entry(0x0018_7028, "DetectorActiveOrigin", "DS", "2", false),
+    entry(0x0018_702A, "DetectorManufacturerName", "LO", "1", false),
+    entry(0x0018_702B, "DetectorManufacturerModelName", "LO", "1", false),
+    entry(0x0018_7030, "FieldOfViewOrigin", "DS", "2", false),
+    entry(0x0018_7032, "FieldOfViewRotation", "DS", "1", false),
+    entry(0x0018_7034, "FieldOfViewHorizontalFlip", "CS", "1", false),
+    entry(0x0018_7036, "PixelDataAreaOriginRelativeToFOV", "FL", "2", false),
+    entry(0x0018_7038, "PixelDataAreaRotationAngleRelativeToFOV", "FL", "1", false),
+    entry(0x0018_7040, "GridAbsorbingMaterial", "LT", "1", false),
+    entry(0x0018_7041, "GridSpacingMaterial", "LT", "1", false),
+    entry(0x0018_7042, "GridThickness", "DS", "1", false),
+    entry(0x0018_7044, "GridPitch", "DS", "1", false),
+    entry(0x0018_7046, "GridAspectRatio", "IS", "2", false),
+    entry(0x0018_7048, "GridPeriod", "DS", "1", false),
+    entry(0x0018_704C, "GridFocalDistance", "DS", "1", false),
+    entry(0x0018_7050, "FilterMaterial", "CS", "1-n", false),
+    entry(0x0018_7052, "FilterThicknessMinimum", "DS", "1-n", false),
+    entry(0x0018_7054, "FilterThicknessMaximum", "DS", "1-n", false),
+    entry(0x0018_7056, "FilterBeamPathLengthMinimum", "FL", "1-n", false),
+    entry(0x0018_7058, "FilterBeamPathLengthMaximum", "FL", "1-n", false),
+    entry(0x0018_7060, "ExposureControlMode", "CS", "1", false),
+    entry(0x0018_7062, "ExposureControlModeDescription", "LT", "1", false),
+    entry(0x0018_7064, "ExposureStatus", "CS", "1", false),
+    entry(0x0018_7065, "PhototimerSetting", "DS", "1", false),
+    entry(0x0018_8150, "ExposureTimeInuS", "DS", "1", false),
+    entry(0x0018_8151, "XRayTubeCurrentInuA", "DS", "1", false),
+    entry(0x0018_9004, "ContentQualification", "CS", "1", false),
+    entry(0x0018_9005, "PulseSequenceName", "SH", "1", false),
+    entry(0x0018_9006, "MRImagingModifierSequence", "SQ", "1", false),
+    entry(0x0018_9008, "EchoPulseSequence", "CS", "1", false),
+    entry(0x0018_9009, "InversionRecovery", "CS", "1", false),
+    entry(0x0018_9010, "FlowCompensation", "CS", "1", false),
+    entry(0x0018_9011, "MultipleSpinEcho", "CS", "1", false),
+    entry(0x0018_9012, "MultiPlanarExcitation", "CS", "1", false),
+    entry(0x0018_9014, "PhaseContrast", "CS", "1", false),
+    entry(0x0018_9015, "TimeOfFlightContrast", "CS", "1", false),
+    entry(0x0018_9016, "Spoiling", "CS", "1", false),
+    entry(0x0018_9017, "SteadyStatePulseSequence", "CS", "1", false),
+    entry(0x0018_9018, "EchoPlanarPulseSequence", "CS", "1", false),
+    entry(0x0018_9019, "TagAngleFirstAxis", "FD", "1", false),
+    entry(0x0018_9020, "MagnetizationTransfer", "CS", "1", false),
+    entry(0x0018_9021, "T2Preparation", "CS", "1", false),
+    entry(0x0018_9022, "BloodSignalNulling", "CS", "1", false),
+    entry(0x0018_9024, "SaturationRecovery", "CS", "1", false),
+    entry(0x0018_9025, "SpectrallySelectedSuppression", "CS", "1", false),
+    entry(0x0018_9026, "SpectrallySelectedExcitation", "CS", "1", false),
+    entry(0x0018_9027, "SpatialPresaturation", "CS", "1", false),
+    entry(0x0018_9028, "Tagging", "CS", "1", false),
+    entry(0x0018_9029, "OversamplingPhase", "CS", "1", false),
+    entry(0x0018_9030, "TagSpacingFirstDimension", "FD", "1", false),
+    entry(0x0018_9032, "GeometryOfKSpaceTraversal", "CS", "1", false),
+    entry(0x0018_9033, "SegmentedKSpaceTraversal", "CS", "1", false),
+    entry(0x0018_9034, "RectilinearPhaseEncodeReordering", "CS", "1", false),
+    entry(0x0018_9035, "TagThickness", "FD", "1", false),
+    entry(0x0018_9036, "PartialFourierDirection", "CS", "1", false),
+    entry(0x0018_9037, "CardiacSynchronizationTechnique", "CS", "1", false),
+    entry(0x0018_9041, "ReceiveCoilManufacturerName", "LO", "1", false),
+    entry(0x0018_9042, "MRReceiveCoilSequence", "SQ", "1", false),
+    entry(0x0018_9043, "ReceiveCoilType", "CS", "1", false),
+    entry(0x0018_9044, "QuadratureReceiveCoil", "CS", "1", false),
+    entry(0x0018_9045, "MultiCoilDefinitionSequence", "SQ", "1", false),
+    entry(0x0018_9046, "MultiCoilConfiguration", "LO", "1", false),
+    entry(0x0018_9047, "MultiCoilElementName", "SH", "1", false),
+    entry(0x0018_9048, "MultiCoilElementUsed", "CS", "1", false),
+    entry(0x0018_9049, "MRTransmitCoilSequence", "SQ", "1", false),
+    entry(0x0018_9050, "TransmitCoilManufacturerName", "LO", "1", false),
+    entry(0x0018_9051, "TransmitCoilType", "CS", "1", false),
+    entry(0x0018_9052, "SpectralWidth", "FD", "1-2", false),
+    entry(0x0018_9053, "ChemicalShiftReference", "FD", "1-2", false),
+    entry(0x0018_9054, "VolumeLocalizationTechnique", "CS", "1", false),
+    entry(0x0018_9058, "MRAcquisitionFrequencyEncodingSteps", "US", "1", false),
+    entry(0x0018_9059, "Decoupling", "CS", "1", false),
+    entry(0x0018_9060, "DecoupledNucleus", "CS", "1-2", false),
+    entry(0x0018_9061, "DecouplingFrequency", "FD", "1-2", false),
+    entry(0x0018_9062, "DecouplingMethod", "CS", "1", false),
+    entry(0x0018_9063, "DecouplingChemicalShiftReference", "FD", "1-2", false),
+    entry(0x0018_9064, "KSpaceFiltering", "CS", "1", false),
+    entry(0x0018_9065, "TimeDomainFiltering", "CS", "1-2", false),
+    entry(0x0018_9066, "NumberOfZeroFills", "US", "1-2", false),
+    entry(0x0018_9067, "BaselineCorrection", "CS", "1", false),
+    entry(0x0018_9069, "ParallelReductionFactorInPlane", "FD", "1", false),
+    entry(0x0018_9070, "CardiacRRIntervalSpecified", "FD", "1", false),
+    entry(0x0018_9073, "AcquisitionDuration", "FD", "1", false),
+    entry(0x0018_9074, "FrameAcquisitionDateTime", "DT", "1", false),
+    entry(0x0018_9075, "DiffusionDirectionality", "CS", "1", false),
+    entry(0x0018_9076, "DiffusionGradientDirectionSequence", "SQ", "1", false),
+    entry(0x0018_9077, "ParallelAcquisition", "CS", "1", false),
+    entry(0x0018_9078, "ParallelAcquisitionTechnique", "CS", "1", false),
+    entry(0x0018_9079, "InversionTimes", "FD", "1-n", false),
+    entry(0x0018_9080, "MetaboliteMapDescription", "ST", "1", false),
+    entry(0x0018_9081, "PartialFourier", "CS", "1", false),
+    entry(0x0018_9082, "EffectiveEchoTime", "FD", "1", false),
+    entry(0x0018_9083, "MetaboliteMapCodeSequence", "SQ", "1", false),
+    entry(0x0018_9084, "ChemicalShiftSequence", "SQ", "1", false),
+    entry(0x0018_9085, "CardiacSignalSource", "CS", "1", false),
+    entry(0x0018_9087, "DiffusionBValue", "FD", "1", false),
+    entry(0x0018_9089, "DiffusionGradientOrientation", "FD", "3", false),
+    entry(0x0018_9090, "VelocityEncodingDirection", "FD", "3", false),
+    entry(0x0018_9091, "VelocityEncodingMinimumValue", "FD", "1", false),
+    entry(0x0018_9092, "VelocityEncodingAcquisitionSequence", "SQ", "1", false),
+    entry(0x0018_9093, "NumberOfKSpaceTrajectories", "US", "1", false),
+    entry(0x0018_9094, "CoverageOfKSpace", "CS", "1", false),
+    entry(0x0018_9095, "SpectroscopyAcquisitionPhaseRows", "UL", "1", false),
+    entry(0x0018_9096, "ParallelReductionFactorInPlaneRetired", "FD", "1", true),
+    entry(0x0018_9098, "TransmitterFrequency", "FD", "1-2", false),
+    entry(0x0018_9100, "ResonantNucleus", "CS", "1-2", false),
+    entry(0x0018_9101, "FrequencyCorrection", "CS", "1", false),
+    entry(0x0018_9103, "MRSpectroscopyFOVGeometrySequence", "SQ", "1", false),
+    entry(0x0018_9104, "SlabThickness", "FD", "1", false),
+    entry(0x0018_9105, "SlabOrientation", "FD", "3", false),
+    entry(0x0018_9106, "MidSlabPosition", "FD", "3", false),
+    entry(0x0018_9107, "MRSpatialSaturationSequence", "SQ", "1", false),
+    entry(0x0018_9112, "MRTimingAndRelatedParametersSequence", "SQ", "1", false),
+    entry(0x0018_9114, "MREchoSequence", "SQ", "1", false),
+    entry(0x0018_9115, "MRModifierSequence", "SQ", "1", false),
+    entry(0x0018_9117, "MRDiffusionSequence", "SQ", "1", false),
+    entry(0x0018_9118, "CardiacSynchronizationSequence", "SQ", "1", false),
+    entry(0x0018_9119, "MRAveragesSequence", "SQ", "1", false),
+    entry(0x0018_9125, "MRFOVGeometrySequence", "SQ", "1", false),
+    entry(0x0018_9126, "VolumeLocalizationSequence", "SQ", "1", false),
+    entry(0x0018_9127, "SpectroscopyAcquisitionDataColumns", "UL", "1", false),
+    entry(0x0018_9147, "DiffusionAnisotropyType", "CS", "1", false),
+    entry(0x0018_9151, "FrameReferenceDateTime", "DT", "1", false),
+    entry(0x0018_9152, "MRMetaboliteMapSequence", "SQ", "1", false),
+    entry(0x0018_9155, "ParallelReductionFactorOutOfPlane", "FD", "1", false),
+    entry(0x0018_9159, "SpectroscopyAcquisitionOutOfPlanePhaseSteps", "UL", "1", false),
+    entry(0x0018_9166, "BulkMotionStatus", "CS", "1", true),
+    entry(0x0018_9168, "ParallelReductionFactorSecondInPlane", "FD", "1", false),
+    entry(0x0018_9169, "CardiacBeatRejectionTechnique", "CS", "1", false),
+    entry(0x0018_9170, "RespiratoryMotionCompensationTechnique", "CS", "1", false),
+    entry(0x0018_9171, "RespiratorySignalSource", "CS", "1", false),
+    entry(0x0018_9172, "BulkMotionCompensationTechnique", "CS", "1", false),
+    entry(0x0018_9173, "BulkMotionSignalSource", "CS", "1", false),
+    entry(0x0018_9174, "ApplicableSafetyStandardAgency", "CS", "1", false),
+    entry(0x0018_9175, "ApplicableSafetyStandardDescription", "LO", "1", false),
+    entry(0x0018_9176, "OperatingModeSequence", "SQ", "1", false),
+    entry(0x0018_9177, "OperatingModeType", "CS", "1", false),
+    entry(0x0018_9178, "OperatingMode", "CS", "1", false),
+    entry(0x0018_9179, "SpecificAbsorptionRateDefinition", "CS", "1", false),
+    entry(0x0018_9180, "GradientOutputType", "CS", "1", false),
+    entry(0x0018_9181, "SpecificAbsorptionRateValue", "FD", "1", false),
+    entry(0x0018_9182, "GradientOutput", "FD", "1", false),
+    entry(0x0018_9183, "FlowCompensationDirection", "CS", "1", false),
+    entry(0x0018_9184, "TaggingDelay", "FD", "1", false),
+    entry(0x0018_9185, "RespiratoryMotionCompensationTechniqueDescription", "ST", "1", false),
+    entry(0x0018_9186, "RespiratorySignalSourceID", "SH", "1", false),
+    entry(0x0018_9195, "ChemicalShiftMinimumIntegrationLimitInHz", "FD", "1", true),
+    entry(0x0018_9196, "ChemicalShiftMaximumIntegrationLimitInHz", "FD", "1", true),
+    entry(0x0018_9197, "MRVelocityEncodingSequence", "SQ", "1", false),
+    entry(0x0018_9198, "FirstOrderPhaseCorrection", "CS", "1", false),
+    entry(0x0018_9199, "WaterReferencedPhaseCorrection", "CS", "1", false),
+    entry(0x0018_9200, "MRSpectroscopyAcquisitionType", "CS", "1", false),
+    entry(0x0018_9214, "RespiratoryCyclePosition", "CS", "1", false),
+    entry(0x0018_9217, "VelocityEncodingMaximumValue", "FD", "1", false),
+    entry(0x0018_9218, "TagSpacingSecondDimension", "FD", "1", false),
+    entry(0x0018_9219, "TagAngleSecondAxis", "SS", "1", false),
+    entry(0x0018_9220, "FrameAcquisitionDuration", "FD", "1", false),
+    entry(0x0018_9226, "MRImageFrameTypeSequence", "SQ", "1", false),
+    entry(0x0018_9227, "MRSpectroscopyFrameTypeSequence", "SQ", "1", false),
+    entry(0x0018_9231, "MRAcquisitionPhaseEncodingStepsInPlane", "US", "1", false),
+    entry(0x0018_9232, "MRAcquisitionPhaseEncodingStepsOutOfPlane", "US", "1", false),
+    entry(0x0018_9234, "SpectroscopyAcquisitionPhaseColumns", "UL", "1", false),
+    entry(0x0018_9236, "CardiacCyclePosition", "CS", "1", false),
+    entry(0x0018_9239, "SpecificAbsorptionRateSequence", "SQ", "1", false),
+    entry(0x0018_9240, "RFEchoTrainLength", "US", "1", false),
+    entry(0x0018_9241, "GradientEchoTrainLength", "US", "1", false),
+    entry(0x0018_9250, "ArterialSpinLabelingContrast", "CS", "1", false),
+    entry(0x0018_9251, "MRArterialSpinLabelingSequence", "SQ", "1", false),
+    entry(0x0018_9252, "ASLTechniqueDescription", "LO", "1", false),
+    entry(0x0018_9253, "ASLSlabNumber", "US", "1", false),
+    entry(0x0018_9254, "ASLSlabThickness", "FD", "1", false),
+    entry(0x0018_9255, "ASLSlabOrientation", "FD", "3", false),
+    entry(0x0018_9256, "ASLMidSlabPosition", "FD", "3", false),
+    entry(0x0018_9257, "ASLContext", "CS", "1", false),
+    entry(0x0018_9258, "ASLPulseTrainDuration", "UL", "1", false),
+    entry(0x0018_9259, "ASLCrusherFlag", "CS", "1", false),
+    entry(0x0018_925A, "ASLCrusherFlowLimit", "FD", "1", false),
+    entry(0x0018_925B, "ASLCrusherDescription", "LO", "1", false),
+    entry(0x0018_925C, "ASLBolusCutoffFlag", "CS", "1", false),
+    entry(0x0018_925D, "ASLBolusCutoffTimingSequence", "SQ", "1", false),
+    entry(0x0018_925E, "ASLBolusCutoffTechnique", "LO", "1", false),
+    entry(0x0018_925F, "ASLBolusCutoffDelayTime", "UL", "1", false),
+    entry(0x0018_9260, "ASLSlabSequence", "SQ", "1", false),
+    entry(0x0018_9295, "ChemicalShiftMinimumIntegrationLimitInppm", "FD", "1", false),
+    entry(0x0018_9296, "ChemicalShiftMaximumIntegrationLimitInppm", "FD", "1", false),
+    entry(0x0018_9297, "WaterReferenceAcquisition", "CS", "1", false),
+    entry(0x0018_9298, "EchoPeakPosition", "IS", "1", false),
+    entry(0x0018_9301, "CTAcquisitionTypeSequence", "SQ", "1", false),
+    entry(0x0018_9302, "AcquisitionType", "CS", "1", false),
+    entry(0x0018_9303, "TubeAngle", "FD", "1", false),
+    entry(0x0018_9304, "CTAcquisitionDetailsSequence", "SQ", "1", false),
+    entry(0x0018_9305, "RevolutionTime", "FD", "1", false),
+    entry(0x0018_9306, "SingleCollimationWidth", "FD", "1", false),
+    entry(0x0018_9307, "TotalCollimationWidth", "FD", "1", false),
+    entry(0x0018_9308, "CTTableDynamicsSequence", "SQ", "1", false),
+    entry(0x0018_9309, "TableSpeed", "FD", "1", false),
+    entry(0x0018_9310, "TableFeedPerRotation", "FD", "1", false),
+    entry(0x0018_9311, "SpiralPitchFactor", "FD", "1", false),
+    entry(0x0018_9312, "CTGeometrySequence", "SQ", "1", false),
+    entry(0x0018_9313, "DataCollectionCenterPatient", "FD", "3", false),
+    entry(0x0018_9314, "CTReconstructionSequence", "SQ", "1", false),
+    entry(0x0018_9315, "ReconstructionAlgorithm", "CS", "1", false),
+    entry(0x0018_9316, "ConvolutionKernelGroup", "CS", "1", false),
+    entry(0x0018_9317, "ReconstructionFieldOfView", "FD", "2", false),
+    entry(0x0018_9318, "ReconstructionTargetCenterPatient", "FD", "3", false),
+    entry(0x0018_9319, "ReconstructionAngle", "FD", "1", false),
+    entry(0x0018_9320, "ImageFilter", "SH", "1", false),
+    entry(0x0018_9321, "CTExposureSequence", "SQ", "1", false),
+    entry(0x0018_9322, "ReconstructionPixelSpacing", "FD", "2", false),
+    entry(0x0018_9323, "ExposureModulationType", "CS", "1-n", false),
+    entry(0x0018_9325, "CTXRayDetailsSequence", "SQ", "1", false),
+    entry(0x0018_9326, "CTPositionSequence", "SQ", "1", false),
+    entry(0x0018_9327, "TablePosition", "FD", "1", false),
+    entry(0x0018_9328, "ExposureTimeInms", "FD", "1", false),
+    entry(0x0018_9329, "CTImageFrameTypeSequence", "SQ", "1", false),
+    entry(0x0018_9330, "XRayTubeCurrentInmA", "FD", "1", false),
+    entry(0x0018_9332, "ExposureInmAs", "FD", "1", false),
+    entry(0x0018_9333, "ConstantVolumeFlag", "CS", "1", false),
+    entry(0x0018_9334, "FluoroscopyFlag", "CS", "1", false),
+    entry(0x0018_9335, "DistanceSourceToDataCollectionCenter", "FD", "1", false),
+    entry(0x0018_9337, "ContrastBolusAgentNumber", "US", "1", false),
+    entry(0x0018_9338, "ContrastBolusIngredientCodeSequence", "SQ", "1", false),
+    entry(0x0018_9340, "ContrastAdministrationProfileSequence", "SQ", "1", false),
+    entry(0x0018_9341, "ContrastBolusUsageSequence", "SQ", "1", false),
+    entry(0x0018_9342, "ContrastBolusAgentAdministered", "CS", "1", false),
+    entry(0x0018_9343, "ContrastBolusAgentDetected", "CS", "1", false),
+    entry(0x0018_9344, "ContrastBolusAgentPhase", "CS", "1", false),
+    entry(0x0018_9345, "CTDIvol", "FD", "1", false),
+    entry(0x0018_9346, "CTDIPhantomTypeCodeSequence", "SQ", "1", false),
+    entry(0x0018_9351, "CalciumScoringMassFactorPatient", "FL", "1", false),
+    entry(0x0018_9352, "CalciumScoringMassFactorDevice", "FL", "3", false),
+    entry(0x0018_9353, "EnergyWeightingFactor", "FL", "1", false),
+    entry(0x0018_9360, "CTAdditionalXRaySourceSequence", "SQ", "1", false),
+    entry(0x0018_A001, "ContributingEquipmentSequence", "SQ", "1", false),
+    entry(0x0018_A002, "ContributionDateTime", "DT", "1", false),
+    entry(0x0018_A003, "ContributionDescription", "ST", "1", false),
+    entry(0x0020_000D, "StudyInstanceUID", "UI", "1", false),
+    entry(0x0020_000E, "SeriesInstanceUID", "UI", "1", false),
+    entry(0x0020_0010, "StudyID", "SH", "1", false),
+    entry(0x0020_0011, "SeriesNumber", "IS", "1", false),
+    entry(0x0020_0012, "AcquisitionNumber", "IS", "1", false),
+    entry(0x0020_0013, "InstanceNumber", "IS", "1", false),
+    entry(0x0020_0014, "IsotopeNumber", "IS", "1", true),
+    entry(0x0020_0015, "PhaseNumber", "IS", "1", true),
+    entry(0x0020_0016, "IntervalNumber", "IS", "1", true),
+    entry(0x0020_0017, "TimeSlotNumber", "IS", "1", true),
+    entry(0x0020_0018, "AngleNumber", "IS", "1", true),
+    entry(0x0020_0019, "ItemNumber", "IS", "1", false),
+    entry(0x0020_0020, "PatientOrientation", "CS", "2", false),
+    entry(0x0020_0022, "OverlayNumber", "IS", "1", true),
+    entry(0x0020_0024, "CurveNumber", "IS", "1", true),
+    entry(0x0020_0026, "LUTNumber", "IS", "1", true),
+    entry(0x0020_0030, "ImagePosition", "DS", "3", true),
+    entry(0x0020_0032, "ImagePositionPatient", "DS", "3", false),
+    entry(0x0020_0035, "ImageOrientation", "DS", "6", true),
+    entry(0x0020_0037, "ImageOrientationPatient", "DS", "6", false),
+    entry(0x0020_0050, "Location", "DS", "1", true),
+    entry(0x0020_0052, "FrameOfReferenceUID", "UI", "1", false),
+    entry(0x0020_0060, "Laterality", "CS", "1", false),
+    entry(0x0020_0062, "ImageLaterality", "CS", "1", false),
+    entry(0x0020_0070, "ImageGeometryType", "LO", "1", true),
+    entry(0x0020_0080, "MaskingImage", "CS", "1-n", true),
+    entry(0x0020_00AA, "ReportNumber", "IS", "1", true),
+    entry(0x0020_0100, "TemporalPositionIdentifier", "IS", "1", false),
+    entry(0x0020_0105, "NumberOfTemporalPositions", "IS", "1", false),
+    entry(0x0020_0110, "TemporalResolution", "DS", "1", false),
+    entry(0x0020_0200, "SynchronizationFrameOfReferenceUID", "UI", "1", false),
+    entry(0x0020_0242, "SOPInstanceUIDOfConcatenationSource", "UI", "1", false),
+    entry(0x0020_1000, "SeriesInStudy", "IS", "1", true),
+    entry(0x0020_1001, "AcquisitionsInSeries", "IS", "1", true),
+    entry(0x0020_1002, "ImagesInAcquisition", "IS", "1", false),
+    entry(0x0020_1003, "ImagesInSeries", "IS", "1", true),
+    entry(0x0020_1004, "AcquisitionsInStudy", "IS", "1", true),
+    entry(0x0020_1005, "ImagesInStudy", "IS", "1", true),
+    entry(0x0020_1020, "Reference", "LO", "1-n", true),
+    entry(0x0020_1040, "PositionReferenceIndicator", "LO", "1", false),
+    entry(0x0020_1041, "SliceLocation", "DS", "1", false),
+    entry(0x0020_1070, "OtherStudyNumbers", "IS", "1-n", true),
+    entry(0x0020_1200, "NumberOfPatientRelatedStudies", "IS", "1", false),
+    entry(0x0020_1202, "NumberOfPatientRelatedSeries", "IS", "1", false),
+    entry(0x0020_1204, "NumberOfPatientRelatedInstances", "IS", "1", false),
+    entry(0x0020_1206, "NumberOfStudyRelatedSeries", "IS", "1", false),
+    entry(0x0020_1208, "NumberOfStudyRelatedInstances", "IS", "1", false),
+    entry(0x0020_1209, "NumberOfSeriesRelatedInstances", "IS", "1", false),
+    entry(0x0020_3401, "ModifyingDeviceID", "CS", "1", true),
+    entry(0x0020_3402, "ModifiedImageID", "CS", "1", true),
+    entry(0x0020_3403, "ModifiedImageDate", "DA", "1", true),
+    entry(0x0020_3404, "ModifyingDeviceManufacturer", "LO", "1", true),
+    entry(0x0020_3405, "ModifiedImageTime", "TM", "1", true),
+    entry(0x0020_3406, "ModifiedImageDescription", "LO", "1", true),
+    entry(0x0020_4000, "ImageComments", "LT", "1", false),
+    entry(0x0020_5000, "OriginalImageIdentification", "AT", "1-n", true),
+    entry(0x0020_5002, "OriginalImageIdentificationNomenclature", "LO", "1-n", true),
+    entry(0x0020_9056, "StackID", "SH", "1", false),
+    entry(0x0020_9057, "InStackPositionNumber", "UL", "1", false),
+    entry(0x0020_9071, "FrameAnatomySequence", "SQ", "1", false),
+    entry(0x0020_9072, "FrameLaterality", "CS", "1", false),
+    entry(0x0020_9111, "FrameContentSequence", "SQ", "1", false),
+    entry(0x0020_9113, "PlanePositionSequence", "SQ", "1", false),
+    entry(0x0020_9116, "PlaneOrientationSequence", "SQ", "1", false),
+    entry(0x0020_9128, "TemporalPositionIndex", "UL", "1", false),
+    entry(0x0020_9153, "NominalCardiacTriggerDelayTime", "FD", "1", false),
+    entry(0x0020_9154, "NominalCardiacTriggerTimePriorToRPeak", "FL", "1", false),
+    entry(0x0020_9155, "ActualCardiacTriggerTimePriorToRPeak", "FL", "1", false),
+    entry(0x0020_9156, "FrameAcquisitionNumber", "US", "1", false),
+    entry(0x0020_9157, "DimensionIndexValues", "UL", "1-n", false),
+    entry(0x0020_9158, "FrameComments", "LT", "1", false),
+    entry(0x0020_9161, "ConcatenationUID", "UI", "1", false),
+    entry(0x0020_9162, "InConcatenationNumber", "US", "1", false),
+    entry(0x0020_9163, "InConcatenationTotalNumber", "US", "1", false),
+    entry(0x0020_9164, "DimensionOrganizationUID", "UI", "1", false),
+    entry(0x0020_9165, "DimensionIndexPointer", "AT", "1", false),
+    entry(0x0020_9167, "FunctionalGroupPointer", "AT", "1", false),
+    entry(0x0020_9170, "UnassignedSharedConvertedAttributesSequence", "SQ", "1", false),
+    entry(0x0020_9171, "UnassignedPerFrameConvertedAttributesSequence", "SQ", "1", false),
+    entry(0x0020_9172, "ConversionSourceAttributesSequence", "SQ", "1", false),
+    entry(0x0020_9213, "DimensionIndexPrivateCreator", "LO", "1", false),
+    entry(0x0020_9221, "DimensionOrganizationSequence", "SQ", "1", false),
+    entry(0x0020_9222, "DimensionIndexSequence", "SQ", "1", false),
+    entry(0x0020_9228, "ConcatenationFrameOffsetNumber", "UL", "1", false),
+    entry(0x0020_9238, "FunctionalGroupPrivateCreator", "LO", "1", false),
+    entry(0x0020_9241, "NominalPercentageOfCardiacPhase", "FL", "1", false),
+    entry(0x0020_9245, "NominalPercentageOfRespiratoryPhase", "FL", "1", false),
+    entry(0x0020_9246, "StartingRespiratoryAmplitude", "FL", "1", false),
+    entry(0x0020_9247, "StartingRespiratoryPhase", "CS", "1", false),
+    entry(0x0020_9248, "EndingRespiratoryAmplitude", "FL", "1", false),
+    entry(0x0020_9249, "EndingRespiratoryPhase", "CS", "1", false),
+    entry(0x0020_9250, "RespiratoryTriggerType", "CS", "1", false),
+    entry(0x0020_9251, "RRIntervalTimeNominal", "FD", "1", false),
+    entry(0x0020_9252, "ActualCardiacTriggerDelayTime", "FD", "1", false),
+    entry(0x0020_9253, "RespiratorySynchronizationSequence", "SQ", "1", false),
+    entry(0x0020_9254, "RespiratoryIntervalTime", "FD", "1", false),
+    entry(0x0020_9255, "NominalRespiratoryTriggerDelayTime", "FD", "1", false),
+    entry(0x0020_9256, "RespiratoryTriggerDelayThreshold", "FD", "1", false),
+    entry(0x0020_9257, "ActualRespiratoryTriggerDelayTime", "FD", "1", false),
+    entry(0x0020_9301, "ImagePositionVolume", "FD", "3", false),
+    entry(0x0020_9302, "ImageOrientationVolume", "FD", "6", false),
+    entry(0x0020_9307, "UltrasoundAcquisitionGeometry", "CS", "1", false),
+    entry(0x0020_9308, "ApexPosition", "FD", "3", false),
+    entry(0x0020_9309, "VolumeToTransducerMappingMatrix", "FD", "16", false),
+    entry(0x0020_930A, "VolumeToTableMappingMatrix", "FD", "16", false),
+    entry(0x0020_930C, "PatientFrameOfReferenceSource", "CS", "1", false),
+    entry(0x0020_930D, "TemporalPositionTimeOffset", "FD", "1", false),
+    entry(0x0020_930E, "PlanePositionVolumeSequence", "SQ", "1", false),
+    entry(0x0020_930F, "PlaneOrientationVolumeSequence", "SQ", "1", false),
+    entry(0x0020_9310, "TemporalPositionSequence", "SQ", "1", false),
+    entry(0x0020_9311, "DimensionOrganizationType", "CS", "1", false),
+    entry(0x0020_9312, "VolumeFrameOfReferenceUID", "UI", "1", false),
+    entry(0x0020_9313, "TableFrameOfReferenceUID", "UI", "1", false),
+    entry(0x0020_9421, "DimensionDescriptionLabel", "LO", "1", false),
+    entry(0x0020_9450, "PatientOrientationInFrameSequence", "SQ", "1", false),
+    entry(0x0020_9453, "FrameLabel", "LO", "1", false),
+    entry(0x0020_9518, "AcquisitionIndex", "US", "1-n", false),
+    entry(0x0020_9529, "ContributingSOPInstancesReferenceSequence", "SQ", "1", false),
+    entry(0x0020_9536, "ReconstructionIndex", "US", "1", false),
+    entry(0x0028_0002, "SamplesPerPixel", "US", "1", false),
+    entry(0x0028_0003, "SamplesPerPixelUsed", "US", "1", false),
+    entry(0x0028_0004, "PhotometricInterpretation", "CS", "1", false),
+    entry(0x0028_0005, "ImageDimensions", "US", "1", true),
+    entry(0x0028_0006, "PlanarConfiguration", "US", "1", false),
+    entry(0x0028_0008, "NumberOfFrames", "IS", "1", false),
+    entry(0x0028_0009, "FrameIncrementPointer", "AT", "1-n", false),
+    entry(0x0028_000A, "FrameDimensionPointer", "AT", "1-n", false),
+    entry(0x0028_0010, "Rows", "US", "1", false),
+    entry(0x0028_0011, "Columns", "US", "1", false),
+    entry(0x0028_0012, "Planes", "US", "1", true),
+    entry(0x0028_0014, "UltrasoundColorDataPresent", "US", "1", false),
+    entry(0x0028_0030, "PixelSpacing", "DS", "2", false),
+    entry(0x0028_0031, "ZoomFactor", "DS", "2", false),
+    entry(0x0028_0032, "ZoomCenter", "DS", "2", false),
+    entry(0x0028_0034, "PixelAspectRatio", "IS", "2", false),
+    entry(0x0028_0040, "ImageFormat", "CS", "1", true),
+    entry(0x0028_0050, "ManipulatedImage", "LO", "1-n", true),
+    entry(0x0028_0051, "CorrectedImage", "CS", "1-n", false),
+    entry(0x0028_005F, "CompressionRecognitionCode", "LO", "1", true),
+    entry(0x0028_0060, "CompressionCode", "CS", "1", true),
+    entry(0x0028_0061, "CompressionOriginator", "SH", "1", true),
+    entry(0x0028_0062, "CompressionLabel", "LO", "1", true),
+    entry(0x0028_0063, "CompressionDescription", "SH", "1", true),
+    entry(0x0028_0065, "CompressionSequence", "CS", "1-n", true),
+    entry(0x0028_0066, "CompressionStepPointers", "AT", "1-n", true),
+    entry(0x0028_0068, "RepeatInterval", "US", "1", true),
+    entry(0x0028_0069, "BitsGrouped", "US", "1", true),
+    entry(0x0028_0070, "PerimeterTable", "US", "1-n", true),
+    entry(0x0028_0071, "PerimeterValue", "US or SS", "1", true),
+    entry(0x0028_0080, "PredictorRows", "US", "1", true),
+    entry(0x0028_0081, "PredictorColumns", "US", "1", true),
+    entry(0x0028_0082, "PredictorConstants", "US", "1-n", true),
+    entry(0x0028_0090, "BlockedPixels", "CS", "1", true),
+    entry(0x0028_0091, "BlockRows", "US", "1", true),
+    entry(0x0028_0092, "BlockColumns", "US", "1", true),
+    entry(0x0028_0093, "RowOverlap", "US", "1", true),
+    entry(0x0028_0094, "ColumnOverlap", "US", "1", true),
+    entry(0x0028_0100, "BitsAllocated", "US", "1", false),
+    entry(0x0028_0101, "BitsStored", "US", "1", false),
+    entry(0x0028_0102, "HighBit", "US", "1", false),
+    entry(0x0028_0103, "PixelRepresentation", "US", "1", false),
+    entry(0x0028_0104, "SmallestValidPixelValue", "US or SS", "1", true),
+    entry(0x0028_0105, "LargestValidPixelValue", "US or SS", "1", true),
+    entry(0x0028_0106, "SmallestImagePixelValue", "US or SS", "1", false),
+    entry(0x0028_0107, "LargestImagePixelValue", "US or SS", "1", false),
+    entry(0x0028_0108, "SmallestPixelValueInSeries", "US or SS", "1", false),
+    entry(0x0028_0109, "LargestPixelValueInSeries", "US or SS", "1", false),
+    entry(0x0028_0110, "SmallestImagePixelValueInPlane", "US or SS", "1", true),
+    entry(0x0028_0111, "LargestImagePixelValueInPlane", "US or SS", "1", true),
+    entry(0x0028_0120, "PixelPaddingValue", "US or SS", "1", false),
+    entry(0x0028_0121, "PixelPaddingRangeLimit", "US or SS", "1", false),
+    entry(0x0028_0122, "FloatPixelPaddingValue", "FL", "1", false),
+    entry(0x0028_0123, "DoubleFloatPixelPaddingValue", "FD", "1", false),
+    entry(0x0028_0124, "FloatPixelPaddingRangeLimit", "FL", "1", false),
+    entry(0x0028_0125, "DoubleFloatPixelPaddingRangeLimit", "FD", "1", false),
+    entry(0x0028_0200, "ImageLocation", "US", "1", true),
+    entry(0x0028_0300, "QualityControlImage", "CS", "1", false),
+    entry(0x0028_0301, "BurnedInAnnotation", "CS", "1", false),
+    entry(0x0028_0302, "RecognizableVisualFeatures", "CS", "1", false),
+    entry(0x0028_0303, "LongitudinalTemporalInformationModified", "CS", "1", false),
+    entry(0x0028_0304, "ReferencedColorPaletteInstanceUID", "UI", "1", false),
+    entry(0x0028_0400, "TransformLabel", "LO", "1", true),
+    entry(0x0028_0401, "TransformVersionNumber", "LO", "1", true),
+    entry(0x0028_0402, "NumberOfTransformSteps", "US", "1", true),
+    entry(0x0028_0403, "SequenceOfCompressedData", "LO", "1-n", true),
+    entry(0x0028_0404, "DetailsOfCoefficients", "AT", "1-n", true),
+    entry(0x0028_0700, "DCTLabel", "LO", "1", true),
+    entry(0x0028_0701, "DataBlockDescription", "CS", "1-n", true),
+    entry(0x0028_0702, "DataBlock", "AT", "1-n", true),
+    entry(0x0028_0710, "NormalizationFactorFormat", "US", "1", true),
+    entry(0x0028_0720, "ZonalMapNumberFormat", "US", "1", true),
+    entry(0x0028_0721, "ZonalMapLocation", "AT", "1-n", true),
+    entry(0x0028_0722, "ZonalMapFormat", "US", "1", true),
+    entry(0x0028_0730, "AdaptiveMapFormat", "US", "1", true),
+    entry(0x0028_0740, "CodeNumberFormat", "US", "1", true),
+    entry(0x0028_0A02, "PixelSpacingCalibrationType", "CS", "1", false),
+    entry(0x0028_0A04, "PixelSpacingCalibrationDescription", "LO", "1", false),
+    entry(0x0028_1040, "PixelIntensityRelationship", "CS", "1", false),
+    entry(0x0028_1041, "PixelIntensityRelationshipSign", "SS", "1", false),
+    entry(0x0028_1050, "WindowCenter", "DS", "1-n", false),
+    entry(0x0028_1051, "WindowWidth", "DS", "1-n", false),
+    entry(0x0028_1052, "RescaleIntercept", "DS", "1", false),
+    entry(0x0028_1053, "RescaleSlope", "DS", "1", false),
+    entry(0x0028_1054, "RescaleType", "LO", "1", false),
+    entry(0x0028_1055, "WindowCenterWidthExplanation", "LO", "1-n", false),
+    entry(0x0028_1056, "VOILUTFunction", "CS", "1", false),
+    entry(0x0028_1080, "GrayScale", "CS", "1", true),
+    entry(0x0028_1090, "RecommendedViewingMode", "CS", "1", false),
+    entry(0x0028_1100, "GrayLookupTableDescriptor", "US or SS", "3", true),
+    entry(0x0028_1101, "RedPaletteColorLookupTableDescriptor", "US or SS", "3", false),
+    entry(0x0028_1102, "GreenPaletteColorLookupTableDescriptor", "US or SS", "3", false),
+    entry(0x0028_1103, "BluePaletteColorLookupTableDescriptor", "US or SS", "3", false),
+    entry(0x0028_1104, "AlphaPaletteColorLookupTableDescriptor", "US", "3", false),
+    entry(0x0028_1111, "LargeRedPaletteColorLookupTableDescriptor", "US or SS", "4", true),
+    entry(0x0028_1112, "LargeGreenPaletteColorLookupTableDescriptor", "US or SS", "4", true),
+    entry(0x0028_1113, "LargeBluePaletteColorLookupTableDescriptor", "US or SS", "4", true),
+    entry(0x0028_1199, "PaletteColorLookupTableUID", "UI", "1", false),
+    entry(0x0028_1200, "GrayLookupTableData", "US or SS or OW", "1-n", true),
+    entry(0x0028_1201, "RedPaletteColorLookupTableData", "OW", "1", false),
+    entry(0x0028_1202, "GreenPaletteColorLookupTableData", "OW", "1", false),
+    entry(0x0028_1203, "BluePaletteColorLookupTableData", "OW", "1", false),
+    entry(0x0028_1204, "AlphaPaletteColorLookupTableData", "OW", "1", false),
+    entry(0x0028_1211, "LargeRedPaletteColorLookupTableData", "OW", "1", true),
+    entry(0x0028_1212, "LargeGreenPaletteColorLookupTableData", "OW", "1", true),
+    entry(0x0028_1213, "LargeBluePaletteColorLookupTableData", "OW", "1", true),
+    entry(0x0028_1214, "LargePaletteColorLookupTableUID", "UI", "1", true),
+    entry(0x0028_1221, "SegmentedRedPaletteColorLookupTableData", "OW", "1", false),
+    entry(0x0028_1222, "SegmentedGreenPaletteColorLookupTableData", "OW", "1", false),
+    entry(0x0028_1223, "SegmentedBluePaletteColorLookupTableData", "OW", "1", false),
+    entry(0x0028_1224, "SegmentedAlphaPaletteColorLookupTableData", "OW", "1", false),
+    entry(0x0028_1230, "StoredValueColorRangeSequence", "SQ", "1", false),
+    entry(0x0028_1231, "MinimumStoredValueMapped", "FD", "1", false),
+    entry(0x0028_1232, "MaximumStoredValueMapped", "FD", "1", false),
+    entry(0x0028_1300, "BreastImplantPresent", "CS", "1", false),
+    entry(0x0028_1350, "PartialView", "CS", "1", false),
+    entry(0x0028_1351, "PartialViewDescription", "ST", "1", false),
+    entry(0x0028_1352, "PartialViewCodeSequence", "SQ", "1", false),
+    entry(0x0028_135A, "SpatialLocationsPreserved", "CS", "1", false),
+    entry(0x0028_1401, "DataFrameAssignmentSequence", "SQ", "1", false),
+    entry(0x0028_1402, "DataPathAssignment", "CS", "1", false),
+    entry(0x0028_1403, "BitsMappedToColorLookupTable", "US", "1", false),
+    entry(0x0028_1404, "BlendingLUT1Sequence", "SQ", "1", false),
+    entry(0x0028_1405, "BlendingLUT1TransferFunction", "CS", "1", false),
+    entry(0x0028_1406, "BlendingWeightConstant", "FD", "1", false),
+    entry(0x0028_1407, "BlendingLookupTableDescriptor", "US", "3", false),
+    entry(0x0028_1408, "BlendingLookupTableData", "OW", "1", false),
+    entry(0x0028_140B, "EnhancedPaletteColorLookupTableSequence", "SQ", "1", false),
+    entry(0x0028_140C, "BlendingLUT2Sequence", "SQ", "1", false),
+    entry(0x0028_140D, "BlendingLUT2TransferFunction", "CS", "1", false),
+    entry(0x0028_140E, "DataPathID", "CS", "1", false),
+    entry(0x0028_140F, "RGBLUTTransferFunction", "CS", "1", false),
+    entry(0x0028_1410, "AlphaLUTTransferFunction", "CS", "1", false),
+    entry(0x0028_2000, "ICCProfile", "OB", "1", false),
+    entry(0x0028_2002, "ColorSpace", "CS", "1", false),
+    entry(0x0028_2110, "LossyImageCompression", "CS", "1", false),
+    entry(0x0028_2112, "LossyImageCompressionRatio", "DS", "1-n", false),
+    entry(0x0028_2114, "LossyImageCompressionMethod", "CS", "1-n", false),
+    entry(0x0028_3000, "ModalityLUTSequence", "SQ", "1", false),
+    entry(0x0028_3002, "LUTDescriptor", "US or SS", "3", false),
+    entry(0x0028_3003, "LUTExplanation", "LO", "1", false),
+    entry(0x0028_3004, "ModalityLUTType", "LO", "1", false),
+    entry(0x0028_3006, "LUTData", "US or OW", "1-n", false),
+    entry(0x0028_3010, "VOILUTSequence", "SQ", "1", false),
+    entry(0x0028_3110, "SoftcopyVOILUTSequence", "SQ", "1", false),
+    entry(0x0028_4000, "ImagePresentationComments", "LT", "1", true),
+    entry(0x0028_5000, "BiPlaneAcquisitionSequence", "SQ", "1", true),
+    entry(0x0028_6010, "RepresentativeFrameNumber", "US", "1", false),
+    entry(0x0028_6020, "FrameNumbersOfInterest", "US", "1-n", false),
+    entry(0x0028_6022, "FrameOfInterestDescription", "LO", "1-n", false),
+    entry(0x0028_6023, "FrameOfInterestType", "CS", "1-n", false),
+    entry(0x0028_6030, "MaskPointers", "US", "1-n", true),
+    entry(0x0028_6040, "RWavePointer", "US", "1-n", false),
+    entry(0x0028_6100, "MaskSubtractionSequence", "SQ", "1", false),
+    entry(0x0028_6101, "MaskOperation", "CS", "1", false),
+    entry(0x0028_6102, "ApplicableFrameRange", "US", "2-2n", false),
+    entry(0x0028_6110, "MaskFrameNumbers", "US", "1-n", false),
+    entry(0x0028_6112, "ContrastFrameAveraging", "US", "1", false),
+    entry(0x0028_6114, "MaskSubPixelShift", "FL", "2", false),
+    entry(0x0028_6120, "TIDOffset", "SS", "1", false),
+    entry(0x0028_6190, "MaskOperationExplanation", "ST", "1", false),
+    entry(0x0028_7000, "EquipmentAdministratorSequence", "SQ", "1", false),
+    entry(0x0028_7001, "NumberOfDisplaySubsystems", "US", "1", false),
+    entry(0x0028_9001, "DataPointRows", "UL", "1", false),
+    entry(0x0028_9002, "DataPointColumns", "UL", "1", false),
+    entry(0x0028_9003, "SignalDomainColumns", "CS", "1", false),
+    entry(0x0028_9099, "LargestMonochromePixelValue", "US", "1", true),
+    entry(0x0028_9108, "DataRepresentation", "CS", "1", false),
+    entry(0x0028_9110, "PixelMeasuresSequence", "SQ", "1", false),
+    entry(0x0028_9132, "FrameVOILUTSequence", "SQ", "1", false),
+    entry(0x0028_9145, "PixelValueTransformationSequence", "SQ", "1", false),
+    entry(0x0028_9235, "SignalDomainRows", "CS", "1", false),
+    entry(0x0028_9411, "DisplayFilterPercentage", "FL", "1", false),
+    entry(0x0028_9415, "FramePixelShiftSequence", "SQ", "1", false),
+    entry(0x0028_9416, "SubtractionItemID", "US", "1", false),
+    entry(0x0028_9422, "PixelIntensityRelationshipLUTSequence", "SQ", "1", false),
+    entry(0x0028_9443, "FramePixelDataPropertiesSequence", "SQ", "1", false),
+    entry(0x0028_9444, "GeometricalProperties", "CS", "1", false),
+    entry(0x0028_9445, "GeometricMaximumDistortion", "FL", "1", false),
+    entry(0x0028_9446, "ImageProcessingApplied", "CS", "1-n", false),
+    entry(0x0028_9454, "MaskSelectionMode", "CS", "1", false),
+    entry(0x0028_9474, "LUTFunction", "CS", "1", false),
+    entry(0x0028_9478, "MaskVisibilityPercentage", "FL", "1", false),
+    entry(0x0028_9501, "PixelShiftSequence", "SQ", "1", false),
+    entry(0x0028_9502, "RegionPixelShiftSequence", "SQ", "1", false),
+    entry(0x0028_9503, "VerticesOfTheRegion", "SS", "2-2n", false),
+    entry(0x0028_9505, "MultiFramePresentationSequence", "SQ", "1", false),
+    entry(0x0028_9506, "PixelShiftFrameRange", "US", "2-2n", false),
+    entry(0x0028_9507, "LUTFrameRange", "US", "2-2n", false),
+    entry(0x0028_9520, "ImageToEquipmentMappingMatrix", "DS", "16", false),
+    entry(0x0028_9537, "EquipmentCoordinateSystemIdentification", "CS", "1", false),
+    entry(0x0032_000A, "StudyStatusID", "CS", "1", true),
+    entry(0x0032_000C, "StudyPriorityID", "CS", "1", true),
+    entry(0x0032_0012, "StudyIDIssuer", "LO", "1", true),
+    entry(0x0032_0032, "StudyVerifiedDate", "DA", "1", true),
+    entry(0x0032_0033, "StudyVerifiedTime", "TM", "1", true),
+    entry(0x0032_0034, "StudyReadDate", "DA", "1", true),
+    entry(0x0032_0035, "StudyReadTime", "TM", "1", true),
+    entry(0x0032_1000, "ScheduledStudyStartDate", "DA", "1", true),
+    entry(0x0032_1001, "ScheduledStudyStartTime", "TM", "1", true),
+    entry(0x0032_1010, "ScheduledStudyStopDate", "DA", "1", true),
+    entry(0x0032_1011, "ScheduledStudyStopTime", "TM", "1", true),
+    entry(0x0032_1020, "ScheduledStudyLocation", "LO", "1", true),
+    entry(0x0032_1021, "ScheduledStudyLocationAETitle", "AE", "1-n", true),
+    entry(0x0032_1030, "ReasonForStudy", "LO", "1", true),
+    entry(0x0032_1031, "RequestingPhysicianIdentificationSequence", "SQ", "1", false),
+    entry(0x0032_1032, "RequestingPhysician", "PN", "1", false),
+    entry(0x0032_1033, "RequestingService", "LO", "1", false),
+    entry(0x0032_1034, "RequestingServiceCodeSequence", "SQ", "1", false),
+    entry(0x0032_1040, "StudyArrivalDate", "DA", "1", true),
+    entry(0x0032_1041, "StudyArrivalTime", "TM", "1", true),
+    entry(0x0032_1050, "StudyCompletionDate", "DA", "1", true),
+    entry(0x0032_1051, "StudyCompletionTime", "TM", "1", true),
+    entry(0x0032_1055, "StudyComponentStatusID", "CS", "1", true),
+    entry(0x0032_1060, "RequestedProcedureDescription", "LO", "1", false),
+    entry(0x0032_1064, "RequestedProcedureCodeSequence", "SQ", "1", false),
+    entry(0x0032_1066, "ReasonForVisit", "UT", "1", false),
+    entry(0x0032_1067, "ReasonForVisitCodeSequence", "SQ", "1", false),
+    entry(0x0032_1070, "RequestedContrastAgent", "LO", "1", false),
+    entry(0x0032_4000, "StudyComments", "LT", "1", true),
+    entry(0x0038_0004, "ReferencedPatientAliasSequence", "SQ", "1", false),
+    entry(0x0038_0008, "VisitStatusID", "CS", "1", false),
+    entry(0x0038_0010, "AdmissionID", "LO", "1", false),
+    entry(0x0038_0011, "IssuerOfAdmissionID", "LO", "1", true),
+    entry(0x0038_0014, "IssuerOfAdmissionIDSequence", "SQ", "1", false),
+    entry(0x0038_0016, "RouteOfAdmissions", "LO", "1", false),
+    entry(0x0038_0020, "AdmittingDate", "DA", "1", false),
+    entry(0x0038_0021, "AdmittingTime", "TM", "1", false),
+    entry(0x0038_0050, "SpecialNeeds", "LO", "1", false),
+    entry(0x0038_0060, "ServiceEpisodeID", "LO", "1", false),
+    entry(0x0038_0062, "ServiceEpisodeDescription", "LO", "1", false),
+    entry(0x0038_0100, "PertinentDocumentsSequence", "SQ", "1", false),
+    entry(0x0038_0300, "CurrentPatientLocation", "LO", "1", false),
+    entry(0x0038_0400, "PatientInstitutionResidence", "LO", "1", false),
+    entry(0x0038_0500, "PatientState", "LO", "1", false),
+    entry(0x0038_0502, "PatientClinicalTrialParticipationSequence", "SQ", "1", false),
+    entry(0x0038_4000, "VisitComments", "LT", "1", false),
+    entry(0x0040_0001, "ScheduledStationAETitle", "AE", "1-n", false),
+    entry(0x0040_0002, "ScheduledProcedureStepStartDate", "DA", "1", false),
+    entry(0x0040_0003, "ScheduledProcedureStepStartTime", "TM", "1", false),
+    entry(0x0040_0004, "ScheduledProcedureStepEndDate", "DA", "1", false),
+    entry(0x0040_0005, "ScheduledProcedureStepEndTime", "TM", "1", false),
+    entry(0x0040_0006, "ScheduledPerformingPhysicianName", "PN", "1", false),
+    entry(0x0040_0007, "ScheduledProcedureStepDescription", "LO", "1", false),
+    entry(0x0040_0008, "ScheduledProtocolCodeSequence", "SQ", "1", false),
+    entry(0x0040_0009, "ScheduledProcedureStepID", "SH", "1", false),
+    entry(0x0040_000A, "StageCodeSequence", "SQ", "1", false),
+    entry(0x0040_000B, "ScheduledPerformingPhysicianIdentificationSequence", "SQ", "1", false),
+    entry(0x0040_0010, "ScheduledStationName", "SH", "1-n", false),
+    entry(0x0040_0011, "ScheduledProcedureStepLocation", "SH", "1", false),
+    entry(0x0040_0012, "PreMedication", "LO", "1", false),
+    entry(0x0040_0020, "ScheduledProcedureStepStatus", "CS", "1", false),
+    entry(0x0040_0100, "ScheduledProcedureStepSequence", "SQ", "1", false),
+    entry(0x0040_0220, "ReferencedNonImageCompositeSOPInstanceSequence", "SQ", "1", false),
+    entry(0x0040_0241, "PerformedStationAETitle", "AE", "1", false),
+    entry(0x0040_0242, "PerformedStationName", "SH", "1", false),
+    entry(0x0040_0243, "PerformedLocation", "SH", "1", false),
+    entry(0x0040_0244, "PerformedProcedureStepStartDate", "DA", "1", false),
+    entry(0x0040_0245, "PerformedProcedureStepStartTime", "TM", "1", false),
+    entry(0x0040_0250, "PerformedProcedureStepEndDate", "DA", "1", false),
+    entry(0x0040_0251, "PerformedProcedureStepEndTime", "TM", "1", false),
+    entry(0x0040_0252, "PerformedProcedureStepStatus", "CS", "1", false),
+    entry(0x0040_0253, "PerformedProcedureStepID", "SH", "1", false),
+    entry(0x0040_0254, "PerformedProcedureStepDescription", "LO", "1", false),
+    entry(0x0040_0255, "PerformedProcedureTypeDescription", "LO", "1", false),
+    entry(0x0040_0260, "PerformedProtocolCodeSequence", "SQ", "1", false),
+    entry(0x0040_0261, "PerformedProtocolType", "CS", "1", false),
+    entry(0x0040_0270, "ScheduledStepAttributesSequence", "SQ", "1", false),
+    entry(0x0040_0275, "RequestAttributesSequence", "SQ", "1", false),
+    entry(0x0040_0280, "CommentsOnThePerformedProcedureStep", "ST", "1", false),
+    entry(0x0040_0281, "PerformedProcedureStepDiscontinuationReasonCodeSequence", "SQ", "1", false),
+    entry(0x0040_0293, "QuantitySequence", "SQ", "1", false),
+    entry(0x0040_0294, "Quantity", "DS", "1", false),
+    entry(0x0040_0295, "MeasuringUnitsSequence", "SQ", "1", false),
+    entry(0x0040_0296, "BillingItemSequence", "SQ", "1", false),
+    entry(0x0040_0300, "TotalTimeOfFluoroscopy", "US", "1", true),
+    entry(0x0040_0301, "TotalNumberOfExposures", "US", "1", true),
+    entry(0x0040_0302, "EntranceDose", "US", "1", false),
+    entry(0x0040_0303, "ExposedArea", "US", "1-2", false),
+    entry(0x0040_0306, "DistanceSourceToEntrance", "DS", "1", false),
+    entry(0x0040_0307, "DistanceSourceToSupport", "DS", "1", true),
+    entry(0x0040_030E, "ExposureDoseSequence", "SQ", "1", false),
+    entry(0x0040_0310, "CommentsOnRadiationDose", "ST", "1", false),
+    entry(0x0040_0312, "XRayOutput", "DS", "1", false),
+    entry(0x0040_0314, "HalfValueLayer", "DS", "1", false),
+    entry(0x0040_0316, "OrganDose", "DS", "1", false),
+    entry(0x0040_0318, "OrganExposed", "CS", "1", false),
+    entry(0x0040_0320, "BillingProcedureStepSequence", "SQ", "1", false),
+    entry(0x0040_0321, "FilmConsumptionSequence", "SQ", "1", false),
+    entry(0x0040_0324, "BillingSuppliesAndDevicesSequence", "SQ", "1", false),
+    entry(0x0040_0330, "ReferencedProcedureStepSequence", "SQ", "1", true),
+    entry(0x0040_0340, "PerformedSeriesSequence", "SQ", "1", false),
+    entry(0x0040_0400, "CommentsOnTheScheduledProcedureStep", "LT", "1", false),
+    entry(0x0040_0440, "ProtocolContextSequence", "SQ", "1", false),
+    entry(0x0040_0441, "ContentItemModifierSequence", "SQ", "1", false),
+    entry(0x0040_0500, "ScheduledSpecimenSequence", "SQ", "1", false),
+    entry(0x0040_050A, "SpecimenAccessionNumber", "LO", "1", true),
+    entry(0x0040_0512, "ContainerIdentifier", "LO", "1", false),
+    entry(0x0040_0513, "IssuerOfTheContainerIdentifierSequence", "SQ", "1", false),
+    entry(0x0040_0515, "AlternateContainerIdentifierSequence", "SQ", "1", false),
+    entry(0x0040_0518, "ContainerTypeCodeSequence", "SQ", "1", false),
+    entry(0x0040_051A, "ContainerDescription", "LO", "1", false),
+    entry(0x0040_0520, "ContainerComponentSequence", "SQ", "1", false),
+    entry(0x0040_0550, "SpecimenSequence", "SQ", "1", true),
+    entry(0x0040_0551, "SpecimenIdentifier", "LO", "1", false),
+    entry(0x0040_0552, "SpecimenDescriptionSequenceTrial", "SQ", "1", true),
+    entry(0x0040_0553, "SpecimenDescriptionTrial", "ST", "1", true),
+    entry(0x0040_0554, "SpecimenUID", "UI", "1", false),
+    entry(0x0040_0555, "AcquisitionContextSequence", "SQ", "1", false),
+    entry(0x0040_0556, "AcquisitionContextDescription", "ST", "1", false),
+    entry(0x0040_0560, "SpecimenDescriptionSequence", "SQ", "1", false),
+    entry(0x0040_0562, "IssuerOfTheSpecimenIdentifierSequence", "SQ", "1", false),
+    entry(0x0040_059A, "SpecimenTypeCodeSequence", "SQ", "1", false),
+    entry(0x0040_0600, "SpecimenShortDescription", "LO", "1", false),
+    entry(0x0040_0602, "SpecimenDetailedDescription", "UT", "1", false),
+    entry(0x0040_0610, "SpecimenPreparationSequence", "SQ", "1", false),
+    entry(0x0040_0612, "SpecimenPreparationStepContentItemSequence", "SQ", "1", false),
+    entry(0x0040_0620, "SpecimenLocalizationContentItemSequence", "SQ", "1", false),
+    entry(0x0040_06FA, "SlideIdentifier", "LO", "1", true),
+    entry(0x0040_071A, "ImageCenterPointCoordinatesSequence", "SQ", "1", false),
+    entry(0x0040_072A, "XOffsetInSlideCoordinateSystem", "DS", "1", false),
+    entry(0x0040_073A, "YOffsetInSlideCoordinateSystem", "DS", "1", false),
+    entry(0x0040_074A, "ZOffsetInSlideCoordinateSystem", "DS", "1", false),
+    entry(0x0040_08D8, "PixelSpacingSequence", "SQ", "1", true),
+    entry(0x0040_08DA, "CoordinateSystemAxisCodeSequence", "SQ", "1", true),
+    entry(0x0040_08EA, "MeasurementUnitsCodeSequence", "SQ", "1", false),
+    entry(0x0040_1001, "RequestedProcedureID", "SH", "1", false),
+    entry(0x0040_1002, "ReasonForTheRequestedProcedure", "LO", "1", false),
+    entry(0x0040_1003, "RequestedProcedurePriority", "SH", "1", false),
+    entry(0x0040_1004, "PatientTransportArrangements", "LO", "1", false),
+    entry(0x0040_1005, "RequestedProcedureLocation", "LO", "1", false),
+    entry(0x0040_1006, "PlacerOrderNumberProcedure", "SH", "1", true),
+    entry(0x0040_1007, "FillerOrderNumberProcedure", "SH", "1", true),
+    entry(0x0040_1008, "ConfidentialityCode", "LO", "1", false),
+    entry(0x0040_1009, "ReportingPriority", "SH", "1", false),
+    entry(0x0040_100A, "ReasonForRequestedProcedureCodeSequence", "SQ", "1", false),
+    entry(0x0040_1010, "NamesOfIntendedRecipientsOfResults", "PN", "1-n", false),
+    entry(0x0040_1011, "IntendedRecipientsOfResultsIdentificationSequence", "SQ", "1", false),
+    entry(0x0040_1012, "ReasonForPerformedProcedureCodeSequence", "SQ", "1", false),
+    entry(0x0040_1060, "RequestedProcedureDescriptionTrial", "LO", "1", true),
+    entry(0x0040_1101, "PersonIdentificationCodeSequence", "SQ", "1", false),
+    entry(0x0040_1102, "PersonAddress", "ST", "1", false),
+    entry(0x0040_1103, "PersonTelephoneNumbers", "LO", "1-n", false),
+    entry(0x0040_1104, "PersonTelecomInformation", "LT", "1", false),
+    entry(0x0040_1400, "RequestedProcedureComments", "LT", "1", false),
+    entry(0x0040_2001, "ReasonForTheImagingServiceRequest", "LO", "1", true),
+    entry(0x0040_2004, "IssueDateOfImagingServiceRequest", "DA", "1", false),
+    entry(0x0040_2005, "IssueTimeOfImagingServiceRequest", "TM", "1", false),
+    entry(0x0040_2006, "PlacerOrderNumberImagingServiceRequestRetired", "SH", "1", true),
+    entry(0x0040_2007, "FillerOrderNumberImagingServiceRequestRetired", "SH", "1", true),
+    entry(0x0040_2008, "OrderEnteredBy", "PN", "1", false),
+    entry(0x0040_2009, "OrderEntererLocation", "SH", "1", false),
+    entry(0x0040_2010, "OrderCallbackPhoneNumber", "SH", "1", false),
+    entry(0x0040_2011, "OrderCallbackTelecomInformation", "LT", "1", false),
+    entry(0x0040_2016, "PlacerOrderNumberImagingServiceRequest", "LO", "1", false),
+    entry(0x0040_2017, "FillerOrderNumberImagingServiceRequest", "LO", "1", false),
+    entry(0x0040_2400, "ImagingServiceRequestComments", "LT", "1", false),
+    entry(0x0040_3001, "ConfidentialityConstraintOnPatientDataDescription", "LO", "1", false),
+    entry(0x0040_9092, "ParameterSequence", "SQ", "1", false),
+    entry(0x0040_9094, "ConsumableFlag", "CS", "1", false),
+    entry(0x0040_9096, "RealWorldValueMappingSequence", "SQ", "1", false),
+    entry(0x0040_9098, "PixelValueMappingCodeSequence", "SQ", "1", false),
+    entry(0x0040_9210, "LUTLabel", "SH", "1", false),
+    entry(0x0040_9211, "RealWorldValueLastValueMapped", "US or SS", "1", false),
+    entry(0x0040_9212, "RealWorldValueLUTData", "FD", "1-n", false),
+    entry(0x0040_9213, "DoubleFloatRealWorldValueLastValueMapped", "FD", "1", false),
+    entry(0x0040_9214, "DoubleFloatRealWorldValueFirstValueMapped", "FD", "1", false),
+    entry(0x0040_9216, "RealWorldValueFirstValueMapped", "US or SS", "1", false),
+    entry(0x0040_9220, "QuantityDefinitionSequence", "SQ", "1", false),
+    entry(0x0040_9224, "RealWorldValueIntercept", "FD", "1", false),
+    entry(0x0040_9225, "RealWorldValueSlope", "FD", "1", false),
+    entry(0x0040_A010, "RelationshipType", "CS", "1", false),
+    entry(0x0040_A027, "VerifyingOrganization", "LO", "1", false),
+    entry(0x0040_A030, "VerificationDateTime", "DT", "1", false),
+    entry(0x0040_A032, "ObservationDateTime", "DT", "1", false),
+    entry(0x0040_A040, "ValueType", "CS", "1", false),
+    entry(0x0040_A043, "ConceptNameCodeSequence", "SQ", "1", false),
+    entry(0x0040_A050, "ContinuityOfContent", "CS", "1", false),
+    entry(0x0040_A073, "VerifyingObserverSequence", "SQ", "1", false),
+    entry(0x0040_A075, "VerifyingObserverName", "PN", "1", false),
+    entry(0x0040_A078, "AuthorObserverSequence", "SQ", "1", false),
+    entry(0x0040_A07A, "ParticipantSequence", "SQ", "1", false),
+    entry(0x0040_A07C, "CustodialOrganizationSequence", "SQ", "1", false),
+    entry(0x0040_A080, "ParticipationType", "CS", "1", false),
+    entry(0x0040_A082, "ParticipationDateTime", "DT", "1", false),
+    entry(0x0040_A084, "ObserverType", "CS", "1", false),
+    entry(0x0040_A088, "VerifyingObserverIdentificationCodeSequence", "SQ", "1", false),
+    entry(0x0040_A0B0, "ReferencedWaveformChannels", "US", "2-2n", false),
+    entry(0x0040_A120, "DateTime", "DT", "1", false),
+    entry(0x0040_A121, "Date", "DA", "1", false),
+    entry(0x0040_A122, "Time", "TM", "1", false),
+    entry(0x0040_A123, "PersonName", "PN", "1", false),
+    entry(0x0040_A124, "UID", "UI", "1", false),
+    entry(0x0040_A130, "TemporalRangeType", "CS", "1", false),
+    entry(0x0040_A132, "ReferencedSamplePositions", "UL", "1-n", false),
+    entry(0x0040_A136, "ReferencedFrameNumbers", "US", "1-n", false),
+    entry(0x0040_A138, "ReferencedTimeOffsets", "DS", "1-n", false),
+    entry(0x0040_A13A, "ReferencedDateTime", "DT", "1-n", false),
+    entry(0x0040_A160, "TextValue", "UT", "1", false),
+    entry(0x0040_A161, "FloatingPointValue", "FD", "1-n", false),
+    entry(0x0040_A162, "RationalNumeratorValue", "SL", "1-n", false),
+    entry(0x0040_A163, "RationalDenominatorValue", "UL", "1-n", false),
+    entry(0x0040_A168, "ConceptCodeSequence", "SQ", "1", false),
+    entry(0x0040_A170, "PurposeOfReferenceCodeSequence", "SQ", "1", false),
+    entry(0x0040_A180, "AnnotationGroupNumber", "US", "1", false),
+    entry(0x0040_A195, "ModifierCodeSequence", "SQ", "1", false),
+    entry(0x0040_A300, "MeasuredValueSequence", "SQ", "1", false),
+    entry(0x0040_A301, "NumericValueQualifierCodeSequence", "SQ", "1", false),
+    entry(0x0040_A30A, "NumericValue", "DS", "1-n", false),
+    entry(0x0040_A360, "PredecessorDocumentsSequence", "SQ", "1", false),
+    entry(0x0040_A370, "ReferencedRequestSequence", "SQ", "1", false),
+    entry(0x0040_A372, "PerformedProcedureCodeSequence", "SQ", "1", false),
+    entry(0x0040_A375, "CurrentRequestedProcedureEvidenceSequence", "SQ", "1", false),
+    entry(0x0040_A385, "PertinentOtherEvidenceSequence", "SQ", "1", false),
+    entry(0x0040_A390, "HL7StructuredDocumentReferenceSequence", "SQ", "1", false),
+    entry(0x0040_A491, "CompletionFlag", "CS", "1", false),
+    entry(0x0040_A492, "CompletionFlagDescription", "LO", "1", false),
+    entry(0x0040_A493, "VerificationFlag", "CS", "1", false),
+    entry(0x0040_A494, "ArchiveRequested", "CS", "1", false),
+    entry(0x0040_A496, "PreliminaryFlag", "CS", "1", false),
+    entry(0x0040_A504, "ContentTemplateSequence", "SQ", "1", false),
+    entry(0x0040_A525, "IdenticalDocumentsSequence", "SQ", "1", false),
+    entry(0x0040_A730, "ContentSequence", "SQ", "1", false),
+    entry(0x0040_B020, "WaveformAnnotationSequence", "SQ", "1", false),
+    entry(0x0040_DB00, "TemplateIdentifier", "CS", "1", false),
+    entry(0x0040_DB73, "ReferencedContentItemIdentifier", "UL", "1-n", false),
+    entry(0x0040_E001, "HL7InstanceIdentifier", "ST", "1", false),
+    entry(0x0040_E004, "HL7DocumentEffectiveTime", "DT", "1", false),
+    entry(0x0040_E006, "HL7DocumentTypeCodeSequence", "SQ", "1", false),
+    entry(0x0040_E008, "DocumentClassCodeSequence", "SQ", "1", false),
+    entry(0x0040_E010, "RetrieveURI", "UR", "1", false),
+    entry(0x0040_E011, "RetrieveLocationUID", "UI", "1", false),
+    entry(0x0040_E020, "TypeOfInstances", "CS", "1", false),
+    entry(0x0040_E021, "DICOMRetrievalSequence", "SQ", "1", false),
+    entry(0x0040_E022, "DICOMMediaRetrievalSequence", "SQ", "1", false),
+    entry(0x0040_E023, "WADORetrievalSequence", "SQ", "1", false),
+    entry(0x0040_E024, "XDSRetrievalSequence", "SQ", "1", false),
+    entry(0x0040_E025, "WADORSRetrievalSequence", "SQ", "1", false),
+    entry(0x0040_E030, "RepositoryUniqueID", "UI", "1", false),
+    entry(0x0040_E031, "HomeCommunityID", "UI", "1", false),
+    entry(0x0042_0010, "DocumentTitle", "ST", "1", false),
+    entry(0x0042_0011, "EncapsulatedDocument", "OB", "1", false),
+    entry(0x0042_0012, "MIMETypeOfEncapsulatedDocument", "LO", "1", false),
+    entry(0x0042_0013, "SourceInstanceSequence", "SQ", "1", false),
+    entry(0x0042_0014, "ListOfMIMETypes", "LO", "1-n", false),
+    entry(0x0042_0015, "EncapsulatedDocumentLength", "UL", "1", false),
+    entry(0x0048_0001, "ImagedVolumeWidth", "FL", "1", false),
+    entry(0x0048_0002, "ImagedVolumeHeight", "FL", "1", false),
+    entry(0x0048_0003, "ImagedVolumeDepth", "FL", "1", false),
+    entry(0x0048_0006, "TotalPixelMatrixColumns", "UL", "1", false),
+    entry(0x0048_0007, "TotalPixelMatrixRows", "UL", "1", false),
+    entry(0x0048_0008, "TotalPixelMatrixOriginSequence", "SQ", "1", false),
+    entry(0x0048_0010, "SpecimenLabelInImage", "CS", "1", false),
+    entry(0x0048_0011, "FocusMethod", "CS", "1", false),
+    entry(0x0048_0012, "ExtendedDepthOfField", "CS", "1", false),
+    entry(0x0048_0013, "NumberOfFocalPlanes", "US", "1", false),
+    entry(0x0048_0014, "DistanceBetweenFocalPlanes", "FL", "1", false),
+    entry(0x0048_0015, "RecommendedAbsentPixelCIELabValue", "US", "3", false),
+    entry(0x0048_0100, "IlluminatorTypeCodeSequence", "SQ", "1", false),
+    entry(0x0048_0102, "ImageOrientationSlide", "DS", "6", false),
+    entry(0x0048_0105, "OpticalPathSequence", "SQ", "1", false),
+    entry(0x0048_0106, "OpticalPathIdentifier", "SH", "1", false),
+    entry(0x0048_0107, "OpticalPathDescription", "ST", "1", false),
+    entry(0x0048_0108, "IlluminationColorCodeSequence", "SQ", "1", false),
+    entry(0x0048_0110, "SpecimenReferenceSequence", "SQ", "1", false),
+    entry(0x0048_0111, "CondenserLensPower", "DS", "1", false),
+    entry(0x0048_0112, "ObjectiveLensPower", "DS", "1", false),
+    entry(0x0048_0113, "ObjectiveLensNumericalAperture", "DS", "1", false),
+    entry(0x0048_0120, "PaletteColorLookupTableSequence", "SQ", "1", false),
+    entry(0x0048_0200, "ReferencedImageNavigationSequence", "SQ", "1", true),
+    entry(0x0048_0201, "TopLeftHandCornerOfLocalizerArea", "US", "2", true),
+    entry(0x0048_0202, "BottomRightHandCornerOfLocalizerArea", "US", "2", true),
+    entry(0x0048_0207, "OpticalPathIdentificationSequence", "SQ", "1", false),
+    entry(0x0048_021A, "PlanePositionSlideSequence", "SQ", "1", false),
+    entry(0x0048_021E, "ColumnPositionInTotalImagePixelMatrix", "SL", "1", false),
+    entry(0x0048_021F, "RowPositionInTotalImagePixelMatrix", "SL", "1", false),
+    entry(0x0048_0301, "PixelOriginInterpretation", "CS", "1", false),
+    entry(0x0048_0302, "NumberOfOpticalPaths", "UL", "1", false),
+    entry(0x0048_0303, "TotalPixelMatrixFocalPlanes", "UL", "1", false),
+    entry(0x0050_0004, "CalibrationImage", "CS", "1", false),
+    entry(0x0050_0010, "DeviceSequence", "SQ", "1", false),
+    entry(0x0050_0012, "ContainerComponentTypeCodeSequence", "SQ", "1", false),
+    entry(0x0050_0013, "ContainerComponentThickness", "FD", "1", false),
+    entry(0x0050_0014, "DeviceLength", "DS", "1", false),
+    entry(0x0050_0015, "ContainerComponentWidth", "FD", "1", false),
+    entry(0x0050_0016, "DeviceDiameter", "DS", "1", false),
+    entry(0x0050_0017, "DeviceDiameterUnits", "CS", "1", false),
+    entry(0x0050_0018, "DeviceVolume", "DS", "1", false),
+    entry(0x0050_0019, "InterMarkerDistance", "DS", "1", false),
+    entry(0x0050_001A, "ContainerComponentMaterial", "CS", "1", false),
+    entry(0x0050_001B, "ContainerComponentID", "LO", "1", false),
+    entry(0x0050_001C, "ContainerComponentLength", "FD", "1", false),
+    entry(0x0050_001D, "ContainerComponentDiameter", "FD", "1", false),
+    entry(0x0050_001E, "ContainerComponentDescription", "LO", "1", false),
+    entry(0x0050_0020, "DeviceDescription", "LO", "1", false),
+    entry(0x0054_0010, "EnergyWindowVector", "US", "1-n", false),
+    entry(0x0054_0011, "NumberOfEnergyWindows", "US", "1", false),
+    entry(0x0054_0012, "EnergyWindowInformationSequence", "SQ", "1", false),
+    entry(0x0054_0013, "EnergyWindowRangeSequence", "SQ", "1", false),
+    entry(0x0054_0014, "EnergyWindowLowerLimit", "DS", "1", false),
+    entry(0x0054_0015, "EnergyWindowUpperLimit", "DS", "1", false),
+    entry(0x0054_0016, "RadiopharmaceuticalInformationSequence", "SQ", "1", false),
+    entry(0x0054_0017, "ResidualSyringeCounts", "IS", "1", false),
+    entry(0x0054_0018, "EnergyWindowName", "SH", "1", false),
+    entry(0x0054_0020, "DetectorVector", "US", "1-n", false),
+    entry(0x0054_0021, "NumberOfDetectors", "US", "1", false),
+    entry(0x0054_0022, "DetectorInformationSequence", "SQ", "1", false),
+    entry(0x0054_0030, "PhaseVector", "US", "1-n", false),
+    entry(0x0054_0031, "NumberOfPhases", "US", "1", false),
+    entry(0x0054_0032, "PhaseInformationSequence", "SQ", "1", false),
+    entry(0x0054_0033, "NumberOfFramesInPhase", "US", "1", false),
+    entry(0x0054_0036, "PhaseDelay", "IS", "1", false),
+    entry(0x0054_0038, "PauseBetweenFrames", "IS", "1", false),
+    entry(0x0054_0039, "PhaseDescription", "CS", "1", false),
+    entry(0x0054_0050, "RotationVector", "US", "1-n", false),
+    entry(0x0054_0051, "NumberOfRotations", "US", "1", false),
+    entry(0x0054_0052, "RotationInformationSequence", "SQ", "1", false),
+    entry(0x0054_0053, "NumberOfFramesInRotation", "US", "1", false),
+    entry(0x0054_0060, "RRIntervalVector", "US", "1-n", false),
+    entry(0x0054_0061, "NumberOfRRIntervals", "US", "1", false),
+    entry(0x0054_0062, "GatedInformationSequence", "SQ", "1", false),
+    entry(0x0054_0063, "DataInformationSequence", "SQ", "1", false),
+    entry(0x0054_0070, "TimeSlotVector", "US", "1-n", false),
+    entry(0x0054_0071, "NumberOfTimeSlots", "US", "1", false),
+    entry(0x0054_0072, "TimeSlotInformationSequence", "SQ", "1", false),
+    entry(0x0054_0073, "TimeSlotTime", "DS", "1", false),
+    entry(0x0054_0080, "SliceVector", "US", "1-n", false),
+    entry(0x0054_0081, "NumberOfSlices", "US", "1", false),
+    entry(0x0054_0090, "AngularViewVector", "US", "1-n", false),
+    entry(0x0054_0100, "TimeSliceVector", "US", "1-n", false),
+    entry(0x0054_0101, "NumberOfTimeSlices", "US", "1", false),
+    entry(0x0054_0200, "StartAngle", "DS", "1", false),
+    entry(0x0054_0202, "TypeOfDetectorMotion", "CS", "1", false),
+    entry(0x0054_0210, "TriggerVector", "IS", "1-n", false),
+    entry(0x0054_0211, "NumberOfTriggersInPhase", "US", "1", false),
+    entry(0x0054_0220, "ViewCodeSequence", "SQ", "1", false),
+    entry(0x0054_0222, "ViewModifierCodeSequence", "SQ", "1", false),
+    entry(0x0054_0300, "RadionuclideCodeSequence", "SQ", "1", false),
+    entry(0x0054_0302, "AdministrationRouteCodeSequence", "SQ", "1", false),
+    entry(0x0054_0304, "RadiopharmaceuticalCodeSequence", "SQ", "1", false),
+    entry(0x0054_0306, "CalibrationDataSequence", "SQ", "1", false),
+    entry(0x0054_0308, "EnergyWindowNumber", "US", "1", false),
+    entry(0x0054_0400, "ImageID", "SH", "1", false),
+    entry(0x0054_0410, "PatientOrientationCodeSequence", "SQ", "1", false),
+    entry(0x0054_0412, "PatientOrientationModifierCodeSequence", "SQ", "1", false),
+    entry(0x0054_0414, "PatientGantryRelationshipCodeSequence", "SQ", "1", false),
+    entry(0x0054_0500, "SliceProgressionDirection", "CS", "1", false),
+    entry(0x0054_0501, "ScanProgressionDirection", "CS", "1", false),
+    entry(0x0054_1000, "SeriesType", "CS", "2", false),
+    entry(0x0054_1001, "Units", "CS", "1", false),
+    entry(0x0054_1002, "CountsSource", "CS", "1", false),
+    entry(0x0054_1004, "ReprojectionMethod", "CS", "1", false),
+    entry(0x0054_1006, "SUVType", "CS", "1", false),
+    entry(0x0054_1100, "RandomsCorrectionMethod", "CS", "1", false),
+    entry(0x0054_1101, "AttenuationCorrectionMethod", "LO", "1", false),
+    entry(0x0054_1102, "DecayCorrection", "CS", "1", false),
+    entry(0x0054_1103, "ReconstructionMethod", "LO", "1", false),
+    entry(0x0054_1104, "DetectorLinesOfResponseUsed", "LO", "1", false),
+    entry(0x0054_1105, "ScatterCorrectionMethod", "LO", "1", false),
+    entry(0x0054_1200, "AxialAcceptance", "DS", "1", false),
+    entry(0x0054_1201, "AxialMash", "IS", "2", false),
+    entry(0x0054_1202, "TransverseMash", "IS", "1", false),
+    entry(0x0054_1203, "DetectorElementSize", "DS", "2", false),
+    entry(0x0054_1210, "CoincidenceWindowWidth", "DS", "1", false),
+    entry(0x0054_1220, "SecondaryCountsType", "CS", "1-n", false),
+    entry(0x0054_1300, "FrameReferenceTime", "DS", "1", false),
+    entry(0x0054_1310, "PrimaryPromptsCountsAccumulated", "IS", "1", false),
+    entry(0x0054_1311, "SecondaryCountsAccumulated", "IS", "1-n", false),
+    entry(0x0054_1320, "SliceSensitivityFactor", "DS", "1", false),
+    entry(0x0054_1321, "DecayFactor", "DS", "1", false),
+    entry(0x0054_1322, "DoseCalibrationFactor", "DS", "1", false),
+    entry(0x0054_1323, "ScatterFractionFactor", "DS", "1", false),
+    entry(0x0054_1324, "DeadTimeFactor", "DS", "1", false),
+    entry(0x0054_1330, "ImageIndex", "US", "1", false),
+    entry(0x0054_1400, "CountsIncluded", "CS", "1-n", true),
+    entry(0x0054_1401, "DeadTimeCorrectionFlag", "CS", "1", true),
+    entry(0x0062_0001, "SegmentationType", "CS", "1", false),
+    entry(0x0062_0002, "SegmentSequence", "SQ", "1", false),
+    entry(0x0062_0003, "SegmentedPropertyCategoryCodeSequence", "SQ", "1", false),
+    entry(0x0062_0004, "SegmentNumber", "US", "1", false),
+    entry(0x0062_0005, "SegmentLabel", "LO", "1", false),
+    entry(0x0062_0006, "SegmentDescription", "ST", "1", false),
+    entry(0x0062_0008, "SegmentAlgorithmType", "CS", "1", false),
+    entry(0x0062_0009, "SegmentAlgorithmName", "LO", "1-n", false),
+    entry(0x0062_000A, "SegmentIdentificationSequence", "SQ", "1", false),
+    entry(0x0062_000B, "ReferencedSegmentNumber", "US", "1-n", false),
+    entry(0x0062_000C, "RecommendedDisplayGrayscaleValue", "US", "1", false),
+    entry(0x0062_000D, "RecommendedDisplayCIELabValue", "US", "3", false),
+    entry(0x0062_000E, "MaximumFractionalValue", "US", "1", false),
+    entry(0x0062_000F, "SegmentedPropertyTypeCodeSequence", "SQ", "1", false),
+    entry(0x0062_0010, "SegmentationFractionalType", "CS", "1", false),
+    entry(0x0062_0011, "SegmentedPropertyTypeModifierCodeSequence", "SQ", "1", false),
+    entry(0x0062_0012, "UsedSegmentsSequence", "SQ", "1", false),
+    entry(0x0062_0013, "SegmentsOverlap", "CS", "1", false),
+    entry(0x0062_0020, "TrackingID", "UT", "1", false),
+    entry(0x0062_0021, "TrackingUID", "UI", "1", false),
+    entry(0x0070_0001, "GraphicAnnotationSequence", "SQ", "1", false),
+    entry(0x0070_0002, "GraphicLayer", "CS", "1", false),
+    entry(0x0070_0003, "BoundingBoxAnnotationUnits", "CS", "1", false),
+    entry(0x0070_0004, "AnchorPointAnnotationUnits", "CS", "1", false),
+    entry(0x0070_0005, "GraphicAnnotationUnits", "CS", "1", false),
+    entry(0x0070_0006, "UnformattedTextValue", "ST", "1", false),
+    entry(0x0070_0008, "TextObjectSequence", "SQ", "1", false),
+    entry(0x0070_0009, "GraphicObjectSequence", "SQ", "1", false),
+    entry(0x0070_0010, "BoundingBoxTopLeftHandCorner", "FL", "2", false),
+    entry(0x0070_0011, "BoundingBoxBottomRightHandCorner", "FL", "2", false),
+    entry(0x0070_0012, "BoundingBoxTextHorizontalJustification", "CS", "1", false),
+    entry(0x0070_0014, "AnchorPoint", "FL", "2", false),
+    entry(0x0070_0015, "AnchorPointVisibility", "CS", "1", false),
+    entry(0x0070_0020, "GraphicDimensions", "US", "1", false),
+    entry(0x0070_0021, "NumberOfGraphicPoints", "US", "1", false),
+    entry(0x0070_0022, "GraphicData", "FL", "2-n", false),
+    entry(0x0070_0023, "GraphicType", "CS", "1", false),
+    entry(0x0070_0024, "GraphicFilled", "CS", "1", false),
+    entry(0x0070_0040, "ImageRotationRetired", "IS", "1", true),
+    entry(0x0070_0041, "ImageHorizontalFlip", "CS", "1", false),
+    entry(0x0070_0042, "ImageRotation", "US", "1", false),
+    entry(0x0070_0050, "DisplayedAreaTopLeftHandCornerTrial", "US", "2", true),
+    entry(0x0070_0051, "DisplayedAreaBottomRightHandCornerTrial", "US", "2", true),
+    entry(0x0070_0052, "DisplayedAreaTopLeftHandCorner", "SL", "2", false),
+    entry(0x0070_0053, "DisplayedAreaBottomRightHandCorner", "SL", "2", false),
+    entry(0x0070_005A, "DisplayedAreaSelectionSequence", "SQ", "1", false),
+    entry(0x0070_0060, "GraphicLayerSequence", "SQ", "1", false),
+    entry(0x0070_0062, "GraphicLayerOrder", "IS", "1", false),
+    entry(0x0070_0066, "GraphicLayerRecommendedDisplayGrayscaleValue", "US", "1", false),
+    entry(0x0070_0067, "GraphicLayerRecommendedDisplayRGBValue", "US", "3", true),
+    entry(0x0070_0068, "GraphicLayerDescription", "LO", "1", false),
+    entry(0x0070_0080, "ContentLabel", "CS", "1", false),
+    entry(0x0070_0081, "ContentDescription", "LO", "1", false),
+    entry(0x0070_0082, "PresentationCreationDate", "DA", "1", false),
+    entry(0x0070_0083, "PresentationCreationTime", "TM", "1", false),
+    entry(0x0070_0084, "ContentCreatorName", "PN", "1", false),
+    entry(0x0070_0086, "ContentCreatorIdentificationCodeSequence", "SQ", "1", false),
+    entry(0x0070_0087, "AlternateContentDescriptionSequence", "SQ", "1", false),
+    entry(0x0070_0100, "PresentationSizeMode", "CS", "1", false),
+    entry(0x0070_0101, "PresentationPixelSpacing", "DS", "2", false),
+    entry(0x0070_0102, "PresentationPixelAspectRatio", "IS", "2", false),
+    entry(0x0070_0103, "PresentationPixelMagnificationRatio", "FL", "1", false),
+    entry(0x0070_0308, "RegistrationSequence", "SQ", "1", false),
+    entry(0x0070_0309, "MatrixRegistrationSequence", "SQ", "1", false),
+    entry(0x0070_030A, "MatrixSequence", "SQ", "1", false),
+    entry(0x0070_030C, "FrameOfReferenceTransformationMatrixType", "CS", "1", false),
+    entry(0x0070_030D, "RegistrationTypeCodeSequence", "SQ", "1", false),
+    entry(0x0070_030F, "FiducialDescription", "ST", "1", false),
+    entry(0x0070_0310, "FiducialIdentifier", "SH", "1", false),
+    entry(0x0070_0311, "FiducialIdentifierCodeSequence", "SQ", "1", false),
+    entry(0x0070_0312, "ContourUncertaintyRadius", "FD", "1", false),
+    entry(0x0070_0314, "UsedFiducialsSequence", "SQ", "1", false),
+    entry(0x0070_0318, "GraphicCoordinatesDataSequence", "SQ", "1", false),
+    entry(0x0070_031A, "FiducialUID", "UI", "1", false),
+    entry(0x0070_031C, "FiducialSetSequence", "SQ", "1", false),
+    entry(0x0070_031E, "FiducialSequence", "SQ", "1", false),
+    entry(0x0070_0401, "GraphicLayerRecommendedDisplayCIELabValue", "US", "3", false),
+    entry(0x0070_0402, "BlendingSequence", "SQ", "1", false),
+    entry(0x0070_0403, "RelativeOpacity", "FL", "1", false),
+    entry(0x0070_0404, "ReferencedSpatialRegistrationSequence", "SQ", "1", false),
+    entry(0x0070_0405, "BlendingPosition", "CS", "1", false),
+    entry(0x0088_0130, "StorageMediaFileSetID", "SH", "1", false),
+    entry(0x0088_0140, "StorageMediaFileSetUID", "UI", "1", false),
+    entry(0x0088_0200, "IconImageSequence", "SQ", "1", false),
+    entry(0x0088_0904, "TopicTitle", "LO", "1", true),
+    entry(0x0088_0906, "TopicSubject", "ST", "1", true),
+    entry(0x0088_0910, "TopicAuthor", "LO", "1", true),
+    entry(0x0088_0912, "TopicKeywords", "LO", "1-32", true),
+    entry(0x0400_0005, "MACIDNumber", "US", "1", false),
+    entry(0x0400_0010, "MACCalculationTransferSyntaxUID", "UI", "1", false),
+    entry(0x0400_0015, "MACAlgorithm", "CS", "1", false),
+    entry(0x0400_0020, "DataElementsSigned", "AT", "1-n", false),
+    entry(0x0400_0100, "DigitalSignatureUID", "UI", "1", false),
+    entry(0x0400_0105, "DigitalSignatureDateTime", "DT", "1", false),
+    entry(0x0400_0110, "CertificateType", "CS", "1", false),
+    entry(0x0400_0115, "CertificateOfSigner", "OB", "1", false),
+    entry(0x0400_0120, "Signature", "OB", "1", false),
+    entry(0x0400_0305, "CertifiedTimestampType", "CS", "1", false),
+    entry(0x0400_0310, "CertifiedTimestamp", "OB", "1", false),
+    entry(0x0400_0401, "DigitalSignaturePurposeCodeSequence", "SQ", "1", false),
+    entry(0x0400_0402, "ReferencedDigitalSignatureSequence", "SQ", "1", false),
+    entry(0x0400_0403, "ReferencedSOPInstanceMACSequence", "SQ", "1", false),
+    entry(0x0400_0404, "MAC", "OB", "1", false),
+    entry(0x0400_0500, "EncryptedAttributesSequence", "SQ", "1", false),
+    entry(0x0400_0510, "EncryptedContentTransferSyntaxUID", "UI", "1", false),
+    entry(0x0400_0520, "EncryptedContent", "OB", "1", false),
+    entry(0x0400_0550, "ModifiedAttributesSequence", "SQ", "1", false),
+    entry(0x0400_0561, "OriginalAttributesSequence", "SQ", "1", false),
+    entry(0x0400_0562, "AttributeModificationDateTime", "DT", "1", false),
+    entry(0x0400_0563, "ModifyingSystem", "LO", "1", false),
+    entry(0x0400_0564, "SourceOfPreviousValues", "LO", "1", false),
+    entry(0x0400_0565, "ReasonForTheAttributeModification", "CS", "1", false),
+    entry(0x2050_0010, "PresentationLUTSequence", "SQ", "1", false),
+    entry(0x2050_0020, "PresentationLUTShape", "CS", "1", false),
+    entry(0x2050_0500, "ReferencedPresentationLUTSequence", "SQ", "1", false),
+    entry(0x3002_0002, "RTImageLabel", "SH", "1", false),
+    entry(0x3002_0003, "RTImageName", "LO", "1", false),
+    entry(0x3002_0004, "RTImageDescription", "ST", "1", false),
+    entry(0x3002_000A, "ReportedValuesOrigin", "CS", "1", false),
+    entry(0x3002_000C, "RTImagePlane", "CS", "1", false),
+    entry(0x3002_000D, "XRayImageReceptorTranslation", "DS", "3", false),
+    entry(0x3002_000E, "XRayImageReceptorAngle", "DS", "1", false),
+    entry(0x3002_0010, "RTImageOrientation", "DS", "6", false),
+    entry(0x3002_0011, "ImagePlanePixelSpacing", "DS", "2", false),
+    entry(0x3002_0012, "RTImagePosition", "DS", "2", false),
+    entry(0x3002_0020, "RadiationMachineName", "SH", "1", false),
+    entry(0x3002_0022, "RadiationMachineSAD", "DS", "1", false),
+    entry(0x3002_0024, "RadiationMachineSSD", "DS", "1", false),
+    entry(0x3002_0026, "RTImageSID", "DS", "1", false),
+    entry(0x3002_0028, "SourceToReferenceObjectDistance", "DS", "1", false),
+    entry(0x3002_0029, "FractionNumber", "IS", "1", false),
+    entry(0x3002_0030, "ExposureSequence", "SQ", "1", false),
+    entry(0x3002_0032, "MetersetExposure", "DS", "1", false),
+    entry(0x3004_0001, "DVHType", "CS", "1", false),
+    entry(0x3004_0002, "DoseUnits", "CS", "1", false),
+    entry(0x3004_0004, "DoseType", "CS", "1", false),
+    entry(0x3004_0005, "SpatialTransformOfDose", "CS", "1", false),
+    entry(0x3004_0006, "DoseComment", "LO", "1", false),
+    entry(0x3004_0008, "NormalizationPoint", "DS", "3", false),
+    entry(0x3004_000A, "DoseSummationType", "CS", "1", false),
+    entry(0x3004_000C, "GridFrameOffsetVector", "DS", "2-n", false),
+    entry(0x3004_000E, "DoseGridScaling", "DS", "1", false),
+    entry(0x3004_0010, "RTDoseROISequence", "SQ", "1", true),
+    entry(0x3004_0012, "DoseValue", "DS", "1", true),
+    entry(0x3004_0014, "TissueHeterogeneityCorrection", "CS", "1-3", false),
+    entry(0x3004_0040, "DVHNormalizationPoint", "DS", "3", false),
+    entry(0x3004_0042, "DVHNormalizationDoseValue", "DS", "1", false),
+    entry(0x3004_0050, "DVHSequence", "SQ", "1", false),
+    entry(0x3004_0052, "DVHDoseScaling", "DS", "1", false),
+    entry(0x3004_0054, "DVHVolumeUnits", "CS", "1", false),
+    entry(0x3004_0056, "DVHNumberOfBins", "IS", "1", false),
+    entry(0x3004_0058, "DVHData", "DS", "2-2n", false),
+    entry(0x3004_0060, "DVHReferencedROISequence", "SQ", "1", false),
+    entry(0x3004_0062, "DVHROIContributionType", "CS", "1", false),
+    entry(0x3004_0070, "DVHMinimumDose", "DS", "1", false),
+    entry(0x3004_0072, "DVHMaximumDose", "DS", "1", false),
+    entry(0x3004_0074, "DVHMeanDose", "DS", "1", false),
+    entry(0x3006_0002, "StructureSetLabel", "SH", "1", false),
+    entry(0x3006_0004, "StructureSetName", "LO", "1", false),
+    entry(0x3006_0006, "StructureSetDescription", "ST", "1", false),
+    entry(0x3006_0008, "StructureSetDate", "DA", "1", false),
+    entry(0x3006_0009, "StructureSetTime", "TM", "1", false),
+    entry(0x3006_0010, "ReferencedFrameOfReferenceSequence", "SQ", "1", false),
+    entry(0x3006_0012, "RTReferencedStudySequence", "SQ", "1", false),
+    entry(0x3006_0014, "RTReferencedSeriesSequence", "SQ", "1", false),
+    entry(0x3006_0016, "ContourImageSequence", "SQ", "1", false),
+    entry(0x3006_0018, "PredecessorStructureSetSequence", "SQ", "1", false),
+    entry(0x3006_0020, "StructureSetROISequence", "SQ", "1", false),
+    entry(0x3006_0022, "ROINumber", "IS", "1", false),
+    entry(0x3006_0024, "ReferencedFrameOfReferenceUID", "UI", "1", false),
+    entry(0x3006_0026, "ROIName", "LO", "1", false),
+    entry(0x3006_0028, "ROIDescription", "ST", "1", false),
+    entry(0x3006_002A, "ROIDisplayColor", "IS", "3", false),
+    entry(0x3006_002C, "ROIVolume", "DS", "1", false),
+    entry(0x3006_0030, "RTRelatedROISequence", "SQ", "1", false),
+    entry(0x3006_0033, "RTROIRelationship", "CS", "1", false),
+    entry(0x3006_0036, "ROIGenerationAlgorithm", "CS", "1", false),
+    entry(0x3006_0037, "ROIDerivationAlgorithmIdentificationSequence", "SQ", "1", false),
+    entry(0x3006_0038, "ROIGenerationDescription", "LO", "1", false),
+    entry(0x3006_0039, "ROIContourSequence", "SQ", "1", false),
+    entry(0x3006_0040, "ContourSequence", "SQ", "1", false),
+    entry(0x3006_0042, "ContourGeometricType", "CS", "1", false),
+    entry(0x3006_0044, "ContourSlabThickness", "DS", "1", true),
+    entry(0x3006_0045, "ContourOffsetVector", "DS", "3", true),
+    entry(0x3006_0046, "NumberOfContourPoints", "IS", "1", false),
+    entry(0x3006_0048, "ContourNumber", "IS", "1", false),
+    entry(0x3006_0049, "AttachedContours", "IS", "1-n", true),
+    entry(0x3006_0050, "ContourData", "DS", "3-3n", false),
+    entry(0x3006_0080, "RTROIObservationsSequence", "SQ", "1", false),
+    entry(0x3006_0082, "ObservationNumber", "IS", "1", false),
+    entry(0x3006_0084, "ReferencedROINumber", "IS", "1", false),
+    entry(0x3006_0085, "ROIObservationLabel", "SH", "1", true),
+    entry(0x3006_0086, "RTROIIdentificationCodeSequence", "SQ", "1", false),
+    entry(0x3006_0088, "ROIObservationDescription", "ST", "1", true),
+    entry(0x3006_00A0, "RelatedRTROIObservationsSequence", "SQ", "1", false),
+    entry(0x3006_00A4, "RTROIInterpretedType", "CS", "1", false),
+    entry(0x3006_00A6, "ROIInterpreter", "PN", "1", false),
+    entry(0x3006_00B0, "ROIPhysicalPropertiesSequence", "SQ", "1", false),
+    entry(0x3006_00B2, "ROIPhysicalProperty", "CS", "1", false),
+    entry(0x3006_00B4, "ROIPhysicalPropertyValue", "DS", "1", false),
+    entry(0x3006_00C0, "FrameOfReferenceRelationshipSequence", "SQ", "1", true),
+    entry(0x3006_00C2, "RelatedFrameOfReferenceUID", "UI", "1", true),
+    entry(0x3006_00C4, "FrameOfReferenceTransformationType", "CS", "1", true),
+    entry(0x3006_00C6, "FrameOfReferenceTransformationMatrix", "DS", "16", false),
+    entry(0x3006_00C8, "FrameOfReferenceTransformationComment", "LO", "1", false),
+    entry(0x300A_0002, "RTPlanLabel", "SH", "1", false),
+    entry(0x300A_0003, "RTPlanName", "LO", "1", false),
+    entry(0x300A_0004, "RTPlanDescription", "ST", "1", false),
+    entry(0x300A_0006, "RTPlanDate", "DA", "1", false),
+    entry(0x300A_0007, "RTPlanTime", "TM", "1", false),
+    entry(0x300A_0009, "TreatmentProtocols", "LO", "1-n", false),
+    entry(0x300A_000A, "PlanIntent", "CS", "1", false),
+    entry(0x300A_000B, "TreatmentSites", "LO", "1-n", false),
+    entry(0x300A_000C, "RTPlanGeometry", "CS", "1", false),
+    entry(0x300A_000E, "PrescriptionDescription", "ST", "1", false),
+    entry(0x300A_0010, "DoseReferenceSequence", "SQ", "1", false),
+    entry(0x300A_0012, "DoseReferenceNumber", "IS", "1", false),
+    entry(0x300A_0013, "DoseReferenceUID", "UI", "1", false),
+    entry(0x300A_0014, "DoseReferenceStructureType", "CS", "1", false),
+    entry(0x300A_0015, "NominalBeamEnergyUnit", "CS", "1", false),
+    entry(0x300A_0016, "DoseReferenceDescription", "LO", "1", false),
+    entry(0x300A_0018, "DoseReferencePointCoordinates", "DS", "3", false),
+    entry(0x300A_001A, "NominalPriorDose", "DS", "1", false),
+    entry(0x300A_0020, "DoseReferenceType", "CS", "1", false),
+    entry(0x300A_0021, "ConstraintWeight", "DS", "1", false),
+    entry(0x300A_0022, "DeliveryWarningDose", "DS", "1", false),
+    entry(0x300A_0023, "DeliveryMaximumDose", "DS", "1", false),
+    entry(0x300A_0025, "TargetMinimumDose", "DS", "1", false),
+    entry(0x300A_0026, "TargetPrescriptionDose", "DS", "1", false),
+    entry(0x300A_0027, "TargetMaximumDose", "DS", "1", false),
+    entry(0x300A_0028, "TargetUnderdoseVolumeFraction", "DS", "1", false),
+    entry(0x300A_002A, "OrganAtRiskFullVolumeDose", "DS", "1", false),
+    entry(0x300A_002B, "OrganAtRiskLimitDose", "DS", "1", false),
+    entry(0x300A_002C, "OrganAtRiskMaximumDose", "DS", "1", false),
+    entry(0x300A_002D, "OrganAtRiskOverdoseVolumeFraction", "DS", "1", false),
+    entry(0x300A_0040, "ToleranceTableSequence", "SQ", "1", false),
+    entry(0x300A_0070, "FractionGroupSequence", "SQ", "1", false),
+    entry(0x300A_0071, "FractionGroupNumber", "IS", "1", false),
+    entry(0x300A_0078, "NumberOfFractionsPlanned", "IS", "1", false),
+    entry(0x300A_0080, "NumberOfBeams", "IS", "1", false),
+    entry(0x300A_00B0, "BeamSequence", "SQ", "1", false),
+    entry(0x300A_00B2, "TreatmentMachineName", "SH", "1", false),
+    entry(0x300A_00B3, "PrimaryDosimeterUnit", "CS", "1", false),
+    entry(0x300A_00B4, "SourceAxisDistance", "DS", "1", false),
+    entry(0x300A_00C0, "BeamNumber", "IS", "1", false),
+    entry(0x300A_00C2, "BeamName", "LO", "1", false),
+    entry(0x300A_00C3, "BeamDescription", "ST", "1", false),
+    entry(0x300A_00C4, "BeamType", "CS", "1", false),
+    entry(0x300A_00C6, "RadiationType", "CS", "1", false),
+    entry(0x300A_00CE, "TreatmentDeliveryType", "CS", "1", false),
+    entry(0x300A_00D0, "NumberOfWedges", "IS", "1", false),
+    entry(0x300A_00E0, "NumberOfCompensators", "IS", "1", false),
+    entry(0x300A_00ED, "NumberOfBoli", "IS", "1", false),
+    entry(0x300A_00F0, "NumberOfBlocks", "IS", "1", false),
+    entry(0x300A_0110, "NumberOfControlPoints", "IS", "1", false),
+    entry(0x300A_0111, "ControlPointSequence", "SQ", "1", false),
+    entry(0x300A_0112, "ControlPointIndex", "IS", "1", false),
+    entry(0x300A_0114, "NominalBeamEnergy", "DS", "1", false),
+    entry(0x300A_0115, "DoseRateSet", "DS", "1", false),
+    entry(0x300A_011A, "BeamLimitingDevicePositionSequence", "SQ", "1", false),
+    entry(0x300A_011C, "LeafJawPositions", "DS", "2-2n", false),
+    entry(0x300A_011E, "GantryAngle", "DS", "1", false),
+    entry(0x300A_011F, "GantryRotationDirection", "CS", "1", false),
+    entry(0x300A_0120, "BeamLimitingDeviceAngle", "DS", "1", false),
+    entry(0x300A_0121, "BeamLimitingDeviceRotationDirection", "CS", "1", false),
+    entry(0x300A_0122, "PatientSupportAngle", "DS", "1", false),
+    entry(0x300A_0123, "PatientSupportRotationDirection", "CS", "1", false),
+    entry(0x300A_012C, "IsocenterPosition", "DS", "3", false),
+    entry(0x300A_0130, "SourceToSurfaceDistance", "DS", "1", false),
+    entry(0x300A_0134, "CumulativeMetersetWeight", "DS", "1", false),
+    entry(0x300A_0180, "PatientSetupSequence", "SQ", "1", false),
+    entry(0x300A_0182, "PatientSetupNumber", "IS", "1", false),
+    entry(0x300C_0002, "ReferencedRTPlanSequence", "SQ", "1", false),
+    entry(0x300C_0004, "ReferencedBeamSequence", "SQ", "1", false),
+    entry(0x300C_0006, "ReferencedBeamNumber", "IS", "1", false),
+    entry(0x300C_0022, "ReferencedFractionGroupNumber", "IS", "1", false),
+    entry(0x300C_0050, "ReferencedDoseReferenceSequence", "SQ", "1", false),
+    entry(0x300C_0051, "ReferencedDoseReferenceNumber", "IS", "1", false),
+    entry(0x300C_0060, "ReferencedStructureSetSequence", "SQ", "1", false),
+    entry(0x300C_006A, "ReferencedPatientSetupNumber", "IS", "1", false),
+    entry(0x300C_0080, "ReferencedDoseSequence", "SQ", "1", false),
+    entry(0x300E_0002, "ApprovalStatus", "CS", "1", false),
+    entry(0x300E_0004, "ReviewDate", "DA", "1", false),
+    entry(0x300E_0005, "ReviewTime", "TM", "1", false),
+    entry(0x300E_0008, "ReviewerName", "PN", "1", false),
+    entry(0x4000_0010, "Arbitrary", "LT", "1", true),
+    entry(0x4000_4000, "TextComments", "LT", "1", true),
+    entry(0x5200_9229, "SharedFunctionalGroupsSequence", "SQ", "1", false),
+    entry(0x5200_9230, "PerFrameFunctionalGroupsSequence", "SQ", "1", false),
+    entry(0x5400_0100, "WaveformSequence", "SQ", "1", false),
+    entry(0x5400_0110, "ChannelMinimumValue", "OB or OW", "1", false),
+    entry(0x5400_0112, "ChannelMaximumValue", "OB or OW", "1", false),
+    entry(0x5400_1004, "WaveformBitsAllocated", "US", "1", false),
+    entry(0x5400_1006, "WaveformSampleInterpretation", "CS", "1", false),
+    entry(0x5400_100A, "WaveformPaddingValue", "OB or OW", "1", false),
+    entry(0x5400_1010, "WaveformData", "OB or OW", "1", false),
+    entry(0x5600_0010, "FirstOrderPhaseCorrectionAngle", "OF", "1", false),
+    entry(0x5600_0020, "SpectroscopyData", "OF", "1", false),
+    entry(0x6000_0010, "OverlayRows", "US", "1", false),
+    entry(0x6000_0011, "OverlayColumns", "US", "1", false),
+    entry(0x6000_0012, "OverlayPlanes", "US", "1", true),
+    entry(0x6000_0015, "NumberOfFramesInOverlay", "IS", "1", false),
+    entry(0x6000_0022, "OverlayDescription", "LO", "1", false),
+    entry(0x6000_0040, "OverlayType", "CS", "1", false),
+    entry(0x6000_0045, "OverlaySubtype", "LO", "1", false),
+    entry(0x6000_0050, "OverlayOrigin", "SS", "2", false),
+    entry(0x6000_0051, "ImageFrameOrigin", "US", "1", false),
+    entry(0x6000_0052, "OverlayPlaneOrigin", "US", "1", true),
+    entry(0x6000_0060, "OverlayCompressionCode", "CS", "1", true),
+    entry(0x6000_0100, "OverlayBitsAllocated", "US", "1", false),
+    entry(0x6000_0102, "OverlayBitPosition", "US", "1", false),
+    entry(0x6000_0110, "OverlayFormat", "CS", "1", true),
+    entry(0x6000_0200, "OverlayLocation", "US", "1", true),
+    entry(0x6000_0800, "OverlayCodeLabel", "CS", "1-n", true),
+    entry(0x6000_0802, "OverlayNumberOfTables", "US", "1", true),
+    entry(0x6000_0803, "OverlayCodeTableLocation", "AT", "1-n", true),
+    entry(0x6000_0804, "OverlayBitsForCodeWord", "US", "1", true),
+    entry(0x6000_1001, "OverlayActivationLayer", "CS", "1", false),
+    entry(0x6000_1100, "OverlayDescriptorGray", "US", "1", true),
+    entry(0x6000_1101, "OverlayDescriptorRed", "US", "1", true),
+    entry(0x6000_1102, "OverlayDescriptorGreen", "US", "1", true),
+    entry(0x6000_1103, "OverlayDescriptorBlue", "US", "1", true),
+    entry(0x6000_1200, "OverlaysGray", "US", "1-n", true),
+    entry(0x6000_1201, "OverlaysRed", "US", "1-n", true),
+    entry(0x6000_1202, "OverlaysGreen", "US", "1-n", true),
+    entry(0x6000_1203, "OverlaysBlue", "US", "1-n", true),
+    entry(0x6000_1301, "ROIArea", "IS", "1", false),
+    entry(0x6000_1302, "ROIMean", "DS", "1", false),
+    entry(0x6000_1303, "ROIStandardDeviation", "DS", "1", false),
+    entry(0x6000_1500, "OverlayLabel", "LO", "1", false),
+    entry(0x6000_3000, "OverlayData", "OB or OW", "1", false),
+    entry(0x6000_4000, "OverlayComments", "LT", "1", true),
+    entry(0x7FE0_0001, "ExtendedOffsetTable", "OV", "1", false),
+    entry(0x7FE0_0002, "ExtendedOffsetTableLengths", "OV", "1", false),
+    entry(0x7FE0_0003, "EncapsulatedPixelDataValueTotalLength", "UV", "1", false),
+    entry(0x7FE0_0008, "FloatPixelData", "OF", "1", false),
+    entry(0x7FE0_0009, "DoubleFloatPixelData", "OD", "1", false),
+    entry(0x7FE0_0010, "PixelData", "OB or OW", "1", false),
+    entry(0x7FE0_0020, "CoefficientsSDVN", "OW", "1", true),
+    entry(0x7FE0_0030, "CoefficientsSDHN", "OW", "1", true),
+    entry(0x7FE0_0040, "CoefficientsSDDN", "OW", "1", true),
+    entry(0xFFFA_FFFA, "DigitalSignaturesSequence", "SQ", "1", false),
+    entry(0xFFFC_FFFC, "DataSetTrailingPadding", "OB", "1", false),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries() {
+        for pair in ENTRIES.windows(2) {
+            assert!(pair[0].tag < pair[1].tag, "{} is out of order", pair[1]);
+        }
+        for entry in ENTRIES {
+            assert!(entry.vm.parse::<Multiplicity>().is_ok(), "{entry}");
+            assert!(entry.vrs().count() > 0, "{entry}");
+            assert_eq!(lookup_keyword(entry.keyword), Some(entry), "{entry}");
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let rows = lookup(Tag::ROWS).unwrap();
+        assert_eq!((rows.keyword, rows.vr, rows.vm), ("Rows", "US", "1"));
+        assert!(!rows.retired);
+        assert_eq!(lookup_keyword("Rows"), Some(rows));
+
+        let pixel_data = lookup(Tag::PIXEL_DATA).unwrap();
+        assert_eq!(pixel_data.implicit_vr(), *b"OW");
+        assert!(pixel_data.allows_vr(b"OB") && pixel_data.allows_vr(b"OW"));
+        assert!(!pixel_data.allows_vr(b"UN"));
+
+        assert_eq!(
+            lookup(Tag::new(0x6000, 0x3000)).unwrap().keyword,
+            "OverlayData"
+        );
+        assert_eq!(
+            lookup(Tag::new(0x601E, 0x0010)).unwrap().keyword,
+            "OverlayRows"
+        );
+        assert_eq!(lookup(Tag::new(0x6001, 0x0010)), None);
+        assert_eq!(lookup(Tag::new(0x6020, 0x0010)), None);
+        assert_eq!(lookup(Tag::new(0x0009, 0x0010)), None);
+        assert_eq!(lookup_keyword("rows"), None);
+    }
+
+    #[test]
+    fn test_multiplicity() {
+        let allowed = |vm: &str, count| vm.parse::<Multiplicity>().unwrap().allows(count);
+        assert!(allowed("1", 1) && !allowed("1", 0) && !allowed("1", 2));
+        assert!(allowed("1-3", 3) && !allowed("1-3", 4));
+        assert!(allowed("1-n", 1) && allowed("1-n", 100) && !allowed("1-n", 0));
+        assert!(allowed("2-2n", 2) && allowed("2-2n", 6) && !allowed("2-2n", 5));
+        assert!(allowed("3-3n", 9) && !allowed("3-3n", 4));
+        for vm in ["", "n", "1-", "a-3", "2-xn"] {
+            assert!(vm.parse::<Multiplicity>().is_err(), "{vm}");
+        }
+    }
+}
@@ -0,0 +1,867 @@
+//! Writes dimble files back out as explicit VR little endian DICOM Part 10 files.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use snafu::prelude::*;
+use std::{fs, ops::Bound};
+
+use crate::character_set::{ISO_IR_192, SPECIFIC_CHARACTER_SET};
+use crate::dicom_json::is_inline_binary_vr;
+use crate::dicom_to_dimble::{
+    has_long_length, DICM_PREFIX, EXPLICIT_VR_LITTLE_ENDIAN, FILE_PREAMBLE_LENGTH, ITEM,
+    PIXEL_DATA, SEQUENCE_DELIMITATION_ITEM, TRANSFER_SYNTAX_UID, UNDEFINED_LENGTH,
+};
+use crate::ir_to_dimble::VR;
+use crate::pixel_array::{self, Dtype, EncapsulatedPixelData, PixelArray, PixelData};
+use crate::reader::{self, Dataset, DimbleReader, Element, FieldValue};
+use crate::tag::{ParseTagError, Tag};
+
+/// UUID derived UID identifying files written by dimble
+const IMPLEMENTATION_CLASS_UID: &str = "2.25.64847701281427912693946104240881330276";
+const IMPLEMENTATION_VERSION_NAME: &str = concat!("DIMBLE_", env!("CARGO_PKG_VERSION"));
+
+const MAX_DECIMAL_STRING_LENGTH: usize = 16;
+
+/// How pixel values are laid out in the Pixel Data element
+struct PixelEncoding {
+    bits_allocated: usize,
+    bits_stored: usize,
+    signed: bool,
+}
+
+impl PixelEncoding {
+    /// Uses the dataset's attributes, falling back to ones matching the pixel array's dtype
+    fn new(dataset: &Dataset, dtype: Dtype) -> InnerResult<Self> {
+        let native = match dtype {
+            Dtype::BOOL | Dtype::U8 => Some((8, false)),
+            Dtype::I8 => Some((8, true)),
+            Dtype::U16 => Some((16, false)),
+            Dtype::I16 => Some((16, true)),
+            Dtype::U32 => Some((32, false)),
+            Dtype::I32 => Some((32, true)),
+            _ => None,
+        };
+        let bits_allocated = match (integer_attribute(dataset, Tag::BITS_ALLOCATED), native) {
+            (Some(bits_allocated), _) => bits_allocated as usize,
+            (None, Some((bits_allocated, _))) => bits_allocated,
+            (None, None) => MissingAttributeSnafu {
+                tag: Tag::BITS_ALLOCATED,
+            }
+            .fail()?,
+        };
+        ensure!(
+            matches!(bits_allocated, 8 | 16 | 32),
+            UnsupportedBitsAllocatedSnafu { bits_allocated }
+        );
+        let bits_stored = integer_attribute(dataset, Tag::BITS_STORED)
+            .map_or(bits_allocated, |bits_stored| bits_stored as usize)
+            .clamp(1, bits_allocated);
+        let signed = match (
+            integer_attribute(dataset, Tag::PIXEL_REPRESENTATION),
+            native,
+        ) {
+            (Some(pixel_representation), _) => pixel_representation == 1,
+            (None, Some((_, signed))) => signed,
+            (None, None) => false,
+        };
+        Ok(Self {
+            bits_allocated,
+            bits_stored,
+            signed,
+        })
+    }
+
+    fn range(&self) -> (f64, f64) {
+        if self.signed {
+            let half = (1i64 << (self.bits_stored - 1)) as f64;
+            (-half, half - 1.0)
+        } else {
+            (0.0, ((1i64 << self.bits_stored) - 1) as f64)
+        }
+    }
+
+    /// Encodes the pixel array, failing rather than losing precision
+    fn encode(&self, pixel_array: &PixelArray) -> InnerResult<Vec<u8>> {
+        let item_size = self.bits_allocated / 8;
+        let (min, max) = self.range();
+        let mut out =
+            Vec::with_capacity(pixel_array.data.len() / pixel_array.dtype.size() * item_size);
+        for element in pixel_array.data.chunks_exact(pixel_array.dtype.size()) {
+            let value =
+                pixel_array::read_element(element, pixel_array.dtype).context(PixelArraySnafu)?;
+            ensure!(
+                value.fract() == 0.0 && (min..=max).contains(&value),
+                PixelValueOutOfRangeSnafu {
+                    value,
+                    bits_stored: self.bits_stored,
+                    signed: self.signed
+                }
+            );
+            // two's complement truncation gives the right bytes for signed values
+            out.extend_from_slice(&(value as i64).to_le_bytes()[..item_size]);
+        }
+        Ok(out)
+    }
+
+    /// Records the encoding in the image pixel attributes
+    fn update(&self, dataset: &mut Dataset) {
+        let us = |value: usize| Element {
+            vr: *b"US",
+            value: FieldValue::Integer(value as i64),
+        };
+        dataset.insert(Tag::BITS_ALLOCATED, us(self.bits_allocated));
+        dataset.insert(Tag::BITS_STORED, us(self.bits_stored));
+        dataset.insert(Tag::HIGH_BIT, us(self.bits_stored - 1));
+        dataset.insert(Tag::PIXEL_REPRESENTATION, us(usize::from(self.signed)));
+        if integer_attribute(dataset, Tag::SAMPLES_PER_PIXEL).is_some_and(|samples| samples > 1) {
+            // dimble always stores colour-by-pixel
+            dataset.insert(Tag::PLANAR_CONFIGURATION, us(0));
+        }
+    }
+}
+
+fn integer_attribute(dataset: &Dataset, tag: Tag) -> Option<i64> {
+    let value = match &dataset.get(&tag)?.value {
+        FieldValue::Multiple(values) => values.first()?,
+        value => value,
+    };
+    match value {
+        FieldValue::Integer(i) => Some(*i),
+        FieldValue::UnsignedInteger(u) => i64::try_from(*u).ok(),
+        FieldValue::Float(f) => Some(*f as i64),
+        FieldValue::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn string_attribute(dataset: &Dataset, tag: Tag) -> InnerResult<String> {
+    match dataset.get(&tag).map(|element| &element.value) {
+        Some(FieldValue::String(s)) => Ok(s.clone()),
+        _ => MissingAttributeSnafu { tag }.fail(),
+    }
+}
+
+fn write_header(out: &mut Vec<u8>, tag: u32, vr: &VR, length: usize) -> InnerResult<()> {
+    out.extend(((tag >> 16) as u16).to_le_bytes());
+    out.extend((tag as u16).to_le_bytes());
+    out.extend_from_slice(vr);
+    if has_long_length(vr) {
+        let length = u32::try_from(length)
+            .ok()
+            .context(ValueTooLongSnafu { tag, length })?;
+        out.extend([0, 0]);
+        out.extend(length.to_le_bytes());
+    } else {
+        let length = u16::try_from(length)
+            .ok()
+            .context(ValueTooLongSnafu { tag, length })?;
+        out.extend(length.to_le_bytes());
+    }
+    Ok(())
+}
+
+fn write_item(out: &mut Vec<u8>, tag: u32, value: &[u8]) {
+    out.extend(((tag >> 16) as u16).to_le_bytes());
+    out.extend((tag as u16).to_le_bytes());
+    out.extend((value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value);
+}
+
+/// Writes encapsulated pixel data as it was read, as an undefined length element holding the basic
+/// offset table item and then an item per fragment
+fn write_encapsulated(out: &mut Vec<u8>, encapsulated: &EncapsulatedPixelData) -> InnerResult<()> {
+    write_header(out, PIXEL_DATA, b"OB", UNDEFINED_LENGTH as usize)?;
+    write_item(out, ITEM, encapsulated.offset_table);
+    for fragment in &encapsulated.fragments {
+        write_item(out, ITEM, fragment);
+    }
+    write_item(out, SEQUENCE_DELIMITATION_ITEM, &[]);
+    Ok(())
+}
+
+fn write_element(out: &mut Vec<u8>, tag: u32, vr: &VR, mut value: Vec<u8>) -> InnerResult<()> {
+    if value.len() % 2 == 1 {
+        let padding = match vr {
+            b"UI" | b"OB" | b"UN" => 0,
+            _ => b' ',
+        };
+        value.push(padding);
+    }
+    write_header(out, tag, vr, value.len())?;
+    out.extend_from_slice(&value);
+    Ok(())
+}
+
+/// Shortest representation of `value` that fits in a DS value
+fn format_decimal_string(value: f64) -> String {
+    let s = value.to_string();
+    if s.len() <= MAX_DECIMAL_STRING_LENGTH {
+        return s;
+    }
+    (0..MAX_DECIMAL_STRING_LENGTH)
+        .rev()
+        .map(|precision| format!("{value:.precision$e}"))
+        .find(|s| s.len() <= MAX_DECIMAL_STRING_LENGTH)
+        .unwrap_or(s)
+}
+
+fn text(tag: u32, vr: &VR, value: &FieldValue) -> InnerResult<String> {
+    Ok(match value {
+        FieldValue::Empty => String::new(),
+        FieldValue::String(s) => s.clone(),
+        FieldValue::Integer(i) => i.to_string(),
+        FieldValue::UnsignedInteger(u) => u.to_string(),
+        FieldValue::Float(f) => format_decimal_string(*f),
+        FieldValue::PersonName(name) => name.to_dicom_string(),
+        FieldValue::Multiple(values) => values
+            .iter()
+            .map(|v| text(tag, vr, v))
+            .collect::<InnerResult<Vec<_>>>()?
+            .join("\\"),
+        FieldValue::Binary(_) | FieldValue::BulkDataUri(_) | FieldValue::Sequence(_) => {
+            UnsupportedValueSnafu {
+                tag,
+                vr: String::from_utf8_lossy(vr),
+            }
+            .fail()?
+        }
+    })
+}
+
+fn values(value: &FieldValue) -> &[FieldValue] {
+    match value {
+        FieldValue::Multiple(values) => values,
+        value => std::slice::from_ref(value),
+    }
+}
+
+fn encode_value(tag: u32, vr: &VR, value: &FieldValue) -> InnerResult<Vec<u8>> {
+    let unsupported = || UnsupportedValueSnafu {
+        tag,
+        vr: String::from_utf8_lossy(vr),
+    };
+
+    // values outside the range of the VR fail rather than wrap or truncate
+    macro_rules! numbers {
+        ($ty:ty, $convert:expr) => {{
+            let mut out = Vec::new();
+            for value in values(value) {
+                let value: Option<$ty> = match value {
+                    FieldValue::String(s) => s.trim().parse().ok(),
+                    value => $convert(value),
+                };
+                out.extend(value.with_context(unsupported)?.to_le_bytes());
+            }
+            out
+        }};
+    }
+    macro_rules! integers {
+        ($ty:ty) => {
+            numbers!($ty, |value: &FieldValue| match value {
+                FieldValue::Integer(i) => <$ty>::try_from(*i).ok(),
+                FieldValue::UnsignedInteger(u) => <$ty>::try_from(*u).ok(),
+                // i128 holds every integer VR, and the cast saturates floats beyond it
+                FieldValue::Float(f) if f.fract() == 0.0 => <$ty>::try_from(*f as i128).ok(),
+                _ => None,
+            })
+        };
+    }
+    macro_rules! floats {
+        ($ty:ty) => {
+            numbers!($ty, |value: &FieldValue| match value {
+                FieldValue::Integer(i) => Some(*i as $ty),
+                FieldValue::UnsignedInteger(u) => Some(*u as $ty),
+                // finite values too large for the VR would become infinite
+                FieldValue::Float(f) => {
+                    Some(*f as $ty).filter(|converted| converted.is_finite() || !f.is_finite())
+                }
+                _ => None,
+            })
+        };
+    }
+
+    Ok(match vr {
+        b"US" => integers!(u16),
+        b"SS" => integers!(i16),
+        b"UL" => integers!(u32),
+        b"SL" => integers!(i32),
+        b"SV" => integers!(i64),
+        b"UV" => integers!(u64),
+        b"FL" => floats!(f32),
+        b"FD" => floats!(f64),
+        b"AT" => {
+            let mut out = Vec::new();
+            for value in values(value) {
+                let FieldValue::String(at) = value else {
+                    return unsupported().fail();
+                };
+                let at = u32::from(
+                    at.parse::<Tag>()
+                        .context(InvalidAttributeTagSnafu { tag })?,
+                );
+                out.extend(((at >> 16) as u16).to_le_bytes());
+                out.extend((at as u16).to_le_bytes());
+            }
+            out
+        }
+        vr if is_inline_binary_vr(vr) => match value {
+            FieldValue::Binary(bytes) => bytes.clone(),
+            // before format version 1.1 inline binary was stored as its base64 text
+            FieldValue::String(inline_binary) => STANDARD
+                .decode(inline_binary)
+                .context(InvalidInlineBinarySnafu { tag })?,
+            _ => unsupported().fail()?,
+        },
+        vr => text(tag, vr, value)?.into_bytes(),
+    })
+}
+
+fn encode_element(out: &mut Vec<u8>, tag: u32, element: &Element) -> InnerResult<()> {
+    match &element.value {
+        FieldValue::Empty => write_header(out, tag, &element.vr, 0),
+        FieldValue::Sequence(items) => {
+            let mut sequence = Vec::new();
+            for item in items {
+                let mut item_bytes = Vec::new();
+                encode_dataset(&mut item_bytes, item)?;
+                write_item(&mut sequence, ITEM, &item_bytes);
+            }
+            write_header(out, tag, b"SQ", sequence.len())?;
+            out.extend(sequence);
+            Ok(())
+        }
+        value => write_element(
+            out,
+            tag,
+            &element.vr,
+            encode_value(tag, &element.vr, value)?,
+        ),
+    }
+}
+
+fn has_non_ascii_text(value: &FieldValue) -> bool {
+    match value {
+        FieldValue::String(s) => !s.is_ascii(),
+        FieldValue::PersonName(name) => !name.to_dicom_string().is_ascii(),
+        FieldValue::Multiple(values) => values.iter().any(has_non_ascii_text),
+        FieldValue::Sequence(items) => items.iter().any(|item| {
+            item.values()
+                .any(|element| has_non_ascii_text(&element.value))
+        }),
+        _ => false,
+    }
+}
+
+/// Text is always written as UTF-8, so a dataset with non-ASCII text declares UTF-8 as its
+/// Specific Character Set, as does every item that declares its own. ASCII text reads the same
+/// in every character set, so the original one is kept otherwise.
+fn declare_utf8(dataset: &mut Dataset) {
+    let specific_character_set = Tag::from(SPECIFIC_CHARACTER_SET);
+    dataset.insert(
+        specific_character_set,
+        Element {
+            vr: *b"CS",
+            value: FieldValue::String(ISO_IR_192.to_string()),
+        },
+    );
+    for element in dataset.values_mut() {
+        if let FieldValue::Sequence(items) = &mut element.value {
+            for item in items
+                .iter_mut()
+                .filter(|item| item.contains_key(&specific_character_set))
+            {
+                declare_utf8(item);
+            }
+        }
+    }
+}
+
+/// Encodes the elements in ascending tag order, leaving out group lengths and file meta elements
+fn encode_dataset<'a>(
+    out: &mut Vec<u8>,
+    elements: impl IntoIterator<Item = (&'a Tag, &'a Element)>,
+) -> InnerResult<()> {
+    for (&tag, element) in elements {
+        if tag.is_group_length() || tag.group == 0x0002 {
+            continue;
+        }
+        encode_element(out, tag.into(), element)?;
+    }
+    Ok(())
+}
+
+/// Encodes the elements like [`encode_dataset`], with the pixel data `write_pixel_data` writes in
+/// its place in tag order. Private groups such as 7FE1 and the digital signature and waveform
+/// sequences at FFFA and FFFC have higher tags than the pixel data, so they go after it.
+fn encode_dataset_with_pixel_data(
+    out: &mut Vec<u8>,
+    dataset: &Dataset,
+    write_pixel_data: impl FnOnce(&mut Vec<u8>) -> InnerResult<()>,
+) -> InnerResult<()> {
+    encode_dataset(out, dataset.range(..Tag::PIXEL_DATA))?;
+    write_pixel_data(out)?;
+    encode_dataset(
+        out,
+        dataset.range((Bound::Excluded(Tag::PIXEL_DATA), Bound::Unbounded)),
+    )
+}
+
+fn encode_file_meta(
+    out: &mut Vec<u8>,
+    dataset: &Dataset,
+    transfer_syntax: &str,
+) -> InnerResult<()> {
+    let uid = |uid: String| uid.into_bytes();
+    let mut meta = Vec::new();
+    write_element(&mut meta, 0x0002_0001, b"OB", vec![0x00, 0x01])?;
+    write_element(
+        &mut meta,
+        0x0002_0002,
+        b"UI",
+        uid(string_attribute(dataset, Tag::SOP_CLASS_UID)?),
+    )?;
+    write_element(
+        &mut meta,
+        0x0002_0003,
+        b"UI",
+        uid(string_attribute(dataset, Tag::SOP_INSTANCE_UID)?),
+    )?;
+    write_element(
+        &mut meta,
+        TRANSFER_SYNTAX_UID,
+        b"UI",
+        uid(transfer_syntax.to_string()),
+    )?;
+    write_element(
+        &mut meta,
+        0x0002_0012,
+        b"UI",
+        uid(IMPLEMENTATION_CLASS_UID.to_string()),
+    )?;
+    write_element(
+        &mut meta,
+        0x0002_0013,
+        b"SH",
+        IMPLEMENTATION_VERSION_NAME.as_bytes().to_vec(),
+    )?;
+    write_element(
+        out,
+        0x0002_0000,
+        b"UL",
+        (meta.len() as u32).to_le_bytes().to_vec(),
+    )?;
+    out.extend(meta);
+    Ok(())
+}
+
+/// Converts a dimble file to an explicit VR little endian DICOM Part 10 file. Pixel data kept
+/// encapsulated is written as it was, in its own transfer syntax.
+pub fn dimble_to_dicom(dimble_path: &str, dicom_path: &str) -> Result<()> {
+    let reader = DimbleReader::open(dimble_path).context(CouldNotReadSnafu)?;
+    let mut dataset = reader.dataset().context(CouldNotReadSnafu)?;
+
+    if dataset
+        .values()
+        .any(|element| has_non_ascii_text(&element.value))
+    {
+        declare_utf8(&mut dataset);
+    }
+
+    let mut out = vec![0; FILE_PREAMBLE_LENGTH];
+    out.extend_from_slice(DICM_PREFIX);
+    let has_pixel_data = reader
+        .header()
+        .context(CouldNotReadSnafu)?
+        .contains_key(&Tag::PIXEL_DATA);
+    if !has_pixel_data {
+        encode_file_meta(&mut out, &dataset, EXPLICIT_VR_LITTLE_ENDIAN)?;
+        encode_dataset(&mut out, &dataset)?;
+    } else if let PixelData::Encapsulated(encapsulated) =
+        reader.pixel_data().context(CouldNotReadSnafu)?
+    {
+        // the image pixel attributes already describe the encapsulated pixel data
+        encode_file_meta(&mut out, &dataset, &encapsulated.transfer_syntax)?;
+        encode_dataset_with_pixel_data(&mut out, &dataset, |out| {
+            write_encapsulated(out, &encapsulated)
+        })?;
+    } else {
+        let pixel_array = reader
+            .pixel_data()
+            .context(CouldNotReadSnafu)?
+            .to_owned_array()
+            .context(PixelArraySnafu)?;
+        let encoding = PixelEncoding::new(&dataset, pixel_array.dtype)?;
+        encoding.update(&mut dataset);
+        let vr = if encoding.bits_allocated == 8 {
+            *b"OB"
+        } else {
+            *b"OW"
+        };
+        let pixel_data = encoding.encode(&pixel_array.as_pixel_array())?;
+        encode_file_meta(&mut out, &dataset, EXPLICIT_VR_LITTLE_ENDIAN)?;
+        encode_dataset_with_pixel_data(&mut out, &dataset, |out| {
+            write_element(out, PIXEL_DATA, &vr, pixel_data)
+        })?;
+    }
+    fs::write(dicom_path, out).context(CouldNotWriteSnafu { dicom_path })?;
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+pub enum InnerError {
+    #[snafu(display("Could not read the dimble file"))]
+    CouldNotRead { source: reader::Error },
+
+    #[snafu(display("Element {tag:08X} has an AT value that is not a DICOM tag"))]
+    InvalidAttributeTag { source: ParseTagError, tag: u32 },
+
+    #[snafu(display("Element {tag:08X} has a value of {length} bytes, too long for its VR"))]
+    ValueTooLong { tag: u32, length: usize },
+
+    #[snafu(display("Element {tag:08X} has a value that cannot be encoded as {vr}"))]
+    UnsupportedValue { tag: u32, vr: String },
+
+    #[snafu(display("Element {tag:08X} should have base64 inline binary"))]
+    InvalidInlineBinary {
+        source: base64::DecodeError,
+        tag: u32,
+    },
+
+    #[snafu(display("Dataset requires the {tag} attribute"))]
+    MissingAttribute { tag: Tag },
+
+    #[snafu(display("Pixel data with {bits_allocated} bits allocated is not supported"))]
+    UnsupportedBitsAllocated { bits_allocated: usize },
+
+    #[snafu(display(
+        "Pixel value {value} cannot be stored in {bits_stored} bits (signed: {signed})"
+    ))]
+    PixelValueOutOfRange {
+        value: f64,
+        bits_stored: usize,
+        signed: bool,
+    },
+
+    #[snafu(display("Could not read the pixel array"))]
+    PixelArray { source: pixel_array::Error },
+
+    #[snafu(display("Could not write the path {dicom_path}"))]
+    CouldNotWrite {
+        source: std::io::Error,
+        dicom_path: String,
+    },
+}
+
+type InnerResult<T, E = InnerError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub struct Error(InnerError);
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Compression;
+    use crate::dicom_json::DicomValue;
+    use crate::dicom_to_dimble::{
+        dicom_to_dimble, parse_dicom,
+        tests::{rle_image, DicomBuilder},
+    };
+    use crate::ir_to_dimble::DEFAULT_PIXEL_ALIGNMENT;
+    use crate::writer::DimbleWriter;
+
+    fn signed_image() -> (DicomBuilder, Vec<u8>) {
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder
+            .element(0x0008_0016, b"UI", b"1.2.840.10008.5.1.4.1.1.2\0")
+            .element(0x0008_0018, b"UI", b"1.2.3.4\0")
+            .element(0x0008_0060, b"CS", b"CT")
+            .sequence(
+                0x0008_9215,
+                &[&|item: &mut DicomBuilder| {
+                    item.element(0x0008_0100, b"SH", b"121327");
+                }],
+            )
+            .sequence(
+                0x5200_9230,
+                &[
+                    &|item: &mut DicomBuilder| {
+                        item.sequence(
+                            0x0028_9110,
+                            &[&|item: &mut DicomBuilder| {
+                                item.element(0x0028_0030, b"DS", b"0.5\\0.5 ");
+                            }],
+                        );
+                    },
+                    &|item: &mut DicomBuilder| {
+                        item.sequence(
+                            0x0028_9110,
+                            &[&|_: &mut DicomBuilder| {}, &|_: &mut DicomBuilder| {}],
+                        );
+                    },
+                ],
+            )
+            .element(0x0010_0010, b"PN", b"Doe^John")
+            .element(0x0020_0032, b"DS", b"-1.5\\2\\3.25 ")
+            .element(0x0028_0009, b"AT", &[0x18, 0x00, 0x63, 0x10])
+            .us(0x0028_0002, 1)
+            .us(0x0028_0010, 2)
+            .us(0x0028_0011, 2)
+            .us(0x0028_0100, 16)
+            .us(0x0028_0101, 12)
+            .us(0x0028_0102, 11)
+            .us(0x0028_0103, 1);
+        let pixels: Vec<u8> = [-2048i16, -1, 0, 2047]
+            .iter()
+            .flat_map(|p| p.to_le_bytes())
+            .collect();
+        builder.pixel_data(b"OW", &pixels);
+        (builder, pixels)
+    }
+
+    #[test]
+    fn test_dicom_round_trip() {
+        let (builder, pixels) = signed_image();
+        let dicom_path = "/tmp/dimble_to_dicom_original.dcm";
+        let dimble_path = "/tmp/dimble_to_dicom.dimble";
+        let reconstructed_path = "/tmp/dimble_to_dicom_reconstructed.dcm";
+        fs::write(dicom_path, &builder.bytes).unwrap();
+        // stored as float, like the python default, and converted back
+        dicom_to_dimble(
+            dicom_path,
+            dimble_path,
+            Some(Dtype::F32),
+            None,
+            Compression::default(),
+            false,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )
+        .unwrap();
+        dimble_to_dicom(dimble_path, reconstructed_path).unwrap();
+
+        let reconstructed = fs::read(reconstructed_path).unwrap();
+        assert_eq!(&reconstructed[FILE_PREAMBLE_LENGTH..][..4], DICM_PREFIX);
+        let original = parse_dicom(&builder.bytes).unwrap();
+        let reconstructed = parse_dicom(&reconstructed).unwrap();
+        assert_eq!(reconstructed.dataset, original.dataset);
+        assert_eq!(reconstructed.pixel_data, Some(pixels.as_slice()));
+    }
+
+    #[test]
+    fn test_encapsulated_round_trip() {
+        let (builder, _) = rle_image(3);
+        let dicom_path = "/tmp/dimble_to_dicom_rle.dcm";
+        let dimble_path = "/tmp/dimble_to_dicom_rle.dimble";
+        let reconstructed_path = "/tmp/dimble_to_dicom_rle_reconstructed.dcm";
+        fs::write(dicom_path, &builder.bytes).unwrap();
+        dicom_to_dimble(
+            dicom_path,
+            dimble_path,
+            None,
+            None,
+            Compression::default(),
+            true,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )
+        .unwrap();
+        dimble_to_dicom(dimble_path, reconstructed_path).unwrap();
+
+        let reconstructed = fs::read(reconstructed_path).unwrap();
+        // the pixel data is the last element, and is written back byte for byte
+        let pixel_data_start = builder
+            .bytes
+            .windows(4)
+            .rposition(|tag| tag == [0xE0, 0x7F, 0x10, 0x00])
+            .unwrap();
+        assert!(reconstructed.ends_with(&builder.bytes[pixel_data_start..]));
+        let original = parse_dicom(&builder.bytes).unwrap();
+        let reconstructed = parse_dicom(&reconstructed).unwrap();
+        assert_eq!(reconstructed.transfer_syntax, pixel_array::RLE_LOSSLESS);
+        assert_eq!(reconstructed.dataset, original.dataset);
+        assert_eq!(reconstructed.encapsulated, original.encapsulated);
+    }
+
+    #[test]
+    fn test_private_elements_after_pixel_data() {
+        let private = |builder: &mut DicomBuilder| {
+            builder
+                .element(0x7FE1_0010, b"LO", b"DIMBLE TEST ")
+                .element(0x7FE1_1001, b"OB", &[1, 2, 3, 4]);
+        };
+        let (mut native, _) = signed_image();
+        private(&mut native);
+        let (mut encapsulated, _) = rle_image(3);
+        private(&mut encapsulated);
+
+        for (name, builder, keep_encapsulated) in [
+            ("native", native, false),
+            ("encapsulated", encapsulated, true),
+        ] {
+            let dicom_path = format!("/tmp/dimble_to_dicom_private_{name}.dcm");
+            let dimble_path = format!("/tmp/dimble_to_dicom_private_{name}.dimble");
+            let reconstructed_path = format!("/tmp/dimble_to_dicom_private_{name}_out.dcm");
+            fs::write(&dicom_path, &builder.bytes).unwrap();
+            dicom_to_dimble(
+                &dicom_path,
+                &dimble_path,
+                None,
+                None,
+                Compression::default(),
+                keep_encapsulated,
+                DEFAULT_PIXEL_ALIGNMENT,
+            )
+            .unwrap();
+            dimble_to_dicom(&dimble_path, &reconstructed_path).unwrap();
+
+            // the private group follows the pixel data, keeping the tags ascending
+            let reconstructed = fs::read(&reconstructed_path).unwrap();
+            let position = |tag: [u8; 4]| reconstructed.windows(4).position(|bytes| bytes == tag);
+            let pixel_data = position([0xE0, 0x7F, 0x10, 0x00]).unwrap();
+            let private_creator = position([0xE1, 0x7F, 0x10, 0x00]).unwrap();
+            assert!(pixel_data < private_creator);
+            let original = parse_dicom(&builder.bytes).unwrap();
+            let reconstructed = parse_dicom(&reconstructed).unwrap();
+            assert_eq!(reconstructed.dataset, original.dataset);
+            assert!(reconstructed
+                .dataset
+                .contains_key(&Tag::new(0x7FE1, 0x1001)));
+        }
+    }
+
+    #[test]
+    fn test_non_ascii_text_is_utf_8() {
+        let mut builder = DicomBuilder::part10(EXPLICIT_VR_LITTLE_ENDIAN);
+        builder
+            .element(0x0008_0005, b"CS", b"ISO_IR 100")
+            .element(0x0008_0016, b"UI", b"1.2.840.10008.5.1.4.1.1.2\0")
+            .element(0x0008_0018, b"UI", b"1.2.3.4\0")
+            .sequence(
+                0x0008_1111,
+                &[&|item: &mut DicomBuilder| {
+                    item.element(0x0008_0005, b"CS", b"GB18030 ").element(
+                        0x0010_0020,
+                        b"LO",
+                        b"\xD6\xD0\xCE\xC4",
+                    );
+                }],
+            )
+            .element(0x0010_0010, b"PN", b"M\xFCller^Hans");
+        let dicom_path = "/tmp/dimble_to_dicom_latin_1.dcm";
+        let dimble_path = "/tmp/dimble_to_dicom_latin_1.dimble";
+        let reconstructed_path = "/tmp/dimble_to_dicom_latin_1_out.dcm";
+        fs::write(dicom_path, &builder.bytes).unwrap();
+        dicom_to_dimble(
+            dicom_path,
+            dimble_path,
+            None,
+            None,
+            Compression::default(),
+            false,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )
+        .unwrap();
+        dimble_to_dicom(dimble_path, reconstructed_path).unwrap();
+
+        let utf_8 = || Some(vec![DicomValue::String(ISO_IR_192.to_string())]);
+        let specific_character_set = Tag::from(SPECIFIC_CHARACTER_SET);
+        let mut original = parse_dicom(&builder.bytes).unwrap().dataset;
+        let mut reconstructed = parse_dicom(&fs::read(reconstructed_path).unwrap())
+            .unwrap()
+            .dataset;
+        for dataset in [&mut original, &mut reconstructed] {
+            let Some([DicomValue::SeqField(item)]) = dataset
+                .get_mut(&Tag::new(0x0008, 0x1111))
+                .unwrap()
+                .value
+                .as_deref_mut()
+            else {
+                panic!("expected a single item");
+            };
+            item.get_mut(&specific_character_set).unwrap().value = utf_8();
+            dataset.get_mut(&specific_character_set).unwrap().value = utf_8();
+        }
+        assert_eq!(reconstructed, original);
+    }
+
+    #[test]
+    fn test_pixel_value_out_of_range() {
+        let mut writer = DimbleWriter::new();
+        for (tag, uid) in [
+            (Tag::SOP_CLASS_UID, "1.2"),
+            (Tag::SOP_INSTANCE_UID, "1.2.3"),
+        ] {
+            let element = Element {
+                vr: *b"UI",
+                value: FieldValue::String(uid.to_string()),
+            };
+            writer.insert(tag, &element).unwrap();
+        }
+        let bits = Element {
+            vr: *b"US",
+            value: FieldValue::Integer(8),
+        };
+        writer.insert(Tag::BITS_ALLOCATED, &bits).unwrap();
+        let pixels: Vec<u8> = [0.0f32, 1.5].iter().flat_map(|p| p.to_le_bytes()).collect();
+        writer
+            .insert_pixel_array(Dtype::F32, &[1, 2], &pixels)
+            .unwrap();
+        let dimble_path = "/tmp/dimble_to_dicom_out_of_range.dimble";
+        writer.write(dimble_path).unwrap();
+
+        let error = dimble_to_dicom(dimble_path, "/tmp/dimble_to_dicom_out_of_range.dcm")
+            .err()
+            .unwrap();
+        assert!(matches!(error.0, InnerError::PixelValueOutOfRange { .. }));
+    }
+
+    #[test]
+    fn test_encode_value_out_of_range() {
+        let encode = |vr: &VR, value: FieldValue| encode_value(0x0009_1001, vr, &value);
+        assert_eq!(
+            encode(b"US", FieldValue::Integer(65535)).unwrap(),
+            [0xFF; 2]
+        );
+        assert_eq!(
+            encode(b"SS", FieldValue::Float(-2.0)).unwrap(),
+            (-2i16).to_le_bytes()
+        );
+        assert_eq!(
+            encode(b"UV", FieldValue::UnsignedInteger(u64::MAX)).unwrap(),
+            u64::MAX.to_le_bytes()
+        );
+        for (vr, value) in [
+            (b"US", FieldValue::Integer(-1)),
+            (b"US", FieldValue::Integer(65536)),
+            (b"SS", FieldValue::String("40000".to_string())),
+            (b"SL", FieldValue::UnsignedInteger(u64::MAX)),
+            (b"SV", FieldValue::UnsignedInteger(u64::MAX)),
+            (b"UV", FieldValue::Integer(-1)),
+            (b"UL", FieldValue::Float(0.5)),
+            (b"UL", FieldValue::Float(f64::NAN)),
+            (b"UV", FieldValue::Float(1e20)),
+            (b"FL", FieldValue::Float(1e300)),
+        ] {
+            assert!(
+                matches!(
+                    encode(vr, value.clone()),
+                    Err(InnerError::UnsupportedValue { .. })
+                ),
+                "{value:?} should not fit {}",
+                String::from_utf8_lossy(vr)
+            );
+        }
+        assert_eq!(
+            encode(b"FL", FieldValue::Float(f64::INFINITY)).unwrap(),
+            f32::INFINITY.to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_format_decimal_string() {
+        assert_eq!(format_decimal_string(2.0), "2");
+        assert_eq!(format_decimal_string(-1.5), "-1.5");
+        let third = format_decimal_string(1.0 / 3.0);
+        assert!(third.len() <= MAX_DECIMAL_STRING_LENGTH, "{third}");
+        assert!((third.parse::<f64>().unwrap() - 1.0 / 3.0).abs() < 1e-12);
+    }
+}
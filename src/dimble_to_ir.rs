@@ -1,178 +1,260 @@
 use crate::dicom_json::*;
-use crate::ir_to_dimble::{HeaderField, HeaderFieldMap};
-use memmap2::MmapOptions;
-use rmpv::{decode, Integer, Value};
+use crate::ir_to_dimble::HeaderFieldMap;
+use crate::reader::{self, Dataset, DimbleReader, Element, FieldValue};
+use crate::tag::Tag;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use snafu::prelude::*;
 use std::fs;
 
-fn headerfield_and_bytes_to_dicom_fields(
-    tag: &str,
-    header_field: &HeaderField,
-    dimble_buffer: &[u8],
-) -> DicomField {
-    match header_field {
-        HeaderField::Empty(vr) => DicomField {
-            value: None,
-            vr: *vr,
-            inline_binary: None,
-        },
-        HeaderField::SQ(sqs) => {
-            let seq_fields = sqs
-                .iter()
-                .map(|sq| DicomValue::SeqField(headers_to_data(sq, dimble_buffer)))
-                .collect::<Vec<_>>();
-
-            DicomField {
-                value: Some(seq_fields),
-                vr: *b"SQ",
-                inline_binary: None,
-            }
+fn field_value_to_dicom_value(tag: Tag, value: FieldValue) -> InnerResult<DicomValue> {
+    Ok(match value {
+        FieldValue::PersonName(name) => DicomValue::PersonName(name),
+        FieldValue::String(s) => DicomValue::String(s),
+        FieldValue::Integer(i) => DicomValue::Integer(i),
+        FieldValue::UnsignedInteger(u) => DicomValue::UnsignedInteger(u),
+        FieldValue::Float(f) => DicomValue::Float(f),
+        FieldValue::Empty
+        | FieldValue::Sequence(_)
+        | FieldValue::Binary(_)
+        | FieldValue::BulkDataUri(_)
+        | FieldValue::Multiple(_) => UnsupportedValueSnafu { tag }.fail()?,
+    })
+}
+
+fn element_to_dicom_field(tag: Tag, element: Element) -> InnerResult<DicomField> {
+    let Element { vr, value } = element;
+    let mut field = DicomField {
+        value: None,
+        vr: Some(vr),
+        inline_binary: None,
+        bulk_data_uri: None,
+    };
+    match value {
+        FieldValue::Empty => {}
+        FieldValue::Binary(bytes) => field.inline_binary = Some(STANDARD.encode(bytes)),
+        // before format version 1.1 inline binary was stored as its base64 text
+        FieldValue::String(inline_binary) if is_inline_binary_vr(&vr) => {
+            field.inline_binary = Some(inline_binary)
         }
-        HeaderField::Deffered(field_pos, field_length, vr) => {
-            // inline_binary VRs are OB and OW. TODO support the other inline binary VRs
-            let field_pos = (*field_pos as usize) + 8;
-            let field_length = *field_length as usize;
-            let field_bytes = &dimble_buffer[field_pos..field_pos + field_length];
-            match vr {
-                b"OB" | b"OW" => {
-                    let inline_binary = match tag {
-                        "7FE00010" => {
-                            // Pixel Data
-                            "TODO encode pixel data correctly".to_string()
-                        }
-                        _ => rmp_serde::decode::from_slice(field_bytes).unwrap(),
-                    };
-
-                    DicomField {
-                        value: None,
-                        vr: *vr,
-                        inline_binary: Some(inline_binary),
-                    }
-                }
-                b"PN" => {
-                    let name = rmp_serde::decode::from_slice(field_bytes).unwrap();
-                    let a = DicomValue::Alphabetic(Alphabetic { alphabetic: name });
-                    DicomField {
-                        value: Some(vec![a]),
-                        vr: *vr,
-                        inline_binary: None,
-                    }
-                }
-                _ => {
-                    let mut cursor = field_bytes;
-                    let v = decode::read_value(&mut cursor).unwrap();
-                    let value: Vec<_> = match v {
-                        Value::String(s) => vec![DicomValue::String(s.into_str().unwrap())],
-                        Value::Integer(i) => vec![integer_to_dicom_value(&i)],
-                        Value::F64(f) => vec![DicomValue::Float(f)],
-                        Value::Array(a) => a
-                            .into_iter()
-                            .map(|v| match v {
-                                Value::String(s) => DicomValue::String(s.into_str().unwrap()),
-                                Value::Integer(i) => integer_to_dicom_value(&i),
-                                Value::F64(f) => DicomValue::Float(f),
-                                _ => panic!("unexpected value type: {v:?}"),
-                            })
-                            .collect(),
-                        _ => panic!("unexpected value type: {v:?}"),
-                    };
-                    DicomField {
-                        value: Some(value),
-                        vr: *vr,
-                        inline_binary: None,
-                    }
-                }
-            }
+        FieldValue::BulkDataUri(uri) => field.bulk_data_uri = Some(uri),
+        FieldValue::Sequence(items) => {
+            field.value = Some(
+                items
+                    .into_iter()
+                    .map(|item| Ok(DicomValue::SeqField(dataset_to_dicom_json(item)?)))
+                    .collect::<InnerResult<_>>()?,
+            )
         }
+        FieldValue::Multiple(values) => {
+            field.value = Some(
+                values
+                    .into_iter()
+                    .map(|v| field_value_to_dicom_value(tag, v))
+                    .collect::<InnerResult<_>>()?,
+            )
+        }
+        value => field.value = Some(vec![field_value_to_dicom_value(tag, value)?]),
     }
+    Ok(field)
 }
 
-fn headers_to_data(sq: &HeaderFieldMap, dimble_buffer: &[u8]) -> DicomJsonData {
-    sq.iter()
-        .map(|(tag, header_field)| {
-            let tag = tag.to_string();
-            let field = headerfield_and_bytes_to_dicom_fields(&tag, header_field, dimble_buffer);
-            (tag, field)
+fn dataset_to_dicom_json(dataset: Dataset) -> InnerResult<DicomJsonData> {
+    dataset
+        .into_iter()
+        .map(|(tag, element)| {
+            let field = element_to_dicom_field(tag, element)?;
+            Ok((tag, field))
         })
         .collect()
 }
 
-fn integer_to_dicom_value(i: &Integer) -> DicomValue {
-    if let Some(v) = i.as_i64() {
-        DicomValue::Integer(v)
-    } else if let Some(v) = i.as_u64() {
-        DicomValue::Integer(v as i64)
-    } else {
-        panic!("Could not represent the integer as i64 or u64")
-    }
+fn headers_to_data(reader: &DimbleReader, header: &HeaderFieldMap) -> InnerResult<DicomJsonData> {
+    header
+        .iter()
+        .map(|(&tag, header_field)| {
+            let field = match tag {
+                Tag::PIXEL_DATA => DicomField {
+                    value: None,
+                    vr: Some(header_field.vr()),
+                    inline_binary: Some("TODO encode pixel data correctly".to_string()),
+                    bulk_data_uri: None,
+                },
+                _ => {
+                    let element = reader
+                        .decode(tag, header_field)
+                        .context(CouldNotReadSnafu)?;
+                    element_to_dicom_field(tag, element)?
+                }
+            };
+            Ok((tag, field))
+        })
+        .collect()
 }
 
-pub fn dimble_to_dicom_json(dimble_path: &str, json_path: &str) {
-    let dimble_file = fs::File::open(dimble_path).unwrap();
-    let dimble_buffer = unsafe { MmapOptions::new().map(&dimble_file).expect("mmap failed") };
+pub fn dimble_to_dicom_json(dimble_path: &str, json_path: &str) -> Result<()> {
+    let reader = DimbleReader::open(dimble_path).context(CouldNotReadSnafu)?;
 
-    let (header, header_len) = deserialise_header(&dimble_buffer);
+    let json_dicom = headers_to_data(&reader, reader.header().context(CouldNotReadSnafu)?)?;
 
-    let json_dicom = headers_to_data(&header, &dimble_buffer[header_len..]);
-
-    let json_file = fs::File::create(json_path).unwrap();
-    serde_json::to_writer_pretty(json_file, &json_dicom).unwrap(); // TODO don't write pretty (this is for debugging)
+    let json_file = fs::File::create(json_path).context(CouldNotCreateSnafu { json_path })?;
+    serde_json::to_writer_pretty(json_file, &json_dicom).context(CouldNotWriteJsonSnafu)?; // TODO don't write pretty (this is for debugging)
+    Ok(())
 }
 
-fn deserialise_header(buffer: &[u8]) -> (HeaderFieldMap, usize) {
-    let header_len = u64::from_le_bytes(buffer[0..8].try_into().unwrap()) as usize;
-    let header =
-        rmp_serde::from_slice(&buffer[8..8 + header_len]).expect("failed to deserialise header");
-    (header, header_len)
+#[derive(Debug, Snafu)]
+pub enum InnerError {
+    #[snafu(display("Could not read the dimble file"))]
+    CouldNotRead { source: reader::Error },
+
+    #[snafu(display("Field {tag} has a value that cannot be represented in DICOM JSON"))]
+    UnsupportedValue { tag: Tag },
+
+    #[snafu(display("Could not create the path {json_path}"))]
+    CouldNotCreate {
+        source: std::io::Error,
+        json_path: String,
+    },
+
+    #[snafu(display("Could not write the DICOM JSON"))]
+    CouldNotWriteJson { source: serde_json::Error },
 }
 
+type InnerResult<T, E = InnerError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub struct Error(InnerError);
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::codec::Compression;
+    use crate::ir_to_dimble::{dicom_json_to_dimble, DEFAULT_PIXEL_ALIGNMENT};
+    use serde_json::{json, Value};
 
-    #[test]
-    fn test_header_deserialisation_single_string() {
-        let buffer = {
-            let mut header_fields = HeaderFieldMap::new();
-            let vr = b"CS";
-            header_fields.insert("00080005".to_string(), HeaderField::Deffered(0, 4, *vr));
-
-            // serialise to buffer and prepend with header length
-            let mut buffer = Vec::new();
-            let header_bytes = rmp_serde::to_vec(&header_fields).unwrap();
-            let header_len = header_bytes.len() as u64;
-            buffer.extend_from_slice(&header_len.to_le_bytes());
-            buffer.extend_from_slice(&header_bytes);
-            buffer
-        };
-
-        let (header, _header_len) = deserialise_header(&buffer);
-        println!("{:?}", header);
-        if let HeaderField::Deffered(offset, length, vr) = *header.get("00080005").unwrap() {
-            assert_eq!(offset, 0);
-            assert_eq!(length, 4);
-            assert_eq!(vr, *b"CS");
-        } else {
-            panic!("expected deffered header field");
-        }
+    type Result<T = (), E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
+
+    fn round_trip(name: &str, dicom_json: &Value) -> Result<Value> {
+        let ir_path = format!("/tmp/vr_{name}.ir.json");
+        let dimble_path = format!("/tmp/vr_{name}.dimble");
+        let ir_recon_path = format!("/tmp/vr_{name}.ir.recon.json");
+        fs::write(&ir_path, dicom_json.to_string())?;
+        dicom_json_to_dimble(
+            &ir_path,
+            None,
+            &dimble_path,
+            None,
+            Compression::default(),
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+        dimble_to_dicom_json(&dimble_path, &ir_recon_path)?;
+        Ok(serde_json::from_slice(&fs::read(ir_recon_path)?)?)
     }
 
     #[test]
-    fn test_header_deserialisation_no_value() {
-        let buffer = {
-            let mut header_fields = HeaderFieldMap::new();
-            let vr = b"PN";
-            header_fields.insert("00100010".to_string(), HeaderField::Empty(*vr));
-
-            // serialise to buffer and prepend with header length
-            let mut buffer = Vec::new();
-            let header_bytes = rmp_serde::to_vec(&header_fields).unwrap();
-            let header_len = header_bytes.len() as u64;
-            buffer.extend_from_slice(&header_len.to_le_bytes());
-            buffer.extend_from_slice(&header_bytes);
-            buffer
-        };
-
-        let (header, _header_len) = deserialise_header(&buffer);
-        println!("{:?}", header);
+    fn test_round_trip_every_vr() -> Result {
+        let fields = [
+            ("AE", json!({"vr": "AE", "Value": ["STORESCP"]})),
+            ("AS", json!({"vr": "AS", "Value": ["042Y"]})),
+            ("AT", json!({"vr": "AT", "Value": ["00280009", "00181063"]})),
+            ("CS", json!({"vr": "CS", "Value": ["ORIGINAL", "PRIMARY"]})),
+            ("DA", json!({"vr": "DA", "Value": ["20230102"]})),
+            ("DS_number", json!({"vr": "DS", "Value": [0.5, -1.25, 2]})),
+            ("DS_string", json!({"vr": "DS", "Value": ["0.50", "1e3"]})),
+            ("DS_mixed", json!({"vr": "DS", "Value": ["0.50", 2.5]})),
+            (
+                "DT",
+                json!({"vr": "DT", "Value": ["20230102123456.000000+0100"]}),
+            ),
+            ("FD", json!({"vr": "FD", "Value": [1.5, -2.0e100]})),
+            ("FL", json!({"vr": "FL", "Value": [0.25]})),
+            ("IS_number", json!({"vr": "IS", "Value": [-3, 7]})),
+            ("IS_string", json!({"vr": "IS", "Value": ["0012"]})),
+            ("LO", json!({"vr": "LO", "Value": ["Long string"]})),
+            ("LT", json!({"vr": "LT", "Value": ["Long\r\ntext"]})),
+            ("OB", json!({"vr": "OB", "InlineBinary": "AAECAw=="})),
+            ("OD", json!({"vr": "OD", "InlineBinary": "AAAAAAAA8D8="})),
+            ("OF", json!({"vr": "OF", "InlineBinary": "AACAPw=="})),
+            ("OL", json!({"vr": "OL", "InlineBinary": "AQAAAA=="})),
+            ("OV", json!({"vr": "OV", "InlineBinary": "AQAAAAAAAAA="})),
+            ("OW", json!({"vr": "OW", "InlineBinary": "AAE="})),
+            (
+                "PN",
+                json!({"vr": "PN", "Value": [{"Alphabetic": "Doe^John"}]}),
+            ),
+            (
+                "PN_groups",
+                json!({"vr": "PN", "Value": [
+                    {"Alphabetic": "Yamada^Tarou", "Ideographic": "山田^太郎", "Phonetic": "やまだ^たろう"},
+                    {"Ideographic": "홍^길동"},
+                    {},
+                ]}),
+            ),
+            ("SH", json!({"vr": "SH", "Value": ["Short"]})),
+            ("SL", json!({"vr": "SL", "Value": [-2147483648]})),
+            ("SS", json!({"vr": "SS", "Value": [-32768, 32767]})),
+            ("ST", json!({"vr": "ST", "Value": ["Short text"]})),
+            ("SV", json!({"vr": "SV", "Value": [i64::MIN, i64::MAX]})),
+            (
+                "SV_string",
+                json!({"vr": "SV", "Value": ["-9007199254740993"]}),
+            ),
+            ("TM", json!({"vr": "TM", "Value": ["123456.789"]})),
+            (
+                "UC",
+                json!({"vr": "UC", "Value": ["Unlimited", "characters"]}),
+            ),
+            ("UI", json!({"vr": "UI", "Value": ["1.2.840.10008.1.2.1"]})),
+            ("UL", json!({"vr": "UL", "Value": [4294967295u32]})),
+            ("UN", json!({"vr": "UN", "InlineBinary": "3q2+7w=="})),
+            (
+                "UR",
+                json!({"vr": "UR", "Value": ["http://example.com/a%20b"]}),
+            ),
+            ("US", json!({"vr": "US", "Value": [0, 65535]})),
+            ("UT", json!({"vr": "UT", "Value": ["Unlimited text"]})),
+            ("UV", json!({"vr": "UV", "Value": [0, u64::MAX]})),
+            (
+                "UV_string",
+                json!({"vr": "UV", "Value": ["18446744073709551615"]}),
+            ),
+            (
+                "BulkDataURI",
+                json!({"vr": "OB", "BulkDataURI": "http://example.com/studies/1/bulk/7FE00010"}),
+            ),
+            ("empty", json!({"vr": "LO"})),
+            (
+                "SQ",
+                json!({"vr": "SQ", "Value": [{"00080100": {"vr": "SH", "Value": ["121327"]}}]}),
+            ),
+            ("SQ_empty", json!({"vr": "SQ", "Value": []})),
+            (
+                "SQ_nested",
+                json!({"vr": "SQ", "Value": [
+                    {
+                        "00081155": {"vr": "UI", "Value": ["1.2.3"]},
+                        "0040A730": {"vr": "SQ", "Value": [
+                            {"0040A160": {"vr": "UT", "Value": ["first"]}},
+                            {
+                                "0040A160": {"vr": "UT", "Value": ["second"]},
+                                "0040A730": {"vr": "SQ", "Value": [
+                                    {"0040A30A": {"vr": "DS", "Value": [1.5]}},
+                                    {},
+                                ]},
+                            },
+                        ]},
+                    },
+                    {"00081155": {"vr": "UI", "Value": ["1.2.4"]}},
+                    {"7FE00010": {"vr": "OW", "InlineBinary": "AAE="}},
+                ]}),
+            ),
+        ];
+        for (name, field) in fields {
+            let dicom_json = json!({ "00091001": field });
+            let recon = round_trip(name, &dicom_json)?;
+            assert_eq!(recon, dicom_json, "{name} should round trip");
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,206 @@
+//! Export of pixel arrays through the DLPack protocol, so that any framework can consume them with
+//! `from_dlpack` without a copy.
+//!
+//! A producer hands out a `DLManagedTensor` in a capsule named `dltensor`. The consumer renames the
+//! capsule to `used_dltensor` and calls the tensor's deleter once it is done with the memory; a
+//! capsule that is never consumed calls the deleter itself when it is garbage collected.
+
+use memmap2::MmapMut;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use std::ffi::{c_void, CStr};
+use std::sync::Arc;
+
+use crate::pixel_array::Dtype;
+
+const DLTENSOR: &CStr = c"dltensor";
+
+/// `kDLCPU`, the only device dimble loads to
+pub(crate) const DL_CPU: i32 = 1;
+
+#[repr(C)]
+struct DLDevice {
+    device_type: i32,
+    device_id: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DLDataType {
+    code: u8,
+    bits: u8,
+    lanes: u16,
+}
+
+#[repr(C)]
+struct DLTensor {
+    data: *mut c_void,
+    device: DLDevice,
+    ndim: i32,
+    dtype: DLDataType,
+    shape: *mut i64,
+    /// In elements, not bytes
+    strides: *mut i64,
+    byte_offset: u64,
+}
+
+#[repr(C)]
+struct DLManagedTensor {
+    dl_tensor: DLTensor,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+/// Keeps the memory map, shape and strides alive until the consumer calls the deleter
+struct ManagerContext {
+    _mmap: Arc<MmapMut>,
+    shape: Vec<i64>,
+    strides: Vec<i64>,
+}
+
+pub(crate) fn data_type(dtype: Dtype) -> DLDataType {
+    // kDLInt, kDLUInt, kDLFloat, kDLBfloat, kDLComplex and kDLBool
+    let (code, bits) = match dtype {
+        Dtype::BOOL => (6, 8),
+        Dtype::U8 => (1, 8),
+        Dtype::I8 => (0, 8),
+        Dtype::U16 => (1, 16),
+        Dtype::I16 => (0, 16),
+        Dtype::F16 => (2, 16),
+        Dtype::BF16 => (4, 16),
+        Dtype::U32 => (1, 32),
+        Dtype::I32 => (0, 32),
+        Dtype::F32 => (2, 32),
+        Dtype::U64 => (1, 64),
+        Dtype::I64 => (0, 64),
+        Dtype::F64 => (2, 64),
+        Dtype::C64 => (5, 64),
+        Dtype::C128 => (5, 128),
+    };
+    DLDataType {
+        code,
+        bits,
+        lanes: 1,
+    }
+}
+
+/// Strides in elements of a C-contiguous array of `shape`
+pub(crate) fn contiguous_strides(shape: &[usize]) -> Vec<i64> {
+    let mut strides = vec![1; shape.len()];
+    for axis in (1..shape.len()).rev() {
+        strides[axis - 1] = strides[axis] * shape[axis] as i64;
+    }
+    strides
+}
+
+/// Wraps the array at `offset` bytes into `mmap` in a `dltensor` capsule
+pub(crate) fn to_capsule(
+    py: Python,
+    mmap: Arc<MmapMut>,
+    offset: usize,
+    dtype: Dtype,
+    shape: &[usize],
+    strides: Vec<i64>,
+) -> PyResult<PyObject> {
+    let mut context = Box::new(ManagerContext {
+        // the mapping is copy on write, so a consumer writing to the array never changes the file
+        _mmap: mmap.clone(),
+        shape: shape.iter().map(|&length| length as i64).collect(),
+        strides,
+    });
+    let managed = Box::new(DLManagedTensor {
+        dl_tensor: DLTensor {
+            data: mmap[offset..].as_ptr() as *mut c_void,
+            device: DLDevice {
+                device_type: DL_CPU,
+                device_id: 0,
+            },
+            ndim: shape.len() as i32,
+            dtype: data_type(dtype),
+            shape: context.shape.as_mut_ptr(),
+            strides: context.strides.as_mut_ptr(),
+            byte_offset: 0,
+        },
+        manager_ctx: Box::into_raw(context) as *mut c_void,
+        deleter: Some(delete_managed_tensor),
+    });
+    let managed = Box::into_raw(managed);
+    unsafe {
+        let capsule = ffi::PyCapsule_New(
+            managed as *mut c_void,
+            DLTENSOR.as_ptr(),
+            Some(capsule_destructor),
+        );
+        if capsule.is_null() {
+            delete_managed_tensor(managed);
+            return Err(PyErr::fetch(py));
+        }
+        Ok(PyObject::from_owned_ptr(py, capsule))
+    }
+}
+
+unsafe extern "C" fn delete_managed_tensor(managed: *mut DLManagedTensor) {
+    let managed = Box::from_raw(managed);
+    drop(Box::from_raw(managed.manager_ctx as *mut ManagerContext));
+}
+
+/// Frees the tensor if no consumer took ownership of it by renaming the capsule
+unsafe extern "C" fn capsule_destructor(capsule: *mut ffi::PyObject) {
+    if ffi::PyCapsule_IsValid(capsule, DLTENSOR.as_ptr()) == 1 {
+        let managed = ffi::PyCapsule_GetPointer(capsule, DLTENSOR.as_ptr()) as *mut DLManagedTensor;
+        if let Some(deleter) = (*managed).deleter {
+            deleter(managed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::AsPyPointer;
+
+    #[test]
+    fn test_contiguous_strides() {
+        assert_eq!(contiguous_strides(&[]), Vec::<i64>::new());
+        assert_eq!(contiguous_strides(&[5]), [1]);
+        assert_eq!(contiguous_strides(&[2, 3, 4]), [12, 4, 1]);
+    }
+
+    #[test]
+    fn test_capsule() -> PyResult<()> {
+        let mut mmap = MmapMut::map_anon(16).unwrap();
+        mmap.copy_from_slice(&(0..16).collect::<Vec<u8>>());
+        let mmap = Arc::new(mmap);
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            // a consumer takes ownership by renaming the capsule, then calls the deleter
+            let capsule = to_capsule(py, mmap.clone(), 4, Dtype::U16, &[2, 3], vec![3, 1])?;
+            assert_eq!(Arc::strong_count(&mmap), 2);
+            unsafe {
+                let managed = ffi::PyCapsule_GetPointer(capsule.as_ptr(), DLTENSOR.as_ptr())
+                    as *mut DLManagedTensor;
+                assert!(!managed.is_null());
+                let tensor = &(*managed).dl_tensor;
+                assert_eq!(tensor.device.device_type, DL_CPU);
+                assert_eq!(tensor.dtype, data_type(Dtype::U16));
+                assert_eq!(std::slice::from_raw_parts(tensor.shape, 2), [2, 3]);
+                assert_eq!(std::slice::from_raw_parts(tensor.strides, 2), [3, 1]);
+                assert_eq!(*(tensor.data as *const u8), 4);
+
+                ffi::PyCapsule_SetName(capsule.as_ptr(), c"used_dltensor".as_ptr());
+                drop(capsule);
+                assert_eq!(Arc::strong_count(&mmap), 2);
+                ((*managed).deleter.unwrap())(managed);
+            }
+            assert_eq!(Arc::strong_count(&mmap), 1);
+
+            // an unconsumed capsule frees the tensor itself
+            let capsule = to_capsule(py, mmap.clone(), 0, Dtype::U8, &[16], vec![1])?;
+            assert_eq!(Arc::strong_count(&mmap), 2);
+            drop(capsule);
+            assert_eq!(Arc::strong_count(&mmap), 1);
+            Ok(())
+        })
+    }
+}
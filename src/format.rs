@@ -0,0 +1,180 @@
+use snafu::prelude::*;
+
+/// Bytes every versioned dimble file starts with.
+pub(crate) const MAGIC: [u8; 6] = *b"DIMBLE";
+
+/// Major version written by this crate. Readers reject files with a major version they do not
+/// know; files without a preamble are read as version 0. Minor versions only add to their major
+/// version, each adding to the one before:
+///
+/// - 1 has a MessagePack header map. Minor 1 stores inline binary as raw bytes and adds bulk data
+///   URIs.
+/// - 2 replaces the header map with a sorted index of tags. Minor 1 adds compressed fields, minor 2
+///   adds checksums.
+pub(crate) const FORMAT_VERSION_MAJOR: u8 = 2;
+/// Minor version of [`FORMAT_VERSION_MAJOR`] written by this crate
+pub(crate) const FORMAT_VERSION_MINOR: u8 = 2;
+/// The oldest major version readers still read
+pub(crate) const OLDEST_SUPPORTED_MAJOR: u8 = 1;
+
+/// Flag of files that end with the checksums of their header and fields, see [`crate::checksum`]
+pub(crate) const CHECKSUMS: u64 = 1;
+
+/// Every flag this crate knows. Files with any other flag use a feature it cannot read.
+const KNOWN_FLAGS: u64 = CHECKSUMS;
+
+// magic, major version, minor version, flags
+pub(crate) const PREAMBLE_LENGTH: usize = MAGIC.len() + 1 + 1 + std::mem::size_of::<u64>();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preamble {
+    pub major: u8,
+    pub minor: u8,
+    /// Optional format features the file uses. Only bit 0, [`CHECKSUMS`], is defined.
+    pub flags: u64,
+}
+
+impl Preamble {
+    pub(crate) const CURRENT: Preamble = Preamble {
+        major: FORMAT_VERSION_MAJOR,
+        minor: FORMAT_VERSION_MINOR,
+        flags: CHECKSUMS,
+    };
+
+    /// Whether the header is a sorted index of tags rather than a MessagePack map
+    pub(crate) fn has_header_index(self) -> bool {
+        self.major >= 2
+    }
+
+    /// Whether the file ends with checksums of its header and fields
+    pub fn has_checksums(self) -> bool {
+        self.flags & CHECKSUMS != 0
+    }
+
+    /// Files written before the preamble was introduced
+    pub(crate) const V0: Preamble = Preamble {
+        major: 0,
+        minor: 0,
+        flags: 0,
+    };
+
+    pub(crate) fn to_bytes(self) -> [u8; PREAMBLE_LENGTH] {
+        let mut bytes = [0; PREAMBLE_LENGTH];
+        bytes[..MAGIC.len()].copy_from_slice(&MAGIC);
+        bytes[MAGIC.len()] = self.major;
+        bytes[MAGIC.len() + 1] = self.minor;
+        bytes[MAGIC.len() + 2..].copy_from_slice(&self.flags.to_le_bytes());
+        bytes
+    }
+
+    /// Reads the preamble from the start of `buffer`, returning it along with the number of bytes
+    /// it occupies (0 for un-versioned files).
+    pub(crate) fn read(buffer: &[u8]) -> Result<(Preamble, usize), PreambleError> {
+        if !buffer.starts_with(&MAGIC) {
+            return Ok((Preamble::V0, 0));
+        }
+        ensure!(
+            buffer.len() >= PREAMBLE_LENGTH,
+            TruncatedSnafu {
+                length: buffer.len()
+            }
+        );
+        let major = buffer[MAGIC.len()];
+        let minor = buffer[MAGIC.len() + 1];
+        let flags = u64::from_le_bytes(
+            buffer[MAGIC.len() + 2..PREAMBLE_LENGTH]
+                .try_into()
+                .expect("slice has the length of a u64"),
+        );
+        ensure!(
+            (OLDEST_SUPPORTED_MAJOR..=FORMAT_VERSION_MAJOR).contains(&major),
+            UnsupportedVersionSnafu { major, minor }
+        );
+        ensure!(
+            flags & !KNOWN_FLAGS == 0,
+            UnsupportedFlagsSnafu {
+                flags: flags & !KNOWN_FLAGS
+            }
+        );
+        Ok((
+            Preamble {
+                major,
+                minor,
+                flags,
+            },
+            PREAMBLE_LENGTH,
+        ))
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum PreambleError {
+    #[snafu(display("File has a dimble preamble but is only {length} bytes long"))]
+    Truncated { length: usize },
+
+    #[snafu(display(
+        "Unsupported dimble format version {major}.{minor}, this reader supports {OLDEST_SUPPORTED_MAJOR}.x to {FORMAT_VERSION_MAJOR}.x"
+    ))]
+    UnsupportedVersion { major: u8, minor: u8 },
+
+    #[snafu(display("File uses format flags {flags:#x} that this reader does not support"))]
+    UnsupportedFlags { flags: u64 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preamble_round_trip() {
+        let bytes = Preamble::CURRENT.to_bytes();
+        let (preamble, length) = Preamble::read(&bytes).unwrap();
+        assert_eq!(preamble, Preamble::CURRENT);
+        assert_eq!(length, PREAMBLE_LENGTH);
+    }
+
+    #[test]
+    fn test_unversioned_file_is_v0() {
+        let header_len = 42u64.to_le_bytes();
+        let (preamble, length) = Preamble::read(&header_len).unwrap();
+        assert_eq!(preamble, Preamble::V0);
+        assert_eq!(length, 0);
+    }
+
+    #[test]
+    fn test_previous_major_version_is_read() {
+        let mut bytes = Preamble::CURRENT.to_bytes();
+        bytes[MAGIC.len()] = OLDEST_SUPPORTED_MAJOR;
+        let (preamble, _length) = Preamble::read(&bytes).unwrap();
+        assert_eq!(preamble.major, OLDEST_SUPPORTED_MAJOR);
+        assert!(!preamble.has_header_index());
+    }
+
+    #[test]
+    fn test_unknown_major_version_is_rejected() {
+        let mut bytes = Preamble::CURRENT.to_bytes();
+        bytes[MAGIC.len()] = FORMAT_VERSION_MAJOR + 1;
+        assert!(matches!(
+            Preamble::read(&bytes),
+            Err(PreambleError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_flags_are_rejected() {
+        let mut preamble = Preamble::CURRENT;
+        preamble.flags |= 1 << 5;
+        assert!(matches!(
+            Preamble::read(&preamble.to_bytes()),
+            Err(PreambleError::UnsupportedFlags { flags: 0x20 })
+        ));
+    }
+
+    #[test]
+    fn test_truncated_preamble_is_rejected() {
+        assert!(matches!(
+            Preamble::read(&MAGIC),
+            Err(PreambleError::Truncated { .. })
+        ));
+    }
+}
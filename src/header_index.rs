@@ -0,0 +1,248 @@
+//! The header of format version 2, a fixed-width index of tags followed by their header fields.
+//!
+//! ```text
+//! u32 LE    number of indexed fields N
+//! u32 LE    length of the record section R
+//! N × 12    index entries sorted by tag: u32 LE tag, u32 LE record offset, u32 LE record length
+//! R bytes   records, each a MessagePack encoded `HeaderField`
+//! ...       zero padding up to the header length
+//! ```
+//!
+//! Record offsets are relative to the start of the record section. A single field is found by
+//! binary searching the index, without decoding any other record. Writers pad the header so that
+//! the data section starts at the pixel alignment.
+
+use snafu::prelude::*;
+use std::ops::Range;
+
+use crate::ir_to_dimble::{HeaderField, HeaderFieldMap};
+use crate::tag::Tag;
+
+const COUNT_LENGTH: usize = std::mem::size_of::<u32>();
+const RECORDS_LENGTH_LENGTH: usize = std::mem::size_of::<u32>();
+pub(crate) const ENTRY_LENGTH: usize = 3 * std::mem::size_of::<u32>();
+
+/// Encodes the header. The output only depends on the fields, not on the order they were added.
+pub(crate) fn encode(header_fields: &HeaderFieldMap) -> Result<Vec<u8>, HeaderIndexError> {
+    let mut entries = Vec::new();
+    let mut records = Vec::new();
+    // the map iterates in tag order
+    for (&tag, header_field) in header_fields {
+        let offset = records.len();
+        rmp_serde::encode::write_named(&mut records, header_field)
+            .context(CouldNotEncodeSnafu { tag })?;
+        entries.extend_from_slice(&u32::from(tag).to_le_bytes());
+        entries.extend_from_slice(&to_u32(offset)?.to_le_bytes());
+        entries.extend_from_slice(&to_u32(records.len() - offset)?.to_le_bytes());
+    }
+
+    let count = entries.len() / ENTRY_LENGTH;
+    let mut header = Vec::with_capacity(COUNT_LENGTH + RECORDS_LENGTH_LENGTH + entries.len());
+    header.extend_from_slice(&to_u32(count)?.to_le_bytes());
+    header.extend_from_slice(&to_u32(records.len())?.to_le_bytes());
+    header.extend_from_slice(&entries);
+    header.extend_from_slice(&records);
+    Ok(header)
+}
+
+fn to_u32(length: usize) -> Result<u32, HeaderIndexError> {
+    u32::try_from(length).ok().context(TooLargeSnafu { length })
+}
+
+/// The location of the index within a file
+#[derive(Debug)]
+pub(crate) struct HeaderIndex {
+    entries_start: usize,
+    count: usize,
+    records: Range<usize>,
+}
+
+impl HeaderIndex {
+    /// Reads the index of the header that occupies `header` within `buffer`
+    pub(crate) fn read(buffer: &[u8], header: Range<usize>) -> Result<Self, HeaderIndexError> {
+        let header_bytes = buffer.get(header.clone()).context(TruncatedSnafu {
+            length: buffer.len(),
+        })?;
+        let read_u32 = |offset: usize| {
+            header_bytes
+                .get(offset..offset + 4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("slice has 4 bytes")))
+                .context(TruncatedSnafu {
+                    length: header_bytes.len(),
+                })
+        };
+        let count = read_u32(0)? as usize;
+        let records_length = read_u32(COUNT_LENGTH)? as usize;
+        let truncated = TruncatedSnafu {
+            length: header_bytes.len(),
+        };
+        let entries_start = header.start + COUNT_LENGTH + RECORDS_LENGTH_LENGTH;
+        let records_start = count
+            .checked_mul(ENTRY_LENGTH)
+            .and_then(|length| entries_start.checked_add(length))
+            .context(truncated)?;
+        let records_end = records_start
+            .checked_add(records_length)
+            .filter(|&end| end <= header.end)
+            .context(truncated)?;
+        let records = records_start..records_end;
+        Ok(Self {
+            entries_start,
+            count,
+            records,
+        })
+    }
+
+    /// Returns the tag and record location of the entry at `position`
+    fn entry(&self, buffer: &[u8], position: usize) -> (Tag, Range<usize>) {
+        let start = self.entries_start + position * ENTRY_LENGTH;
+        let field = |i: usize| {
+            let bytes = &buffer[start + 4 * i..start + 4 * (i + 1)];
+            u32::from_le_bytes(bytes.try_into().expect("slice has 4 bytes"))
+        };
+        let offset = self.records.start + field(1) as usize;
+        (Tag::from(field(0)), offset..offset + field(2) as usize)
+    }
+
+    fn decode_record(
+        &self,
+        buffer: &[u8],
+        tag: Tag,
+        record: Range<usize>,
+    ) -> Result<HeaderField, HeaderIndexError> {
+        ensure!(
+            record.end <= self.records.end,
+            RecordOutOfBoundsSnafu {
+                tag,
+                offset: record.start,
+                length: record.len(),
+            }
+        );
+        rmp_serde::from_slice(&buffer[record]).context(InvalidRecordSnafu { tag })
+    }
+
+    /// Looks up a single field, decoding only its record
+    pub(crate) fn get(
+        &self,
+        buffer: &[u8],
+        tag: Tag,
+    ) -> Result<Option<HeaderField>, HeaderIndexError> {
+        let (mut low, mut high) = (0, self.count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let (entry_tag, record) = self.entry(buffer, middle);
+            match entry_tag.cmp(&tag) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => {
+                    return self.decode_record(buffer, tag, record).map(Some)
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Decodes every field
+    pub(crate) fn decode_all(&self, buffer: &[u8]) -> Result<HeaderFieldMap, HeaderIndexError> {
+        let mut header_fields = HeaderFieldMap::new();
+        for position in 0..self.count {
+            let (tag, record) = self.entry(buffer, position);
+            let header_field = self.decode_record(buffer, tag, record)?;
+            header_fields.insert(tag, header_field);
+        }
+        Ok(header_fields)
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum HeaderIndexError {
+    #[snafu(display("Header of {length} bytes is too short for its index"))]
+    Truncated { length: usize },
+
+    #[snafu(display("Header record for {tag} at byte offset {offset} with length {length} extends past the record section"))]
+    RecordOutOfBounds {
+        tag: Tag,
+        offset: usize,
+        length: usize,
+    },
+
+    #[snafu(display("Header record for {tag} is not a valid header field"))]
+    InvalidRecord {
+        source: rmp_serde::decode::Error,
+        tag: Tag,
+    },
+
+    #[snafu(display("Could not encode the header record for {tag}"))]
+    CouldNotEncode {
+        source: rmp_serde::encode::Error,
+        tag: Tag,
+    },
+
+    #[snafu(display("Header section of {length} bytes does not fit a 32 bit offset"))]
+    TooLarge { length: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let mut header_fields = HeaderFieldMap::new();
+        for element in 0..100u16 {
+            header_fields.insert(
+                Tag::new(0x0009, 0x1000 + element * 3),
+                HeaderField::Deffered(element.into(), 1, *b"LO"),
+            );
+        }
+        header_fields.insert(
+            Tag::new(0x0008, 0x9215),
+            HeaderField::SQ(vec![HeaderFieldMap::from([(
+                Tag::new(0x0008, 0x0100),
+                HeaderField::Empty(*b"SH"),
+            )])]),
+        );
+
+        let header = encode(&header_fields).unwrap();
+        let mut buffer = vec![0xFF; 3];
+        buffer.extend_from_slice(&header);
+        let index = HeaderIndex::read(&buffer, 3..buffer.len()).unwrap();
+
+        for (&tag, header_field) in &header_fields {
+            assert_eq!(
+                index.get(&buffer, tag).unwrap().as_ref(),
+                Some(header_field)
+            );
+        }
+        assert_eq!(index.get(&buffer, Tag::new(0x0009, 0x1001)).unwrap(), None);
+        assert_eq!(index.get(&buffer, Tag::new(0xFFFF, 0xFFFF)).unwrap(), None);
+        assert_eq!(index.get(&buffer, Tag::new(0x0000, 0x0000)).unwrap(), None);
+        assert_eq!(index.decode_all(&buffer).unwrap(), header_fields);
+    }
+
+    #[test]
+    fn test_truncated_index() {
+        let header = encode(&HeaderFieldMap::from([(
+            Tag::new(0x0008, 0x0005),
+            HeaderField::Empty(*b"CS"),
+        )]))
+        .unwrap();
+        let truncated = &header[..COUNT_LENGTH + RECORDS_LENGTH_LENGTH + ENTRY_LENGTH];
+        assert!(matches!(
+            HeaderIndex::read(truncated, 0..truncated.len()),
+            Err(HeaderIndexError::Truncated { .. })
+        ));
+        assert!(matches!(
+            HeaderIndex::read(&header, 0..header.len() + 1),
+            Err(HeaderIndexError::Truncated { .. })
+        ));
+
+        // counts and lengths too large for the header, however large
+        let mut corrupt = header.clone();
+        corrupt[..COUNT_LENGTH + RECORDS_LENGTH_LENGTH].fill(0xFF);
+        assert!(matches!(
+            HeaderIndex::read(&corrupt, 0..corrupt.len()),
+            Err(HeaderIndexError::Truncated { .. })
+        ));
+    }
+}
@@ -1,249 +1,776 @@
-use rmp_serde::{to_vec, Serializer};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rmpv::Value;
 use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
 use std::{
-    collections::HashMap,
-    fs,
-    io::{prelude::*, BufReader, SeekFrom, Write},
+    borrow::Cow,
+    cell::Cell,
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, prelude::*, BufReader, BufWriter, SeekFrom, Write},
+    path::Path,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
+use crate::checksum;
+use crate::codec::{self, Codec, CodecError, Compression};
 use crate::dicom_json::*;
+use crate::dictionary;
+use crate::format::{Preamble, PREAMBLE_LENGTH};
+use crate::header_index::{self, HeaderIndexError};
+use crate::pixel_array;
+use crate::spool::Spool;
+use crate::tag::Tag;
 
-pub(crate) type VR = [u8; 2]; // TODO use newtype pattern?
+pub type VR = [u8; 2]; // TODO use newtype pattern?
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum HeaderField {
     // offset, length, VR
     Deffered(u64, u64, VR), // TODO use struct with names?
     Empty(VR),
     SQ(Vec<HeaderFieldMap>),
+    /// The value lives outside the file, at this URI
+    BulkDataURI(String, VR),
+    /// Like `Deffered`, but the offset and length are of the bytes `Codec` compressed the value to,
+    /// followed by the length of the value they decompress to
+    Compressed(u64, u64, u64, VR, Codec),
 }
 
-fn extend_and_make_field(data_bytes: &mut Vec<u8>, field_bytes: &[u8], vr: VR) -> HeaderField {
+impl HeaderField {
+    /// The field's VR, which for sequences is always SQ
+    pub fn vr(&self) -> VR {
+        match self {
+            HeaderField::Deffered(_, _, vr)
+            | HeaderField::Empty(vr)
+            | HeaderField::BulkDataURI(_, vr)
+            | HeaderField::Compressed(_, _, _, vr, _) => *vr,
+            HeaderField::SQ(_) => *b"SQ",
+        }
+    }
+}
+
+pub(crate) fn extend_and_make_field(
+    data_bytes: &mut Vec<u8>,
+    field_bytes: &[u8],
+    vr: VR,
+) -> HeaderField {
     let offset = data_bytes.len() as u64;
     data_bytes.extend_from_slice(field_bytes);
     HeaderField::Deffered(offset, field_bytes.len() as u64, vr)
 }
 
-pub type HeaderFieldMap = HashMap<String, HeaderField>;
+/// Header fields by tag, sorted so that the same fields always serialise to the same bytes
+pub type HeaderFieldMap = BTreeMap<Tag, HeaderField>;
 
-fn get_file_bytes(safetensors_path: &str) -> Vec<u8> {
-    fs::read(safetensors_path).unwrap()
+/// Opens a safetensors file and reads its header, leaving the file at the start of the tensor data.
+/// Returns the file, the header and the length of the tensor data.
+fn read_safetensors_header(safetensors_path: &str) -> InnerResult<(fs::File, Vec<u8>, u64)> {
+    let mut file = fs::File::open(safetensors_path)
+        .context(CouldNotReadPixelArraySnafu { safetensors_path })?;
+    let mut header = vec![0; std::mem::size_of::<u64>()];
+    file.read_exact(&mut header)
+        .context(CouldNotReadPixelArraySnafu { safetensors_path })?;
+    let header_len = u64::from_le_bytes(header[..].try_into().expect("slice has 8 bytes"));
+    (&mut file)
+        .take(header_len)
+        .read_to_end(&mut header)
+        .context(CouldNotReadPixelArraySnafu { safetensors_path })?;
+    let data_length = pixel_array::data_length(&header).context(PixelArraySnafu)?;
+    Ok((file, header, data_length as u64))
 }
 
-fn dicom_values_to_vec(tag: &str, dicom_values: &[DicomValue]) -> Option<Vec<u8>> {
-    let field_bytes = match dicom_values {
-        [DicomValue::String(s)] => to_vec(&s),
-        [DicomValue::Integer(u)] => to_vec(&u),
-        [DicomValue::Float(u)] => to_vec(&u),
-        [DicomValue::Alphabetic(u)] => to_vec(&u.alphabetic),
-        many => match many
-            .first()
-            .expect("This should definitely have a first element")
-        {
-            DicomValue::String(_) => to_vec(
-                &many
-                    .iter()
-                    .map(|v| match v {
-                        DicomValue::String(s) => s.to_owned(),
-                        _ => panic!("{tag} expected only strings"),
-                    })
-                    .collect::<Vec<String>>(),
-            ),
-            DicomValue::Integer(_) => to_vec(
-                &many
-                    .iter()
-                    .map(|v| match v {
-                        DicomValue::Integer(i) => *i,
-                        _ => panic!("{tag} expected only ints"),
-                    })
-                    .collect::<Vec<i64>>(),
+fn dicom_value_to_value(tag: Tag, vr: &VR, dicom_value: &DicomValue) -> InnerResult<Value> {
+    Ok(match dicom_value {
+        DicomValue::Integer(i) => Value::from(*i),
+        DicomValue::UnsignedInteger(u) => Value::from(*u),
+        DicomValue::Float(f) => Value::from(*f),
+        DicomValue::String(s) => Value::from(s.as_str()),
+        DicomValue::PersonName(name) => Value::from(name.to_dicom_string()),
+        DicomValue::SeqField(item) if vr == b"PN" && item.is_empty() => Value::from(""),
+        DicomValue::SeqField(_) => NestedSequenceSnafu { tag }.fail()?,
+    })
+}
+
+/// Encodes a single value as is and several as an array. The values of an array need not share
+/// a type, so DS and IS values keep whichever of the string or number form they came in.
+fn dicom_values_to_vec(tag: Tag, vr: &VR, dicom_values: &[DicomValue]) -> InnerResult<Vec<u8>> {
+    let value = match dicom_values {
+        [dicom_value] => dicom_value_to_value(tag, vr, dicom_value)?,
+        many => Value::Array(
+            many.iter()
+                .map(|v| dicom_value_to_value(tag, vr, v))
+                .collect::<InnerResult<_>>()?,
+        ),
+    };
+    let mut field_bytes = Vec::new();
+    rmpv::encode::write_value(&mut field_bytes, &value).context(CouldNotEncodeSnafu { tag })?;
+    Ok(field_bytes)
+}
+
+/// An attribute whose VR or number of values disagrees with the data dictionary. Conversion
+/// keeps the attribute as it is and reports the mismatch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DictionaryMismatch {
+    Vr {
+        tag: Tag,
+        vr: VR,
+        expected: &'static str,
+    },
+    Vm {
+        tag: Tag,
+        count: usize,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for DictionaryMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keyword = |tag: &Tag| tag.keyword().unwrap_or_default();
+        match self {
+            DictionaryMismatch::Vr { tag, vr, expected } => write!(
+                f,
+                "{tag} {} has VR {}, the dictionary has {expected}",
+                keyword(tag),
+                String::from_utf8_lossy(vr)
             ),
-            DicomValue::Float(_) => to_vec(
-                &many
-                    .iter()
-                    .map(|v| match v {
-                        DicomValue::Float(f) => *f,
-                        _ => panic!("{tag} expected only floats"),
-                    })
-                    .collect::<Vec<f64>>(),
+            DictionaryMismatch::Vm {
+                tag,
+                count,
+                expected,
+            } => write!(
+                f,
+                "{tag} {} has {count} values, the dictionary has VM {expected}",
+                keyword(tag)
             ),
-            DicomValue::SeqField(_) => {
-                // TODO: handle sequences of sequences properly
-                return None;
+        }
+    }
+}
+
+/// Fills in a missing VR from the data dictionary, and reports a VR or number of values the
+/// dictionary does not allow
+fn checked_vr(
+    tag: Tag,
+    dicom_field: &DicomField,
+    mismatches: &mut Vec<DictionaryMismatch>,
+) -> InnerResult<VR> {
+    let entry = dictionary::lookup(tag);
+    let vr = match (dicom_field.vr, entry) {
+        (Some(vr), Some(entry)) => {
+            if vr != *b"UN" && !entry.allows_vr(&vr) {
+                mismatches.push(DictionaryMismatch::Vr {
+                    tag,
+                    vr,
+                    expected: entry.vr,
+                });
             }
-            other => panic!("{tag} unexpected value type {:?}", other),
-        },
+            vr
+        }
+        (Some(vr), None) => vr,
+        (None, Some(entry)) => entry.implicit_vr(),
+        (None, None) => MissingVrSnafu { tag }.fail()?,
     };
-    let field_bytes = field_bytes.unwrap();
-    Some(field_bytes)
+    if let (Some(entry), Some(values)) = (entry, dicom_field.value.as_deref()) {
+        let count = values.len();
+        if vr != *b"SQ" && count > 0 && !entry.multiplicity().allows(count) {
+            mismatches.push(DictionaryMismatch::Vm {
+                tag,
+                count,
+                expected: entry.vm,
+            });
+        }
+    }
+    Ok(vr)
 }
 
 fn prepare_dimble_fields(
     dicom_fields: &DicomJsonData,
     data_bytes: &mut Vec<u8>,
-    pixel_array_safetensors_path: Option<&str>,
+    pixel_array_safetensors: Option<&[u8]>,
+    mismatches: &mut Vec<DictionaryMismatch>,
 ) -> InnerResult<HeaderFieldMap> {
     dicom_fields
         .iter()
         .map(|(tag, dicom_field)| {
             Ok((
-                tag.to_owned(),
-                prepare_dimble_field(tag, dicom_field, data_bytes, pixel_array_safetensors_path)?,
+                *tag,
+                prepare_dimble_field(
+                    *tag,
+                    dicom_field,
+                    data_bytes,
+                    pixel_array_safetensors,
+                    mismatches,
+                )?,
             ))
         })
         .collect()
 }
 
 fn prepare_dimble_field(
-    tag: &str,
+    tag: Tag,
     dicom_field: &DicomField,
     data_bytes: &mut Vec<u8>,
-    pixel_array_safetensors_path: Option<&str>,
+    pixel_array_safetensors: Option<&[u8]>,
+    mismatches: &mut Vec<DictionaryMismatch>,
 ) -> InnerResult<HeaderField> {
-    match dicom_field {
-        DicomField {
-            value: Some(value),
-            vr,
-            inline_binary: None,
-        } => {
-            match value.as_slice() {
-                [] if vr == b"SQ" => Ok(HeaderField::SQ(vec![])),
-                [] => panic!("empty value"),
-                [DicomValue::SeqField(seq)] => {
-                    let sq_header_field_map =
-                        prepare_dimble_fields(seq, data_bytes, pixel_array_safetensors_path)?;
-                    Ok(HeaderField::SQ(vec![sq_header_field_map]))
-                }
-                dicom_values => {
-                    // call a function to handle this
-                    match dicom_values_to_vec(tag, dicom_values) {
-                        Some(field_bytes) => {
-                            Ok(extend_and_make_field(data_bytes, &field_bytes, *vr))
-                        }
-                        None => {
-                            // TODO this is kind of a hack for gracefully not handling sequences of sequences
-                            Ok(HeaderField::Empty(*vr))
-                        }
+    let vr = &checked_vr(tag, dicom_field, mismatches)?;
+    let DicomField {
+        value,
+        inline_binary,
+        bulk_data_uri,
+        ..
+    } = dicom_field;
+    match (value.as_deref(), inline_binary, bulk_data_uri) {
+        (Some(items), None, None) if vr == b"SQ" => {
+            let items = items
+                .iter()
+                .map(|item| match item {
+                    // pixel data nested in a sequence, like an icon image, is ordinary inline
+                    // binary
+                    DicomValue::SeqField(item) => {
+                        prepare_dimble_fields(item, data_bytes, None, mismatches)
                     }
-                }
-            }
+                    _ => InvalidSequenceItemSnafu { tag }.fail(),
+                })
+                .collect::<InnerResult<_>>()?;
+            Ok(HeaderField::SQ(items))
         }
-        DicomField {
-            value: None,
-            vr,
-            inline_binary: None,
-        } => Ok(HeaderField::Empty(*vr)),
-        DicomField {
-            value: None,
-            vr,
-            inline_binary: Some(inline_binary),
-        } => match tag {
-            "7FE00010" => {
-                let field_bytes = get_file_bytes(
-                    pixel_array_safetensors_path.expect("expected pixel_array_safetensors_path"),
-                );
-                // data_bytes.extend(field_bytes);
-                Ok(extend_and_make_field(data_bytes, &field_bytes, *vr))
-            }
-            _ => {
-                let field_bytes = to_vec(&inline_binary).unwrap();
-                Ok(extend_and_make_field(data_bytes, &field_bytes, *vr))
-            }
-        },
-        DicomField {
-            value: Some(_),
-            vr: _vr,
-            inline_binary: Some(_),
-        } => ValueAndInlineBinaryBothPresentSnafu.fail(),
+        (None | Some([]), None, None) => Ok(HeaderField::Empty(*vr)),
+        (Some(dicom_values), None, None) => {
+            let field_bytes = dicom_values_to_vec(tag, vr, dicom_values)?;
+            Ok(extend_and_make_field(data_bytes, &field_bytes, *vr))
+        }
+        (None, Some(_), None) if tag == Tag::PIXEL_DATA && pixel_array_safetensors.is_some() => {
+            let field_bytes = pixel_array_safetensors.expect("checked above");
+            Ok(extend_and_make_field(data_bytes, field_bytes, *vr))
+        }
+        (None, Some(inline_binary), None) => {
+            let binary = STANDARD
+                .decode(inline_binary)
+                .context(InvalidInlineBinarySnafu { tag })?;
+            let mut field_bytes = Vec::new();
+            rmpv::encode::write_value(&mut field_bytes, &Value::Binary(binary))
+                .context(CouldNotEncodeSnafu { tag })?;
+            Ok(extend_and_make_field(data_bytes, &field_bytes, *vr))
+        }
+        (None, None, Some(bulk_data_uri)) => {
+            Ok(HeaderField::BulkDataURI(bulk_data_uri.clone(), *vr))
+        }
+        _ => ConflictingValuesSnafu { tag }.fail(),
     }
 }
 
-fn prepare_dicom_fields_for_serialisation(
+/// Returns the header fields, the data section and any attributes that disagree with the data
+/// dictionary
+pub(crate) fn prepare_dicom_fields_for_serialisation(
     dicom_json_data: DicomJsonData,
-    pixel_array_safetensors_path: Option<&str>,
-) -> InnerResult<(HeaderFieldMap, Vec<u8>)> {
+    pixel_array_safetensors: Option<&[u8]>,
+) -> InnerResult<(HeaderFieldMap, Vec<u8>, Vec<DictionaryMismatch>)> {
     let mut data_bytes = Vec::new();
+    let mut mismatches = Vec::new();
 
     let header_fields = prepare_dimble_fields(
         &dicom_json_data,
         &mut data_bytes,
-        pixel_array_safetensors_path,
+        pixel_array_safetensors,
+        &mut mismatches,
     )?;
 
-    Ok((header_fields, data_bytes))
+    Ok((header_fields, data_bytes, mismatches))
 }
 
 pub(crate) const HEADER_LENGTH_LENGTH: u8 = std::mem::size_of::<u64>() as u8;
 
-fn serialise_dimble_fields(
-    header_fields: HeaderFieldMap,
+/// The boundary the tensor data of the pixel data is aligned to unless told otherwise, a page
+pub const DEFAULT_PIXEL_ALIGNMENT: usize = 4096;
+/// The largest boundary the tensor data of the pixel data can be aligned to, a huge page
+pub const MAX_PIXEL_ALIGNMENT: usize = 2 * 1024 * 1024;
+
+/// Pixel data written at the end of the data section, with its tensor data aligned unless it is
+/// compressed, and streamed into the file so that it is never held in memory as a whole
+pub(crate) struct PixelStream<'a> {
+    /// The safetensors object up to its tensor data
+    pub(crate) header: Cow<'a, [u8]>,
+    /// The tensor data, `data_length` bytes of it
+    pub(crate) data: Box<dyn Read + 'a>,
+    pub(crate) data_length: u64,
+    pub(crate) vr: VR,
+}
+
+/// Takes the top level pixel data out of the data section, returning the header fields and data
+/// section without it and the pixel data as a stream. Returns `None` if there is no uncompressed
+/// pixel data to take out.
+fn split_pixel_data<'a>(
+    header_fields: &HeaderFieldMap,
+    data_bytes: &'a [u8],
+) -> Option<(HeaderFieldMap, Vec<u8>, PixelStream<'a>)> {
+    fn shift_offsets(header_fields: &mut HeaderFieldMap, end: u64, length: u64) {
+        for header_field in header_fields.values_mut() {
+            match header_field {
+                HeaderField::Deffered(offset, ..) | HeaderField::Compressed(offset, ..)
+                    if *offset >= end =>
+                {
+                    *offset -= length
+                }
+                HeaderField::SQ(items) => {
+                    for item in items {
+                        shift_offsets(item, end, length);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let HeaderField::Deffered(offset, length, vr) = *header_fields.get(&Tag::PIXEL_DATA)? else {
+        return None;
+    };
+    let (start, end) = (offset as usize, offset.checked_add(length)? as usize);
+    let pixel_bytes = data_bytes.get(start..end)?;
+    let data_start = pixel_array::data_start(pixel_bytes).ok()?;
+    let (header, data) = pixel_bytes.split_at_checked(data_start)?;
+
+    // the fields after the pixel data move down to take its place
+    let mut data_without_pixels = Vec::with_capacity(data_bytes.len() - pixel_bytes.len());
+    data_without_pixels.extend_from_slice(&data_bytes[..start]);
+    data_without_pixels.extend_from_slice(&data_bytes[end..]);
+    let mut header_fields = header_fields.clone();
+    header_fields.remove(&Tag::PIXEL_DATA);
+    shift_offsets(&mut header_fields, end as u64, length);
+
+    let pixel_stream = PixelStream {
+        header: header.into(),
+        data: Box::new(data),
+        data_length: data.len() as u64,
+        vr,
+    };
+    Some((header_fields, data_without_pixels, pixel_stream))
+}
+
+/// Whether any of the `data_length` bytes of the data section belong to no deferred field, like the
+/// values of fields that were replaced
+fn has_unreferenced_data(header_fields: &HeaderFieldMap, data_length: u64) -> bool {
+    fn add_ranges(header_fields: &HeaderFieldMap, ranges: &mut Vec<(u64, u64)>) {
+        for header_field in header_fields.values() {
+            match header_field {
+                HeaderField::Deffered(offset, length, _)
+                | HeaderField::Compressed(offset, length, ..) => {
+                    ranges.push((*offset, offset.saturating_add(*length)))
+                }
+                HeaderField::SQ(items) => {
+                    for item in items {
+                        add_ranges(item, ranges);
+                    }
+                }
+                HeaderField::Empty(_) | HeaderField::BulkDataURI(..) => {}
+            }
+        }
+    }
+
+    let mut ranges = Vec::new();
+    add_ranges(header_fields, &mut ranges);
+    ranges.sort_unstable();
+    // fields may share bytes, so the end of the bytes referenced so far
+    let mut referenced = 0;
+    for (start, end) in ranges {
+        if start > referenced {
+            break;
+        }
+        referenced = referenced.max(end);
+    }
+    referenced < data_length
+}
+
+/// Copies the deferred fields into a new data section, compressing them as `compression` says,
+/// and returns the new header fields and the spool the data section is written to. A field is only
+/// stored compressed if that makes it smaller.
+fn rebuild_fields(
+    header_fields: &HeaderFieldMap,
     data_bytes: &[u8],
+    compression: Compression,
+) -> Result<(HeaderFieldMap, Spool), SerialiseFieldsError> {
+    use serialise_fields_error::*;
+
+    fn compress_map(
+        header_fields: &HeaderFieldMap,
+        data_bytes: &[u8],
+        compression: Compression,
+        top_level: bool,
+        out: &mut Spool,
+    ) -> Result<HeaderFieldMap, SerialiseFieldsError> {
+        header_fields
+            .iter()
+            .map(|(&tag, header_field)| {
+                let header_field = match header_field {
+                    HeaderField::Deffered(offset, length, vr) => {
+                        // pixel data nested in a sequence item is not a safetensors object
+                        let is_pixel_data = top_level && tag == Tag::PIXEL_DATA;
+                        let codec = if is_pixel_data {
+                            compression.pixel_data
+                        } else {
+                            compression.fields
+                        };
+                        let bytes = usize::try_from(*offset)
+                            .ok()
+                            .zip(usize::try_from(*length).ok())
+                            .and_then(|(offset, length)| {
+                                data_bytes.get(offset..offset.checked_add(length)?)
+                            })
+                            .context(FieldOutOfBoundsSnafu {
+                                tag,
+                                offset: *offset,
+                                length: *length,
+                                data_length: data_bytes.len(),
+                            })?;
+                        let compressed = match codec {
+                            Some(codec) if is_pixel_data => Some(
+                                pixel_array::compressed::compress(codec, bytes)
+                                    .context(CouldNotCompressPixelDataSnafu)?,
+                            ),
+                            Some(codec) => {
+                                Some(codec::compress(codec, bytes).context(CouldNotCompressSnafu)?)
+                            }
+                            None => None,
+                        }
+                        .filter(|compressed| compressed.len() < bytes.len());
+                        match (codec, compressed) {
+                            (Some(codec), Some(compressed)) => HeaderField::Compressed(
+                                out.append(&compressed).context(CouldNotSpoolSnafu)?,
+                                compressed.len() as u64,
+                                *length,
+                                *vr,
+                                codec,
+                            ),
+                            _ => HeaderField::Deffered(
+                                out.append(bytes).context(CouldNotSpoolSnafu)?,
+                                *length,
+                                *vr,
+                            ),
+                        }
+                    }
+                    HeaderField::SQ(items) => HeaderField::SQ(
+                        items
+                            .iter()
+                            .map(|item| compress_map(item, data_bytes, compression, false, out))
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    header_field => header_field.clone(),
+                };
+                Ok((tag, header_field))
+            })
+            .collect()
+    }
+
+    let mut out = Spool::new().context(CouldNotSpoolSnafu)?;
+    let header_fields = compress_map(header_fields, data_bytes, compression, true, &mut out)?;
+    Ok((header_fields, out))
+}
+
+/// Writes a dimble file of `header_fields` and their `data_bytes`, followed by the pixel data if it
+/// is streamed separately. The tensor data of uncompressed pixel data, streamed or not, is aligned
+/// to `pixel_alignment` bytes within the file. Streamed pixel data is compressed as it is written
+/// if `compression` says to, and is then stored compressed even if that does not make it smaller.
+///
+/// The file is written beside `dimble_path` and only moved there once it is whole, so a write that
+/// fails leaves no partial file behind.
+pub(crate) fn serialise_dimble_fields(
+    header_fields: &HeaderFieldMap,
+    data_bytes: &[u8],
+    pixel_stream: Option<PixelStream<'_>>,
     dimble_path: &str,
+    compression: Compression,
+    pixel_alignment: usize,
 ) -> Result<(), SerialiseFieldsError> {
     use serialise_fields_error::*;
 
-    let mut file =
-        fs::File::create(dimble_path).context(CouldNotCreateFileSnafu { dimble_path })?;
-    file.seek(SeekFrom::Start(HEADER_LENGTH_LENGTH.into()))
-        .context(CouldNotSkipHeaderLengthSnafu)?;
-    // leave room for header length field
-
-    let mut serialiser = Serializer::new(&file).with_struct_map();
-    header_fields
-        .serialize(&mut serialiser)
-        .context(CouldNotSerializeHeadersSnafu)?;
-
-    let end_of_headers = file
-        .stream_position()
-        .context(CouldNotQueryStreamPositionSnafu)?;
-    let header_len = end_of_headers - u64::from(HEADER_LENGTH_LENGTH);
-    file.seek(SeekFrom::Start(0))
-        .context(CouldNotSeekToStartSnafu)?;
-    file.write_all(&header_len.to_le_bytes())
-        .context(CouldNotWriteHeaderLengthSnafu)?;
-    file.seek(SeekFrom::Start(end_of_headers))
-        .context(CouldNotSeekToEndOfHeadersSnafu)?;
-
-    file.write_all(data_bytes).context(CouldNotWriteDataSnafu)
+    ensure!(
+        pixel_alignment.is_power_of_two() && pixel_alignment <= MAX_PIXEL_ALIGNMENT,
+        InvalidPixelAlignmentSnafu {
+            alignment: pixel_alignment
+        }
+    );
+
+    // a streamed pixel data field replaces any in the data section
+    let without_pixels;
+    let header_fields = if pixel_stream.is_some() {
+        without_pixels = {
+            let mut header_fields = header_fields.clone();
+            header_fields.remove(&Tag::PIXEL_DATA);
+            header_fields
+        };
+        &without_pixels
+    } else {
+        header_fields
+    };
+    let pixel_codec = pixel_stream.as_ref().and(compression.pixel_data);
+
+    // the data section is rebuilt to compress its fields, or to leave out the values of replaced
+    // fields, and spooled rather than held in memory
+    let (rebuilt, spooled);
+    let (header_fields, data_bytes) = if compression.is_none()
+        && !has_unreferenced_data(header_fields, data_bytes.len() as u64)
+    {
+        (header_fields, data_bytes)
+    } else {
+        rebuilt = rebuild_fields(header_fields, data_bytes, compression)?;
+        spooled = rebuilt.1.bytes().context(CouldNotSpoolSnafu)?;
+        (&rebuilt.0, &*spooled)
+    };
+
+    // pixel data in the data section is already compressed if it is to be, and streams out as it is
+    let split;
+    let (header_fields, data_bytes, pixel_stream) = match pixel_stream {
+        Some(pixel_stream) => (header_fields, data_bytes, Some(pixel_stream)),
+        None => match split_pixel_data(header_fields, data_bytes) {
+            Some((header_fields, data_bytes, pixel_stream)) => {
+                split = (header_fields, data_bytes);
+                (&split.0, split.1.as_slice(), Some(pixel_stream))
+            }
+            None => (header_fields, data_bytes, None),
+        },
+    };
+
+    let mut header_fields = header_fields.clone();
+    let mut checksums = checksum::field_checksums(&header_fields, data_bytes);
+    let decompressed_length = pixel_stream.as_ref().map_or(0, |pixel_stream| {
+        pixel_stream.header.len() as u64 + pixel_stream.data_length
+    });
+    let pixel_field = |offset, length, vr| match pixel_codec {
+        Some(codec) => HeaderField::Compressed(offset, length, decompressed_length, vr, codec),
+        None => HeaderField::Deffered(offset, length, vr),
+    };
+    let mut padding = 0;
+    if let Some(pixel_stream) = &pixel_stream {
+        // compressed pixel data is not read in place, so is not aligned
+        if pixel_codec.is_none() {
+            let data_start = data_bytes.len() + pixel_stream.header.len();
+            padding = data_start.next_multiple_of(pixel_alignment) - data_start;
+        }
+        // the length of compressed pixel data is only known once it is written, so the header is
+        // first written with the longest length, to leave room for the header with its real length
+        let length = match pixel_codec {
+            Some(_) => u64::MAX,
+            None => decompressed_length,
+        };
+        header_fields.insert(
+            Tag::PIXEL_DATA,
+            pixel_field((data_bytes.len() + padding) as u64, length, pixel_stream.vr),
+        );
+    }
+    let pixel_offset = (data_bytes.len() + padding) as u64;
+
+    let mut header = header_index::encode(&header_fields).context(CouldNotSerializeHeadersSnafu)?;
+    if pixel_stream.is_some() && pixel_codec.is_none() {
+        // zero padding after the header index aligns the data section, and so the pixel data
+        let head_len = PREAMBLE_LENGTH + usize::from(HEADER_LENGTH_LENGTH) + header.len();
+        header.resize(
+            header.len() + head_len.next_multiple_of(pixel_alignment) - head_len,
+            0,
+        );
+    }
+    let preamble = Preamble::CURRENT.to_bytes();
+    let header_len = (header.len() as u64).to_le_bytes();
+
+    write_atomically(dimble_path, |file| {
+        let mut file = BufWriter::new(file);
+        file.write_all(&preamble)
+            .context(CouldNotWritePreambleSnafu)?;
+        file.write_all(&header_len)
+            .context(CouldNotWriteHeaderLengthSnafu)?;
+        file.write_all(&header).context(CouldNotWriteHeadersSnafu)?;
+
+        file.write_all(data_bytes).context(CouldNotWriteDataSnafu)?;
+        if let Some(pixel_stream) = pixel_stream {
+            io::copy(&mut io::repeat(0).take(padding as u64), &mut file)
+                .context(CouldNotWriteDataSnafu)?;
+            let read = Cell::new(0);
+            let mut data = CountingReader {
+                inner: pixel_stream.data.take(pixel_stream.data_length),
+                count: &read,
+            };
+            let mut out = checksum::ChecksumWriter::new(&mut file);
+            let written = match pixel_codec {
+                Some(codec) => pixel_array::compressed::compress_stream(
+                    codec,
+                    &pixel_stream.header,
+                    &mut data,
+                    pixel_stream.data_length,
+                    &mut out,
+                )
+                .context(CouldNotCompressPixelDataSnafu),
+                None => out
+                    .write_all(&pixel_stream.header)
+                    .and_then(|()| io::copy(&mut data, &mut out))
+                    .map(drop)
+                    .context(CouldNotWritePixelDataSnafu),
+            };
+            // a stream that ends early fails to compress, but the real problem is its length
+            ensure!(
+                read.get() == pixel_stream.data_length,
+                PixelDataTooShortSnafu {
+                    expected_length: pixel_stream.data_length,
+                    length: read.get(),
+                }
+            );
+            written?;
+            let length = out.length();
+            checksums.push([pixel_offset, length, out.checksum()]);
+
+            if pixel_codec.is_some() {
+                header_fields.insert(
+                    Tag::PIXEL_DATA,
+                    pixel_field(pixel_offset, length, pixel_stream.vr),
+                );
+                let mut real_header =
+                    header_index::encode(&header_fields).context(CouldNotSerializeHeadersSnafu)?;
+                debug_assert!(real_header.len() <= header.len());
+                real_header.resize(header.len(), 0);
+                header = real_header;
+                file.seek(SeekFrom::Start(
+                    (PREAMBLE_LENGTH + usize::from(HEADER_LENGTH_LENGTH)) as u64,
+                ))
+                .and_then(|_| file.write_all(&header))
+                .and_then(|()| file.seek(SeekFrom::End(0)))
+                .context(CouldNotWriteHeadersSnafu)?;
+            }
+        }
+
+        let head = [&preamble[..], &header_len, &header].concat();
+        file.write_all(&checksum::encode(&head, checksums))
+            .context(CouldNotWriteChecksumsSnafu)?;
+        file.flush().context(CouldNotWriteChecksumsSnafu)
+    })
+}
+
+/// Counts the bytes read through it
+struct CountingReader<'c, R> {
+    inner: R,
+    count: &'c Cell<u64>,
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        self.count.set(self.count.get() + length as u64);
+        Ok(length)
+    }
+}
+
+/// Writes the file at `dimble_path` with `write`, into a new file in the same directory that is
+/// renamed over `dimble_path` once written, or removed if writing it fails
+fn write_atomically(
+    dimble_path: &str,
+    write: impl FnOnce(fs::File) -> Result<(), SerialiseFieldsError>,
+) -> Result<(), SerialiseFieldsError> {
+    use serialise_fields_error::*;
+    static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+    let path = Path::new(dimble_path);
+    let file_name = path
+        .file_name()
+        .context(NotAFilePathSnafu { dimble_path })?;
+    let temporary_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name.to_string_lossy(),
+        process::id(),
+        TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temporary_path)
+        .context(CouldNotCreateFileSnafu { dimble_path })?;
+    let written = write(file).and_then(|()| {
+        fs::rename(&temporary_path, path).context(CouldNotCreateFileSnafu { dimble_path })
+    });
+    if written.is_err() {
+        // the error being returned matters more than one removing the file
+        let _ = fs::remove_file(&temporary_path);
+    }
+    written
 }
 
 #[derive(Debug, Snafu)]
 #[snafu(module)]
 #[allow(clippy::enum_variant_names)]
 pub enum SerialiseFieldsError {
+    #[snafu(display(
+        "Pixel alignment {alignment} is not a power of two of at most {MAX_PIXEL_ALIGNMENT} bytes"
+    ))]
+    InvalidPixelAlignment {
+        alignment: usize,
+    },
+
     CouldNotCreateFile {
         source: std::io::Error,
         dimble_path: String,
     },
 
-    CouldNotSkipHeaderLength {
+    #[snafu(display("{dimble_path} is not the path of a file"))]
+    NotAFilePath {
+        dimble_path: String,
+    },
+
+    CouldNotWritePreamble {
         source: std::io::Error,
     },
 
     CouldNotSerializeHeaders {
-        source: rmp_serde::encode::Error,
+        source: HeaderIndexError,
     },
 
-    CouldNotQueryStreamPosition {
-        source: std::io::Error,
+    CouldNotCompress {
+        source: CodecError,
     },
 
-    CouldNotSeekToStart {
+    CouldNotCompressPixelData {
+        source: pixel_array::Error,
+    },
+
+    #[snafu(display("Could not spool the fields to a temporary file"))]
+    CouldNotSpool {
         source: std::io::Error,
     },
 
+    #[snafu(display(
+        "Field {tag} at offset {offset} with length {length} extends past the end of the {data_length} bytes of data"
+    ))]
+    FieldOutOfBounds {
+        tag: Tag,
+        offset: u64,
+        length: u64,
+        data_length: usize,
+    },
+
     CouldNotWriteHeaderLength {
         source: std::io::Error,
     },
 
-    CouldNotSeekToEndOfHeaders {
+    CouldNotWriteHeaders {
         source: std::io::Error,
     },
 
     CouldNotWriteData {
         source: std::io::Error,
     },
+
+    #[snafu(display("Could not stream the pixel data into the file"))]
+    CouldNotWritePixelData {
+        source: std::io::Error,
+    },
+
+    #[snafu(display(
+        "Pixel data stream ended after {length} of its {expected_length} bytes of tensor data"
+    ))]
+    PixelDataTooShort {
+        expected_length: u64,
+        length: u64,
+    },
+
+    CouldNotWriteChecksums {
+        source: std::io::Error,
+    },
 }
 
 #[derive(Debug, Snafu)]
@@ -257,8 +784,40 @@ pub enum InnerError {
     #[snafu(display("Could not parse the DICOM JSON"))]
     FailedToParseJson { source: serde_json::Error },
 
-    #[snafu(display("DICOM data contains both a value and inline binary"))]
-    ValueAndInlineBinaryBothPresent,
+    #[snafu(display("Could not read the pixel array {safetensors_path}"))]
+    CouldNotReadPixelArray {
+        source: std::io::Error,
+        safetensors_path: String,
+    },
+
+    #[snafu(display(
+        "DICOM data for {tag} should have only one of a value, inline binary or a bulk data URI"
+    ))]
+    ConflictingValues { tag: Tag },
+
+    #[snafu(display("DICOM data for sequence {tag} has an item that is not a dataset"))]
+    InvalidSequenceItem { tag: Tag },
+
+    #[snafu(display("DICOM data for {tag} has a sequence nested inside a multi-valued field"))]
+    NestedSequence { tag: Tag },
+
+    #[snafu(display("DICOM data for {tag} should have base64 inline binary"))]
+    InvalidInlineBinary {
+        source: base64::DecodeError,
+        tag: Tag,
+    },
+
+    #[snafu(display("DICOM data for {tag} has no VR, and the data dictionary does not know it"))]
+    MissingVr { tag: Tag },
+
+    #[snafu(display("Could not encode field {tag}"))]
+    CouldNotEncode {
+        source: rmpv::encode::Error,
+        tag: Tag,
+    },
+
+    #[snafu(display("Could not read or chunk the pixel array"))]
+    PixelArray { source: pixel_array::Error },
 
     #[snafu(display("Could not serialize the fields"))]
     SerialiseFields { source: SerialiseFieldsError },
@@ -271,22 +830,74 @@ pub struct Error(InnerError);
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// Converts DICOM JSON to dimble, storing the pixel data in chunks of `chunk_shape` if given,
+/// compressing fields as `compression` says and aligning the pixel data's tensor data within the
+/// file to `pixel_alignment` bytes. Returns the attributes that disagree with the data dictionary.
+///
+/// The pixel data streams from the safetensors file, so it is never held in memory as a whole. The
+/// other fields are converted in memory.
 pub fn dicom_json_to_dimble(
     json_path: &str,
     pixel_array_safetensors_path: Option<&str>,
     dimble_path: &str,
-) -> Result<()> {
+    chunk_shape: Option<&[usize]>,
+    compression: Compression,
+    pixel_alignment: usize,
+) -> Result<Vec<DictionaryMismatch>> {
     let file = fs::File::open(json_path).context(CouldNotOpenSnafu { json_path })?;
     let json_reader = BufReader::new(file);
     let json_dicom = deserialise_ir(json_reader)?;
 
-    let (header_fields, data_bytes) =
-        prepare_dicom_fields_for_serialisation(json_dicom, pixel_array_safetensors_path)?;
+    // the pixel data streams from the safetensors file into the dimble file, chunked and compressed
+    // as it goes, so it is never read into memory as a whole
+    let pixel_source = pixel_array_safetensors_path
+        .map(|safetensors_path| {
+            let (mut file, header, data_length) = read_safetensors_header(safetensors_path)?;
+            let Some(chunk_shape) = chunk_shape else {
+                return Ok((header, data_length, Box::new(file) as Box<dyn Read>));
+            };
+            let (parsed, range) =
+                pixel_array::parse_pixel_array_header(&header).context(PixelArraySnafu)?;
+            file.seek(SeekFrom::Current(range.start as i64))
+                .context(CouldNotReadPixelArraySnafu { safetensors_path })?;
+            pixel_array::pixel_array_to_safetensors_stream(
+                parsed.dtype,
+                &parsed.shape,
+                parsed.frames,
+                Some(chunk_shape),
+                file.take(range.len() as u64),
+            )
+            .context(PixelArraySnafu)
+        })
+        .transpose()?;
+    // a placeholder the streamed pixel data replaces
+    let pixel_array_safetensors = pixel_source.as_ref().map(|_| Vec::new());
 
-    serialise_dimble_fields(header_fields, &data_bytes, dimble_path)
-        .context(SerialiseFieldsSnafu)?;
+    let (header_fields, data_bytes, mismatches) =
+        prepare_dicom_fields_for_serialisation(json_dicom, pixel_array_safetensors.as_deref())?;
+    let pixel_stream = match (pixel_source, header_fields.get(&Tag::PIXEL_DATA)) {
+        (Some((header, data_length, data)), Some(HeaderField::Deffered(_, _, vr))) => {
+            Some(PixelStream {
+                header: header.into(),
+                data,
+                data_length,
+                vr: *vr,
+            })
+        }
+        _ => None,
+    };
 
-    Ok(())
+    serialise_dimble_fields(
+        &header_fields,
+        &data_bytes,
+        pixel_stream,
+        dimble_path,
+        compression,
+        pixel_alignment,
+    )
+    .context(SerialiseFieldsSnafu)?;
+
+    Ok(mismatches)
 }
 
 fn deserialise_ir(data: impl Read) -> InnerResult<DicomJsonData> {
@@ -333,8 +944,10 @@ mod tests {
 
         let ir = deserialise_ir(ir_data.as_bytes())?;
         {
-            let field = ir.get("00080005").expect("expected 00080005 to exist");
-            assert_eq!(field.vr, *b"CS");
+            let field = ir
+                .get(&Tag::new(0x0008, 0x0005))
+                .expect("expected 00080005 to exist");
+            assert_eq!(field.vr, Some(*b"CS"));
             let value: Vec<_> = field
                 .value
                 .iter()
@@ -346,8 +959,10 @@ mod tests {
             assert_eq!(value, ["ISO_IR 100"])
         }
         {
-            let field = ir.get("00080008").expect("expected 00080008 to exist");
-            assert_eq!(field.vr, *b"CS");
+            let field = ir
+                .get(&Tag::new(0x0008, 0x0008))
+                .expect("expected 00080008 to exist");
+            assert_eq!(field.vr, Some(*b"CS"));
             let value: Vec<_> = field
                 .value
                 .as_ref()
@@ -361,21 +976,25 @@ mod tests {
             assert_eq!(value, ["ORIGINAL", "PRIMARY", "OTHER"]);
         }
         {
-            let field = ir.get("00080090").expect("expected 00080090 to exist");
-            assert_eq!(field.vr, *b"PN");
+            let field = ir
+                .get(&Tag::new(0x0008, 0x0090))
+                .expect("expected 00080090 to exist");
+            assert_eq!(field.vr, Some(*b"PN"));
             assert_eq!(field.value, None);
         }
         {
-            let field = ir.get("00100010").expect("expected 00100010 to exist");
-            assert_eq!(field.vr, *b"PN");
+            let field = ir
+                .get(&Tag::new(0x0010, 0x0010))
+                .expect("expected 00100010 to exist");
+            assert_eq!(field.vr, Some(*b"PN"));
             let value: Vec<_> = field
                 .value
                 .as_ref()
                 .unwrap()
                 .iter()
                 .map(|v| match v {
-                    DicomValue::Alphabetic(a) => &a.alphabetic,
-                    _ => panic!("expected only alphabetic"),
+                    DicomValue::PersonName(name) => name.alphabetic.as_deref().unwrap(),
+                    _ => panic!("expected only person names"),
                 })
                 .collect();
             assert_eq!(value, ["Doe^John"])
@@ -384,22 +1003,186 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_dictionary_checks() -> Result {
+        let ir = deserialise_ir(
+            r#"{
+                "00080060": {"Value": ["MR"]},
+                "00280010": {"vr": "DS", "Value": [512]},
+                "00280030": {"vr": "DS", "Value": [0.5, 0.5, 1]},
+                "00280034": {"vr": "UN", "InlineBinary": "AAE="},
+                "00289110": {"vr": "SQ", "Value": [{"00280030": {"vr": "DS", "Value": [0.5]}}]},
+                "00091001": {"vr": "LO", "Value": ["private"]}
+            }"#
+            .as_bytes(),
+        )?;
+        let (header_fields, _, mismatches) = prepare_dicom_fields_for_serialisation(ir, None)?;
+        assert_eq!(header_fields[&Tag::new(0x0008, 0x0060)].vr(), *b"CS");
+        assert_eq!(
+            mismatches,
+            [
+                DictionaryMismatch::Vr {
+                    tag: Tag::ROWS,
+                    vr: *b"DS",
+                    expected: "US",
+                },
+                DictionaryMismatch::Vm {
+                    tag: Tag::new(0x0028, 0x0030),
+                    count: 3,
+                    expected: "2",
+                },
+                DictionaryMismatch::Vm {
+                    tag: Tag::new(0x0028, 0x0030),
+                    count: 1,
+                    expected: "2",
+                },
+            ]
+        );
+        assert_eq!(
+            mismatches[0].to_string(),
+            "00280010 Rows has VR DS, the dictionary has US"
+        );
+
+        let ir = deserialise_ir(r#"{"00091001": {"Value": ["private"]}}"#.as_bytes())?;
+        assert!(matches!(
+            prepare_dicom_fields_for_serialisation(ir, None),
+            Err(InnerError::MissingVr { tag }) if tag == Tag::new(0x0009, 0x1001)
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_serialise_dimble_fields() -> Result {
         let mut header_fields = HeaderFieldMap::new();
         let vr = b"CS";
-        header_fields.insert("0008005".to_string(), HeaderField::Deffered(0, 1, *vr));
+        header_fields.insert(Tag::new(0x0008, 0x0005), HeaderField::Deffered(0, 1, *vr));
         let data_bytes = [0x42];
         let dimble_path = "/tmp/test.dimble";
-        serialise_dimble_fields(header_fields, &data_bytes, dimble_path)?;
+        serialise_dimble_fields(
+            &header_fields,
+            &data_bytes,
+            None,
+            dimble_path,
+            Compression::default(),
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
 
         let file_bytes = fs::read(dimble_path).unwrap();
-        assert_eq!(file_bytes.last().unwrap(), &0x42);
-        let header_len = u64::from_le_bytes(file_bytes[0..8].try_into().unwrap()) as usize;
-        let mut cursor = &file_bytes[8..8 + header_len];
+        let (preamble, preamble_len) = Preamble::read(&file_bytes)?;
+        assert_eq!(preamble, Preamble::CURRENT);
+        let header_start = preamble_len + 8;
+        let header_len =
+            u64::from_le_bytes(file_bytes[preamble_len..header_start].try_into().unwrap()) as usize;
+        let data_offset = header_start + header_len;
+        // the data is followed by the checksum of its one field, then the header checksum and count
+        assert_eq!(file_bytes[data_offset], 0x42);
+        assert_eq!(file_bytes.len(), data_offset + 1 + 24 + 16);
+
+        let index =
+            header_index::HeaderIndex::read(&file_bytes, header_start..header_start + header_len)?;
+        assert_eq!(index.decode_all(&file_bytes)?, header_fields);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compress_field_out_of_bounds() {
+        let header_fields = HeaderFieldMap::from([(
+            Tag::new(0x0008, 0x0005),
+            HeaderField::Deffered(u64::MAX, 2, *b"CS"),
+        )]);
+        let compression = Compression {
+            fields: Some(Codec::Zstd),
+            pixel_data: None,
+        };
+        let error = serialise_dimble_fields(
+            &header_fields,
+            &[0x42],
+            None,
+            "/tmp/compress_out_of_bounds.dimble",
+            compression,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            SerialiseFieldsError::FieldOutOfBounds { length: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_pixel_data_streams_chunked_and_compressed() -> Result {
+        let pixels: Vec<u8> = (0..60).collect();
+        let safetensors_path = "/tmp/streamed_json.safetensors";
+        fs::write(
+            safetensors_path,
+            pixel_array::pixel_array_to_safetensors(
+                pixel_array::Dtype::U8,
+                &[3, 4, 5],
+                Some(3),
+                None,
+                &pixels,
+            )?,
+        )?;
+        let json_path = "/tmp/streamed_json.ir.json";
+        fs::write(
+            json_path,
+            r#"{"7FE00010": {"vr": "OW", "InlineBinary": "placeholder"}}"#,
+        )?;
+        let dimble_path = "/tmp/streamed_json.dimble";
+        let compression = Compression {
+            fields: None,
+            pixel_data: Some(Codec::DeltaZstd),
+        };
+        dicom_json_to_dimble(
+            json_path,
+            Some(safetensors_path),
+            dimble_path,
+            Some(&[2, 3, 2]),
+            compression,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
 
-        let _decoded = rmpv::decode::read_value(&mut cursor).unwrap();
+        let reader = crate::reader::DimbleReader::open(dimble_path)?;
+        assert!(matches!(
+            reader.header_field(Tag::PIXEL_DATA)?,
+            HeaderField::Compressed(.., Codec::DeltaZstd)
+        ));
+        let pixel_array::PixelData::Chunked(chunked) = reader.pixel_data()? else {
+            panic!("pixel data should be chunked");
+        };
+        assert_eq!(chunked.chunk_shape, [2, 3, 2]);
+        assert_eq!(chunked.slice(&[])?.data, pixels);
+        assert!(reader.verify()?.is_empty());
+        Ok(())
+    }
 
+    #[test]
+    fn test_conversion_is_reproducible() -> Result {
+        let fields = [
+            r#""00100010": {"vr": "PN", "Value": [{"Alphabetic": "Doe^John"}]}"#,
+            r#""00080008": {"vr": "CS", "Value": ["ORIGINAL", "PRIMARY"]}"#,
+            r#""00089215": {"vr": "SQ", "Value": [{"00080104": {"vr": "LO", "Value": ["Full"]}, "00080100": {"vr": "SH", "Value": ["121327"]}}]}"#,
+            r#""00280010": {"vr": "US", "Value": [512]}"#,
+        ];
+        let mut outputs = Vec::new();
+        for (name, order) in [("forwards", [0, 1, 2, 3]), ("backwards", [3, 2, 1, 0])] {
+            let json_path = format!("/tmp/reproducible_{name}.ir.json");
+            let dimble_path = format!("/tmp/reproducible_{name}.dimble");
+            let fields = order.map(|i| fields[i]).join(",");
+            fs::write(&json_path, format!("{{{fields}}}"))?;
+            dicom_json_to_dimble(
+                &json_path,
+                None,
+                &dimble_path,
+                None,
+                Compression::default(),
+                DEFAULT_PIXEL_ALIGNMENT,
+            )?;
+            outputs.push(fs::read(dimble_path)?);
+        }
+        assert_eq!(outputs[0], outputs[1]);
         Ok(())
     }
 }
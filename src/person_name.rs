@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+const GROUP_DELIMITER: char = '=';
+const COMPONENT_DELIMITER: char = '^';
+
+/// A DICOM person name (PN) value, made of up to three component groups.
+///
+/// Dimble stores a person name as its DICOM string form, `Alphabetic=Ideographic=Phonetic`, so
+/// every group survives a round trip.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PersonName {
+    #[serde(rename = "Alphabetic")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alphabetic: Option<String>,
+    #[serde(rename = "Ideographic")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ideographic: Option<String>,
+    #[serde(rename = "Phonetic")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phonetic: Option<String>,
+}
+
+/// The five components of a single person name group
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PersonNameComponents {
+    pub family_name: String,
+    pub given_name: String,
+    pub middle_name: String,
+    pub name_prefix: String,
+    pub name_suffix: String,
+}
+
+impl PersonNameComponents {
+    /// Splits a component group such as `Adams^John Robert Quincy^^Rev.^B.A. M.Div.`
+    pub fn parse(group: &str) -> Self {
+        let mut components = group.splitn(5, COMPONENT_DELIMITER).map(str::to_string);
+        let mut next = || components.next().unwrap_or_default();
+        Self {
+            family_name: next(),
+            given_name: next(),
+            middle_name: next(),
+            name_prefix: next(),
+            name_suffix: next(),
+        }
+    }
+}
+
+impl PersonName {
+    /// Parses the DICOM string form of a single person name
+    pub fn parse(value: &str) -> Self {
+        let mut groups = value
+            .splitn(3, GROUP_DELIMITER)
+            .map(|group| (!group.is_empty()).then(|| group.to_string()));
+        Self {
+            alphabetic: groups.next().flatten(),
+            ideographic: groups.next().flatten(),
+            phonetic: groups.next().flatten(),
+        }
+    }
+
+    /// The DICOM string form, leaving out trailing empty groups
+    pub fn to_dicom_string(&self) -> String {
+        let groups = [&self.alphabetic, &self.ideographic, &self.phonetic]
+            .map(|group| group.as_deref().unwrap_or_default());
+        let used = groups
+            .iter()
+            .rposition(|group| !group.is_empty())
+            .map_or(0, |last| last + 1);
+        groups[..used].join(&GROUP_DELIMITER.to_string())
+    }
+
+    pub fn alphabetic_components(&self) -> Option<PersonNameComponents> {
+        self.alphabetic.as_deref().map(PersonNameComponents::parse)
+    }
+
+    pub fn ideographic_components(&self) -> Option<PersonNameComponents> {
+        self.ideographic.as_deref().map(PersonNameComponents::parse)
+    }
+
+    pub fn phonetic_components(&self) -> Option<PersonNameComponents> {
+        self.phonetic.as_deref().map(PersonNameComponents::parse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_all_groups() {
+        let value = "Yamada^Tarou=山田^太郎=やまだ^たろう";
+        let name = PersonName::parse(value);
+        assert_eq!(name.alphabetic.as_deref(), Some("Yamada^Tarou"));
+        assert_eq!(name.ideographic.as_deref(), Some("山田^太郎"));
+        assert_eq!(name.phonetic.as_deref(), Some("やまだ^たろう"));
+        assert_eq!(name.to_dicom_string(), value);
+
+        let components = name.ideographic_components().unwrap();
+        assert_eq!(components.family_name, "山田");
+        assert_eq!(components.given_name, "太郎");
+        assert_eq!(components.middle_name, "");
+    }
+
+    #[test]
+    fn test_parse_missing_groups() {
+        let name = PersonName::parse("=Hong^Gildong");
+        assert_eq!(name.alphabetic, None);
+        assert_eq!(name.ideographic.as_deref(), Some("Hong^Gildong"));
+        assert_eq!(name.phonetic, None);
+        assert_eq!(name.to_dicom_string(), "=Hong^Gildong");
+
+        assert_eq!(PersonName::parse("Doe^John").to_dicom_string(), "Doe^John");
+        assert_eq!(PersonName::parse("").to_dicom_string(), "");
+    }
+
+    #[test]
+    fn test_components() {
+        let components = PersonNameComponents::parse("Adams^John Robert Quincy^^Rev.^B.A. M.Div.");
+        assert_eq!(
+            components,
+            PersonNameComponents {
+                family_name: "Adams".to_string(),
+                given_name: "John Robert Quincy".to_string(),
+                middle_name: "".to_string(),
+                name_prefix: "Rev.".to_string(),
+                name_suffix: "B.A. M.Div.".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,1339 @@
+use half::{bf16, f16};
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+use std::ops::Range;
+
+use crate::codec::CodecError;
+
+mod chunked;
+pub(crate) mod compressed;
+mod encapsulated;
+
+pub use chunked::{Chunk, ChunkedPixelArray};
+pub use encapsulated::EncapsulatedPixelData;
+pub(crate) use encapsulated::RLE_LOSSLESS;
+
+/// Name of the tensor holding the pixel data inside the embedded safetensors object
+pub(crate) const PIXEL_ARRAY_NAME: &str = "pixel_array";
+
+/// Safetensors metadata key holding the number of frames of multi-frame pixel data, whose first
+/// axis is then the frame axis
+pub(crate) const FRAMES_METADATA_KEY: &str = "frames";
+
+/// Safetensors metadata key holding the shape, as a JSON list, of pixel data stored in more than one
+/// tensor
+pub(crate) const SHAPE_METADATA_KEY: &str = "shape";
+
+const SAFETENSORS_HEADER_LENGTH_LENGTH: usize = std::mem::size_of::<u64>();
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TensorInfo {
+    /// The type of each element of the tensor
+    pub dtype: Dtype,
+    /// The shape of the tensor
+    pub shape: Vec<usize>,
+    /// The offsets to find the data within the byte-buffer array.
+    pub data_offsets: (usize, usize),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Ord, PartialOrd)]
+#[non_exhaustive]
+pub enum Dtype {
+    /// Boolan type
+    BOOL,
+    /// Unsigned byte
+    U8,
+    /// Signed byte
+    I8,
+    /// Signed integer (16-bit)
+    I16,
+    /// Unsigned integer (16-bit)
+    U16,
+    /// Half-precision floating point
+    F16,
+    /// Brain floating point
+    BF16,
+    /// Signed integer (32-bit)
+    I32,
+    /// Unsigned integer (32-bit)
+    U32,
+    /// Floating point (32-bit)
+    F32,
+    /// Floating point (64-bit)
+    F64,
+    /// Signed integer (64-bit)
+    I64,
+    /// Unsigned integer (64-bit)
+    U64,
+    /// Complex number (64-bit)
+    C64,
+    /// Complex number (128-bit)
+    C128,
+}
+
+impl Dtype {
+    /// Size in bytes of a single element
+    pub fn size(&self) -> usize {
+        match self {
+            Dtype::BOOL | Dtype::U8 | Dtype::I8 => 1,
+            Dtype::I16 | Dtype::U16 | Dtype::F16 | Dtype::BF16 => 2,
+            Dtype::I32 | Dtype::U32 | Dtype::F32 => 4,
+            Dtype::I64 | Dtype::U64 | Dtype::F64 | Dtype::C64 => 8,
+            Dtype::C128 => 16,
+        }
+    }
+}
+
+/// Helper struct used only for safetensors deserialization
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct HashMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "__metadata__")]
+    pub(crate) metadata: Option<HashMap<String, String>>,
+    #[serde(flatten)]
+    pub(crate) tensors: HashMap<String, TensorInfo>,
+}
+
+/// A pixel array borrowed from a dimble file
+#[derive(Debug, Clone)]
+pub struct PixelArray<'a> {
+    pub dtype: Dtype,
+    pub shape: Vec<usize>,
+    /// The number of frames of multi-frame pixel data, whose first axis is the frame axis. `None`
+    /// for a single frame.
+    pub frames: Option<usize>,
+    /// Raw little endian, C-order element bytes
+    pub data: &'a [u8],
+}
+
+/// A pixel array copied out of a dimble file, such as a region of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedPixelArray {
+    pub dtype: Dtype,
+    pub shape: Vec<usize>,
+    /// See [`PixelArray::frames`]
+    pub frames: Option<usize>,
+    /// Raw little endian, C-order element bytes
+    pub data: Vec<u8>,
+}
+
+impl OwnedPixelArray {
+    pub fn as_pixel_array(&self) -> PixelArray<'_> {
+        PixelArray {
+            dtype: self.dtype,
+            shape: self.shape.clone(),
+            frames: self.frames,
+            data: &self.data,
+        }
+    }
+}
+
+/// A Python style slice of a single axis. Unlike Python, bounds outside the axis are an error
+/// rather than being clamped to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slice {
+    /// Counts from the end of the axis if negative. The first or last index by default, as `step`
+    /// is positive or negative.
+    pub start: Option<isize>,
+    /// Exclusive, counts from the end of the axis if negative. Past the end in the direction of
+    /// `step` by default.
+    pub stop: Option<isize>,
+    /// Not 0, may be negative
+    pub step: isize,
+}
+
+impl Slice {
+    /// Every index of an axis
+    pub const FULL: Slice = Slice {
+        start: None,
+        stop: None,
+        step: 1,
+    };
+
+    /// Resolves the slice against an axis of `length`, returning its first index and number of
+    /// indices
+    fn resolve(&self, axis: usize, length: usize) -> Result<(usize, usize), Error> {
+        ensure!(self.step != 0, ZeroStepSnafu { axis });
+        let length = length as isize;
+        let bound = |index: isize| {
+            let resolved = if index < 0 { index + length } else { index };
+            ensure!(
+                (0..=length).contains(&resolved),
+                SliceOutOfBoundsSnafu {
+                    axis,
+                    index,
+                    length
+                }
+            );
+            Ok(resolved)
+        };
+        let (start, stop) = if self.step > 0 {
+            (
+                self.start.map_or(Ok(0), bound)?,
+                self.stop.map_or(Ok(length), bound)?,
+            )
+        } else {
+            (
+                self.start.map_or(Ok(length - 1), bound)?,
+                self.stop.map_or(Ok(-1), bound)?,
+            )
+        };
+        let (distance, step) = if self.step > 0 {
+            (stop - start, self.step)
+        } else {
+            (start - stop, -self.step)
+        };
+        let count = if distance > 0 {
+            ((distance + step - 1) / step) as usize
+        } else {
+            0
+        };
+        // the bounds may be the end of the axis, but the first index must lie in it
+        ensure!(
+            count == 0 || start < length,
+            SliceOutOfBoundsSnafu {
+                axis,
+                index: self.start.unwrap_or(start),
+                length
+            }
+        );
+        Ok((start.max(0) as usize, count))
+    }
+}
+
+/// The shape of each frame of a pixel array of `shape`
+pub(crate) fn frame_shape(shape: &[usize], frames: Option<usize>) -> &[usize] {
+    match frames {
+        Some(_) => &shape[1..],
+        None => shape,
+    }
+}
+
+/// Resolves `slices`, one per leading axis of `shape`, to the first index, step and number of
+/// indices of every axis
+pub(crate) fn resolve_slices(
+    shape: &[usize],
+    slices: &[Slice],
+) -> Result<Vec<(usize, isize, usize)>, Error> {
+    let ndim = shape.len();
+    ensure!(
+        slices.len() <= ndim,
+        TooManySlicesSnafu {
+            slices: slices.len(),
+            ndim
+        }
+    );
+    shape
+        .iter()
+        .enumerate()
+        .map(|(axis, &length)| {
+            let slice = slices.get(axis).unwrap_or(&Slice::FULL);
+            let (start, count) = slice.resolve(axis, length)?;
+            Ok((start, slice.step, count))
+        })
+        .collect()
+}
+
+/// Advances `index` over a grid of `counts`, last axis fastest. Returns false, with `index` back at
+/// the start, once every index has been visited.
+pub(crate) fn next_index(index: &mut [usize], counts: &[usize]) -> bool {
+    for axis in (0..index.len()).rev() {
+        index[axis] += 1;
+        if index[axis] < counts[axis] {
+            return true;
+        }
+        index[axis] = 0;
+    }
+    false
+}
+
+/// The modality LUT of a linear rescale, which maps stored values to units such as Hounsfield units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rescale {
+    pub slope: f64,
+    pub intercept: f64,
+}
+
+impl Default for Rescale {
+    fn default() -> Self {
+        Self {
+            slope: 1.0,
+            intercept: 0.0,
+        }
+    }
+}
+
+impl Rescale {
+    pub fn apply(&self, value: f64) -> f64 {
+        value * self.slope + self.intercept
+    }
+}
+
+/// A linear VOI LUT window, applied to rescaled values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    pub center: f64,
+    /// At least 1
+    pub width: f64,
+}
+
+impl Window {
+    /// Maps a value into 0 to 1, following the LINEAR function of PS3.3 C.11.2.1.2.1
+    pub fn apply(&self, value: f64) -> f64 {
+        let center = self.center - 0.5;
+        let width = self.width - 1.0;
+        if value <= center - width / 2.0 {
+            0.0
+        } else if value > center + width / 2.0 {
+            1.0
+        } else {
+            (value - center) / width + 0.5
+        }
+    }
+}
+
+impl<'a> PixelArray<'a> {
+    pub fn frame_count(&self) -> usize {
+        self.frames.unwrap_or(1)
+    }
+
+    pub fn frame_shape(&self) -> &[usize] {
+        frame_shape(&self.shape, self.frames)
+    }
+
+    /// Number of bytes of each frame
+    pub fn frame_length(&self) -> usize {
+        self.frame_shape().iter().product::<usize>() * self.dtype.size()
+    }
+
+    /// Borrows a run of frames, as multi-frame pixel data even if the run is a single frame
+    pub fn frame_range(&self, range: Range<usize>) -> Result<PixelArray<'a>, Error> {
+        let frames = self.frame_count();
+        ensure!(
+            range.start <= range.end && range.end <= frames,
+            FrameOutOfRangeSnafu {
+                frame: range.end.max(range.start),
+                frames
+            }
+        );
+        let frame_length = self.frame_length();
+        let mut shape = vec![range.len()];
+        shape.extend_from_slice(self.frame_shape());
+        Ok(PixelArray {
+            dtype: self.dtype,
+            shape,
+            frames: Some(range.len()),
+            data: &self.data[range.start * frame_length..range.end * frame_length],
+        })
+    }
+
+    /// Borrows a single frame
+    pub fn frame(&self, index: usize) -> Result<PixelArray<'a>, Error> {
+        let frames = self.frame_count();
+        ensure!(
+            index < frames,
+            FrameOutOfRangeSnafu {
+                frame: index,
+                frames
+            }
+        );
+        let mut frame = self.frame_range(index..index + 1)?;
+        frame.shape.remove(0);
+        frame.frames = None;
+        Ok(frame)
+    }
+
+    /// Copies `frames` in the order given, as multi-frame pixel data. Reads no other frame.
+    pub fn gather_frames(&self, frames: &[usize]) -> Result<OwnedPixelArray, Error> {
+        let mut data = Vec::with_capacity(frames.len() * self.frame_length());
+        for &frame in frames {
+            data.extend_from_slice(self.frame(frame)?.data);
+        }
+        Ok(OwnedPixelArray {
+            dtype: self.dtype,
+            shape: [&[frames.len()], self.frame_shape()].concat(),
+            frames: Some(frames.len()),
+            data,
+        })
+    }
+
+    /// Copies the region `slices` select, one slice per leading axis, into a C-contiguous array.
+    /// Only the bytes of the region are read, in runs as long as the slices of the trailing axes
+    /// allow.
+    pub fn slice(&self, slices: &[Slice]) -> Result<OwnedPixelArray, Error> {
+        let ndim = self.shape.len();
+        let region = resolve_slices(&self.shape, slices)?;
+        let shape: Vec<usize> = region.iter().map(|&(_, _, count)| count).collect();
+        let frames = self.frames.map(|_| shape[0]);
+
+        let item_size = self.dtype.size();
+        let mut strides = vec![item_size; ndim];
+        for axis in (1..ndim).rev() {
+            strides[axis - 1] = strides[axis] * self.shape[axis];
+        }
+
+        // the trailing axes read with a step of 1 are copied as a single run, up to and including
+        // the first one that is not read whole
+        let mut outer = ndim;
+        let mut run = item_size;
+        while outer > 0 {
+            let (_, step, count) = region[outer - 1];
+            if step != 1 {
+                break;
+            }
+            outer -= 1;
+            run *= count;
+            if count != self.shape[outer] {
+                break;
+            }
+        }
+
+        let length = shape.iter().product::<usize>() * item_size;
+        let mut data = Vec::with_capacity(length);
+        if length > 0 {
+            let first: usize = region
+                .iter()
+                .zip(&strides)
+                .map(|(&(start, _, _), stride)| start * stride)
+                .sum();
+            let mut index = vec![0; outer];
+            loop {
+                let offset = index
+                    .iter()
+                    .zip(&region)
+                    .zip(&strides)
+                    .map(|((&i, &(_, step, _)), &stride)| i as isize * step * stride as isize)
+                    .sum::<isize>();
+                let offset = (first as isize + offset) as usize;
+                data.extend_from_slice(&self.data[offset..offset + run]);
+                if !next_index(&mut index, &shape[..outer]) {
+                    break;
+                }
+            }
+        }
+        Ok(OwnedPixelArray {
+            dtype: self.dtype,
+            shape,
+            frames,
+            data,
+        })
+    }
+
+    /// Copies the whole array
+    pub fn to_owned_array(&self) -> OwnedPixelArray {
+        OwnedPixelArray {
+            dtype: self.dtype,
+            shape: self.shape.clone(),
+            frames: self.frames,
+            data: self.data.to_vec(),
+        }
+    }
+
+    /// Rescales every element, then windows it to 0 to 1 if there is a `window`
+    pub fn to_f32(&self, rescale: Rescale, window: Option<Window>) -> Result<Vec<f32>, Error> {
+        self.data
+            .chunks_exact(self.dtype.size())
+            .map(|element| {
+                let value = rescale.apply(read_element(element, self.dtype)?);
+                Ok(window.map_or(value, |window| window.apply(value)) as f32)
+            })
+            .collect()
+    }
+}
+
+/// Pixel data borrowed from a dimble file, in whichever layout it was written
+#[derive(Debug, Clone)]
+pub enum PixelData<'a> {
+    Contiguous(PixelArray<'a>),
+    Chunked(ChunkedPixelArray<'a>),
+    Encapsulated(EncapsulatedPixelData<'a>),
+}
+
+impl<'a> PixelData<'a> {
+    pub fn dtype(&self) -> Dtype {
+        match self {
+            PixelData::Contiguous(pixel_array) => pixel_array.dtype,
+            PixelData::Chunked(chunked) => chunked.dtype,
+            PixelData::Encapsulated(encapsulated) => encapsulated.dtype,
+        }
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        match self {
+            PixelData::Contiguous(pixel_array) => &pixel_array.shape,
+            PixelData::Chunked(chunked) => &chunked.shape,
+            PixelData::Encapsulated(encapsulated) => &encapsulated.shape,
+        }
+    }
+
+    /// See [`PixelArray::frames`]
+    pub fn frames(&self) -> Option<usize> {
+        match self {
+            PixelData::Contiguous(pixel_array) => pixel_array.frames,
+            PixelData::Chunked(chunked) => chunked.frames,
+            PixelData::Encapsulated(encapsulated) => encapsulated.frames,
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames().unwrap_or(1)
+    }
+
+    pub fn frame_shape(&self) -> &[usize] {
+        frame_shape(self.shape(), self.frames())
+    }
+
+    /// The chunk shape of chunked pixel data
+    pub fn chunk_shape(&self) -> Option<&[usize]> {
+        match self {
+            PixelData::Chunked(chunked) => Some(&chunked.chunk_shape),
+            _ => None,
+        }
+    }
+
+    /// The transfer syntax UID of encapsulated pixel data
+    pub fn transfer_syntax(&self) -> Option<&str> {
+        match self {
+            PixelData::Encapsulated(encapsulated) => Some(&encapsulated.transfer_syntax),
+            _ => None,
+        }
+    }
+
+    /// See [`PixelArray::slice`]
+    pub fn slice(&self, slices: &[Slice]) -> Result<OwnedPixelArray, Error> {
+        match self {
+            PixelData::Contiguous(pixel_array) => pixel_array.slice(slices),
+            PixelData::Chunked(chunked) => chunked.slice(slices),
+            PixelData::Encapsulated(encapsulated) => encapsulated.slice(slices),
+        }
+    }
+
+    /// See [`PixelArray::gather_frames`]
+    pub fn gather_frames(&self, frames: &[usize]) -> Result<OwnedPixelArray, Error> {
+        match self {
+            PixelData::Contiguous(pixel_array) => pixel_array.gather_frames(frames),
+            PixelData::Chunked(chunked) => chunked.gather_frames(frames),
+            PixelData::Encapsulated(encapsulated) => encapsulated.gather_frames(frames),
+        }
+    }
+
+    /// Copies all of the pixel data into a single C-contiguous array, decoding encapsulated pixel
+    /// data
+    pub fn to_owned_array(&self) -> Result<OwnedPixelArray, Error> {
+        self.slice(&[])
+    }
+
+    /// The pixel array of pixel data held in a single tensor
+    pub(crate) fn into_pixel_array(self) -> Result<PixelArray<'a>, Error> {
+        match self {
+            PixelData::Contiguous(pixel_array) => Ok(pixel_array),
+            PixelData::Chunked(_) => ChunkedSnafu.fail(),
+            PixelData::Encapsulated(encapsulated) => EncapsulatedSnafu {
+                transfer_syntax: encapsulated.transfer_syntax,
+            }
+            .fail(),
+        }
+    }
+}
+
+/// Where the elements of the pixel data are within the tensor data of the safetensors object
+#[derive(Debug, Clone)]
+pub(crate) enum Layout {
+    /// The data offsets of a single C-order tensor
+    Contiguous((usize, usize)),
+    /// See [`ChunkedPixelArray`]
+    Chunked {
+        chunk_shape: Vec<usize>,
+        /// The data offsets of each chunk, in C order of the grid of chunks
+        chunks: Vec<(usize, usize)>,
+    },
+    /// See [`EncapsulatedPixelData`]
+    Encapsulated {
+        transfer_syntax: String,
+        bits_stored: usize,
+        /// The data offsets of the basic offset table and of each fragment
+        offset_table: (usize, usize),
+        fragments: Vec<(usize, usize)>,
+        frame_fragments: Vec<usize>,
+    },
+}
+
+/// The header of the safetensors object embedded as the pixel data
+#[derive(Debug, Clone)]
+pub(crate) struct SafetensorsHeader {
+    pub(crate) dtype: Dtype,
+    pub(crate) shape: Vec<usize>,
+    /// See [`PixelArray::frames`]
+    pub(crate) frames: Option<usize>,
+    pub(crate) layout: Layout,
+    /// Offset of the tensor data relative to the start of the object
+    pub(crate) data_start: usize,
+}
+
+/// Offset of the tensor data relative to the start of a safetensors object, read from the length
+/// of its header without parsing it
+pub(crate) fn data_start(buffer: &[u8]) -> Result<usize, Error> {
+    let header_len = buffer
+        .get(..SAFETENSORS_HEADER_LENGTH_LENGTH)
+        .context(TooSmallSnafu {
+            length: buffer.len(),
+        })?;
+    let header_len = u64::from_le_bytes(
+        header_len
+            .try_into()
+            .expect("slice has the length of a u64"),
+    ) as usize;
+    Ok(SAFETENSORS_HEADER_LENGTH_LENGTH + header_len)
+}
+
+/// Parses the JSON header of a safetensors object, returning it along with the offset of the
+/// tensor data
+fn read_metadata(buffer: &[u8]) -> Result<(HashMetadata, usize), Error> {
+    ensure!(
+        buffer.len() >= SAFETENSORS_HEADER_LENGTH_LENGTH,
+        TooSmallSnafu {
+            length: buffer.len()
+        }
+    );
+    let (header_len, rest) = buffer.split_at(SAFETENSORS_HEADER_LENGTH_LENGTH);
+    let header_len = u64::from_le_bytes(
+        header_len
+            .try_into()
+            .expect("slice has the length of a u64"),
+    ) as usize;
+    ensure!(
+        header_len <= rest.len(),
+        HeaderTooLongSnafu {
+            header_len,
+            length: buffer.len()
+        }
+    );
+    let metadata: HashMetadata =
+        serde_json::from_slice(&rest[..header_len]).context(InvalidHeaderSnafu)?;
+    Ok((metadata, SAFETENSORS_HEADER_LENGTH_LENGTH + header_len))
+}
+
+/// The rows along the last axis of every tensor of a safetensors object of `length` bytes, as the
+/// byte range of the tensor's data within the object, its element size and the length of its rows
+/// in bytes. `buffer` only needs to hold the object's header.
+pub(crate) fn tensor_rows(
+    buffer: &[u8],
+    length: usize,
+) -> Result<Vec<(Range<usize>, usize, usize)>, Error> {
+    let (metadata, data_start) = read_metadata(buffer)?;
+    metadata
+        .tensors
+        .values()
+        .map(|tensor_info| {
+            let (start, stop) = tensor_info.data_offsets;
+            let (start, stop) = (
+                data_start.saturating_add(start),
+                data_start.saturating_add(stop),
+            );
+            ensure!(
+                start <= stop && stop <= length,
+                DataOutOfBoundsSnafu {
+                    start,
+                    stop,
+                    length
+                }
+            );
+            let item_size = tensor_info.dtype.size();
+            let expected_length = tensor_length(tensor_info.dtype, &tensor_info.shape)?;
+            ensure!(
+                stop - start == expected_length,
+                ShapeMismatchSnafu {
+                    expected_length,
+                    length: stop - start
+                }
+            );
+            let row_length = tensor_info.shape.last().copied().unwrap_or(1) * item_size;
+            Ok((start..stop, item_size, row_length))
+        })
+        .collect()
+}
+
+/// Parses the header of the embedded safetensors object, without reading the tensor data
+pub(crate) fn parse_safetensors_header(buffer: &[u8]) -> Result<SafetensorsHeader, Error> {
+    let (metadata, data_start) = read_metadata(buffer)?;
+    let extra = metadata.metadata.unwrap_or_default();
+    let (dtype, shape, layout) = if extra.contains_key(chunked::CHUNK_SHAPE_METADATA_KEY) {
+        chunked::parse_layout(&extra, &metadata.tensors)?
+    } else if extra.contains_key(encapsulated::TRANSFER_SYNTAX_METADATA_KEY) {
+        encapsulated::parse_layout(&extra, &metadata.tensors)?
+    } else {
+        let tensor_info = metadata
+            .tensors
+            .get(PIXEL_ARRAY_NAME)
+            .context(MissingPixelArraySnafu)?;
+        (
+            tensor_info.dtype,
+            tensor_info.shape.clone(),
+            Layout::Contiguous(tensor_info.data_offsets),
+        )
+    };
+    let frames = match extra.get(FRAMES_METADATA_KEY) {
+        Some(frames) => {
+            let parsed = frames.parse().ok();
+            ensure!(
+                parsed.is_some() && shape.first() == parsed.as_ref(),
+                InvalidFramesSnafu {
+                    frames,
+                    shape: shape.clone()
+                }
+            );
+            parsed
+        }
+        None => None,
+    };
+    Ok(SafetensorsHeader {
+        dtype,
+        shape,
+        frames,
+        layout,
+        data_start,
+    })
+}
+
+/// Borrows the pixel data out of an embedded safetensors object
+pub(crate) fn pixel_data_from_safetensors(buffer: &[u8]) -> Result<PixelData<'_>, Error> {
+    let SafetensorsHeader {
+        dtype,
+        shape,
+        frames,
+        layout,
+        data_start,
+    } = parse_safetensors_header(buffer)?;
+    let tensor_data = |(start, stop): (usize, usize)| {
+        let (start, stop) = (data_start + start, data_start + stop);
+        buffer.get(start..stop).context(DataOutOfBoundsSnafu {
+            start,
+            stop,
+            length: buffer.len(),
+        })
+    };
+    Ok(match layout {
+        Layout::Contiguous(offsets) => PixelData::Contiguous(PixelArray {
+            dtype,
+            shape,
+            frames,
+            data: tensor_data(offsets)?,
+        }),
+        Layout::Chunked {
+            chunk_shape,
+            chunks,
+        } => PixelData::Chunked(ChunkedPixelArray {
+            dtype,
+            shape,
+            frames,
+            chunk_shape,
+            chunks: chunks
+                .into_iter()
+                .map(|offsets| tensor_data(offsets).map(Chunk::Raw))
+                .collect::<Result<_, _>>()?,
+        }),
+        Layout::Encapsulated {
+            transfer_syntax,
+            bits_stored,
+            offset_table,
+            fragments,
+            frame_fragments,
+        } => PixelData::Encapsulated(EncapsulatedPixelData {
+            transfer_syntax,
+            dtype,
+            shape,
+            frames,
+            bits_stored,
+            offset_table: tensor_data(offset_table)?,
+            fragments: fragments
+                .into_iter()
+                .map(tensor_data)
+                .collect::<Result<_, _>>()?,
+            frame_fragments,
+        }),
+    })
+}
+
+/// Serialises a pixel array as a safetensors object, as a single tensor or as chunks of
+/// `chunk_shape`. Multi-frame pixel data has its number of `frames` as the first axis of `shape`.
+pub(crate) fn pixel_array_to_safetensors(
+    dtype: Dtype,
+    shape: &[usize],
+    frames: Option<usize>,
+    chunk_shape: Option<&[usize]>,
+    data: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let expected_length = tensor_length(dtype, shape)?;
+    ensure!(
+        data.len() == expected_length,
+        ShapeMismatchSnafu {
+            expected_length,
+            length: data.len()
+        }
+    );
+    let mut extra = frames_metadata(shape, frames)?;
+    let (tensors, data) = match chunk_shape {
+        None => (contiguous_tensor(dtype, shape)?, Cow::Borrowed(data)),
+        Some(chunk_shape) => {
+            let pixel_array = PixelArray {
+                dtype,
+                shape: shape.to_vec(),
+                frames,
+                data,
+            };
+            let (tensors, data) = chunked::encode(&pixel_array, chunk_shape, &mut extra)?;
+            (tensors, Cow::Owned(data))
+        }
+    };
+    write_safetensors(tensors, extra, &data)
+}
+
+/// Serialises encapsulated pixel data as a safetensors object, keeping its offset table and
+/// fragments as they are
+pub(crate) fn encapsulated_to_safetensors(
+    encapsulated: &EncapsulatedPixelData,
+) -> Result<Vec<u8>, Error> {
+    let mut extra = HashMap::new();
+    if let Some(frames) = encapsulated.frames {
+        extra.insert(FRAMES_METADATA_KEY.to_string(), frames.to_string());
+    }
+    let (tensors, data) = encapsulated::encode(encapsulated, &mut extra);
+    write_safetensors(tensors, extra, &data)
+}
+
+/// The header of a safetensors object, the length of its tensor data and the tensor data to read
+pub(crate) type SafetensorsStream<'a> = (Vec<u8>, u64, Box<dyn Read + 'a>);
+
+/// Serialises a pixel array as a safetensors object like [`pixel_array_to_safetensors`], but with
+/// its little endian, C-order elements streamed in from `data` rather than held in memory. Returns
+/// the object's header, the length of its tensor data and the tensor data as a stream. Chunks are
+/// cut from a band of `chunk_shape[0]` indices of the first axis at a time, so only a band is ever
+/// in memory.
+pub(crate) fn pixel_array_to_safetensors_stream<'a>(
+    dtype: Dtype,
+    shape: &[usize],
+    frames: Option<usize>,
+    chunk_shape: Option<&[usize]>,
+    data: impl Read + 'a,
+) -> Result<SafetensorsStream<'a>, Error> {
+    let mut extra = frames_metadata(shape, frames)?;
+    let (tensors, data_length, data): (_, _, Box<dyn Read>) = match chunk_shape {
+        None => (
+            contiguous_tensor(dtype, shape)?,
+            tensor_length(dtype, shape)?,
+            Box::new(data),
+        ),
+        Some(chunk_shape) => {
+            let (tensors, data_length) = chunked::layout(dtype, shape, chunk_shape, &mut extra)?;
+            let bands = chunked::ChunkBands::new(dtype, shape, chunk_shape, data);
+            (tensors, data_length, Box::new(bands))
+        }
+    };
+    let header = safetensors_header(tensors, extra)?;
+    Ok((header, data_length as u64, data))
+}
+
+/// Parses the header of a safetensors object holding a single tensor, returning it along with the
+/// byte range of the tensor's data within the tensor data
+pub(crate) fn parse_pixel_array_header(
+    header: &[u8],
+) -> Result<(SafetensorsHeader, Range<usize>), Error> {
+    let parsed = parse_safetensors_header(header)?;
+    let (start, stop) = match &parsed.layout {
+        Layout::Contiguous(offsets) => *offsets,
+        Layout::Chunked { .. } => return ChunkedSnafu.fail(),
+        Layout::Encapsulated {
+            transfer_syntax, ..
+        } => return EncapsulatedSnafu { transfer_syntax }.fail(),
+    };
+    let expected_length = tensor_length(parsed.dtype, &parsed.shape)?;
+    let length = stop.saturating_sub(start);
+    ensure!(
+        length == expected_length,
+        ShapeMismatchSnafu {
+            expected_length,
+            length
+        }
+    );
+    Ok((parsed, start..stop))
+}
+
+/// The length of the tensor data of the safetensors object whose header is `header`
+pub(crate) fn data_length(header: &[u8]) -> Result<usize, Error> {
+    let (metadata, _data_start) = read_metadata(header)?;
+    Ok(metadata
+        .tensors
+        .values()
+        .map(|tensor_info| tensor_info.data_offsets.1)
+        .max()
+        .unwrap_or(0))
+}
+
+fn frames_metadata(
+    shape: &[usize],
+    frames: Option<usize>,
+) -> Result<HashMap<String, String>, Error> {
+    let mut extra = HashMap::new();
+    if let Some(frames) = frames {
+        ensure!(
+            shape.first() == Some(&frames),
+            InvalidFramesSnafu {
+                frames: frames.to_string(),
+                shape
+            }
+        );
+        extra.insert(FRAMES_METADATA_KEY.to_string(), frames.to_string());
+    }
+    Ok(extra)
+}
+
+/// The length in bytes of a tensor of `dtype` and `shape`
+fn tensor_length(dtype: Dtype, shape: &[usize]) -> Result<usize, Error> {
+    shape
+        .iter()
+        .try_fold(dtype.size(), |length, &axis| length.checked_mul(axis))
+        .context(TooLargeSnafu { dtype, shape })
+}
+
+/// The single tensor of the contiguous layout
+fn contiguous_tensor(dtype: Dtype, shape: &[usize]) -> Result<HashMap<String, TensorInfo>, Error> {
+    let length = tensor_length(dtype, shape)?;
+    Ok(HashMap::from([(
+        PIXEL_ARRAY_NAME.to_string(),
+        TensorInfo {
+            dtype,
+            shape: shape.to_vec(),
+            data_offsets: (0, length),
+        },
+    )]))
+}
+
+/// The length prefixed JSON header of a safetensors object
+fn safetensors_header(
+    tensors: HashMap<String, TensorInfo>,
+    extra: HashMap<String, String>,
+) -> Result<Vec<u8>, Error> {
+    let metadata = HashMetadata {
+        metadata: (!extra.is_empty()).then_some(extra),
+        tensors,
+    };
+    let mut header = serde_json::to_vec(&metadata).context(InvalidHeaderSnafu)?;
+    // safetensors pads the header with spaces so the data is 8 byte aligned
+    let padded_len = header
+        .len()
+        .next_multiple_of(SAFETENSORS_HEADER_LENGTH_LENGTH);
+    header.resize(padded_len, b' ');
+
+    let mut bytes = Vec::with_capacity(SAFETENSORS_HEADER_LENGTH_LENGTH + header.len());
+    bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&header);
+    Ok(bytes)
+}
+
+/// Writes the header describing `tensors` and `extra` metadata, followed by the tensor `data`
+fn write_safetensors(
+    tensors: HashMap<String, TensorInfo>,
+    extra: HashMap<String, String>,
+    data: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut bytes = safetensors_header(tensors, extra)?;
+    bytes.extend_from_slice(data);
+    Ok(bytes)
+}
+
+/// Reads a single little endian element as a float
+pub(crate) fn read_element(bytes: &[u8], dtype: Dtype) -> Result<f64, Error> {
+    let bytes = &bytes[..dtype.size()];
+    Ok(match dtype {
+        Dtype::BOOL => f64::from(u8::from(bytes[0] != 0)),
+        Dtype::U8 => f64::from(bytes[0]),
+        Dtype::I8 => f64::from(bytes[0] as i8),
+        Dtype::U16 => f64::from(u16::from_le_bytes([bytes[0], bytes[1]])),
+        Dtype::I16 => f64::from(i16::from_le_bytes([bytes[0], bytes[1]])),
+        Dtype::F16 => f16::from_le_bytes([bytes[0], bytes[1]]).to_f64(),
+        Dtype::BF16 => bf16::from_le_bytes([bytes[0], bytes[1]]).to_f64(),
+        Dtype::U32 => f64::from(u32::from_le_bytes(bytes.try_into().expect("4 bytes"))),
+        Dtype::I32 => f64::from(i32::from_le_bytes(bytes.try_into().expect("4 bytes"))),
+        Dtype::F32 => f64::from(f32::from_le_bytes(bytes.try_into().expect("4 bytes"))),
+        // 64 bit integers beyond 2^53 lose precision
+        Dtype::U64 => u64::from_le_bytes(bytes.try_into().expect("8 bytes")) as f64,
+        Dtype::I64 => i64::from_le_bytes(bytes.try_into().expect("8 bytes")) as f64,
+        Dtype::F64 => f64::from_le_bytes(bytes.try_into().expect("8 bytes")),
+        dtype => UnsupportedDtypeSnafu { dtype }.fail()?,
+    })
+}
+
+/// Appends `value` to `out` as a single little endian element
+pub(crate) fn write_element(value: f64, dtype: Dtype, out: &mut Vec<u8>) -> Result<(), Error> {
+    // `as` casts saturate, like numpy's `astype` for in-range values
+    match dtype {
+        Dtype::BOOL => out.push(u8::from(value != 0.0)),
+        Dtype::U8 => out.push(value as u8),
+        Dtype::I8 => out.extend((value as i8).to_le_bytes()),
+        Dtype::U16 => out.extend((value as u16).to_le_bytes()),
+        Dtype::I16 => out.extend((value as i16).to_le_bytes()),
+        Dtype::U32 => out.extend((value as u32).to_le_bytes()),
+        Dtype::I32 => out.extend((value as i32).to_le_bytes()),
+        Dtype::U64 => out.extend((value as u64).to_le_bytes()),
+        Dtype::I64 => out.extend((value as i64).to_le_bytes()),
+        Dtype::F16 => out.extend(f16::from_f64(value).to_le_bytes()),
+        Dtype::BF16 => out.extend(bf16::from_f64(value).to_le_bytes()),
+        Dtype::F32 => out.extend((value as f32).to_le_bytes()),
+        Dtype::F64 => out.extend(value.to_le_bytes()),
+        dtype => UnsupportedDtypeSnafu { dtype }.fail()?,
+    }
+    Ok(())
+}
+
+/// Sign extends little endian elements of `item_size` bytes from their low `bits_stored` bits
+pub(crate) fn sign_extend(data: &mut [u8], item_size: usize, bits_stored: usize) {
+    if bits_stored == 0 || bits_stored >= item_size * 8 {
+        return;
+    }
+    let shift = 64 - bits_stored;
+    for element in data.chunks_exact_mut(item_size) {
+        let mut le = [0u8; 8];
+        le[..item_size].copy_from_slice(element);
+        let value = ((i64::from_le_bytes(le) << shift) >> shift).to_le_bytes();
+        element.copy_from_slice(&value[..item_size]);
+    }
+}
+
+/// Converts little endian elements from one dtype to another
+pub(crate) fn cast(data: &[u8], from: Dtype, to: Dtype) -> Result<Vec<u8>, Error> {
+    if from == to {
+        return Ok(data.to_vec());
+    }
+    let mut out = Vec::with_capacity(data.len() / from.size() * to.size());
+    for element in data.chunks_exact(from.size()) {
+        write_element(read_element(element, from)?, to, &mut out)?;
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Safetensors object should have an 8 byte header, is only {length} bytes"))]
+    TooSmall { length: usize },
+
+    #[snafu(display("Safetensors header length {header_len} exceeds the object length {length}"))]
+    HeaderTooLong { header_len: usize, length: usize },
+
+    #[snafu(display("Safetensors object should have a valid json header"))]
+    InvalidHeader { source: serde_json::Error },
+
+    #[snafu(display("Safetensors object has no `{PIXEL_ARRAY_NAME}` tensor"))]
+    MissingPixelArray,
+
+    #[snafu(display("Pixel data is chunked and has no single `{PIXEL_ARRAY_NAME}` tensor"))]
+    Chunked,
+
+    #[snafu(display(
+        "Pixel data is encapsulated in transfer syntax {transfer_syntax} and has no single `{PIXEL_ARRAY_NAME}` tensor"
+    ))]
+    Encapsulated { transfer_syntax: String },
+
+    #[snafu(display("Encapsulated pixel data has no `{key}` metadata"))]
+    MissingEncapsulatedMetadata { key: &'static str },
+
+    #[snafu(display("Encapsulated pixel data metadata `{key}` is invalid: {value:?}"))]
+    InvalidEncapsulatedMetadata { key: &'static str, value: String },
+
+    #[snafu(display("Fragment {index} of the encapsulated pixel data is missing or not bytes"))]
+    InvalidFragment { index: usize },
+
+    #[snafu(display(
+        "Pixel data encapsulated in transfer syntax {transfer_syntax} cannot be decoded, only RLE Lossless can"
+    ))]
+    Undecodable { transfer_syntax: String },
+
+    #[snafu(display("RLE frame {frame} is corrupt"))]
+    InvalidRleFrame { frame: usize },
+
+    #[snafu(display("Chunk shape {chunk_shape:?} does not tile pixel data of shape {shape:?}"))]
+    InvalidChunkShape {
+        chunk_shape: Vec<usize>,
+        shape: Vec<usize>,
+    },
+
+    #[snafu(display("Chunked pixel data has no `{key}` metadata"))]
+    MissingChunkMetadata { key: &'static str },
+
+    #[snafu(display(
+        "Chunked pixel data metadata `{key}` should be a list of lengths, is {value:?}"
+    ))]
+    InvalidChunkMetadata { key: &'static str, value: String },
+
+    #[snafu(display("Chunk {index} is missing or does not match the chunk shape"))]
+    InvalidChunk { index: usize },
+
+    #[snafu(display("Tensor data {start}..{stop} is outside the object of length {length}"))]
+    DataOutOfBounds {
+        start: usize,
+        stop: usize,
+        length: usize,
+    },
+
+    #[snafu(display("Expected {expected_length} bytes of tensor data, got {length}"))]
+    ShapeMismatch {
+        expected_length: usize,
+        length: usize,
+    },
+
+    #[snafu(display("Pixel data of dtype {dtype:?} and shape {shape:?} is too large to address"))]
+    TooLarge { dtype: Dtype, shape: Vec<usize> },
+
+    #[snafu(display("Pixel data of shape {shape:?} cannot have {frames} frames"))]
+    InvalidFrames { frames: String, shape: Vec<usize> },
+
+    #[snafu(display("Frame {frame} is out of range for pixel data with {frames} frames"))]
+    FrameOutOfRange { frame: usize, frames: usize },
+
+    #[snafu(display("{slices} slices given for pixel data with {ndim} axes"))]
+    TooManySlices { slices: usize, ndim: usize },
+
+    #[snafu(display("Slice of axis {axis} has a step of 0"))]
+    ZeroStep { axis: usize },
+
+    #[snafu(display("Slice bound {index} is out of range for axis {axis} of length {length}"))]
+    SliceOutOfBounds {
+        axis: usize,
+        index: isize,
+        length: isize,
+    },
+
+    #[snafu(display("Elements of dtype {dtype:?} cannot be converted"))]
+    UnsupportedDtype { dtype: Dtype },
+
+    #[snafu(display("Tensor data at offset {offset} does not belong to exactly one tensor"))]
+    UntiledTensorData { offset: usize },
+
+    #[snafu(display("Could not compress the pixel data"))]
+    CouldNotCompress { source: CodecError },
+
+    #[snafu(display("Could not write the compressed pixel data"))]
+    CouldNotWrite { source: std::io::Error },
+
+    #[snafu(display("Could not decompress tensor {index} of the pixel data"))]
+    CouldNotDecompress { source: CodecError, index: usize },
+
+    #[snafu(display("The compressed lengths of the tensors do not match the pixel data"))]
+    InvalidCompressedLengths,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pixel_array_safetensors_round_trip() {
+        let data: Vec<u8> = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let bytes = pixel_array_to_safetensors(Dtype::F32, &[2, 3], None, None, &data).unwrap();
+
+        let pixel_array = pixel_data_from_safetensors(&bytes)
+            .and_then(PixelData::into_pixel_array)
+            .unwrap();
+        assert_eq!(pixel_array.dtype, Dtype::F32);
+        assert_eq!(pixel_array.shape, [2, 3]);
+        assert_eq!(pixel_array.frames, None);
+        assert_eq!(pixel_array.data, data);
+    }
+
+    #[test]
+    fn test_frames() {
+        let data: Vec<u8> = (0..24).collect();
+        let bytes =
+            pixel_array_to_safetensors(Dtype::U16, &[3, 2, 2], Some(3), None, &data).unwrap();
+        let pixel_array = pixel_data_from_safetensors(&bytes)
+            .and_then(PixelData::into_pixel_array)
+            .unwrap();
+        assert_eq!(pixel_array.frames, Some(3));
+        assert_eq!(pixel_array.frame_count(), 3);
+        assert_eq!(pixel_array.frame_shape(), [2, 2]);
+
+        let frame = pixel_array.frame(1).unwrap();
+        assert_eq!(frame.shape, [2, 2]);
+        assert_eq!(frame.frames, None);
+        assert_eq!(frame.data, &data[8..16]);
+
+        let frames = pixel_array.frame_range(1..3).unwrap();
+        assert_eq!(frames.shape, [2, 2, 2]);
+        assert_eq!(frames.frames, Some(2));
+        assert_eq!(frames.data, &data[8..]);
+
+        assert!(matches!(
+            pixel_array.frame(3),
+            Err(Error::FrameOutOfRange {
+                frame: 3,
+                frames: 3
+            })
+        ));
+        assert!(pixel_array.frame_range(2..4).is_err());
+
+        // a single frame is its whole array
+        let bytes = pixel_array_to_safetensors(Dtype::U16, &[3, 2, 2], None, None, &data).unwrap();
+        let pixel_array = pixel_data_from_safetensors(&bytes)
+            .and_then(PixelData::into_pixel_array)
+            .unwrap();
+        assert_eq!(pixel_array.frame_count(), 1);
+        assert_eq!(pixel_array.frame(0).unwrap().shape, [3, 2, 2]);
+        assert_eq!(pixel_array.frame_range(0..1).unwrap().shape, [1, 3, 2, 2]);
+
+        assert!(matches!(
+            pixel_array_to_safetensors(Dtype::U16, &[3, 2, 2], Some(2), None, &data),
+            Err(Error::InvalidFrames { .. })
+        ));
+    }
+
+    #[test]
+    fn test_slice() {
+        // element values are their flat index
+        let data: Vec<u8> = (0..24).collect();
+        let pixel_array = PixelArray {
+            dtype: Dtype::U8,
+            shape: vec![2, 3, 4],
+            frames: Some(2),
+            data: &data,
+        };
+        let slice = |start, stop, step| Slice { start, stop, step };
+
+        let region = pixel_array.slice(&[]).unwrap();
+        assert_eq!(region.shape, [2, 3, 4]);
+        assert_eq!(region.data, data);
+
+        let region = pixel_array
+            .slice(&[slice(Some(1), None, 1), slice(Some(1), Some(3), 1)])
+            .unwrap();
+        assert_eq!(region.shape, [1, 2, 4]);
+        assert_eq!(region.frames, Some(1));
+        assert_eq!(region.data, (16..24).collect::<Vec<u8>>());
+
+        let region = pixel_array
+            .slice(&[
+                Slice::FULL,
+                slice(None, None, -1),
+                slice(Some(-3), Some(-1), 1),
+            ])
+            .unwrap();
+        assert_eq!(region.shape, [2, 3, 2]);
+        assert_eq!(region.data, [9, 10, 5, 6, 1, 2, 21, 22, 17, 18, 13, 14]);
+
+        let region = pixel_array
+            .slice(&[slice(None, None, 1), Slice::FULL, slice(Some(3), None, -2)])
+            .unwrap();
+        assert_eq!(region.shape, [2, 3, 2]);
+        assert_eq!(region.data, [3, 1, 7, 5, 11, 9, 15, 13, 19, 17, 23, 21]);
+
+        let region = pixel_array.slice(&[slice(Some(1), Some(1), 1)]).unwrap();
+        assert_eq!(region.shape, [0, 3, 4]);
+        assert!(region.data.is_empty());
+
+        let wide: Vec<u8> = [1u16, 2, 3, 4]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let wide = PixelArray {
+            dtype: Dtype::U16,
+            shape: vec![2, 2],
+            frames: None,
+            data: &wide,
+        };
+        let region = wide.slice(&[Slice::FULL, slice(Some(1), None, 1)]).unwrap();
+        assert_eq!(region.data, [2, 0, 4, 0]);
+
+        assert!(matches!(
+            pixel_array.slice(&[slice(Some(3), None, 1)]),
+            Err(Error::SliceOutOfBounds { axis: 0, .. })
+        ));
+        assert!(matches!(
+            pixel_array.slice(&[Slice::FULL, slice(None, Some(-4), 1)]),
+            Err(Error::SliceOutOfBounds { axis: 1, .. })
+        ));
+        assert!(matches!(
+            pixel_array.slice(&[slice(Some(2), None, -1)]),
+            Err(Error::SliceOutOfBounds { axis: 0, .. })
+        ));
+        assert!(matches!(
+            pixel_array.slice(&[slice(None, None, 0)]),
+            Err(Error::ZeroStep { axis: 0 })
+        ));
+        assert!(matches!(
+            pixel_array.slice(&[Slice::FULL; 4]),
+            Err(Error::TooManySlices { .. })
+        ));
+    }
+
+    #[test]
+    fn test_gather_frames() {
+        let data: Vec<u8> = (0..12).collect();
+        let pixel_array = PixelArray {
+            dtype: Dtype::U8,
+            shape: vec![3, 4],
+            frames: Some(3),
+            data: &data,
+        };
+        let gathered = pixel_array.gather_frames(&[2, 0]).unwrap();
+        assert_eq!(gathered.shape, [2, 4]);
+        assert_eq!(gathered.frames, Some(2));
+        assert_eq!(gathered.data, [8, 9, 10, 11, 0, 1, 2, 3]);
+        assert!(pixel_array.gather_frames(&[3]).is_err());
+    }
+
+    #[test]
+    fn test_to_f32() {
+        let data: Vec<u8> = [0i16, 1000, -1024, 40]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let pixel_array = PixelArray {
+            dtype: Dtype::I16,
+            shape: vec![2, 2],
+            frames: None,
+            data: &data,
+        };
+        let rescale = Rescale {
+            slope: 1.0,
+            intercept: -1024.0,
+        };
+        assert_eq!(
+            pixel_array.to_f32(rescale, None).unwrap(),
+            [-1024.0, -24.0, -2048.0, -984.0]
+        );
+        assert_eq!(
+            pixel_array.to_f32(Rescale::default(), None).unwrap(),
+            [0.0, 1000.0, -1024.0, 40.0]
+        );
+
+        let window = Window {
+            center: 40.0,
+            width: 401.0,
+        };
+        assert_eq!(
+            pixel_array
+                .to_f32(Rescale::default(), Some(window))
+                .unwrap(),
+            [0.40125, 1.0, 0.0, 0.50125]
+        );
+        assert_eq!(window.apply(40.0 - 0.5 - 200.0), 0.0);
+        assert_eq!(window.apply(40.0 - 0.5 + 200.0), 1.0);
+        assert_eq!(window.apply(40.0 - 0.5), 0.5);
+    }
+
+    #[test]
+    fn test_load_eye3_safetensors() {
+        let bytes = std::fs::read("testfiles/eye3.safetensors").unwrap();
+        let pixel_array = pixel_data_from_safetensors(&bytes)
+            .and_then(PixelData::into_pixel_array)
+            .unwrap();
+        assert_eq!(
+            pixel_array.data.len(),
+            pixel_array.shape.iter().product::<usize>() * pixel_array.dtype.size()
+        );
+    }
+}
@@ -0,0 +1,634 @@
+//! Chunked pixel data, stored as a tensor per chunk so that reading a region of a large volume or
+//! slide only reads the chunks the region overlaps.
+//!
+//! The chunks tile the array in C order and are named `chunk_{n}` for their position `n` in that
+//! order. Chunks at the end of an axis are cut short rather than padded. The array's shape and the
+//! chunk shape are JSON lists in the `shape` and `chunk_shape` metadata of the safetensors object,
+//! whose header is then the index of the chunks.
+
+use snafu::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+use super::{
+    next_index, resolve_slices, tensor_length, CouldNotDecompressSnafu, Dtype, Error,
+    FrameOutOfRangeSnafu, InvalidChunkMetadataSnafu, InvalidChunkShapeSnafu, InvalidChunkSnafu,
+    Layout, MissingChunkMetadataSnafu, OwnedPixelArray, PixelArray, Slice, TensorInfo,
+    SHAPE_METADATA_KEY,
+};
+use crate::codec::{self, Codec};
+
+pub(crate) const CHUNK_SHAPE_METADATA_KEY: &str = "chunk_shape";
+
+fn chunk_name(index: usize) -> String {
+    format!("chunk_{index}")
+}
+
+/// The number of chunks along each axis
+fn grid(shape: &[usize], chunk_shape: &[usize]) -> Vec<usize> {
+    shape
+        .iter()
+        .zip(chunk_shape)
+        .map(|(&length, &chunk_length)| length.div_ceil(chunk_length))
+        .collect()
+}
+
+/// The shape of the chunk at `position` in the grid, cut short at the end of each axis
+fn chunk_extent(shape: &[usize], chunk_shape: &[usize], position: &[usize]) -> Vec<usize> {
+    shape
+        .iter()
+        .zip(chunk_shape)
+        .zip(position)
+        .map(|((&length, &chunk_length), &position)| {
+            chunk_length.min(length - position * chunk_length)
+        })
+        .collect()
+}
+
+fn check_chunk_shape(shape: &[usize], chunk_shape: &[usize]) -> Result<(), Error> {
+    ensure!(
+        !shape.is_empty()
+            && chunk_shape.len() == shape.len()
+            && shape.iter().chain(chunk_shape).all(|&length| length > 0),
+        InvalidChunkShapeSnafu { chunk_shape, shape }
+    );
+    Ok(())
+}
+
+/// Pixel data borrowed from a dimble file a chunk at a time
+#[derive(Debug, Clone)]
+pub struct ChunkedPixelArray<'a> {
+    pub dtype: Dtype,
+    pub shape: Vec<usize>,
+    /// See [`PixelArray::frames`]
+    pub frames: Option<usize>,
+    /// The shape of every chunk not cut short by the end of an axis
+    pub chunk_shape: Vec<usize>,
+    /// Each chunk, in C order of the grid of chunks
+    pub chunks: Vec<Chunk<'a>>,
+}
+
+/// The stored bytes of a chunk of a [`ChunkedPixelArray`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chunk<'a> {
+    /// Raw little endian, C-order element bytes
+    Raw(&'a [u8]),
+    /// Those bytes compressed on their own with the codec, and decompressed whenever the chunk is
+    /// read
+    Compressed(&'a [u8], Codec),
+}
+
+/// Consecutive indices of a slice of one axis that fall in the same chunk
+#[derive(Debug, PartialEq)]
+struct ChunkRun {
+    /// Position of the chunk along the axis
+    chunk: usize,
+    /// Index of the run within the slice
+    start: usize,
+    /// Index of the run's first element within the chunk
+    first: usize,
+    count: usize,
+}
+
+impl ChunkRun {
+    /// The slice of the chunk that the run reads
+    fn slice(&self, step: isize) -> Slice {
+        let last = self.first as isize + (self.count as isize - 1) * step;
+        let stop = last + step.signum();
+        Slice {
+            start: Some(self.first as isize),
+            stop: (stop >= 0).then_some(stop),
+            step,
+        }
+    }
+}
+
+/// Splits the `count` indices of a slice of one axis from `start` by `step` at chunk boundaries
+fn chunk_runs(start: usize, step: isize, count: usize, chunk_length: usize) -> Vec<ChunkRun> {
+    let mut runs: Vec<ChunkRun> = Vec::new();
+    for i in 0..count {
+        let index = (start as isize + i as isize * step) as usize;
+        let chunk = index / chunk_length;
+        match runs.last_mut() {
+            Some(run) if run.chunk == chunk => run.count += 1,
+            _ => runs.push(ChunkRun {
+                chunk,
+                start: i,
+                first: index % chunk_length,
+                count: 1,
+            }),
+        }
+    }
+    runs
+}
+
+impl<'a> ChunkedPixelArray<'a> {
+    pub fn frame_count(&self) -> usize {
+        self.frames.unwrap_or(1)
+    }
+
+    pub fn frame_shape(&self) -> &[usize] {
+        super::frame_shape(&self.shape, self.frames)
+    }
+
+    /// Copies the chunk at `position` in the grid of chunks, decompressing it if it is compressed.
+    /// Panics if there is no such chunk.
+    pub fn chunk(&self, position: &[usize]) -> Result<OwnedPixelArray, Error> {
+        let (shape, data) = self.chunk_data(position)?;
+        Ok(OwnedPixelArray {
+            dtype: self.dtype,
+            shape,
+            frames: None,
+            data: data.into_owned(),
+        })
+    }
+
+    /// The shape and element bytes of the chunk at `position`, borrowed unless the chunk is
+    /// compressed
+    fn chunk_data(&self, position: &[usize]) -> Result<(Vec<usize>, Cow<'a, [u8]>), Error> {
+        let grid = grid(&self.shape, &self.chunk_shape);
+        let index = position
+            .iter()
+            .zip(&grid)
+            .fold(0, |index, (&position, &chunks)| index * chunks + position);
+        let shape = chunk_extent(&self.shape, &self.chunk_shape, position);
+        let data = match self.chunks[index] {
+            Chunk::Raw(data) => Cow::Borrowed(data),
+            Chunk::Compressed(bytes, codec) => {
+                // the length is exactly that of the chunk's shape, as it was checked to be
+                let item_size = self.dtype.size();
+                let length = shape.iter().product::<usize>() * item_size;
+                let row_length = shape.last().copied().unwrap_or(1) * item_size;
+                Cow::Owned(
+                    codec::decompress_tensor(codec, bytes, length, item_size, row_length)
+                        .context(CouldNotDecompressSnafu { index })?,
+                )
+            }
+        };
+        Ok((shape, data))
+    }
+
+    /// Copies the region `slices` select into a C-contiguous array, like [`PixelArray::slice`].
+    /// Only the chunks the region overlaps are read.
+    pub fn slice(&self, slices: &[Slice]) -> Result<OwnedPixelArray, Error> {
+        let region = resolve_slices(&self.shape, slices)?;
+        let shape: Vec<usize> = region.iter().map(|&(_, _, count)| count).collect();
+        let frames = self.frames.map(|_| shape[0]);
+        let item_size = self.dtype.size();
+        let mut data = vec![0; shape.iter().product::<usize>() * item_size];
+
+        if !data.is_empty() {
+            let ndim = shape.len();
+            let runs: Vec<Vec<ChunkRun>> = region
+                .iter()
+                .zip(&self.chunk_shape)
+                .map(|(&(start, step, count), &chunk_length)| {
+                    chunk_runs(start, step, count, chunk_length)
+                })
+                .collect();
+            let run_counts: Vec<usize> = runs.iter().map(Vec::len).collect();
+            let mut strides = vec![item_size; ndim];
+            for axis in (1..ndim).rev() {
+                strides[axis - 1] = strides[axis] * shape[axis];
+            }
+
+            // every combination of a run along each axis is the part of the region in one chunk
+            let mut combination = vec![0; ndim];
+            loop {
+                let runs: Vec<&ChunkRun> = combination
+                    .iter()
+                    .zip(&runs)
+                    .map(|(&run, runs)| &runs[run])
+                    .collect();
+                let position: Vec<usize> = runs.iter().map(|run| run.chunk).collect();
+                let chunk_slices: Vec<Slice> = runs
+                    .iter()
+                    .zip(&region)
+                    .map(|(run, &(_, step, _))| run.slice(step))
+                    .collect();
+                let (extent, chunk_data) = self.chunk_data(&position)?;
+                let chunk = PixelArray {
+                    dtype: self.dtype,
+                    shape: extent,
+                    frames: None,
+                    data: &chunk_data,
+                };
+                let block = chunk.slice(&chunk_slices)?;
+
+                // copy the block into the region a row at a time
+                let offset: usize = runs
+                    .iter()
+                    .zip(&strides)
+                    .map(|(run, stride)| run.start * stride)
+                    .sum();
+                let row_length = block.shape[ndim - 1] * item_size;
+                let mut index = vec![0; ndim - 1];
+                for row in block.data.chunks_exact(row_length) {
+                    let row_offset = offset
+                        + index
+                            .iter()
+                            .zip(&strides)
+                            .map(|(i, stride)| i * stride)
+                            .sum::<usize>();
+                    data[row_offset..row_offset + row_length].copy_from_slice(row);
+                    next_index(&mut index, &block.shape[..ndim - 1]);
+                }
+
+                if !next_index(&mut combination, &run_counts) {
+                    break;
+                }
+            }
+        }
+        Ok(OwnedPixelArray {
+            dtype: self.dtype,
+            shape,
+            frames,
+            data,
+        })
+    }
+
+    /// Copies `frames` in the order given, like [`PixelArray::gather_frames`]
+    pub fn gather_frames(&self, frames: &[usize]) -> Result<OwnedPixelArray, Error> {
+        let frame_count = self.frame_count();
+        let mut data = Vec::new();
+        for &frame in frames {
+            ensure!(
+                frame < frame_count,
+                FrameOutOfRangeSnafu {
+                    frame,
+                    frames: frame_count
+                }
+            );
+            let slices = match self.frames {
+                Some(_) => vec![Slice {
+                    start: Some(frame as isize),
+                    stop: Some(frame as isize + 1),
+                    step: 1,
+                }],
+                None => Vec::new(),
+            };
+            data.extend(self.slice(&slices)?.data);
+        }
+        Ok(OwnedPixelArray {
+            dtype: self.dtype,
+            shape: [&[frames.len()], self.frame_shape()].concat(),
+            frames: Some(frames.len()),
+            data,
+        })
+    }
+}
+
+/// Reads the layout of chunked pixel data from the safetensors `metadata` and `tensors`
+pub(crate) fn parse_layout(
+    metadata: &HashMap<String, String>,
+    tensors: &HashMap<String, TensorInfo>,
+) -> Result<(Dtype, Vec<usize>, Layout), Error> {
+    let lengths = |key: &'static str| -> Result<Vec<usize>, Error> {
+        let value = metadata
+            .get(key)
+            .context(MissingChunkMetadataSnafu { key })?;
+        serde_json::from_str(value)
+            .ok()
+            .context(InvalidChunkMetadataSnafu { key, value })
+    };
+    let shape = lengths(SHAPE_METADATA_KEY)?;
+    let chunk_shape = lengths(CHUNK_SHAPE_METADATA_KEY)?;
+    check_chunk_shape(&shape, &chunk_shape)?;
+
+    let grid = grid(&shape, &chunk_shape);
+    let dtype = tensors
+        .get(&chunk_name(0))
+        .context(InvalidChunkSnafu { index: 0usize })?
+        .dtype;
+    let count = grid.iter().product();
+    let mut chunks = Vec::with_capacity(count);
+    let mut position = vec![0; grid.len()];
+    for index in 0..count {
+        let extent = chunk_extent(&shape, &chunk_shape, &position);
+        let length = extent.iter().product::<usize>() * dtype.size();
+        let chunk = tensors
+            .get(&chunk_name(index))
+            .filter(|chunk| {
+                let (start, stop) = chunk.data_offsets;
+                chunk.dtype == dtype
+                    && chunk.shape == extent
+                    && stop.checked_sub(start) == Some(length)
+            })
+            .context(InvalidChunkSnafu { index })?;
+        chunks.push(chunk.data_offsets);
+        next_index(&mut position, &grid);
+    }
+    Ok((
+        dtype,
+        shape,
+        Layout::Chunked {
+            chunk_shape,
+            chunks,
+        },
+    ))
+}
+
+/// The tensor of every chunk of `chunk_shape` of pixel data of `dtype` and `shape`, their data laid
+/// out one after another in C order of the grid of chunks, and the length of that data. Adds the
+/// shapes to the safetensors `metadata`.
+pub(crate) fn layout(
+    dtype: Dtype,
+    shape: &[usize],
+    chunk_shape: &[usize],
+    metadata: &mut HashMap<String, String>,
+) -> Result<(HashMap<String, TensorInfo>, usize), Error> {
+    check_chunk_shape(shape, chunk_shape)?;
+    // the chunks hold every element once, so no offset is past the length of the whole
+    let data_length = tensor_length(dtype, shape)?;
+    let grid = grid(shape, chunk_shape);
+    let mut tensors = HashMap::new();
+    let mut offset = 0;
+    let mut position = vec![0; grid.len()];
+    for index in 0..grid.iter().product() {
+        let extent = chunk_extent(shape, chunk_shape, &position);
+        let length = extent.iter().product::<usize>() * dtype.size();
+        tensors.insert(
+            chunk_name(index),
+            TensorInfo {
+                dtype,
+                shape: extent,
+                data_offsets: (offset, offset + length),
+            },
+        );
+        offset += length;
+        next_index(&mut position, &grid);
+    }
+    let to_json = |lengths: &[usize]| serde_json::to_string(lengths).expect("lengths serialise");
+    metadata.insert(SHAPE_METADATA_KEY.to_string(), to_json(shape));
+    metadata.insert(CHUNK_SHAPE_METADATA_KEY.to_string(), to_json(chunk_shape));
+    Ok((tensors, data_length))
+}
+
+/// Appends the data of every chunk of `chunk_shape` of `pixel_array` to `out`, in C order of the
+/// grid of chunks
+fn append_chunks(
+    pixel_array: &PixelArray,
+    chunk_shape: &[usize],
+    out: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let grid = grid(&pixel_array.shape, chunk_shape);
+    let mut position = vec![0; grid.len()];
+    loop {
+        let slices: Vec<Slice> = position
+            .iter()
+            .zip(chunk_shape)
+            .zip(&pixel_array.shape)
+            .map(|((&position, &chunk_length), &length)| Slice {
+                start: Some((position * chunk_length) as isize),
+                stop: Some(((position + 1) * chunk_length).min(length) as isize),
+                step: 1,
+            })
+            .collect();
+        out.extend(pixel_array.slice(&slices)?.data);
+        if !next_index(&mut position, &grid) {
+            return Ok(());
+        }
+    }
+}
+
+/// Splits `pixel_array` into chunks of `chunk_shape`, returning the tensor of each chunk and their
+/// data. Adds the shapes to the safetensors `metadata`.
+pub(crate) fn encode(
+    pixel_array: &PixelArray,
+    chunk_shape: &[usize],
+    metadata: &mut HashMap<String, String>,
+) -> Result<(HashMap<String, TensorInfo>, Vec<u8>), Error> {
+    let (tensors, data_length) =
+        layout(pixel_array.dtype, &pixel_array.shape, chunk_shape, metadata)?;
+    let mut data = Vec::with_capacity(data_length);
+    append_chunks(pixel_array, chunk_shape, &mut data)?;
+    Ok((tensors, data))
+}
+
+/// Splits a C-order tensor read from `inner` into chunks as it is read, in the order [`layout`] lays
+/// them out. The chunks of a band of `chunk_shape[0]` indices of the first axis are contiguous in
+/// that order, so only a band is held in memory at a time. Ends at the last whole band if `inner`
+/// ends early.
+pub(crate) struct ChunkBands<R> {
+    dtype: Dtype,
+    shape: Vec<usize>,
+    chunk_shape: Vec<usize>,
+    inner: R,
+    /// The first index along the first axis of the band after the one being read
+    next_band: usize,
+    /// The chunks of the band being read, and how much of them has been read
+    chunks: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> ChunkBands<R> {
+    /// Takes the shapes [`layout`] has checked
+    pub(crate) fn new(dtype: Dtype, shape: &[usize], chunk_shape: &[usize], inner: R) -> Self {
+        Self {
+            dtype,
+            shape: shape.to_vec(),
+            chunk_shape: chunk_shape.to_vec(),
+            inner,
+            next_band: 0,
+            chunks: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Reads and chunks the next band, leaving no chunks once there are no more bands
+    fn read_band(&mut self) -> io::Result<()> {
+        self.chunks.clear();
+        self.position = 0;
+        if self.next_band == self.shape[0] {
+            return Ok(());
+        }
+        let band_length = self.chunk_shape[0].min(self.shape[0] - self.next_band);
+        let band_shape = [&[band_length], &self.shape[1..]].concat();
+        let length = band_shape.iter().product::<usize>() * self.dtype.size();
+        let mut band = Vec::with_capacity(length);
+        (&mut self.inner)
+            .take(length as u64)
+            .read_to_end(&mut band)?;
+        if band.len() < length {
+            return Ok(());
+        }
+        self.next_band += band_length;
+        let band = PixelArray {
+            dtype: self.dtype,
+            shape: band_shape,
+            frames: None,
+            data: &band,
+        };
+        append_chunks(&band, &self.chunk_shape, &mut self.chunks).map_err(io::Error::other)
+    }
+}
+
+impl<R: Read> Read for ChunkBands<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.chunks.len() {
+            self.read_band()?;
+        }
+        let length = buf.len().min(self.chunks.len() - self.position);
+        buf[..length].copy_from_slice(&self.chunks[self.position..][..length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel_array::{
+        pixel_array_to_safetensors, pixel_array_to_safetensors_stream, pixel_data_from_safetensors,
+        PixelData,
+    };
+
+    #[test]
+    fn test_chunk_runs() {
+        assert_eq!(
+            chunk_runs(2, 1, 5, 3),
+            [
+                ChunkRun {
+                    chunk: 0,
+                    start: 0,
+                    first: 2,
+                    count: 1
+                },
+                ChunkRun {
+                    chunk: 1,
+                    start: 1,
+                    first: 0,
+                    count: 3
+                },
+                ChunkRun {
+                    chunk: 2,
+                    start: 4,
+                    first: 0,
+                    count: 1
+                },
+            ]
+        );
+        let runs = chunk_runs(7, -2, 4, 3);
+        assert_eq!(runs.len(), 3);
+        assert_eq!(
+            runs[1].slice(-2),
+            Slice {
+                start: Some(2),
+                stop: None,
+                step: -2
+            }
+        );
+    }
+
+    #[test]
+    fn test_chunked_round_trip() {
+        // element values are their flat index
+        let shape = [5, 4, 7];
+        let data: Vec<u8> = (0..140).collect();
+        let bytes = pixel_array_to_safetensors(Dtype::U8, &shape, Some(5), Some(&[2, 3, 3]), &data)
+            .unwrap();
+        let PixelData::Chunked(chunked) = pixel_data_from_safetensors(&bytes).unwrap() else {
+            panic!("pixel data should be chunked");
+        };
+        assert_eq!(chunked.shape, shape);
+        assert_eq!(chunked.frames, Some(5));
+        assert_eq!(chunked.chunks.len(), 3 * 2 * 3);
+        let chunk = chunked.chunk(&[2, 1, 2]).unwrap();
+        assert_eq!(chunk.shape, [1, 1, 1]);
+        assert_eq!(chunk.data, [139]);
+
+        let contiguous = PixelArray {
+            dtype: Dtype::U8,
+            shape: shape.to_vec(),
+            frames: Some(5),
+            data: &data,
+        };
+        let slice = |start, stop, step| Slice { start, stop, step };
+        for slices in [
+            vec![],
+            vec![
+                slice(Some(1), Some(4), 1),
+                slice(Some(1), None, 1),
+                slice(Some(2), Some(6), 1),
+            ],
+            vec![
+                slice(None, None, -2),
+                Slice::FULL,
+                slice(Some(-1), None, -3),
+            ],
+            vec![slice(Some(3), Some(3), 1)],
+        ] {
+            assert_eq!(
+                chunked.slice(&slices).unwrap(),
+                contiguous.slice(&slices).unwrap()
+            );
+        }
+        assert_eq!(
+            chunked.gather_frames(&[4, 0]).unwrap(),
+            contiguous.gather_frames(&[4, 0]).unwrap()
+        );
+        assert!(chunked.slice(&[slice(Some(6), None, 1)]).is_err());
+        assert!(chunked.gather_frames(&[5]).is_err());
+    }
+
+    #[test]
+    fn test_chunked_stream() {
+        let shape = [5, 4, 7];
+        let chunk_shape = [2, 3, 3];
+        let data: Vec<u8> = (0..140).collect();
+        let chunked = |bytes| match pixel_data_from_safetensors(bytes).unwrap() {
+            PixelData::Chunked(chunked) => chunked,
+            _ => panic!("pixel data should be chunked"),
+        };
+
+        // read a few bytes at a time, the streamed chunks are those chunked in memory
+        let (mut streamed, data_length, mut stream) = pixel_array_to_safetensors_stream(
+            Dtype::U8,
+            &shape,
+            Some(5),
+            Some(&chunk_shape),
+            &data[..],
+        )
+        .unwrap();
+        assert_eq!(data_length, 140);
+        let mut buf = [0; 5];
+        loop {
+            let length = stream.read(&mut buf).unwrap();
+            if length == 0 {
+                break;
+            }
+            streamed.extend_from_slice(&buf[..length]);
+        }
+        let in_memory =
+            pixel_array_to_safetensors(Dtype::U8, &shape, Some(5), Some(&chunk_shape), &data)
+                .unwrap();
+        let (streamed, in_memory) = (chunked(&streamed), chunked(&in_memory));
+        assert_eq!(streamed.shape, in_memory.shape);
+        assert_eq!(streamed.chunks, in_memory.chunks);
+
+        // a stream cut short ends after the last band of 2 * 4 * 7 bytes it has all of
+        let (_, _, mut stream) = pixel_array_to_safetensors_stream(
+            Dtype::U8,
+            &shape,
+            None,
+            Some(&chunk_shape),
+            &data[..100],
+        )
+        .unwrap();
+        let mut read = Vec::new();
+        stream.read_to_end(&mut read).unwrap();
+        assert_eq!(read.len(), 56);
+    }
+
+    #[test]
+    fn test_invalid_chunk_shape() {
+        let data = [0u8; 6];
+        for chunk_shape in [&[2][..], &[0, 2], &[1, 2, 3]] {
+            assert!(matches!(
+                pixel_array_to_safetensors(Dtype::U8, &[2, 3], None, Some(chunk_shape), &data),
+                Err(Error::InvalidChunkShape { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Compressed pixel data, with the data of each tensor compressed on its own so that reading a
+//! region of chunked pixel data only decompresses the chunks the region overlaps.
+//!
+//! The header of the safetensors object is kept as it is. The data of each tensor follows it,
+//! compressed in the order of the tensors' data offsets, and then the length of each tensor's
+//! compressed data as a little endian u64, in the same order. Every tensor decompresses to exactly
+//! the length its shape and dtype give.
+
+use snafu::prelude::*;
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use super::{
+    data_length, data_start, parse_safetensors_header, pixel_data_from_safetensors, tensor_rows,
+    Chunk, ChunkedPixelArray, CouldNotCompressSnafu, CouldNotDecompressSnafu, CouldNotWriteSnafu,
+    Error, InvalidCompressedLengthsSnafu, Layout, PixelData, UntiledTensorDataSnafu,
+};
+use crate::codec::{self, Codec};
+
+const COMPRESSED_LENGTH_LENGTH: usize = std::mem::size_of::<u64>();
+
+/// A tensor of a safetensors object, in the order of their data
+struct Tensor {
+    /// The range of the tensor's data within the tensor data
+    range: Range<usize>,
+    item_size: usize,
+    row_length: usize,
+}
+
+/// The tensors of the object whose header is `header` and whose tensor data is `data_length` bytes
+/// long. The tensors must cover the tensor data exactly once.
+fn tensors(header: &[u8], data_length: usize) -> Result<Vec<Tensor>, Error> {
+    let data_start = data_start(header)?;
+    let mut rows = tensor_rows(header, data_start.saturating_add(data_length))?;
+    rows.sort_unstable_by_key(|(range, ..)| (range.start, range.end));
+    let mut offset = 0;
+    let mut tensors = Vec::with_capacity(rows.len());
+    for (range, item_size, row_length) in rows {
+        let range = range.start - data_start..range.end - data_start;
+        ensure!(range.start == offset, UntiledTensorDataSnafu { offset });
+        offset = range.end;
+        tensors.push(Tensor {
+            range,
+            item_size,
+            row_length,
+        });
+    }
+    ensure!(offset == data_length, UntiledTensorDataSnafu { offset });
+    Ok(tensors)
+}
+
+/// Compresses a safetensors object a tensor at a time, reading its tensor data from `data` as it
+/// writes to `out`, so that only a row of it is in memory at a time. `header` is the object up to
+/// its tensor data, which is `data_length` bytes long.
+pub(crate) fn compress_stream(
+    codec: Codec,
+    header: &[u8],
+    mut data: impl Read,
+    data_length: u64,
+    mut out: impl Write,
+) -> Result<(), Error> {
+    let tensors = tensors(header, usize::try_from(data_length).unwrap_or(usize::MAX))?;
+    out.write_all(header).context(CouldNotWriteSnafu)?;
+    let mut compressed_lengths = Vec::with_capacity(tensors.len());
+    for tensor in tensors {
+        let length = tensor.range.len() as u64;
+        compressed_lengths.push(
+            codec::compress_tensor(
+                codec,
+                (&mut data).take(length),
+                length,
+                tensor.item_size,
+                tensor.row_length,
+                &mut out,
+            )
+            .context(CouldNotCompressSnafu)?,
+        );
+    }
+    for compressed_length in compressed_lengths {
+        out.write_all(&compressed_length.to_le_bytes())
+            .context(CouldNotWriteSnafu)?;
+    }
+    Ok(())
+}
+
+/// Compresses a whole safetensors object like [`compress_stream`]
+pub(crate) fn compress(codec: Codec, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    // the header may claim more than there is, which `tensors` reports
+    let data_start = data_start(bytes)?.min(bytes.len());
+    let (header, data) = bytes.split_at(data_start);
+    let mut compressed = Vec::new();
+    compress_stream(codec, header, data, data.len() as u64, &mut compressed)?;
+    Ok(compressed)
+}
+
+/// The tensors of compressed pixel data, along with the compressed data of each
+fn compressed_tensors(buffer: &[u8]) -> Result<Vec<(Tensor, &[u8])>, Error> {
+    let data_length = data_length(buffer)?;
+    let data_start = data_start(buffer)?;
+    let tensors = tensors(&buffer[..data_start], data_length)?;
+    let compressed = &buffer[data_start..];
+    let index_start = tensors
+        .len()
+        .checked_mul(COMPRESSED_LENGTH_LENGTH)
+        .and_then(|index_length| compressed.len().checked_sub(index_length))
+        .context(InvalidCompressedLengthsSnafu)?;
+    let (compressed, index) = compressed.split_at(index_start);
+    let mut offset = 0usize;
+    let tensors = tensors
+        .into_iter()
+        .zip(index.chunks_exact(COMPRESSED_LENGTH_LENGTH))
+        .map(|(tensor, compressed_length)| {
+            let compressed_length = u64::from_le_bytes(
+                compressed_length
+                    .try_into()
+                    .expect("chunk has the length of a u64"),
+            );
+            let end = usize::try_from(compressed_length)
+                .ok()
+                .and_then(|compressed_length| offset.checked_add(compressed_length))
+                .filter(|&end| end <= compressed.len())
+                .context(InvalidCompressedLengthsSnafu)?;
+            let bytes = &compressed[offset..end];
+            offset = end;
+            Ok((tensor, bytes))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    ensure!(offset == compressed.len(), InvalidCompressedLengthsSnafu);
+    Ok(tensors)
+}
+
+/// Decompresses the whole of compressed pixel data back into its safetensors object
+pub(crate) fn decompress(codec: Codec, buffer: &[u8]) -> Result<Vec<u8>, Error> {
+    let data_start = data_start(buffer)?;
+    let tensors = compressed_tensors(buffer)?;
+    let mut bytes = buffer[..data_start].to_vec();
+    for (index, (tensor, compressed)) in tensors.iter().enumerate() {
+        bytes.extend(
+            codec::decompress_tensor(
+                codec,
+                compressed,
+                tensor.range.len(),
+                tensor.item_size,
+                tensor.row_length,
+            )
+            .context(CouldNotDecompressSnafu { index })?,
+        );
+    }
+    Ok(bytes)
+}
+
+/// Reads compressed pixel data. The chunks of chunked pixel data are left compressed until a
+/// region that overlaps them is read, while the other layouts are decompressed whole into
+/// `decompressed` the first time they are read.
+pub(crate) fn pixel_data<'a>(
+    codec: Codec,
+    buffer: &'a [u8],
+    decompressed: &'a OnceLock<Vec<u8>>,
+) -> Result<PixelData<'a>, Error> {
+    if let Some(bytes) = decompressed.get() {
+        return pixel_data_from_safetensors(bytes);
+    }
+    let header = parse_safetensors_header(buffer)?;
+    let Layout::Chunked {
+        chunk_shape,
+        chunks,
+    } = header.layout
+    else {
+        let bytes = decompress(codec, buffer)?;
+        return pixel_data_from_safetensors(decompressed.get_or_init(|| bytes));
+    };
+    let tensors = compressed_tensors(buffer)?;
+    let chunks = chunks
+        .into_iter()
+        .map(|(start, stop)| {
+            let index = tensors
+                .binary_search_by_key(&(start, stop), |(tensor, _)| {
+                    (tensor.range.start, tensor.range.end)
+                })
+                .expect("every chunk is a tensor");
+            Chunk::Compressed(tensors[index].1, codec)
+        })
+        .collect();
+    Ok(PixelData::Chunked(ChunkedPixelArray {
+        dtype: header.dtype,
+        shape: header.shape,
+        frames: header.frames,
+        chunk_shape,
+        chunks,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel_array::{pixel_array_to_safetensors, Dtype, Slice};
+
+    fn pixels() -> Vec<u8> {
+        (0..5 * 7u16)
+            .flat_map(|value| (value * 300).to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pixels = pixels();
+        for chunk_shape in [None, Some(&[2, 3][..])] {
+            let bytes = pixel_array_to_safetensors(Dtype::U16, &[5, 7], None, chunk_shape, &pixels)
+                .unwrap();
+            let (header, data) = bytes.split_at(data_start(&bytes).unwrap());
+            for codec in [Codec::Zstd, Codec::Lz4, Codec::DeltaZstd] {
+                let mut streamed = Vec::new();
+                compress_stream(codec, header, data, data.len() as u64, &mut streamed).unwrap();
+                let compressed = compress(codec, &bytes).unwrap();
+                assert_eq!(streamed, compressed);
+                assert!(compressed.starts_with(header));
+                assert_eq!(decompress(codec, &compressed).unwrap(), bytes);
+
+                let decompressed = OnceLock::new();
+                let pixel_data = pixel_data(codec, &compressed, &decompressed).unwrap();
+                assert_eq!(pixel_data.to_owned_array().unwrap().data, pixels);
+                // only pixel data that is not chunked is decompressed whole
+                assert_eq!(decompressed.get().is_some(), chunk_shape.is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunks_decompress_on_their_own() {
+        let pixels = pixels();
+        let bytes =
+            pixel_array_to_safetensors(Dtype::U16, &[5, 7], None, Some(&[2, 3]), &pixels).unwrap();
+        let mut compressed = compress(Codec::Zstd, &bytes).unwrap();
+        let decompressed = OnceLock::new();
+        let PixelData::Chunked(chunked) =
+            pixel_data(Codec::Zstd, &compressed, &decompressed).unwrap()
+        else {
+            panic!("pixel data should be chunked");
+        };
+        assert_eq!(chunked.chunks.len(), 3 * 3);
+        let Chunk::Compressed(first, _) = chunked.chunks[0] else {
+            panic!("chunks should be compressed");
+        };
+
+        // corrupting the frame of the first chunk leaves regions outside it readable
+        let first = first.as_ptr() as usize - compressed.as_ptr() as usize;
+        compressed[first..first + 4].fill(0xFF);
+        let PixelData::Chunked(chunked) =
+            pixel_data(Codec::Zstd, &compressed, &decompressed).unwrap()
+        else {
+            panic!("pixel data should be chunked");
+        };
+        let rows = |start, stop| Slice {
+            start: Some(start),
+            stop: Some(stop),
+            step: 1,
+        };
+        assert_eq!(
+            chunked.slice(&[rows(2, 5)]).unwrap().data,
+            pixels[2 * 7 * 2..]
+        );
+        assert!(matches!(
+            chunked.slice(&[rows(0, 1)]),
+            Err(Error::CouldNotDecompress { .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_lengths() {
+        let bytes = pixel_array_to_safetensors(Dtype::U16, &[5, 7], None, None, &pixels()).unwrap();
+        let compressed = compress(Codec::Lz4, &bytes).unwrap();
+        let decompressed = OnceLock::new();
+        for corrupt in [
+            // the lengths cut off
+            compressed[..compressed.len() - 4].to_vec(),
+            // a length past the end of the compressed data
+            [&compressed[..compressed.len() - 8], &u64::MAX.to_le_bytes()].concat(),
+            // bytes left over after the compressed data
+            [
+                &compressed[..compressed.len() - 8],
+                &[0],
+                &compressed[compressed.len() - 8..],
+            ]
+            .concat(),
+        ] {
+            assert!(matches!(
+                pixel_data(Codec::Lz4, &corrupt, &decompressed),
+                Err(Error::InvalidCompressedLengths)
+            ));
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! Pixel data kept encapsulated as it was in its DICOM file, so that converting back to DICOM
+//! reproduces the compressed pixel data bit for bit.
+//!
+//! The basic offset table and each fragment are `U8` tensors named `offset_table` and
+//! `fragment_{n}`, in the order they were in the file. The transfer syntax UID, the dtype, shape and
+//! bits stored of the decoded pixels, and the number of consecutive fragments of each frame are in
+//! the metadata of the safetensors object. Only RLE Lossless is decoded.
+
+use serde::de::DeserializeOwned;
+use snafu::prelude::*;
+use std::collections::HashMap;
+
+use super::{
+    resolve_slices, Dtype, Error, FrameOutOfRangeSnafu, InvalidEncapsulatedMetadataSnafu,
+    InvalidFragmentSnafu, InvalidRleFrameSnafu, Layout, MissingEncapsulatedMetadataSnafu,
+    OwnedPixelArray, Slice, TensorInfo, UndecodableSnafu, FRAMES_METADATA_KEY, SHAPE_METADATA_KEY,
+};
+
+pub(crate) const RLE_LOSSLESS: &str = "1.2.840.10008.1.2.5";
+
+pub(crate) const TRANSFER_SYNTAX_METADATA_KEY: &str = "transfer_syntax";
+const DTYPE_METADATA_KEY: &str = "dtype";
+const BITS_STORED_METADATA_KEY: &str = "bits_stored";
+const FRAME_FRAGMENTS_METADATA_KEY: &str = "frame_fragments";
+
+const OFFSET_TABLE_NAME: &str = "offset_table";
+
+/// An RLE frame starts with the number of segments and the offsets of up to 15 segments
+const RLE_HEADER_LENGTH: usize = 64;
+const RLE_MAX_SEGMENTS: usize = 15;
+/// The most bytes one byte of a segment decodes to, as a replicate run of 2 bytes decodes to 128
+const RLE_MAX_RATIO: usize = 128;
+
+fn fragment_name(index: usize) -> String {
+    format!("fragment_{index}")
+}
+
+/// Compressed pixel data borrowed from a dimble file, with the fragments of its DICOM encapsulation
+#[derive(Debug, Clone)]
+pub struct EncapsulatedPixelData<'a> {
+    /// The UID of the transfer syntax the fragments are compressed with
+    pub transfer_syntax: String,
+    /// The dtype of the decoded pixels
+    pub dtype: Dtype,
+    /// The shape of the decoded pixels
+    pub shape: Vec<usize>,
+    /// See [`PixelArray::frames`](super::PixelArray::frames)
+    pub frames: Option<usize>,
+    /// The number of low bits of each element the pixels are stored in
+    pub bits_stored: usize,
+    /// The value of the basic offset table item, which is empty if the file had no offset table
+    pub offset_table: &'a [u8],
+    pub fragments: Vec<&'a [u8]>,
+    /// The number of consecutive fragments holding each frame
+    pub frame_fragments: Vec<usize>,
+}
+
+impl<'a> EncapsulatedPixelData<'a> {
+    pub fn frame_count(&self) -> usize {
+        self.frames.unwrap_or(1)
+    }
+
+    pub fn frame_shape(&self) -> &[usize] {
+        super::frame_shape(&self.shape, self.frames)
+    }
+
+    /// The fragments holding `frame`
+    pub fn frame_fragments(&self, frame: usize) -> Result<&[&'a [u8]], Error> {
+        let frames = self.frame_count();
+        ensure!(frame < frames, FrameOutOfRangeSnafu { frame, frames });
+        let start = self.frame_fragments[..frame].iter().sum::<usize>();
+        Ok(&self.fragments[start..start + self.frame_fragments[frame]])
+    }
+
+    /// Decodes `frame` into little endian, colour-by-pixel elements of the dtype
+    pub fn decode_frame(&self, frame: usize) -> Result<Vec<u8>, Error> {
+        let fragments = self.frame_fragments(frame)?;
+        ensure!(
+            self.transfer_syntax == RLE_LOSSLESS,
+            UndecodableSnafu {
+                transfer_syntax: &self.transfer_syntax
+            }
+        );
+        let frame_shape = self.frame_shape();
+        let pixels = frame_shape
+            .iter()
+            .take(2)
+            .try_fold(1usize, |pixels, &length| pixels.checked_mul(length));
+        let samples = frame_shape.get(2).copied().unwrap_or(1);
+        let item_size = self.dtype.size();
+        let mut data = pixels
+            .and_then(|pixels| decode_rle(&fragments.concat(), pixels, samples, item_size))
+            .context(InvalidRleFrameSnafu { frame })?;
+        if matches!(self.dtype, Dtype::I8 | Dtype::I16 | Dtype::I32) {
+            super::sign_extend(&mut data, item_size, self.bits_stored);
+        }
+        Ok(data)
+    }
+
+    /// Decodes `frames` in the order given, like [`PixelArray::gather_frames`](super::PixelArray::gather_frames)
+    pub fn gather_frames(&self, frames: &[usize]) -> Result<OwnedPixelArray, Error> {
+        let mut data = Vec::new();
+        for &frame in frames {
+            data.extend(self.decode_frame(frame)?);
+        }
+        Ok(OwnedPixelArray {
+            dtype: self.dtype,
+            shape: [&[frames.len()], self.frame_shape()].concat(),
+            frames: Some(frames.len()),
+            data,
+        })
+    }
+
+    /// Decodes the region `slices` select, like [`PixelArray::slice`](super::PixelArray::slice).
+    /// Only the frames the region overlaps are decoded.
+    pub fn slice(&self, slices: &[Slice]) -> Result<OwnedPixelArray, Error> {
+        resolve_slices(&self.shape, slices)?;
+        let (frames, rest) = match (self.frames, slices.split_first()) {
+            (Some(_), Some((frame_slice, rest))) => {
+                let (start, step, count) = resolve_slices(&self.shape[..1], &[*frame_slice])?[0];
+                let frames: Vec<usize> = (0..count)
+                    .map(|i| (start as isize + i as isize * step) as usize)
+                    .collect();
+                (frames, rest)
+            }
+            _ => ((0..self.frame_count()).collect(), slices),
+        };
+        let mut decoded = self.gather_frames(&frames)?;
+        if self.frames.is_none() {
+            decoded.shape.remove(0);
+            decoded.frames = None;
+        }
+        let slices = match self.frames {
+            Some(_) => [&[Slice::FULL], rest].concat(),
+            None => rest.to_vec(),
+        };
+        decoded.as_pixel_array().slice(&slices)
+    }
+}
+
+/// Decodes one run length encoded segment of `length` bytes, which PS3.5 Annex G encodes like
+/// PackBits
+fn unpack_bits(mut data: &[u8], length: usize) -> Option<Vec<u8>> {
+    // the segment cannot decode to more than this, whatever length the frame claims
+    if length > data.len().checked_mul(RLE_MAX_RATIO)? {
+        return None;
+    }
+    let mut out = Vec::with_capacity(length);
+    while out.len() < length {
+        let (&header, rest) = data.split_first()?;
+        data = match header as i8 {
+            // a literal run of header + 1 bytes
+            header @ 0.. => {
+                let count = header as usize + 1;
+                out.extend_from_slice(rest.get(..count)?);
+                &rest[count..]
+            }
+            // the next byte repeated 1 - header times
+            header @ -127..=-1 => {
+                let (&value, rest) = rest.split_first()?;
+                out.resize(out.len() + (1 - isize::from(header)) as usize, value);
+                rest
+            }
+            -128 => rest,
+        };
+    }
+    // segments are padded to an even length, and the last run may overshoot that padding
+    out.truncate(length);
+    Some(out)
+}
+
+/// Decodes a frame of RLE Lossless into little endian, colour-by-pixel elements. Each segment holds
+/// one byte of every element of one sample, most significant byte first.
+fn decode_rle(frame: &[u8], pixels: usize, samples: usize, item_size: usize) -> Option<Vec<u8>> {
+    let header = frame.get(..RLE_HEADER_LENGTH)?;
+    let word = |index: usize| {
+        let bytes = header[index * 4..index * 4 + 4]
+            .try_into()
+            .expect("4 bytes");
+        u32::from_le_bytes(bytes) as usize
+    };
+    let segments = word(0);
+    if Some(segments) != samples.checked_mul(item_size) || segments > RLE_MAX_SEGMENTS {
+        return None;
+    }
+    // checked before allocating, as the frame's shape may be corrupt
+    let length = pixels.checked_mul(segments)?;
+    if length > frame.len().checked_mul(RLE_MAX_RATIO)? {
+        return None;
+    }
+    let mut data = vec![0; length];
+    for segment in 0..segments {
+        let start = word(segment + 1);
+        let stop = if segment + 1 < segments {
+            word(segment + 2)
+        } else {
+            frame.len()
+        };
+        let bytes = unpack_bits(frame.get(start..stop)?, pixels)?;
+        let (sample, byte) = (segment / item_size, item_size - 1 - segment % item_size);
+        for (pixel, value) in bytes.into_iter().enumerate() {
+            data[(pixel * samples + sample) * item_size + byte] = value;
+        }
+    }
+    Some(data)
+}
+
+/// Parses the JSON metadata value of `key`
+fn json_metadata<T: DeserializeOwned>(
+    metadata: &HashMap<String, String>,
+    key: &'static str,
+) -> Result<T, Error> {
+    let value = metadata
+        .get(key)
+        .context(MissingEncapsulatedMetadataSnafu { key })?;
+    serde_json::from_str(value)
+        .ok()
+        .context(InvalidEncapsulatedMetadataSnafu { key, value })
+}
+
+/// Reads the layout of encapsulated pixel data from the safetensors `metadata` and `tensors`
+pub(crate) fn parse_layout(
+    metadata: &HashMap<String, String>,
+    tensors: &HashMap<String, TensorInfo>,
+) -> Result<(Dtype, Vec<usize>, Layout), Error> {
+    let value = |key: &'static str| {
+        metadata
+            .get(key)
+            .context(MissingEncapsulatedMetadataSnafu { key })
+    };
+    let transfer_syntax = value(TRANSFER_SYNTAX_METADATA_KEY)?.clone();
+    // the dtype is stored by its name rather than as JSON
+    let dtype = value(DTYPE_METADATA_KEY)?;
+    let dtype: Dtype = serde_json::from_value(serde_json::Value::String(dtype.clone()))
+        .ok()
+        .context(InvalidEncapsulatedMetadataSnafu {
+            key: DTYPE_METADATA_KEY,
+            value: dtype,
+        })?;
+    let shape: Vec<usize> = json_metadata(metadata, SHAPE_METADATA_KEY)?;
+    let bits_stored: usize = json_metadata(metadata, BITS_STORED_METADATA_KEY)?;
+    let frame_fragments: Vec<usize> = json_metadata(metadata, FRAME_FRAGMENTS_METADATA_KEY)?;
+
+    let frame_count = match metadata.contains_key(FRAMES_METADATA_KEY) {
+        true => shape.first().copied().unwrap_or(0),
+        false => 1,
+    };
+    ensure!(
+        frame_fragments.len() == frame_count,
+        InvalidEncapsulatedMetadataSnafu {
+            key: FRAME_FRAGMENTS_METADATA_KEY,
+            value: value(FRAME_FRAGMENTS_METADATA_KEY)?
+        }
+    );
+    let bytes = |name: &str, index: usize| {
+        tensors
+            .get(name)
+            .filter(|tensor| {
+                let (start, stop) = tensor.data_offsets;
+                tensor.dtype == Dtype::U8
+                    && tensor.shape.len() == 1
+                    && stop.checked_sub(start) == Some(tensor.shape[0])
+            })
+            .map(|tensor| tensor.data_offsets)
+            .context(InvalidFragmentSnafu { index })
+    };
+    let offset_table = bytes(OFFSET_TABLE_NAME, 0)?;
+    let fragments = (0..frame_fragments.iter().sum())
+        .map(|index| bytes(&fragment_name(index), index))
+        .collect::<Result<_, _>>()?;
+    Ok((
+        dtype,
+        shape,
+        Layout::Encapsulated {
+            transfer_syntax,
+            bits_stored,
+            offset_table,
+            fragments,
+            frame_fragments,
+        },
+    ))
+}
+
+/// Returns the tensors of the offset table and fragments of `encapsulated` and their data. Adds the
+/// rest of its description to the safetensors `metadata`.
+pub(crate) fn encode(
+    encapsulated: &EncapsulatedPixelData,
+    metadata: &mut HashMap<String, String>,
+) -> (HashMap<String, TensorInfo>, Vec<u8>) {
+    let mut tensors = HashMap::new();
+    let mut data = Vec::new();
+    let named = std::iter::once((OFFSET_TABLE_NAME.to_string(), encapsulated.offset_table)).chain(
+        encapsulated
+            .fragments
+            .iter()
+            .enumerate()
+            .map(|(index, fragment)| (fragment_name(index), *fragment)),
+    );
+    for (name, bytes) in named {
+        tensors.insert(
+            name,
+            TensorInfo {
+                dtype: Dtype::U8,
+                shape: vec![bytes.len()],
+                data_offsets: (data.len(), data.len() + bytes.len()),
+            },
+        );
+        data.extend_from_slice(bytes);
+    }
+    metadata.insert(
+        TRANSFER_SYNTAX_METADATA_KEY.to_string(),
+        encapsulated.transfer_syntax.clone(),
+    );
+    let dtype = serde_json::to_value(encapsulated.dtype).expect("dtypes serialise");
+    metadata.insert(
+        DTYPE_METADATA_KEY.to_string(),
+        dtype
+            .as_str()
+            .expect("dtypes serialise to names")
+            .to_string(),
+    );
+    metadata.insert(
+        SHAPE_METADATA_KEY.to_string(),
+        serde_json::to_string(&encapsulated.shape).expect("lengths serialise"),
+    );
+    metadata.insert(
+        BITS_STORED_METADATA_KEY.to_string(),
+        encapsulated.bits_stored.to_string(),
+    );
+    metadata.insert(
+        FRAME_FRAGMENTS_METADATA_KEY.to_string(),
+        serde_json::to_string(&encapsulated.frame_fragments).expect("lengths serialise"),
+    );
+    (tensors, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel_array::{encapsulated_to_safetensors, pixel_data_from_safetensors, PixelData};
+
+    #[test]
+    fn test_unpack_bits() {
+        // a literal run of 2, the next byte 3 times, a no-op and a literal run of 1
+        let data = [1, 10, 11, (-2i8) as u8, 12, 0x80, 0, 13];
+        assert_eq!(unpack_bits(&data, 6).unwrap(), [10, 11, 12, 12, 12, 13]);
+        assert_eq!(unpack_bits(&data, 4).unwrap(), [10, 11, 12, 12]);
+        assert!(unpack_bits(&data, 7).is_none());
+        assert!(unpack_bits(&[3, 1, 2], 4).is_none());
+        // longer than the segment could decode to
+        assert!(unpack_bits(&[0x81, 7], 257).is_none());
+        assert_eq!(unpack_bits(&[0x81, 7], 128).unwrap(), [7; 128]);
+    }
+
+    #[test]
+    fn test_decode_rle() {
+        // two pixels of two 8 bit samples, the first segment holding the first sample
+        let mut frame = vec![0u8; RLE_HEADER_LENGTH];
+        frame[..4].copy_from_slice(&2u32.to_le_bytes());
+        frame[4..8].copy_from_slice(&64u32.to_le_bytes());
+        frame[8..12].copy_from_slice(&68u32.to_le_bytes());
+        frame.extend([1, 1, 2, 0x80, (-1i8) as u8, 3, 0x80, 0x80]);
+        assert_eq!(decode_rle(&frame, 2, 2, 1).unwrap(), [1, 3, 2, 3]);
+        // big endian bytes of one 16 bit sample
+        assert_eq!(decode_rle(&frame, 2, 1, 2).unwrap(), [3, 1, 3, 2]);
+        assert!(decode_rle(&frame, 2, 3, 1).is_none());
+        assert!(decode_rle(&frame[..60], 2, 2, 1).is_none());
+        // sizes that overflow, or that the frame is far too short to hold, are not allocated
+        assert!(decode_rle(&frame, usize::MAX, 2, 1).is_none());
+        assert!(decode_rle(&frame, 1 << 40, 2, 1).is_none());
+    }
+
+    #[test]
+    fn test_encapsulated_safetensors_round_trip() {
+        let fragments: [&[u8]; 3] = [b"ab", b"cdef", b"gh"];
+        let encapsulated = EncapsulatedPixelData {
+            transfer_syntax: "1.2.840.10008.1.2.4.90".to_string(),
+            dtype: Dtype::U16,
+            shape: vec![2, 4, 5, 3],
+            frames: Some(2),
+            bits_stored: 12,
+            offset_table: &[0, 0, 0, 0, 22, 0, 0, 0],
+            fragments: fragments.to_vec(),
+            frame_fragments: vec![2, 1],
+        };
+        let bytes = encapsulated_to_safetensors(&encapsulated).unwrap();
+        let PixelData::Encapsulated(read) = pixel_data_from_safetensors(&bytes).unwrap() else {
+            panic!("pixel data should be encapsulated");
+        };
+        assert_eq!(read.transfer_syntax, encapsulated.transfer_syntax);
+        assert_eq!(read.dtype, Dtype::U16);
+        assert_eq!(read.shape, encapsulated.shape);
+        assert_eq!(read.frames, Some(2));
+        assert_eq!(read.bits_stored, 12);
+        assert_eq!(read.offset_table, encapsulated.offset_table);
+        assert_eq!(read.fragments, fragments);
+        assert_eq!(read.frame_fragments(0).unwrap(), &fragments[..2]);
+        assert_eq!(read.frame_fragments(1).unwrap(), &fragments[2..]);
+        assert!(read.frame_fragments(2).is_err());
+        assert!(matches!(
+            read.decode_frame(0),
+            Err(Error::Undecodable { .. })
+        ));
+
+        // the fragments of each frame have to account for every frame
+        let encapsulated = EncapsulatedPixelData {
+            frame_fragments: vec![3],
+            ..encapsulated
+        };
+        let bytes = encapsulated_to_safetensors(&encapsulated).unwrap();
+        assert!(matches!(
+            pixel_data_from_safetensors(&bytes),
+            Err(Error::InvalidEncapsulatedMetadata { .. })
+        ));
+    }
+}
@@ -0,0 +1,2806 @@
+use memmap2::{Mmap, MmapMut, MmapOptions};
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::{PyBufferError, PyFileNotFoundError, PyIndexError, PyValueError};
+use pyo3::ffi;
+use pyo3::intern;
+use pyo3::once_cell::GILOnceCell;
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+use pyo3::types::PyByteArray;
+use pyo3::types::PyBytes;
+use pyo3::types::PyDict;
+use pyo3::types::PyList;
+use pyo3::types::PySlice;
+use pyo3::types::PyTuple;
+use pyo3::types::{PyDate, PyDateTime, PyDelta, PyTime, PyTzInfo};
+use pyo3::wrap_pyfunction;
+use pyo3::AsPyPointer;
+use std::fs::File;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::sync::{Arc, OnceLock};
+
+use crate::codec::{self, Codec, Compression};
+use crate::dimble_to_ir;
+use crate::dlpack;
+use crate::format::PreambleError;
+use crate::ir_to_dimble::{
+    self, DictionaryMismatch, HeaderField, HeaderFieldMap, DEFAULT_PIXEL_ALIGNMENT,
+};
+use crate::person_name::{PersonName, PersonNameComponents};
+use crate::pixel_array::{self, Dtype, OwnedPixelArray, PixelArray, PixelData, Slice};
+use crate::reader::{self, DimbleReader, FieldValue, Selection};
+use crate::tag::Tag;
+use crate::typed_value::TypedValue;
+
+static TORCH_MODULE: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+static NUMPY_MODULE: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+#[pyfunction]
+#[pyo3(signature = (
+    json_path, dimble_path, pixel_array_safetensors_path, chunk_shape=None, codec=None,
+    pixel_codec=None, pixel_alignment=DEFAULT_PIXEL_ALIGNMENT,
+))]
+fn dicom_json_to_dimble(
+    json_path: &str,
+    dimble_path: &str,
+    pixel_array_safetensors_path: Option<&str>,
+    chunk_shape: Option<Vec<usize>>,
+    codec: Option<Codec>,
+    pixel_codec: Option<Codec>,
+    pixel_alignment: usize,
+) -> PyResult<()> {
+    let mismatches = ir_to_dimble::dicom_json_to_dimble(
+        json_path,
+        pixel_array_safetensors_path,
+        dimble_path,
+        chunk_shape.as_deref(),
+        Compression {
+            fields: codec,
+            pixel_data: pixel_codec,
+        },
+        pixel_alignment,
+    )?;
+    warn_dictionary_mismatches(&mismatches)
+}
+
+/// Warns about every attribute that disagrees with the data dictionary
+fn warn_dictionary_mismatches(mismatches: &[DictionaryMismatch]) -> PyResult<()> {
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    Python::with_gil(|py| {
+        let category = py.get_type::<DictionaryWarning>();
+        for mismatch in mismatches {
+            PyErr::warn(py, category, &mismatch.to_string(), 1)?;
+        }
+        Ok(())
+    })
+}
+
+#[pyfunction]
+fn dimble_to_dicom_json(dimble_path: &str, json_path: &str) -> PyResult<()> {
+    dimble_to_ir::dimble_to_dicom_json(dimble_path, json_path).map_err(Into::into)
+}
+
+/// Converts a numpy dtype name such as `"float32"` to a `Dtype`
+fn dtype_from_name(name: &str) -> PyResult<Dtype> {
+    Ok(match name {
+        "bool" => Dtype::BOOL,
+        "uint8" => Dtype::U8,
+        "int8" => Dtype::I8,
+        "uint16" => Dtype::U16,
+        "int16" => Dtype::I16,
+        "uint32" => Dtype::U32,
+        "int32" => Dtype::I32,
+        "uint64" => Dtype::U64,
+        "int64" => Dtype::I64,
+        "float16" => Dtype::F16,
+        "bfloat16" => Dtype::BF16,
+        "float32" => Dtype::F32,
+        "float64" => Dtype::F64,
+        name => {
+            return Err(UnsupportedValueError::new_err(format!(
+                "Dtype not understood: {name}"
+            )));
+        }
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    dicom_path,
+    dimble_path,
+    dtype=None,
+    chunk_shape=None,
+    codec=None,
+    pixel_codec=None,
+    keep_encapsulated=false,
+    pixel_alignment=DEFAULT_PIXEL_ALIGNMENT,
+))]
+#[allow(clippy::too_many_arguments)]
+fn dicom_to_dimble(
+    dicom_path: &str,
+    dimble_path: &str,
+    dtype: Option<&str>,
+    chunk_shape: Option<Vec<usize>>,
+    codec: Option<Codec>,
+    pixel_codec: Option<Codec>,
+    keep_encapsulated: bool,
+    pixel_alignment: usize,
+) -> PyResult<()> {
+    let dtype = dtype.map(dtype_from_name).transpose()?;
+    let compression = Compression {
+        fields: codec,
+        pixel_data: pixel_codec,
+    };
+    let mismatches = crate::dicom_to_dimble(
+        dicom_path,
+        dimble_path,
+        dtype,
+        chunk_shape.as_deref(),
+        compression,
+        keep_encapsulated,
+        pixel_alignment,
+    )?;
+    warn_dictionary_mismatches(&mismatches)
+}
+
+#[pyfunction]
+fn dimble_to_dicom(dimble_path: &str, dicom_path: &str) -> PyResult<()> {
+    crate::dimble_to_dicom(dimble_path, dicom_path).map_err(Into::into)
+}
+
+/// The NumPy and torch name of a `Dtype`, the inverse of [`dtype_from_name`]
+fn dtype_name(dtype: Dtype) -> &'static str {
+    match dtype {
+        Dtype::BOOL => "bool",
+        Dtype::U8 => "uint8",
+        Dtype::I8 => "int8",
+        Dtype::U16 => "uint16",
+        Dtype::I16 => "int16",
+        Dtype::U32 => "uint32",
+        Dtype::I32 => "int32",
+        Dtype::U64 => "uint64",
+        Dtype::I64 => "int64",
+        Dtype::F16 => "float16",
+        Dtype::BF16 => "bfloat16",
+        Dtype::F32 => "float32",
+        Dtype::F64 => "float64",
+        Dtype::C64 => "complex64",
+        Dtype::C128 => "complex128",
+    }
+}
+
+/// The framework's type for `dtype`, or `None` if this version of the framework has none
+fn get_pydtype(module: &PyModule, dtype: Dtype) -> PyResult<Option<&PyAny>> {
+    let name = dtype_name(dtype);
+    // only `bool_` is in every version of NumPy
+    let names = if dtype == Dtype::BOOL {
+        &["bool_", "bool"][..]
+    } else {
+        std::slice::from_ref(&name)
+    };
+    for name in names {
+        if module.hasattr(*name)? {
+            return Ok(Some(module.getattr(*name)?));
+        }
+    }
+    Ok(None)
+}
+
+/// What to load pixel data as when the framework has no type for its dtype
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DtypeFallback {
+    /// Copy into the next wider type that holds every value: uint16 to int32, uint32 to int64 and
+    /// bfloat16 to float32
+    Widen,
+    /// The raw little endian bytes, with a trailing axis over the bytes of each element, in a dict
+    /// with the dtype name
+    Bytes,
+}
+
+impl<'source> FromPyObject<'source> for DtypeFallback {
+    fn extract(value: &'source PyAny) -> PyResult<Self> {
+        match value.extract::<&str>()? {
+            "widen" => Ok(DtypeFallback::Widen),
+            "bytes" => Ok(DtypeFallback::Bytes),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown dtype fallback {other:?}, expected \"widen\" or \"bytes\""
+            ))),
+        }
+    }
+}
+
+impl<'source> FromPyObject<'source> for Codec {
+    fn extract(value: &'source PyAny) -> PyResult<Self> {
+        value
+            .extract::<&str>()?
+            .parse()
+            .map_err(|error: codec::CodecError| PyValueError::new_err(error.to_string()))
+    }
+}
+
+/// A pixel array in a framework's array type
+enum Pixels<'py> {
+    Array(&'py PyAny),
+    /// The bytes of each element, when the framework has no type for them
+    Bytes(&'py PyAny),
+}
+
+impl<'py> Pixels<'py> {
+    fn map(self, f: impl FnOnce(&'py PyAny) -> PyResult<&'py PyAny>) -> PyResult<Self> {
+        Ok(match self {
+            Pixels::Array(array) => Pixels::Array(f(array)?),
+            Pixels::Bytes(bytes) => Pixels::Bytes(f(bytes)?),
+        })
+    }
+
+    fn into_py(self, py: Python, dtype: Dtype) -> PyResult<PyObject> {
+        Ok(match self {
+            Pixels::Array(array) => array.into_py(py),
+            Pixels::Bytes(bytes) => [
+                ("bytes", bytes.into_py(py)),
+                ("dtype", dtype_name(dtype).into_py(py)),
+            ]
+            .into_py_dict(py)
+            .into_py(py),
+        })
+    }
+}
+
+/// Views `bytes`, a flat uint8 array of the pixel data, as `dtype` in the pixel data's `shape`,
+/// falling back to `fallback` if `module` has no type for `dtype`
+fn pixels_from_bytes<'py>(
+    py: Python<'py>,
+    module: &'py PyModule,
+    bytes: &'py PyAny,
+    dtype: Dtype,
+    shape: &[usize],
+    fallback: DtypeFallback,
+) -> PyResult<Pixels<'py>> {
+    // a trailing axis over the bytes of each element
+    let mut byte_shape = shape.to_vec();
+    byte_shape.push(dtype.size());
+    let bytes = bytes.call_method1(intern!(py, "reshape"), (byte_shape,))?;
+    let first_element = (py.Ellipsis(), 0);
+
+    if let Some(pydtype) = get_pydtype(module, dtype)? {
+        let array = bytes.call_method1(intern!(py, "view"), (pydtype,))?;
+        return Ok(Pixels::Array(array.get_item(first_element)?));
+    }
+    let (wide, position) = match (fallback, dtype) {
+        (DtypeFallback::Bytes, _) => return Ok(Pixels::Bytes(bytes)),
+        (DtypeFallback::Widen, Dtype::U16) => (Dtype::I32, 0),
+        (DtypeFallback::Widen, Dtype::U32) => (Dtype::I64, 0),
+        // a bfloat16 is the high half of a float32
+        (DtypeFallback::Widen, Dtype::BF16) => (Dtype::F32, 2),
+        (DtypeFallback::Widen, dtype) => {
+            return Err(UnsupportedValueError::new_err(format!(
+                "{} has no {} type and no wider type holds every value, load the pixel data with dtype_fallback=\"bytes\"",
+                module.name()?,
+                dtype_name(dtype)
+            )))
+        }
+    };
+    let Some(wide_pydtype) = get_pydtype(module, wide)? else {
+        return Err(UnsupportedValueError::new_err(format!(
+            "{} has neither a {} nor a {} type",
+            module.name()?,
+            dtype_name(dtype),
+            dtype_name(wide)
+        )));
+    };
+    let mut wide_shape: Vec<usize> = bytes.getattr(intern!(py, "shape"))?.extract()?;
+    *wide_shape.last_mut().expect("the byte axis") = wide.size();
+    let kwargs = [(intern!(py, "dtype"), module.getattr(intern!(py, "uint8"))?)].into_py_dict(py);
+    let wide_bytes = module.call_method("zeros", (wide_shape,), Some(kwargs))?;
+    let position = PySlice::new(py, position, position + dtype.size() as isize, 1);
+    wide_bytes.set_item((py.Ellipsis(), position), bytes)?;
+    let array = wide_bytes.call_method1(intern!(py, "view"), (wide_pydtype,))?;
+    Ok(Pixels::Array(array.get_item(first_element)?))
+}
+
+/// Loads the safetensors pixel array at `st_offset` as a torch tensor viewing a private memory map
+/// of the pixel data alone, as a read-only NumPy array viewing the memory map if `backend` is
+/// `"numpy"`, or as a [`PyPixelArray`] exporting the memory map through DLPack if `backend` is
+/// `"dlpack"`. If torch or NumPy has no type for the pixel
+/// dtype, the array loads as `dtype_fallback` says. Given `frames`, only those frames are read,
+/// stacked along a leading frame axis that any `slices` then apply to. A region selected by
+/// `slices` or by frames out of order is read into an array of its own rather than viewed. Pixel
+/// data compressed with `codec` always loads as a copy, decompressing only the chunks the region
+/// overlaps if it is chunked.
+#[pyfunction]
+#[pyo3(signature = (
+    filename, st_offset, st_length, device, slices=None, backend="torch",
+    dtype_fallback=DtypeFallback::Widen, frames=None, codec=None,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn load_pixel_array(
+    filename: &str,
+    st_offset: usize,
+    st_length: usize,
+    device: &str,
+    slices: Option<Vec<&PySlice>>,
+    backend: &str,
+    dtype_fallback: DtypeFallback,
+    frames: Option<Vec<usize>>,
+    codec: Option<Codec>,
+) -> PyResult<PyObject> {
+    check_backend(backend, device)?;
+    let path = std::path::Path::new(filename);
+    let file = File::open(path).map_err(|source| reader::Error::CouldNotOpen {
+        source,
+        path: path.into(),
+    })?;
+    let file_length = file
+        .metadata()
+        .map_err(|source| reader::Error::CouldNotMap {
+            source,
+            path: path.into(),
+        })?
+        .len() as usize;
+    if st_offset
+        .checked_add(st_length)
+        .is_none_or(|end| end > file_length)
+    {
+        return Err(reader::Error::FieldOutOfBounds {
+            tag: Tag::PIXEL_DATA,
+            offset: st_offset,
+            length: st_length,
+            file_length,
+        }
+        .into());
+    }
+    let buffer = unsafe {
+        MmapOptions::new()
+            .offset(st_offset as u64)
+            .len(st_length)
+            .map(&file)
+    }
+    .map_err(|source| reader::Error::CouldNotMap {
+        source,
+        path: path.into(),
+    })?;
+    let invalid_pixel_array = |source| reader::Error::InvalidPixelArray {
+        source,
+        offset: st_offset,
+    };
+    if let Some(codec) = codec {
+        let decompressed = OnceLock::new();
+        let pixel_data = pixel_array::compressed::pixel_data(codec, &buffer, &decompressed)
+            .map_err(invalid_pixel_array)?;
+        let region = match select_region(pixel_data, frames.as_deref(), slices)? {
+            Region::Borrowed(region) => region.to_owned_array(),
+            Region::Owned(region) => region,
+        };
+        return Python::with_gil(|py| owned_pixels(py, &region, device, backend, dtype_fallback));
+    }
+    let pixel_data =
+        pixel_array::pixel_data_from_safetensors(&buffer).map_err(invalid_pixel_array)?;
+    let dtype = pixel_data.dtype();
+    let region = match select_region(pixel_data, frames.as_deref(), slices)? {
+        Region::Borrowed(region) => region,
+        Region::Owned(region) => {
+            return Python::with_gil(|py| {
+                owned_pixels(py, &region, device, backend, dtype_fallback)
+            })
+        }
+    };
+    // the region is a view of the memory map
+    let start = region.data.as_ptr() as usize - buffer.as_ptr() as usize;
+    let stop = start + region.data.len();
+    let shape = region.shape;
+
+    if backend == "numpy" {
+        return numpy_pixel_array(buffer, start..stop, dtype, &shape, dtype_fallback);
+    }
+    // a private copy on write mapping of the pixel data alone, as DLPack consumers and torch
+    // tensors may write to the array
+    let mmap = unsafe {
+        MmapOptions::new()
+            .offset(st_offset as u64)
+            .len(st_length)
+            .map_copy(&file)
+    }
+    .map_err(|source| reader::Error::CouldNotMap {
+        source,
+        path: path.into(),
+    })?;
+    if backend == "dlpack" {
+        return Python::with_gil(|py| {
+            Ok(Py::new(
+                py,
+                PyPixelArray {
+                    mmap: Arc::new(mmap),
+                    offset: start,
+                    dtype,
+                    shape,
+                },
+            )?
+            .into_py(py))
+        });
+    }
+
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        let torch = TORCH_MODULE
+            .get_or_try_init(py, || PyModule::import(py, "torch").map(Into::into))?
+            .as_ref(py);
+        // the bytes of the region, which torch views without a copy
+        let bytes = Py::new(
+            py,
+            PyPixelArray {
+                mmap: Arc::new(mmap),
+                offset: start,
+                dtype: Dtype::U8,
+                shape: vec![stop - start],
+            },
+        )?;
+        let bytes = torch.getattr(intern!(py, "from_dlpack"))?.call1((bytes,))?;
+        let tensor = pixels_from_bytes(py, torch, bytes, dtype, &shape, dtype_fallback)?;
+        to_device(py, tensor, device)?.into_py(py, dtype)
+    })
+}
+
+/// The pixel data, or the region of it selected by frames and slices
+enum Region<'a> {
+    /// All the contiguous pixel data, or a run of its frames
+    Borrowed(PixelArray<'a>),
+    Owned(OwnedPixelArray),
+}
+
+impl Region<'_> {
+    fn as_pixel_array(&self) -> PixelArray<'_> {
+        match self {
+            Region::Borrowed(pixel_array) => pixel_array.clone(),
+            Region::Owned(pixel_array) => pixel_array.as_pixel_array(),
+        }
+    }
+}
+
+/// Selects `frames` of the pixel data, then the region of them `slices` select. Only the bytes of
+/// the selected region are read, or for chunked pixel data the chunks it overlaps, which are always
+/// copied. Encapsulated pixel data decodes only the frames the region overlaps.
+fn select_region<'a>(
+    pixel_data: PixelData<'a>,
+    frames: Option<&[usize]>,
+    slices: Option<Vec<&PySlice>>,
+) -> PyResult<Region<'a>> {
+    let slices = slices
+        .map(|slices| {
+            slices
+                .into_iter()
+                .map(slice_from_py)
+                .collect::<PyResult<Vec<_>>>()
+        })
+        .transpose()?;
+    let pixel_array = match pixel_data {
+        PixelData::Contiguous(pixel_array) => pixel_array,
+        pixel_data => {
+            let region = match frames {
+                Some(frames) => {
+                    frame_run(pixel_data.frame_count(), frames)?;
+                    pixel_data.gather_frames(frames).and_then(|gathered| {
+                        gathered
+                            .as_pixel_array()
+                            .slice(slices.as_deref().unwrap_or_default())
+                    })
+                }
+                None => pixel_data.slice(slices.as_deref().unwrap_or_default()),
+            };
+            return Ok(Region::Owned(region.map_err(region_error)?));
+        }
+    };
+    let region = match frames {
+        None => Region::Borrowed(pixel_array),
+        Some(frames) => {
+            let (run, gather) = frame_run(pixel_array.frame_count(), frames)?;
+            let run = pixel_array.frame_range(run).map_err(region_error)?;
+            match gather {
+                Some(positions) => {
+                    Region::Owned(run.gather_frames(&positions).map_err(region_error)?)
+                }
+                None => Region::Borrowed(run),
+            }
+        }
+    };
+    let Some(slices) = slices else {
+        return Ok(region);
+    };
+    let sliced = region
+        .as_pixel_array()
+        .slice(&slices)
+        .map_err(region_error)?;
+    Ok(Region::Owned(sliced))
+}
+
+fn slice_from_py(slice: &PySlice) -> PyResult<Slice> {
+    let py = slice.py();
+    let step: Option<isize> = slice.getattr(intern!(py, "step"))?.extract()?;
+    Ok(Slice {
+        start: slice.getattr(intern!(py, "start"))?.extract()?,
+        stop: slice.getattr(intern!(py, "stop"))?.extract()?,
+        step: step.unwrap_or(1),
+    })
+}
+
+fn region_error(error: pixel_array::Error) -> PyErr {
+    use pixel_array::Error::*;
+    match error {
+        FrameOutOfRange { .. } | SliceOutOfBounds { .. } | TooManySlices { .. } => {
+            PyIndexError::new_err(error.to_string())
+        }
+        error => PyValueError::new_err(error.to_string()),
+    }
+}
+
+/// Loads a region copied out of the pixel data with `backend`, as [`load_pixel_array`] does
+fn owned_pixels(
+    py: Python,
+    region: &OwnedPixelArray,
+    device: &str,
+    backend: &str,
+    dtype_fallback: DtypeFallback,
+) -> PyResult<PyObject> {
+    let module = match backend {
+        "dlpack" => {
+            // an anonymous map can't be empty
+            let mut mmap = MmapMut::map_anon(region.data.len().max(1))
+                .map_err(|source| DimbleError::new_err(source.to_string()))?;
+            mmap[..region.data.len()].copy_from_slice(&region.data);
+            let array = PyPixelArray {
+                mmap: Arc::new(mmap),
+                offset: 0,
+                dtype: region.dtype,
+                shape: region.shape.clone(),
+            };
+            return Ok(Py::new(py, array)?.into_py(py));
+        }
+        "numpy" => NUMPY_MODULE
+            .get_or_try_init(py, || PyModule::import(py, "numpy").map(Into::into))?
+            .as_ref(py),
+        _ => TORCH_MODULE
+            .get_or_try_init(py, || PyModule::import(py, "torch").map(Into::into))?
+            .as_ref(py),
+    };
+    let kwargs = [(intern!(py, "dtype"), module.getattr(intern!(py, "uint8"))?)].into_py_dict(py);
+    let bytes = if region.data.is_empty() {
+        // torch can't view an empty buffer
+        module.call_method("zeros", ((0,),), Some(kwargs))?
+    } else {
+        let buffer = PyByteArray::new(py, &region.data);
+        module.call_method("frombuffer", (buffer,), Some(kwargs))?
+    };
+    let pixels = pixels_from_bytes(
+        py,
+        module,
+        bytes,
+        region.dtype,
+        &region.shape,
+        dtype_fallback,
+    )?;
+    to_device(py, pixels, device)?.into_py(py, region.dtype)
+}
+
+fn to_device<'py>(py: Python<'py>, pixels: Pixels<'py>, device: &str) -> PyResult<Pixels<'py>> {
+    if device == "cpu" {
+        return Ok(pixels);
+    }
+    let kwargs = [(intern!(py, "device"), device)].into_py_dict(py);
+    pixels.map(|array| array.call_method(intern!(py, "to"), (), Some(kwargs)))
+}
+
+/// Checks `frames` against the number of frames of the pixel data, returning the contiguous run
+/// of frames spanning them and, unless they are that run, the positions within it to gather
+fn frame_run(
+    frame_count: usize,
+    frames: &[usize],
+) -> PyResult<(std::ops::Range<usize>, Option<Vec<usize>>)> {
+    if let Some(frame) = frames.iter().find(|&&frame| frame >= frame_count) {
+        return Err(PyIndexError::new_err(format!(
+            "Frame {frame} is out of range for pixel data with {frame_count} frames"
+        )));
+    }
+    let (Some(&first), Some(&last)) = (frames.iter().min(), frames.iter().max()) else {
+        return Err(PyValueError::new_err("Expected at least one frame to load"));
+    };
+    let run = first..last + 1;
+    let gather = (!frames.iter().copied().eq(run.clone()))
+        .then(|| frames.iter().map(|frame| frame - first).collect());
+    Ok((run, gather))
+}
+
+fn check_backend(backend: &str, device: &str) -> PyResult<()> {
+    if !matches!(backend, "torch" | "numpy" | "dlpack") {
+        return Err(PyValueError::new_err(format!(
+            "Unknown backend {backend:?}, expected \"torch\", \"numpy\" or \"dlpack\""
+        )));
+    }
+    if backend != "torch" && device != "cpu" {
+        return Err(PyValueError::new_err(format!(
+            "The {backend} backend can only load to the cpu, not {device:?}"
+        )));
+    }
+    Ok(())
+}
+
+/// Which values `load_dimble` loads the pixel data as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelValues {
+    /// The values as stored, in their stored dtype
+    Stored,
+    /// float32 values with the rescale applied, Hounsfield units for CT
+    Rescaled,
+    /// float32 values rescaled and then windowed to 0 to 1 with the first VOI window
+    Windowed,
+}
+
+impl<'source> FromPyObject<'source> for PixelValues {
+    fn extract(value: &'source PyAny) -> PyResult<Self> {
+        match value.extract::<&str>()? {
+            "stored" => Ok(PixelValues::Stored),
+            "rescaled" => Ok(PixelValues::Rescaled),
+            "windowed" => Ok(PixelValues::Windowed),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown pixel values {other:?}, expected \"stored\", \"rescaled\" or \"windowed\""
+            ))),
+        }
+    }
+}
+
+/// Loads the pixel data as float32 values computed from the stored values, see [`PixelValues`].
+/// Only the stored values of the region `frames` and `slices` select are read.
+fn load_pixel_values(
+    reader: &DimbleReader,
+    values: PixelValues,
+    device: &str,
+    slices: Option<Vec<&PySlice>>,
+    backend: &str,
+    frames: Option<Vec<usize>>,
+) -> PyResult<PyObject> {
+    check_backend(backend, device)?;
+    let window = match values {
+        PixelValues::Stored => unreachable!("stored values load with `load_pixel_array`"),
+        PixelValues::Rescaled => None,
+        PixelValues::Windowed => Some(reader.window()?.ok_or_else(|| {
+            MissingFieldError::new_err("Pixel data has no VOI window to load windowed values with")
+        })?),
+    };
+    let rescale = reader.rescale()?;
+    let region = select_region(reader.pixel_data()?, frames.as_deref(), slices)?;
+    let region = region.as_pixel_array();
+    let data = region
+        .to_f32(rescale, window)
+        .map_err(|source| UnsupportedValueError::new_err(source.to_string()))?;
+    let values = OwnedPixelArray {
+        dtype: Dtype::F32,
+        shape: region.shape,
+        frames: region.frames,
+        data: data.iter().flat_map(|value| value.to_le_bytes()).collect(),
+    };
+    Python::with_gil(|py| owned_pixels(py, &values, device, backend, DtypeFallback::Widen))
+}
+
+/// Views `range` of `mmap` as a read-only NumPy array, which keeps the memory map alive
+fn numpy_pixel_array(
+    mmap: Mmap,
+    range: std::ops::Range<usize>,
+    dtype: Dtype,
+    shape: &[usize],
+    dtype_fallback: DtypeFallback,
+) -> PyResult<PyObject> {
+    Python::with_gil(|py| {
+        let numpy = NUMPY_MODULE
+            .get_or_try_init(py, || PyModule::import(py, "numpy").map(Into::into))?
+            .as_ref(py);
+        let buffer = Py::new(py, PyMmapBuffer(mmap))?;
+        let kwargs = [
+            (intern!(py, "dtype"), numpy.getattr(intern!(py, "uint8"))?),
+            (intern!(py, "count"), range.len().into_py(py).into_ref(py)),
+            (intern!(py, "offset"), range.start.into_py(py).into_ref(py)),
+        ]
+        .into_py_dict(py);
+        let bytes = numpy
+            .getattr(intern!(py, "frombuffer"))?
+            .call((buffer,), Some(kwargs))?;
+        pixels_from_bytes(py, numpy, bytes, dtype, shape, dtype_fallback)?.into_py(py, dtype)
+    })
+}
+
+/// A pixel array in a memory map, which any framework can load without a copy through
+/// `from_dlpack`
+#[pyclass(name = "PixelArray", module = "dimble_rs")]
+struct PyPixelArray {
+    mmap: Arc<MmapMut>,
+    /// Byte offset of the first element in `mmap`
+    offset: usize,
+    dtype: Dtype,
+    shape: Vec<usize>,
+}
+
+#[pymethods]
+impl PyPixelArray {
+    #[pyo3(signature = (stream=None))]
+    fn __dlpack__(&self, py: Python, stream: Option<&PyAny>) -> PyResult<PyObject> {
+        if stream.is_some_and(|stream| !stream.is_none()) {
+            return Err(PyBufferError::new_err(
+                "Pixel arrays are on the cpu, which takes no stream",
+            ));
+        }
+        dlpack::to_capsule(
+            py,
+            self.mmap.clone(),
+            self.offset,
+            self.dtype,
+            &self.shape,
+            dlpack::contiguous_strides(&self.shape),
+        )
+    }
+
+    fn __dlpack_device__(&self) -> (i32, i32) {
+        (dlpack::DL_CPU, 0)
+    }
+
+    #[getter]
+    fn shape(&self, py: Python) -> PyObject {
+        PyTuple::new(py, &self.shape).into_py(py)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PixelArray(shape={:?}, dtype={:?})", self.shape, self.dtype)
+    }
+}
+
+/// The struct module format of the buffer's items
+const UNSIGNED_BYTE_FORMAT: &std::ffi::CStr = c"B";
+
+/// Exposes a memory map to Python as a read-only buffer
+#[pyclass(name = "MmapBuffer", module = "dimble_rs")]
+struct PyMmapBuffer(Mmap);
+
+#[pymethods]
+impl PyMmapBuffer {
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    unsafe fn __getbuffer__(
+        slf: &PyCell<Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if flags & ffi::PyBUF_WRITABLE == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("Dimble pixel data is read-only"));
+        }
+        let data = &slf.borrow().0;
+        // the view holds a reference to `slf`, so the memory map outlives it
+        ffi::Py_INCREF(slf.as_ptr());
+        (*view).obj = slf.as_ptr();
+        (*view).buf = data.as_ptr() as *mut c_void;
+        (*view).len = data.len() as isize;
+        (*view).readonly = 1;
+        (*view).itemsize = 1;
+        (*view).format = if flags & ffi::PyBUF_FORMAT == ffi::PyBUF_FORMAT {
+            UNSIGNED_BYTE_FORMAT.as_ptr() as *mut c_char
+        } else {
+            ptr::null_mut()
+        };
+        (*view).ndim = 1;
+        (*view).shape = if flags & ffi::PyBUF_ND == ffi::PyBUF_ND {
+            &mut (*view).len
+        } else {
+            ptr::null_mut()
+        };
+        (*view).strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES {
+            &mut (*view).itemsize
+        } else {
+            ptr::null_mut()
+        };
+        (*view).suboffsets = ptr::null_mut();
+        (*view).internal = ptr::null_mut();
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
+
+/// A DICOM person name with its alphabetic, ideographic and phonetic groups
+#[pyclass(name = "PersonName", module = "dimble_rs")]
+#[derive(Clone)]
+struct PyPersonName(PersonName);
+
+/// The family, given and middle names, prefix and suffix of a person name group
+#[pyclass(name = "PersonNameComponents", module = "dimble_rs")]
+#[derive(Clone)]
+struct PyPersonNameComponents(PersonNameComponents);
+
+#[pymethods]
+impl PyPersonName {
+    #[new]
+    fn new(value: &str) -> Self {
+        Self(PersonName::parse(value))
+    }
+
+    #[getter]
+    fn alphabetic(&self) -> Option<String> {
+        self.0.alphabetic.clone()
+    }
+
+    #[getter]
+    fn ideographic(&self) -> Option<String> {
+        self.0.ideographic.clone()
+    }
+
+    #[getter]
+    fn phonetic(&self) -> Option<String> {
+        self.0.phonetic.clone()
+    }
+
+    #[getter]
+    fn alphabetic_components(&self) -> Option<PyPersonNameComponents> {
+        self.0.alphabetic_components().map(PyPersonNameComponents)
+    }
+
+    #[getter]
+    fn ideographic_components(&self) -> Option<PyPersonNameComponents> {
+        self.0.ideographic_components().map(PyPersonNameComponents)
+    }
+
+    #[getter]
+    fn phonetic_components(&self) -> Option<PyPersonNameComponents> {
+        self.0.phonetic_components().map(PyPersonNameComponents)
+    }
+
+    /// Family name of the alphabetic group, like pydicom's `PersonName.family_name`
+    #[getter]
+    fn family_name(&self) -> String {
+        self.0
+            .alphabetic_components()
+            .unwrap_or_default()
+            .family_name
+    }
+
+    #[getter]
+    fn given_name(&self) -> String {
+        self.0
+            .alphabetic_components()
+            .unwrap_or_default()
+            .given_name
+    }
+
+    #[getter]
+    fn middle_name(&self) -> String {
+        self.0
+            .alphabetic_components()
+            .unwrap_or_default()
+            .middle_name
+    }
+
+    #[getter]
+    fn name_prefix(&self) -> String {
+        self.0
+            .alphabetic_components()
+            .unwrap_or_default()
+            .name_prefix
+    }
+
+    #[getter]
+    fn name_suffix(&self) -> String {
+        self.0
+            .alphabetic_components()
+            .unwrap_or_default()
+            .name_suffix
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_dicom_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PersonName({:?})", self.0.to_dicom_string())
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp, py: Python) -> PyObject {
+        match op {
+            CompareOp::Eq => (self.0 == other.0).into_py(py),
+            CompareOp::Ne => (self.0 != other.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyPersonNameComponents {
+    #[getter]
+    fn family_name(&self) -> String {
+        self.0.family_name.clone()
+    }
+
+    #[getter]
+    fn given_name(&self) -> String {
+        self.0.given_name.clone()
+    }
+
+    #[getter]
+    fn middle_name(&self) -> String {
+        self.0.middle_name.clone()
+    }
+
+    #[getter]
+    fn name_prefix(&self) -> String {
+        self.0.name_prefix.clone()
+    }
+
+    #[getter]
+    fn name_suffix(&self) -> String {
+        self.0.name_suffix.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+fn value_to_py(py: Python, value: FieldValue) -> PyResult<PyObject> {
+    Ok(match value {
+        FieldValue::Empty => py.None(),
+        FieldValue::String(s) => s.into_py(py),
+        FieldValue::Float(f) => f.into_py(py),
+        FieldValue::Integer(i) => i.into_py(py),
+        FieldValue::UnsignedInteger(u) => u.into_py(py),
+        FieldValue::PersonName(name) => Py::new(py, PyPersonName(name))?.into_py(py),
+        FieldValue::Binary(b) => PyBytes::new(py, &b).into_py(py),
+        FieldValue::BulkDataUri(uri) => uri.into_py(py),
+        FieldValue::Multiple(a) => {
+            let py_array = PyList::empty(py);
+            for v in a {
+                py_array.append(value_to_py(py, v)?)?;
+            }
+            py_array.into_py(py)
+        }
+        FieldValue::Sequence(items) => {
+            let py_items = PyList::empty(py);
+            for item in items {
+                let py_item = PyDict::new(py);
+                for (tag, element) in item {
+                    py_item.set_item(tag.to_string(), value_to_py(py, element.value)?)?;
+                }
+                py_items.append(py_item)?;
+            }
+            py_items.into_py(py)
+        }
+    })
+}
+
+fn get_field(
+    py: Python,
+    reader: &DimbleReader,
+    tag: Tag,
+    header_field: &HeaderField,
+) -> PyResult<PyObject> {
+    let element = reader.decode(tag, header_field)?;
+    value_to_py(py, element.value)
+}
+
+/// Decodes a field, converting dates, times, ages and numeric strings if `options.typed`
+fn get_field_with_options(
+    py: Python,
+    reader: &DimbleReader,
+    tag: Tag,
+    header_field: &HeaderField,
+    options: LoadOptions,
+) -> PyResult<PyObject> {
+    if options.typed {
+        typed_value_to_py(py, reader.decode_typed(tag, header_field)?)
+    } else {
+        get_field(py, reader, tag, header_field)
+    }
+}
+
+fn typed_value_to_py(py: Python, value: TypedValue) -> PyResult<PyObject> {
+    Ok(match value {
+        TypedValue::Date(date) => {
+            PyDate::new(py, date.year.into(), date.month, date.day)?.into_py(py)
+        }
+        TypedValue::Time(time) => PyTime::new(
+            py,
+            time.hour,
+            time.minute,
+            time.second,
+            time.microsecond,
+            None,
+        )?
+        .into_py(py),
+        TypedValue::DateTime(datetime) => {
+            let tzinfo = datetime
+                .utc_offset
+                .map(|minutes| utc_offset_to_py(py, minutes))
+                .transpose()?;
+            PyDateTime::new(
+                py,
+                datetime.date.year.into(),
+                datetime.date.month,
+                datetime.date.day,
+                datetime.time.hour,
+                datetime.time.minute,
+                datetime.time.second,
+                datetime.time.microsecond,
+                tzinfo,
+            )?
+            .into_py(py)
+        }
+        TypedValue::Age(age) => {
+            let seconds = age.duration().as_secs();
+            PyDelta::new(
+                py,
+                (seconds / 86_400) as i32,
+                (seconds % 86_400) as i32,
+                0,
+                false,
+            )?
+            .into_py(py)
+        }
+        TypedValue::Decimals(values) => values.into_py(py),
+        TypedValue::Integers(values) => values.into_py(py),
+        TypedValue::Multiple(values) => {
+            let list = PyList::empty(py);
+            for value in values {
+                list.append(typed_value_to_py(py, value)?)?;
+            }
+            list.into_py(py)
+        }
+        TypedValue::Untyped(value) => value_to_py(py, value)?,
+    })
+}
+
+/// A `datetime.timezone` `minutes` east of UTC
+fn utc_offset_to_py(py: Python<'_>, minutes: i16) -> PyResult<&PyTzInfo> {
+    let offset = PyDelta::new(py, 0, i32::from(minutes) * 60, 0, true)?;
+    let timezone = py.import("datetime")?.getattr("timezone")?;
+    Ok(timezone.call1((offset,))?.downcast()?)
+}
+
+/// A sequence whose items are decoded from the memory map only when indexed or iterated
+#[pyclass(name = "DimbleSequence", module = "dimble_rs")]
+struct PyDimbleSequence {
+    reader: Arc<DimbleReader>,
+    /// Path of the sequence field, like `52009230[17].00289110`
+    path: String,
+    /// The items of the outermost sequence, shared by every sequence nested in them rather than
+    /// cloned for each
+    root: Arc<Vec<HeaderFieldMap>>,
+    /// The item and tag of each sequence on the way down from `root` to this one
+    nesting: Vec<(usize, Tag)>,
+    options: LoadOptions,
+}
+
+impl PyDimbleSequence {
+    fn items(&self) -> &[HeaderFieldMap] {
+        nested_items(&self.root, &self.nesting)
+    }
+
+    fn item(&self, py: Python, index: usize) -> PyResult<PyObject> {
+        let item_path = format!("{}[{index}]", self.path);
+        item_to_pydict(
+            py,
+            &self.reader,
+            &self.root,
+            &self.nesting,
+            index,
+            &item_path,
+            self.options,
+        )
+    }
+}
+
+/// The items of the sequence `nesting` leads to from the items of `root`
+fn nested_items<'a>(root: &'a [HeaderFieldMap], nesting: &[(usize, Tag)]) -> &'a [HeaderFieldMap] {
+    nesting
+        .iter()
+        .fold(root, |items, (index, tag)| match &items[*index][tag] {
+            HeaderField::SQ(items) => items,
+            _ => unreachable!("sequences are only nested in sequence fields"),
+        })
+}
+
+#[pymethods]
+impl PyDimbleSequence {
+    fn __len__(&self) -> usize {
+        self.items().len()
+    }
+
+    fn __getitem__(&self, py: Python, index: isize) -> PyResult<PyObject> {
+        let length = self.items().len();
+        let position = if index < 0 {
+            index + length as isize
+        } else {
+            index
+        };
+        if !(0..length as isize).contains(&position) {
+            return Err(PyIndexError::new_err(format!(
+                "Sequence item {index} is out of range, {} has {length} items",
+                self.path
+            )));
+        }
+        self.item(py, position as usize)
+    }
+
+    fn __iter__(slf: PyRef<Self>) -> PyDimbleSequenceIterator {
+        PyDimbleSequenceIterator {
+            sequence: slf.into(),
+            index: 0,
+        }
+    }
+
+    /// Decodes every item, returning a list of dicts
+    fn to_list(&self, py: Python) -> PyResult<PyObject> {
+        let items = PyList::empty(py);
+        for index in 0..self.items().len() {
+            items.append(self.item(py, index)?)?;
+        }
+        Ok(items.into_py(py))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DimbleSequence({:?}, {} items)",
+            self.path,
+            self.items().len()
+        )
+    }
+
+    fn __richcmp__(&self, other: &PyAny, op: CompareOp, py: Python) -> PyResult<PyObject> {
+        let items = self.to_list(py)?;
+        let other = match other.extract::<PyRef<Self>>() {
+            Ok(other) => other.to_list(py)?,
+            Err(_) => other.into_py(py),
+        };
+        Ok(match op {
+            CompareOp::Eq => items.as_ref(py).eq(other)?.into_py(py),
+            CompareOp::Ne => items.as_ref(py).ne(other)?.into_py(py),
+            _ => py.NotImplemented(),
+        })
+    }
+}
+
+#[pyclass(name = "DimbleSequenceIterator", module = "dimble_rs")]
+struct PyDimbleSequenceIterator {
+    sequence: Py<PyDimbleSequence>,
+    index: usize,
+}
+
+#[pymethods]
+impl PyDimbleSequenceIterator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>, py: Python) -> PyResult<Option<PyObject>> {
+        let sequence = slf.sequence.borrow(py);
+        if slf.index >= sequence.items().len() {
+            return Ok(None);
+        }
+        let item = sequence.item(py, slf.index)?;
+        drop(sequence);
+        slf.index += 1;
+        Ok(Some(item))
+    }
+}
+
+/// How `load_dimble` presents the fields it loads
+#[derive(Debug, Clone, Copy)]
+struct LoadOptions {
+    /// Key dicts by keyword instead of by tag
+    keywords: bool,
+    /// Convert DA, TM, DT, AS, DS and IS values, see [`TypedValue`]
+    typed: bool,
+}
+
+/// The dict key of a tag: its keyword if asked for and the dictionary knows it, else its hex
+fn tag_key(tag: Tag, keywords: bool) -> String {
+    match tag.keyword() {
+        Some(keyword) if keywords => keyword.to_string(),
+        _ => tag.to_string(),
+    }
+}
+
+/// Spells the tags of a valid path as keywords, like
+/// `PerFrameFunctionalGroupsSequence[17].PixelMeasuresSequence`
+fn keyword_path(path: &str) -> PyResult<String> {
+    let segments = path
+        .split('.')
+        .map(|segment| {
+            let (tag, index) = reader::parse_path_segment(path, segment)?;
+            let key = tag_key(tag, true);
+            Ok(match index {
+                Some(index) => format!("{key}[{index}]"),
+                None => key,
+            })
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(segments.join("."))
+}
+
+/// Decodes the fields of item `index` of the sequence `nesting` leads to from `root`, leaving its
+/// own sequences lazy
+fn item_to_pydict(
+    py: Python,
+    reader: &Arc<DimbleReader>,
+    root: &Arc<Vec<HeaderFieldMap>>,
+    nesting: &[(usize, Tag)],
+    index: usize,
+    item_path: &str,
+    options: LoadOptions,
+) -> PyResult<PyObject> {
+    let dataset = PyDict::new(py);
+    for (&tag, header_field) in &nested_items(root, nesting)[index] {
+        let value = match header_field {
+            HeaderField::SQ(_) => Py::new(
+                py,
+                PyDimbleSequence {
+                    reader: reader.clone(),
+                    path: format!("{item_path}.{tag}"),
+                    root: root.clone(),
+                    nesting: [nesting, &[(index, tag)]].concat(),
+                    options,
+                },
+            )?
+            .into_py(py),
+            header_field => get_field_with_options(py, reader, tag, header_field, options)?,
+        };
+        dataset.set_item(tag_key(tag, options.keywords), value)?;
+    }
+    Ok(dataset.into_py(py))
+}
+
+/// Decodes a field selected by `path`, leaving a sequence lazy
+fn header_field_to_py(
+    py: Python,
+    reader: &Arc<DimbleReader>,
+    tag: Tag,
+    header_field: HeaderField,
+    path: String,
+    options: LoadOptions,
+) -> PyResult<PyObject> {
+    match header_field {
+        HeaderField::SQ(items) => Ok(Py::new(
+            py,
+            PyDimbleSequence {
+                reader: reader.clone(),
+                path,
+                root: Arc::new(items),
+                nesting: Vec::new(),
+                options,
+            },
+        )?
+        .into_py(py)),
+        header_field => get_field_with_options(py, reader, tag, &header_field, options),
+    }
+}
+
+/// Loads `fields` into a dict keyed by the fields as given, or by keyword paths if `keywords`.
+/// If `typed`, dates, times, ages and numeric strings are converted to Python values. Pixel data
+/// loads according to `backend` and `dtype_fallback`, see [`load_pixel_array`], as the stored
+/// values or as the float32 values of [`PixelValues`], of only the given `frames` if any.
+#[pyfunction]
+#[pyo3(signature = (
+    filename, fields, device, slices=None, keywords=false, typed=false, backend="torch",
+    dtype_fallback=DtypeFallback::Widen, values=PixelValues::Stored, frames=None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn load_dimble(
+    filename: &str,
+    fields: Vec<&str>,
+    device: &str,
+    slices: Option<Vec<&PySlice>>,
+    keywords: bool,
+    typed: bool,
+    backend: &str,
+    dtype_fallback: DtypeFallback,
+    values: PixelValues,
+    frames: Option<Vec<usize>>,
+) -> PyResult<PyObject> {
+    // this function takes in a filename and some fields and loads the data of those fields into a python dict
+    let reader = Arc::new(DimbleReader::open(filename)?);
+    let options = LoadOptions { keywords, typed };
+
+    Python::with_gil(|py| {
+        let dataset = PyDict::new(py);
+        for field in fields {
+            let py_field = match reader.select(field)? {
+                // pixel data nested in a sequence item is not a safetensors tensor
+                Selection::Field(
+                    Tag::PIXEL_DATA,
+                    HeaderField::Deffered(..) | HeaderField::Compressed(..),
+                ) if !field.contains('.') && values != PixelValues::Stored => load_pixel_values(
+                    &reader,
+                    values,
+                    device,
+                    slices.clone(),
+                    backend,
+                    frames.clone(),
+                )?,
+                Selection::Field(
+                    Tag::PIXEL_DATA,
+                    header_field @ (HeaderField::Deffered(..) | HeaderField::Compressed(..)),
+                ) if !field.contains('.') => {
+                    let codec = match header_field {
+                        HeaderField::Compressed(.., codec) => Some(codec),
+                        _ => None,
+                    };
+                    let (field_pos, field_length) =
+                        reader.field_location(Tag::PIXEL_DATA, &header_field)?;
+                    load_pixel_array(
+                        filename,
+                        field_pos,
+                        field_length,
+                        device,
+                        slices.clone(),
+                        backend,
+                        dtype_fallback,
+                        frames.clone(),
+                        codec,
+                    )?
+                }
+                Selection::Field(tag, header_field) => {
+                    header_field_to_py(py, &reader, tag, header_field, field.to_string(), options)?
+                }
+                Selection::Item(item) => {
+                    item_to_pydict(py, &reader, &Arc::new(vec![item]), &[], 0, field, options)?
+                }
+            };
+            if keywords {
+                dataset.set_item(keyword_path(field)?, py_field)?;
+            } else {
+                dataset.set_item(field, py_field)?;
+            }
+        }
+        Ok(dataset.into_py(py))
+    })
+}
+
+/// The dtype, shape and layout of the pixel data, read from its header without reading any pixels.
+/// `frames` is 1 for single frame pixel data, whose `frame_shape` is its `shape`. `chunk_shape` is
+/// `None` unless the pixel data is chunked, and `transfer_syntax` is `None` unless it is kept
+/// encapsulated. `data_offset` is the offset of the tensor data within the file, aligned to the
+/// `pixel_alignment` the file was written with, or `None` if the pixel data is compressed.
+#[pyfunction]
+fn pixel_array_info(filename: &str) -> PyResult<PyObject> {
+    let reader = DimbleReader::open(filename)?;
+    let pixel_data = reader.pixel_data()?;
+    Python::with_gil(|py| {
+        let info = PyDict::new(py);
+        info.set_item("dtype", dtype_name(pixel_data.dtype()))?;
+        info.set_item("shape", PyTuple::new(py, pixel_data.shape()))?;
+        info.set_item("frames", pixel_data.frame_count())?;
+        info.set_item("frame_shape", PyTuple::new(py, pixel_data.frame_shape()))?;
+        info.set_item(
+            "chunk_shape",
+            pixel_data
+                .chunk_shape()
+                .map(|chunk_shape| PyTuple::new(py, chunk_shape)),
+        )?;
+        info.set_item("transfer_syntax", pixel_data.transfer_syntax())?;
+        info.set_item("data_offset", reader.pixel_data_offset()?)?;
+        Ok(info.into_py(py))
+    })
+}
+
+/// Checks a file against the checksums written with it, returning the paths of the fields whose
+/// bytes are corrupted, e.g. `["7FE00010", "52009230[17].00289110[0].00280030"]`. Raises
+/// `CorruptFileError` if the header itself is corrupted.
+#[pyfunction]
+fn verify(filename: &str) -> PyResult<Vec<String>> {
+    Ok(DimbleReader::open(filename)?.verify()?)
+}
+
+/// Loads the top level fields whose keys are not tags, such as the NIfTI header fields of files
+/// written before the header keys became tags, into a dict keyed by those keys. Empty for files
+/// written since.
+#[pyfunction]
+fn load_legacy_fields(filename: &str) -> PyResult<PyObject> {
+    let reader = DimbleReader::open(filename)?;
+    Python::with_gil(|py| {
+        let fields = PyDict::new(py);
+        for key in reader.legacy_fields().keys() {
+            fields.set_item(key, value_to_py(py, reader.legacy_element(key)?.value)?)?;
+        }
+        Ok(fields.into_py(py))
+    })
+}
+
+pyo3::create_exception!(
+    dimble_rs,
+    DimbleError,
+    pyo3::exceptions::PyException,
+    "Custom Python Exception for Dimble errors."
+);
+
+pyo3::create_exception!(
+    dimble_rs,
+    UnsupportedVersionError,
+    DimbleError,
+    "Raised when a dimble file was written with a format version or flags this reader does not support."
+);
+
+pyo3::create_exception!(
+    dimble_rs,
+    CorruptFileError,
+    DimbleError,
+    "Raised when a dimble file is truncated or its header cannot be read."
+);
+
+pyo3::create_exception!(
+    dimble_rs,
+    CorruptFieldError,
+    DimbleError,
+    "Raised when a field's bytes are out of bounds or cannot be decoded."
+);
+
+pyo3::create_exception!(
+    dimble_rs,
+    MissingFieldError,
+    DimbleError,
+    "Raised when a requested tag is not in the dimble file."
+);
+
+pyo3::create_exception!(
+    dimble_rs,
+    InvalidPathError,
+    DimbleError,
+    "Raised when a field path is malformed or indexes past the items of a sequence."
+);
+
+pyo3::create_exception!(
+    dimble_rs,
+    UnsupportedValueError,
+    DimbleError,
+    "Raised when a field holds a value type that cannot be converted to Python."
+);
+
+pyo3::create_exception!(
+    dimble_rs,
+    UnsupportedDicomError,
+    DimbleError,
+    "Raised when a DICOM file uses a transfer syntax or pixel encoding the native reader does not support."
+);
+
+pyo3::create_exception!(
+    dimble_rs,
+    DictionaryWarning,
+    pyo3::exceptions::PyUserWarning,
+    "Warned when a converted attribute has a VR or number of values the DICOM data dictionary does not allow."
+);
+
+impl From<ir_to_dimble::Error> for PyErr {
+    fn from(value: ir_to_dimble::Error) -> Self {
+        DimbleError::new_err(snafu::Report::from_error(value).to_string())
+    }
+}
+
+impl From<crate::dicom_to_dimble::Error> for PyErr {
+    fn from(value: crate::dicom_to_dimble::Error) -> Self {
+        let message = snafu::Report::from_error(&value).to_string();
+        if value.is_unsupported() {
+            UnsupportedDicomError::new_err(message)
+        } else {
+            DimbleError::new_err(message)
+        }
+    }
+}
+
+/// Picks the Python exception class for a reader error
+fn reader_error_to_pyerr(error: &reader::Error, message: String) -> PyErr {
+    use reader::Error::*;
+    match error {
+        CouldNotOpen { source, .. } if source.kind() == std::io::ErrorKind::NotFound => {
+            PyFileNotFoundError::new_err(message)
+        }
+        CouldNotOpen { .. } | CouldNotMap { .. } | PixelDataIsNotAValue | NoChecksums => {
+            DimbleError::new_err(message)
+        }
+        InvalidPreamble {
+            source:
+                PreambleError::UnsupportedVersion { .. } | PreambleError::UnsupportedFlags { .. },
+        } => UnsupportedVersionError::new_err(message),
+        InvalidPreamble { .. }
+        | Truncated { .. }
+        | HeaderOutOfBounds { .. }
+        | InvalidHeader { .. }
+        | InvalidHeaderIndex { .. }
+        | InvalidChecksums { .. }
+        | CorruptHeader => CorruptFileError::new_err(message),
+        MissingField { .. } => MissingFieldError::new_err(message),
+        InvalidPath { .. }
+        | InvalidPathTag { .. }
+        | NotASequence { .. }
+        | ItemOutOfRange { .. } => InvalidPathError::new_err(message),
+        NotDeferred { .. }
+        | FieldOutOfBounds { .. }
+        | InvalidField { .. }
+        | CouldNotDecompress { .. }
+        | InvalidUtf8 { .. }
+        | InvalidTypedValue { .. }
+        | InvalidWindow { .. }
+        | InvalidPixelArray { .. } => CorruptFieldError::new_err(message),
+        UnsupportedValue { .. } => UnsupportedValueError::new_err(message),
+        InvalidLegacyField { source, .. } => reader_error_to_pyerr(source, message),
+    }
+}
+
+impl From<reader::Error> for PyErr {
+    fn from(value: reader::Error) -> Self {
+        let message = snafu::Report::from_error(&value).to_string();
+        reader_error_to_pyerr(&value, message)
+    }
+}
+
+/// Raises the exception for the first reader error in the chain, or `DimbleError` if there is none
+fn wrapped_reader_error_to_pyerr(value: &(dyn std::error::Error + 'static)) -> PyErr {
+    let message = snafu::Report::from_error(value).to_string();
+    let mut source = Some(value);
+    while let Some(error) = source {
+        if let Some(error) = error.downcast_ref::<reader::Error>() {
+            return reader_error_to_pyerr(error, message);
+        }
+        source = error.source();
+    }
+    DimbleError::new_err(message)
+}
+
+impl From<dimble_to_ir::Error> for PyErr {
+    fn from(value: dimble_to_ir::Error) -> Self {
+        wrapped_reader_error_to_pyerr(&value)
+    }
+}
+
+impl From<crate::dimble_to_dicom::Error> for PyErr {
+    fn from(value: crate::dimble_to_dicom::Error) -> Self {
+        wrapped_reader_error_to_pyerr(&value)
+    }
+}
+
+#[pymodule]
+fn dimble_rs(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_wrapped(wrap_pyfunction!(dicom_json_to_dimble))?;
+    m.add_wrapped(wrap_pyfunction!(dicom_to_dimble))?;
+    m.add_wrapped(wrap_pyfunction!(dimble_to_dicom_json))?;
+    m.add_wrapped(wrap_pyfunction!(dimble_to_dicom))?;
+    m.add_wrapped(wrap_pyfunction!(load_dimble))?;
+    m.add_wrapped(wrap_pyfunction!(load_pixel_array))?;
+    m.add_wrapped(wrap_pyfunction!(pixel_array_info))?;
+    m.add_wrapped(wrap_pyfunction!(verify))?;
+    m.add_wrapped(wrap_pyfunction!(load_legacy_fields))?;
+    m.add_class::<PyPersonName>()?;
+    m.add_class::<PyPersonNameComponents>()?;
+    m.add_class::<PyDimbleSequence>()?;
+    m.add_class::<PyPixelArray>()?;
+    m.add("DimbleError", py.get_type::<DimbleError>())?;
+    m.add(
+        "UnsupportedVersionError",
+        py.get_type::<UnsupportedVersionError>(),
+    )?;
+    m.add("CorruptFileError", py.get_type::<CorruptFileError>())?;
+    m.add("CorruptFieldError", py.get_type::<CorruptFieldError>())?;
+    m.add("MissingFieldError", py.get_type::<MissingFieldError>())?;
+    m.add("InvalidPathError", py.get_type::<InvalidPathError>())?;
+    m.add(
+        "UnsupportedValueError",
+        py.get_type::<UnsupportedValueError>(),
+    )?;
+    m.add(
+        "UnsupportedDicomError",
+        py.get_type::<UnsupportedDicomError>(),
+    )?;
+    m.add("DictionaryWarning", py.get_type::<DictionaryWarning>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    type Result<T = (), E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
+
+    #[test]
+    fn test_load_pixel_array_safetensors() {
+        let path = "testfiles/eye3.safetensors";
+        let size = std::fs::metadata(path).unwrap().len() as usize;
+
+        pyo3::prepare_freethreaded_python();
+        load_pixel_array(
+            path,
+            0,
+            size,
+            "cpu",
+            None,
+            "torch",
+            DtypeFallback::Widen,
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_load_pixel_array_safetensors_cat2x() {
+        let path = "testfiles/eye3.safetensors";
+        let size = std::fs::metadata(path).unwrap().len() as usize;
+
+        let path_cat3 = "testfiles/eye3.safetensors_cat3x";
+
+        pyo3::prepare_freethreaded_python();
+        load_pixel_array(
+            path_cat3,
+            0,
+            size,
+            "cpu",
+            None,
+            "torch",
+            DtypeFallback::Widen,
+            None,
+            None,
+        )
+        .unwrap();
+        load_pixel_array(
+            path_cat3,
+            size,
+            size,
+            "cpu",
+            None,
+            "torch",
+            DtypeFallback::Widen,
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_load_pixel_array_safetensors_cat3x() {
+        let path = "testfiles/eye3.safetensors";
+        let size = std::fs::metadata(path).unwrap().len() as usize;
+
+        let path_cat3 = "testfiles/eye3.safetensors_cat3x";
+
+        pyo3::prepare_freethreaded_python();
+        load_pixel_array(
+            path_cat3,
+            0,
+            size,
+            "cpu",
+            None,
+            "torch",
+            DtypeFallback::Widen,
+            None,
+            None,
+        )
+        .unwrap();
+        load_pixel_array(
+            path_cat3,
+            size,
+            size,
+            "cpu",
+            None,
+            "torch",
+            DtypeFallback::Widen,
+            None,
+            None,
+        )
+        .unwrap();
+        load_pixel_array(
+            path_cat3,
+            2 * size,
+            size,
+            "cpu",
+            None,
+            "torch",
+            DtypeFallback::Widen,
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_mmap_buffer_is_read_only() -> Result {
+        let path = "testfiles/eye3.safetensors";
+        let file = File::open(path)?;
+        let mmap = unsafe { MmapOptions::new().map(&file) }?;
+        let bytes = std::fs::read(path)?;
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let buffer = Py::new(py, PyMmapBuffer(mmap))?;
+            let memoryview = py
+                .import("builtins")?
+                .getattr("memoryview")?
+                .call1((buffer,))?;
+            assert!(memoryview.getattr("readonly")?.extract::<bool>()?);
+            let contents: Vec<u8> = memoryview.call_method0("tobytes")?.extract()?;
+            assert_eq!(contents, bytes);
+            Ok(())
+        })?;
+
+        let size = bytes.len();
+        for (device, backend) in [("cuda", "numpy"), ("cpu", "jax")] {
+            let error = load_pixel_array(
+                path,
+                0,
+                size,
+                device,
+                None,
+                backend,
+                DtypeFallback::Widen,
+                None,
+                None,
+            )
+            .unwrap_err();
+            Python::with_gil(|py| assert!(error.is_instance_of::<PyValueError>(py)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_pixel_array_dlpack() -> Result {
+        let path = "testfiles/eye3.safetensors";
+        let size = std::fs::metadata(path)?.len() as usize;
+
+        pyo3::prepare_freethreaded_python();
+        let array = load_pixel_array(
+            path,
+            0,
+            size,
+            "cpu",
+            None,
+            "dlpack",
+            DtypeFallback::Widen,
+            None,
+            None,
+        )?;
+        Python::with_gil(|py| -> PyResult<()> {
+            let array = array.as_ref(py);
+            assert_eq!(array.getattr("shape")?.extract::<Vec<usize>>()?, [3, 3]);
+            let device: (i32, i32) = array.call_method0("__dlpack_device__")?.extract()?;
+            assert_eq!(device, (dlpack::DL_CPU, 0));
+            let capsule = array.call_method0("__dlpack__")?;
+            assert_eq!(capsule.get_type().name()?, "PyCapsule");
+            Ok(())
+        })?;
+
+        // slices are read into an array of just the region
+        let bytes = fs::read(path)?;
+        let eye = pixel_array::pixel_data_from_safetensors(&bytes)
+            .and_then(pixel_array::PixelData::into_pixel_array)?;
+        let expected = eye.slice(&[
+            Slice {
+                start: Some(1),
+                stop: Some(3),
+                step: 1,
+            },
+            Slice {
+                start: None,
+                stop: None,
+                step: -1,
+            },
+        ])?;
+        Python::with_gil(|py| -> PyResult<()> {
+            let reversed = py.eval("slice(None, None, -1)", None, None)?.downcast()?;
+            let slices = vec![PySlice::new(py, 1, 3, 1), reversed];
+            let array = load_pixel_array(
+                path,
+                0,
+                size,
+                "cpu",
+                Some(slices),
+                "dlpack",
+                DtypeFallback::Widen,
+                None,
+                None,
+            )?;
+            let array = array
+                .as_ref(py)
+                .downcast::<PyCell<PyPixelArray>>()?
+                .borrow();
+            assert_eq!(array.shape, [2, 3]);
+            assert_eq!(array.mmap[..expected.data.len()], expected.data);
+
+            let slices = vec![PySlice::new(py, 0, 4, 1)];
+            let error = load_pixel_array(
+                path,
+                0,
+                size,
+                "cpu",
+                Some(slices),
+                "dlpack",
+                DtypeFallback::Widen,
+                None,
+                None,
+            )
+            .unwrap_err();
+            assert!(error.is_instance_of::<PyIndexError>(py));
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_integration_single_string() -> Result {
+        let dicom_json_text = r#"
+        {
+            "00080005": {
+                "vr": "CS",
+                "Value": [
+                    "ISO_IR 100"
+                ]
+            } 
+        }
+        "#;
+        let ir_path = "/tmp/single_string.ir.json";
+        let dimble_path = "/tmp/single_string.dimble";
+        let ir_recon_path = "/tmp/single_string.ir.recon.json";
+
+        fs::write(ir_path, dicom_json_text).expect("should be able to write to file");
+
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        dimble_to_dicom_json(dimble_path, ir_recon_path)?;
+
+        let recon_json_reader = fs::File::open(ir_recon_path).expect("should be able to open file");
+        use serde_json::Value;
+        let recon_json: Value =
+            serde_json::from_reader(recon_json_reader).expect("should be able to read json");
+        assert_eq!(recon_json["00080005"]["Value"][0], "ISO_IR 100");
+        assert_eq!(recon_json["00080005"]["vr"], "CS");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_integration_string_array() -> Result {
+        let dicom_json_text = r#"
+        {
+            "00080008": {
+                "vr": "CS",
+                "Value": [
+                    "ORIGINAL",
+                    "PRIMARY",
+                    "OTHER"
+                ]
+            } 
+        }
+        "#;
+        let ir_path = "/tmp/single_string_array.ir.json";
+        let dimble_path = "/tmp/single_string_array.dimble";
+        let ir_recon_path = "/tmp/single_string_array.ir.recon.json";
+
+        fs::write(ir_path, dicom_json_text).expect("should be able to write to file");
+
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        dimble_to_dicom_json(dimble_path, ir_recon_path)?;
+
+        let recon_json_reader = fs::File::open(ir_recon_path).expect("should be able to open file");
+        use serde_json::Value;
+        let recon_json: Value =
+            serde_json::from_reader(recon_json_reader).expect("should be able to read json");
+        assert_eq!(recon_json["00080008"]["Value"][0], "ORIGINAL");
+        assert_eq!(recon_json["00080008"]["Value"][1], "PRIMARY");
+        assert_eq!(recon_json["00080008"]["Value"][2], "OTHER");
+        assert_eq!(recon_json["00080008"]["vr"], "CS");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_integration_no_value() -> Result {
+        // the VR disagrees with the dictionary, which raises a Python warning
+        pyo3::prepare_freethreaded_python();
+
+        let dicom_json_text = r#"
+        {
+            "00080008": {
+                "vr": "PN"
+            } 
+        }
+        "#;
+        let ir_path = "/tmp/no_value.ir.json";
+        let dimble_path = "/tmp/no_value.dimble";
+        let ir_recon_path = "/tmp/no_value.ir.recon.json";
+
+        fs::write(ir_path, dicom_json_text).expect("should be able to write to file");
+
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        dimble_to_dicom_json(dimble_path, ir_recon_path)?;
+
+        let recon_json_reader = fs::File::open(ir_recon_path).expect("should be able to open file");
+        use serde_json::Value;
+        let recon_json: Value =
+            serde_json::from_reader(recon_json_reader).expect("should be able to read json");
+        assert_eq!(recon_json["00080008"]["vr"], "PN");
+        assert_eq!(recon_json["00080008"]["Value"], Value::Null);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_integration_inline_binary() -> Result {
+        // the VR disagrees with the dictionary, which raises a Python warning
+        pyo3::prepare_freethreaded_python();
+
+        let dicom_json_text = r#"
+        {
+            "00080008": {
+                "vr": "OB",
+                "InlineBinary": "ABCD"
+            } 
+        }
+        "#;
+        let ir_path = "/tmp/inline_binary.ir.json";
+        let dimble_path = "/tmp/inline_binary.dimble";
+        let ir_recon_path = "/tmp/inline_binary.ir.recon.json";
+
+        fs::write(ir_path, dicom_json_text).expect("should be able to write to file");
+
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        dimble_to_dicom_json(dimble_path, ir_recon_path)?;
+
+        let recon_json_reader = fs::File::open(ir_recon_path).expect("should be able to open file");
+        use serde_json::Value;
+        let recon_json: Value =
+            serde_json::from_reader(recon_json_reader).expect("should be able to read json");
+        assert_eq!(recon_json["00080008"]["vr"], "OB");
+        assert_eq!(recon_json["00080008"]["InlineBinary"], "ABCD");
+        // assert that there is no Value field
+        assert!(
+            !recon_json["00080008"]
+                .as_object()
+                .unwrap()
+                .contains_key("Value"),
+            "should not have Value field"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_person_name() -> Result {
+        pyo3::prepare_freethreaded_python();
+
+        let dicom_json_text = r#"
+        {
+            "00100010": {
+                "vr": "PN",
+                "Value": [
+                    {
+                        "Alphabetic": "Yamada^Tarou",
+                        "Ideographic": "山田^太郎",
+                        "Phonetic": "やまだ^たろう"
+                    }
+                ]
+            }
+        }
+        "#;
+        let ir_path = "/tmp/person_name.ir.json";
+        let dimble_path = "/tmp/person_name.dimble";
+        fs::write(ir_path, dicom_json_text)?;
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        let dataset = load_dimble(
+            dimble_path,
+            vec!["00100010"],
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )?;
+        Python::with_gil(|py| -> PyResult<()> {
+            let name = dataset.as_ref(py).get_item("00100010")?;
+            assert_eq!(name.getattr("family_name")?.extract::<String>()?, "Yamada");
+            assert_eq!(
+                name.getattr("ideographic")?.extract::<String>()?,
+                "山田^太郎"
+            );
+            let components = name.getattr("phonetic_components")?;
+            assert_eq!(
+                components.getattr("given_name")?.extract::<String>()?,
+                "たろう"
+            );
+            assert_eq!(
+                name.str()?.to_str()?,
+                "Yamada^Tarou=山田^太郎=やまだ^たろう"
+            );
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_nested_sequence() -> Result {
+        pyo3::prepare_freethreaded_python();
+
+        let dicom_json_text = r#"
+        {
+            "52009230": {
+                "vr": "SQ",
+                "Value": [
+                    {
+                        "00289110": {
+                            "vr": "SQ",
+                            "Value": [
+                                {"00280030": {"vr": "DS", "Value": [0.5, 0.5]}},
+                                {"00280030": {"vr": "DS", "Value": [0.25, 0.25]}}
+                            ]
+                        }
+                    },
+                    {"00289110": {"vr": "SQ", "Value": []}}
+                ]
+            }
+        }
+        "#;
+        let ir_path = "/tmp/nested_sequence.ir.json";
+        let dimble_path = "/tmp/nested_sequence.dimble";
+        fs::write(ir_path, dicom_json_text)?;
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        let dataset = load_dimble(
+            dimble_path,
+            vec!["52009230"],
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )?;
+        Python::with_gil(|py| -> PyResult<()> {
+            let frames = dataset.as_ref(py).get_item("52009230")?;
+            assert_eq!(frames.len()?, 2);
+            let spacing = frames
+                .get_item(0)?
+                .get_item("00289110")?
+                .get_item(1)?
+                .get_item("00280030")?
+                .extract::<Vec<f64>>()?;
+            assert_eq!(spacing, vec![0.25, 0.25]);
+            assert_eq!(frames.get_item(1)?.get_item("00289110")?.len()?, 0);
+            assert!(frames.get_item(-1)?.eq(frames.get_item(1)?)?);
+            assert!(frames
+                .get_item(2)
+                .unwrap_err()
+                .is_instance_of::<PyIndexError>(py));
+            assert_eq!(frames.iter()?.count(), 2);
+            Ok(())
+        })?;
+
+        let paths = vec![
+            "52009230[0].00289110[1].00280030",
+            "52009230[0].00289110",
+            "52009230[1]",
+        ];
+        let dataset = load_dimble(
+            dimble_path,
+            paths.clone(),
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )?;
+        Python::with_gil(|py| -> PyResult<()> {
+            let dataset = dataset.as_ref(py);
+            let spacing = dataset.get_item(paths[0])?.extract::<Vec<f64>>()?;
+            assert_eq!(spacing, vec![0.25, 0.25]);
+            let sequence = dataset.get_item(paths[1])?;
+            assert_eq!(sequence.len()?, 2);
+            assert_eq!(
+                sequence.repr()?.to_str()?,
+                r#"DimbleSequence("52009230[0].00289110", 2 items)"#
+            );
+            let item = dataset.get_item(paths[2])?;
+            assert!(item.get_item("00289110")?.eq(PyList::empty(py))?);
+            Ok(())
+        })?;
+
+        for path in [
+            "52009230[2]",
+            "52009230.00289110",
+            "52009230[0].00289110[0].00280030[0]",
+        ] {
+            let error = load_dimble(
+                dimble_path,
+                vec![path],
+                "cpu",
+                None,
+                false,
+                false,
+                "torch",
+                DtypeFallback::Widen,
+                PixelValues::Stored,
+                None,
+            )
+            .unwrap_err();
+            Python::with_gil(|py| assert!(error.is_instance_of::<InvalidPathError>(py), "{path}"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_tag_forms() -> Result {
+        pyo3::prepare_freethreaded_python();
+
+        let dicom_json_text = r#"{
+            "00080060": {"vr": "CS", "Value": ["MR"]},
+            "0008103E": {"vr": "LO", "Value": ["head"]}
+        }"#;
+        let ir_path = "/tmp/tag_forms.ir.json";
+        let dimble_path = "/tmp/tag_forms.dimble";
+        fs::write(ir_path, dicom_json_text)?;
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        let fields = vec!["Modality", "(0008,0060)", "0008103e", "(0008, 103E)"];
+        let dataset = load_dimble(
+            dimble_path,
+            fields.clone(),
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )?;
+        Python::with_gil(|py| -> PyResult<()> {
+            let dataset = dataset.as_ref(py).downcast::<PyDict>()?;
+            // fields keep the form they were asked for in
+            for (field, value) in fields.into_iter().zip(["MR", "MR", "head", "head"]) {
+                assert_eq!(dataset.get_item(field).unwrap().extract::<&str>()?, value);
+            }
+            Ok(())
+        })?;
+
+        let error = load_dimble(
+            dimble_path,
+            vec!["modality"],
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )
+        .unwrap_err();
+        Python::with_gil(|py| assert!(error.is_instance_of::<InvalidPathError>(py)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_keywords() -> Result {
+        pyo3::prepare_freethreaded_python();
+
+        let dicom_json_text = r#"{
+            "00080060": {"vr": "CS", "Value": ["MR"]},
+            "00091001": {"vr": "LO", "Value": ["private"]},
+            "52009230": {"vr": "SQ", "Value": [
+                {"00289110": {"vr": "SQ", "Value": [
+                    {"00280030": {"vr": "DS", "Value": [0.5, 0.5]}}
+                ]}}
+            ]}
+        }"#;
+        let ir_path = "/tmp/keywords.ir.json";
+        let dimble_path = "/tmp/keywords.dimble";
+        fs::write(ir_path, dicom_json_text)?;
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        let fields = vec![
+            "00080060",
+            "(0009,1001)",
+            "52009230",
+            "52009230[0].00289110[0]",
+        ];
+        let dataset = load_dimble(
+            dimble_path,
+            fields,
+            "cpu",
+            None,
+            true,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )?;
+        Python::with_gil(|py| -> PyResult<()> {
+            let dataset = dataset.as_ref(py).downcast::<PyDict>()?;
+            let keys: Vec<String> = dataset.keys().extract()?;
+            assert_eq!(
+                keys,
+                [
+                    "Modality",
+                    "00091001",
+                    "PerFrameFunctionalGroupsSequence",
+                    "PerFrameFunctionalGroupsSequence[0].PixelMeasuresSequence[0]",
+                ]
+            );
+            let sequence = dataset
+                .get_item("PerFrameFunctionalGroupsSequence")
+                .unwrap();
+            let pixel_spacing = sequence
+                .get_item(0)?
+                .get_item("PixelMeasuresSequence")?
+                .get_item(0)?
+                .get_item("PixelSpacing")?;
+            assert_eq!(pixel_spacing.extract::<Vec<f64>>()?, [0.5, 0.5]);
+            let item = dataset
+                .get_item("PerFrameFunctionalGroupsSequence[0].PixelMeasuresSequence[0]")
+                .unwrap();
+            assert!(item.get_item("PixelSpacing").is_ok());
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_pixel_values() -> Result {
+        use crate::reader::Element;
+        use crate::writer::DimbleWriter;
+
+        pyo3::prepare_freethreaded_python();
+
+        let ds = |value: f64| Element {
+            vr: *b"DS",
+            value: FieldValue::Float(value),
+        };
+        let pixels: Vec<u8> = [0u16, 1000, 1024, 1064]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let mut writer = DimbleWriter::new();
+        writer.insert_pixel_array(Dtype::U16, &[2, 2], &pixels)?;
+        let no_window_path = "/tmp/pixel_values_no_window.dimble";
+        writer.write(no_window_path)?;
+        writer.insert(Tag::new(0x0028, 0x1052), &ds(-1024.0))?;
+        writer.insert(Tag::new(0x0028, 0x1053), &ds(1.0))?;
+        writer.insert(Tag::new(0x0028, 0x1050), &ds(40.0))?;
+        writer.insert(Tag::new(0x0028, 0x1051), &ds(401.0))?;
+        let dimble_path = "/tmp/pixel_values.dimble";
+        writer.write(dimble_path)?;
+
+        let load = |path, values| {
+            load_dimble(
+                path,
+                vec!["7FE00010"],
+                "cpu",
+                None,
+                false,
+                false,
+                "dlpack",
+                DtypeFallback::Widen,
+                values,
+                None,
+            )
+        };
+        let pixel_values = |dataset: PyObject| {
+            Python::with_gil(|py| -> PyResult<Vec<f32>> {
+                let array = dataset.as_ref(py).get_item("7FE00010")?;
+                let array = array.downcast::<PyCell<PyPixelArray>>()?.borrow();
+                assert_eq!(array.dtype, Dtype::F32);
+                assert_eq!(array.shape, [2, 2]);
+                Ok(array.mmap[..16]
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                    .collect())
+            })
+        };
+        let rescaled = pixel_values(load(dimble_path, PixelValues::Rescaled)?)?;
+        assert_eq!(rescaled, [-1024.0, -24.0, 0.0, 40.0]);
+        let windowed = pixel_values(load(dimble_path, PixelValues::Windowed)?)?;
+        assert_eq!(windowed, [0.0, 0.34125, 0.40125, 0.50125]);
+
+        let error = load(no_window_path, PixelValues::Windowed).unwrap_err();
+        Python::with_gil(|py| assert!(error.is_instance_of::<MissingFieldError>(py)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_frames() -> Result {
+        use crate::writer::DimbleWriter;
+
+        pyo3::prepare_freethreaded_python();
+
+        let mut writer = DimbleWriter::new();
+        let pixels: Vec<u8> = (0..12).collect();
+        writer.insert_frames(Dtype::U8, 3, &[2, 2], &pixels)?;
+        let dimble_path = "/tmp/frames.dimble";
+        writer.write(dimble_path)?;
+
+        Python::with_gil(|py| -> PyResult<()> {
+            let info = pixel_array_info(dimble_path)?;
+            let info = info.as_ref(py);
+            assert_eq!(info.get_item("frames")?.extract::<usize>()?, 3);
+            assert_eq!(
+                info.get_item("frame_shape")?.extract::<Vec<usize>>()?,
+                [2, 2]
+            );
+            assert_eq!(info.get_item("shape")?.extract::<Vec<usize>>()?, [3, 2, 2]);
+            Ok(())
+        })?;
+
+        let load = |frames: Vec<usize>, values| {
+            load_dimble(
+                dimble_path,
+                vec!["7FE00010"],
+                "cpu",
+                None,
+                false,
+                false,
+                "dlpack",
+                DtypeFallback::Widen,
+                values,
+                Some(frames),
+            )
+        };
+        let pixel_array = |dataset: PyObject| {
+            Python::with_gil(|py| -> PyResult<(Vec<usize>, Vec<u8>)> {
+                let array = dataset.as_ref(py).get_item("7FE00010")?;
+                let array = array.downcast::<PyCell<PyPixelArray>>()?.borrow();
+                let length = array.shape.iter().product::<usize>() * array.dtype.size();
+                let data = array.mmap[array.offset..array.offset + length].to_vec();
+                Ok((array.shape.clone(), data))
+            })
+        };
+
+        // a contiguous run of frames is a view of just those frames
+        let (shape, data) = pixel_array(load(vec![1, 2], PixelValues::Stored)?)?;
+        assert_eq!(shape, [2, 2, 2]);
+        assert_eq!(data, &pixels[4..]);
+
+        // frames out of order are gathered into an array of their own
+        let (shape, data) = pixel_array(load(vec![2, 0], PixelValues::Stored)?)?;
+        assert_eq!(shape, [2, 2, 2]);
+        assert_eq!(data, [&pixels[8..], &pixels[..4]].concat());
+
+        let (shape, data) = pixel_array(load(vec![2, 0], PixelValues::Rescaled)?)?;
+        assert_eq!(shape, [2, 2, 2]);
+        let rescaled: Vec<f32> = data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(rescaled, [8.0, 9.0, 10.0, 11.0, 0.0, 1.0, 2.0, 3.0]);
+
+        let error = load(vec![3], PixelValues::Stored).unwrap_err();
+        Python::with_gil(|py| assert!(error.is_instance_of::<PyIndexError>(py)));
+        let error = load(vec![], PixelValues::Stored).unwrap_err();
+        Python::with_gil(|py| assert!(error.is_instance_of::<PyValueError>(py)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_chunked() -> Result {
+        pyo3::prepare_freethreaded_python();
+
+        // element values are their flat index
+        let pixels: Vec<u8> = (0..60).collect();
+        let safetensors_path = "/tmp/chunked.safetensors";
+        fs::write(
+            safetensors_path,
+            pixel_array::pixel_array_to_safetensors(Dtype::U8, &[3, 4, 5], Some(3), None, &pixels)?,
+        )?;
+        let ir_path = "/tmp/chunked.ir.json";
+        fs::write(
+            ir_path,
+            r#"{"7FE00010": {"vr": "OW", "InlineBinary": "placeholder"}}"#,
+        )?;
+        let dimble_path = "/tmp/chunked.dimble";
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            Some(safetensors_path),
+            Some(vec![2, 3, 2]),
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        Python::with_gil(|py| -> PyResult<()> {
+            let info = pixel_array_info(dimble_path)?;
+            let info = info.as_ref(py);
+            assert_eq!(info.get_item("shape")?.extract::<Vec<usize>>()?, [3, 4, 5]);
+            assert_eq!(
+                info.get_item("chunk_shape")?.extract::<Vec<usize>>()?,
+                [2, 3, 2]
+            );
+            Ok(())
+        })?;
+
+        let contiguous = PixelArray {
+            dtype: Dtype::U8,
+            shape: vec![3, 4, 5],
+            frames: Some(3),
+            data: &pixels,
+        };
+        let load = |frames: Option<Vec<usize>>| {
+            Python::with_gil(|py| -> PyResult<(Vec<usize>, Vec<u8>)> {
+                let reversed = py.eval("slice(None, None, -1)", None, None)?.downcast()?;
+                let slices = vec![
+                    PySlice::new(py, 0, 2, 1),
+                    reversed,
+                    PySlice::new(py, 1, 4, 1),
+                ];
+                let dataset = load_dimble(
+                    dimble_path,
+                    vec!["7FE00010"],
+                    "cpu",
+                    Some(slices),
+                    false,
+                    false,
+                    "dlpack",
+                    DtypeFallback::Widen,
+                    PixelValues::Stored,
+                    frames,
+                )?;
+                let array = dataset.as_ref(py).get_item("7FE00010")?;
+                let array = array.downcast::<PyCell<PyPixelArray>>()?.borrow();
+                let length = array.shape.iter().product::<usize>();
+                let data = array.mmap[array.offset..array.offset + length].to_vec();
+                Ok((array.shape.clone(), data))
+            })
+        };
+        let slices = [
+            Slice {
+                start: Some(0),
+                stop: Some(2),
+                step: 1,
+            },
+            Slice {
+                start: None,
+                stop: None,
+                step: -1,
+            },
+            Slice {
+                start: Some(1),
+                stop: Some(4),
+                step: 1,
+            },
+        ];
+
+        let (shape, data) = load(None)?;
+        let expected = contiguous.slice(&slices)?;
+        assert_eq!(shape, expected.shape);
+        assert_eq!(data, expected.data);
+
+        let (shape, data) = load(Some(vec![2, 0]))?;
+        let expected = contiguous
+            .gather_frames(&[2, 0])?
+            .as_pixel_array()
+            .slice(&slices)?;
+        assert_eq!(shape, expected.shape);
+        assert_eq!(data, expected.data);
+
+        let error = load(Some(vec![3])).unwrap_err();
+        Python::with_gil(|py| assert!(error.is_instance_of::<PyIndexError>(py)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_compressed() -> Result {
+        use crate::writer::DimbleWriter;
+
+        pyo3::prepare_freethreaded_python();
+
+        let mut writer = DimbleWriter::new();
+        writer.set_compression(Compression {
+            fields: Some(Codec::Lz4),
+            pixel_data: Some(Codec::Zstd),
+        });
+        let pixels: Vec<u8> = (0..48).map(|i| i / 4).collect();
+        writer.insert_frames(Dtype::U8, 3, &[4, 4], &pixels)?;
+        let dimble_path = "/tmp/compressed.dimble";
+        writer.write(dimble_path)?;
+
+        let load = |frames| {
+            Python::with_gil(|py| -> PyResult<(Vec<usize>, Vec<u8>)> {
+                let dataset = load_dimble(
+                    dimble_path,
+                    vec!["7FE00010"],
+                    "cpu",
+                    None,
+                    false,
+                    false,
+                    "dlpack",
+                    DtypeFallback::Widen,
+                    PixelValues::Stored,
+                    frames,
+                )?;
+                let array = dataset.as_ref(py).get_item("7FE00010")?;
+                let array = array.downcast::<PyCell<PyPixelArray>>()?.borrow();
+                let length = array.shape.iter().product::<usize>();
+                let data = array.mmap[array.offset..array.offset + length].to_vec();
+                Ok((array.shape.clone(), data))
+            })
+        };
+        let (shape, data) = load(None)?;
+        assert_eq!(shape, [3, 4, 4]);
+        assert_eq!(data, pixels);
+        let (shape, data) = load(Some(vec![2, 0]))?;
+        assert_eq!(shape, [2, 4, 4]);
+        assert_eq!(data, [&pixels[32..], &pixels[..16]].concat());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_typed() -> Result {
+        pyo3::prepare_freethreaded_python();
+
+        let dicom_json_text = r#"{
+            "00080020": {"vr": "DA", "Value": ["20230102"]},
+            "00080023": {"vr": "DA", "Value": ["20231301"]},
+            "00080032": {"vr": "TM", "Value": ["103045.25"]},
+            "0008002A": {"vr": "DT", "Value": ["20230102103045+1030"]},
+            "00080060": {"vr": "CS", "Value": ["MR"]},
+            "00101010": {"vr": "AS", "Value": ["003W"]},
+            "00200013": {"vr": "IS", "Value": [7]},
+            "00280030": {"vr": "DS", "Value": [0.5, 0.25]}
+        }"#;
+        let ir_path = "/tmp/typed.ir.json";
+        let dimble_path = "/tmp/typed.dimble";
+        fs::write(ir_path, dicom_json_text)?;
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+
+        let fields = vec![
+            "StudyDate",
+            "AcquisitionTime",
+            "AcquisitionDateTime",
+            "Modality",
+            "PatientAge",
+            "InstanceNumber",
+            "PixelSpacing",
+        ];
+        let dataset = load_dimble(
+            dimble_path,
+            fields,
+            "cpu",
+            None,
+            false,
+            true,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )?;
+        Python::with_gil(|py| -> PyResult<()> {
+            let dataset = dataset.as_ref(py).downcast::<PyDict>()?;
+            let get = |field| dataset.get_item(field).unwrap();
+            let date = get("StudyDate").downcast::<PyDate>()?;
+            assert_eq!(date.str()?.to_str()?, "2023-01-02");
+            let time = get("AcquisitionTime").downcast::<PyTime>()?;
+            assert_eq!(time.str()?.to_str()?, "10:30:45.250000");
+            let datetime = get("AcquisitionDateTime").downcast::<PyDateTime>()?;
+            assert_eq!(datetime.str()?.to_str()?, "2023-01-02 10:30:45+10:30");
+            assert_eq!(get("Modality").extract::<String>()?, "MR");
+            let age = get("PatientAge").downcast::<PyDelta>()?;
+            assert_eq!(age.str()?.to_str()?, "21 days, 0:00:00");
+            assert_eq!(get("InstanceNumber").extract::<Vec<i64>>()?, [7]);
+            assert_eq!(get("PixelSpacing").extract::<Vec<f64>>()?, [0.5, 0.25]);
+            Ok(())
+        })?;
+
+        let error = load_dimble(
+            dimble_path,
+            vec!["00080023"],
+            "cpu",
+            None,
+            false,
+            true,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )
+        .unwrap_err();
+        Python::with_gil(|py| {
+            assert!(error.is_instance_of::<CorruptFieldError>(py));
+            assert!(error.value(py).to_string().contains("20231301"));
+        });
+        // without `typed` the invalid date loads as the string it is
+        load_dimble(
+            dimble_path,
+            vec!["00080023"],
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dimble_errors_are_dimble_error_subclasses() -> Result {
+        pyo3::prepare_freethreaded_python();
+
+        let truncated_path = "/tmp/truncated.dimble";
+        fs::write(truncated_path, crate::format::Preamble::CURRENT.to_bytes())?;
+        let error = load_dimble(
+            truncated_path,
+            vec!["00080005"],
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )
+        .unwrap_err();
+        Python::with_gil(|py| {
+            assert!(error.is_instance_of::<CorruptFileError>(py));
+            assert!(error.is_instance_of::<DimbleError>(py));
+        });
+
+        let dicom_json_text = r#"{"00080005": {"vr": "CS", "Value": ["ISO_IR 100"]}}"#;
+        let ir_path = "/tmp/missing_field.ir.json";
+        let dimble_path = "/tmp/missing_field.dimble";
+        fs::write(ir_path, dicom_json_text)?;
+        dicom_json_to_dimble(
+            ir_path,
+            dimble_path,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_PIXEL_ALIGNMENT,
+        )?;
+        let error = load_dimble(
+            dimble_path,
+            vec!["00100010"],
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )
+        .unwrap_err();
+        Python::with_gil(|py| {
+            assert!(error.is_instance_of::<MissingFieldError>(py));
+            assert!(error.value(py).to_string().contains("00100010"));
+        });
+
+        let error = load_dimble(
+            "/tmp/does_not_exist.dimble",
+            vec![],
+            "cpu",
+            None,
+            false,
+            false,
+            "torch",
+            DtypeFallback::Widen,
+            PixelValues::Stored,
+            None,
+        )
+        .unwrap_err();
+        Python::with_gil(|py| {
+            assert!(error.is_instance_of::<pyo3::exceptions::PyFileNotFoundError>(py));
+        });
+
+        // an offset and length from a corrupt header that overflow past the end of the file
+        let error = load_pixel_array(
+            "testfiles/eye3.safetensors",
+            usize::MAX,
+            16,
+            "cpu",
+            None,
+            "dlpack",
+            DtypeFallback::Widen,
+            None,
+            None,
+        )
+        .unwrap_err();
+        Python::with_gil(|py| {
+            assert!(error.is_instance_of::<CorruptFieldError>(py));
+        });
+
+        Ok(())
+    }
+}
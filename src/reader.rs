@@ -0,0 +1,1221 @@
+use memmap2::{Mmap, MmapOptions};
+use rmpv::{decode::read_value, Value};
+use snafu::prelude::*;
+use std::{borrow::Cow, collections::BTreeMap, fs::File, path::Path, sync::OnceLock};
+
+use crate::checksum::{checksum, ChecksumError, Checksums};
+use crate::codec::{self, CodecError};
+use crate::format::{Preamble, PreambleError};
+use crate::header_index::{HeaderIndex, HeaderIndexError};
+use crate::ir_to_dimble::{HeaderField, HeaderFieldMap, HEADER_LENGTH_LENGTH, VR};
+use crate::person_name::PersonName;
+use crate::pixel_array::{self, PixelArray, PixelData, Rescale, Window};
+use crate::tag::{ParseTagError, Tag};
+use crate::typed_value::{ParseValueError, TypedValue};
+
+/// A decoded field value
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Empty,
+    String(String),
+    Integer(i64),
+    UnsignedInteger(u64),
+    Float(f64),
+    PersonName(PersonName),
+    /// Inline binary, such as the value of an OB or OW element
+    Binary(Vec<u8>),
+    /// The value is stored outside the file, at this URI
+    BulkDataUri(String),
+    Multiple(Vec<FieldValue>),
+    Sequence(Vec<Dataset>),
+}
+
+/// A decoded field along with its value representation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    pub vr: VR,
+    pub value: FieldValue,
+}
+
+impl Element {
+    /// Parses the value according to its VR, see [`TypedValue::parse`]
+    pub fn typed(self) -> Result<TypedValue, ParseValueError> {
+        TypedValue::parse(self.vr, self.value)
+    }
+}
+
+pub type Dataset = BTreeMap<Tag, Element>;
+
+/// Top level fields whose keys are not tags, which files written before the header keys became
+/// tags may have, such as the NIfTI header fields `dim[0]` and `descrip`
+pub type LegacyFieldMap = BTreeMap<String, HeaderField>;
+
+/// Stands in for the tag of a legacy field while it is decoded, see
+/// [`DimbleReader::legacy_element`]
+const LEGACY_FIELD_TAG: Tag = Tag::new(0xFFFF, 0xFFFF);
+
+const SHARED_PIXEL_VALUE_TRANSFORMATION: &str =
+    "SharedFunctionalGroupsSequence[0].PixelValueTransformationSequence[0]";
+const SHARED_FRAME_VOI_LUT: &str = "SharedFunctionalGroupsSequence[0].FrameVOILUTSequence[0]";
+
+/// What a field path such as `52009230[17].00289110.00280030` points at
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection {
+    /// A field at any depth of the header, with its tag
+    Field(Tag, HeaderField),
+    /// A single sequence item, when the path ends in an index
+    Item(HeaderFieldMap),
+}
+
+/// Read access to a memory mapped dimble file.
+pub struct DimbleReader {
+    mmap: Mmap,
+    preamble: Preamble,
+    /// Set for files with a header index, whose fields are decoded one at a time until the whole
+    /// header is asked for
+    index: Option<HeaderIndex>,
+    header: OnceLock<HeaderFieldMap>,
+    legacy_fields: LegacyFieldMap,
+    data_offset: usize,
+    /// The decompressed pixel data, once compressed pixel data that is not chunked is read
+    pixel_data: OnceLock<Vec<u8>>,
+}
+
+/// A header as it is stored on disk
+#[derive(Debug)]
+pub(crate) enum Header {
+    Map(HeaderFieldMap, LegacyFieldMap),
+    Index(HeaderIndex),
+}
+
+impl DimbleReader {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).context(CouldNotOpenSnafu { path })?;
+        let mmap = unsafe { MmapOptions::new().map(&file) }.context(CouldNotMapSnafu { path })?;
+        let (preamble, header, data_offset) = deserialise_header(&mmap)?;
+        let (index, header, legacy_fields) = match header {
+            Header::Map(header, legacy_fields) => (None, OnceLock::from(header), legacy_fields),
+            Header::Index(index) => (Some(index), OnceLock::new(), LegacyFieldMap::new()),
+        };
+        Ok(Self {
+            mmap,
+            preamble,
+            index,
+            header,
+            legacy_fields,
+            data_offset,
+            pixel_data: OnceLock::new(),
+        })
+    }
+
+    pub fn preamble(&self) -> Preamble {
+        self.preamble
+    }
+
+    /// Decodes the whole header. Use [`DimbleReader::header_field`] to look up a single field.
+    pub fn header(&self) -> Result<&HeaderFieldMap> {
+        if let Some(header) = self.header.get() {
+            return Ok(header);
+        }
+        let index = self
+            .index
+            .as_ref()
+            .expect("headers without an index are decoded on open");
+        let header = index
+            .decode_all(&self.mmap)
+            .context(InvalidHeaderIndexSnafu)?;
+        Ok(self.header.get_or_init(|| header))
+    }
+
+    fn find(&self, tag: Tag) -> Result<Option<HeaderField>> {
+        match (self.header.get(), &self.index) {
+            (Some(header), _) => Ok(header.get(&tag).cloned()),
+            (None, Some(index)) => index.get(&self.mmap, tag).context(InvalidHeaderIndexSnafu),
+            (None, None) => unreachable!("headers without an index are decoded on open"),
+        }
+    }
+
+    /// Looks up a top level field, binary searching the header index when the file has one
+    pub fn header_field(&self, tag: Tag) -> Result<HeaderField> {
+        self.find(tag)?.context(MissingFieldSnafu { tag })
+    }
+
+    /// The top level fields whose keys are not tags, which only files written before the header
+    /// keys became tags have. They are left out of [`DimbleReader::header`].
+    pub fn legacy_fields(&self) -> &LegacyFieldMap {
+        &self.legacy_fields
+    }
+
+    /// Decodes a field of [`DimbleReader::legacy_fields`]
+    pub fn legacy_element(&self, key: &str) -> Result<Element> {
+        let header_field = self
+            .legacy_fields
+            .get(key)
+            .context(MissingFieldSnafu { tag: key })?;
+        // legacy fields have no tag for errors to name, so they name the key instead
+        self.decode(LEGACY_FIELD_TAG, header_field)
+            .map_err(Box::new)
+            .context(InvalidLegacyFieldSnafu { key })
+    }
+
+    /// Offset within the file at which the field data starts
+    pub fn data_offset(&self) -> usize {
+        self.data_offset
+    }
+
+    /// The field data section, which deferred field offsets are relative to
+    pub fn data(&self) -> &[u8] {
+        &self.mmap[self.data_offset..]
+    }
+
+    /// Returns the absolute position and length within the file of a deferred field's bytes, which
+    /// are compressed if the field is. The field may still extend past the end of the file, which
+    /// [`DimbleReader::field_bytes`] checks.
+    pub fn field_location(&self, tag: Tag, header_field: &HeaderField) -> Result<(usize, usize)> {
+        let (offset, length) = match header_field {
+            HeaderField::Deffered(offset, length, _)
+            | HeaderField::Compressed(offset, length, ..) => (*offset, *length),
+            _ => return NotDeferredSnafu { tag }.fail(),
+        };
+        let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+        let length = usize::try_from(length).unwrap_or(usize::MAX);
+        let out_of_bounds = FieldOutOfBoundsSnafu {
+            tag,
+            offset: self.data_offset.saturating_add(offset),
+            length,
+            file_length: self.mmap.len(),
+        };
+        let position = self
+            .data_offset
+            .checked_add(offset)
+            .context(out_of_bounds)?;
+        position.checked_add(length).context(out_of_bounds)?;
+        Ok((position, length))
+    }
+
+    /// Returns the value bytes of a deferred field, borrowed from the memory map unless the field
+    /// is compressed. A compressed field must decompress to exactly the length recorded for it,
+    /// and the top level pixel data to exactly the lengths of its tensors.
+    pub fn field_value_bytes(&self, tag: Tag, header_field: &HeaderField) -> Result<Cow<'_, [u8]>> {
+        let bytes = self.field_bytes(tag, header_field)?;
+        match header_field {
+            HeaderField::Compressed(.., codec) if self.is_pixel_field(tag, header_field) => {
+                let (offset, _length) = self.field_location(tag, header_field)?;
+                Ok(Cow::Owned(
+                    pixel_array::compressed::decompress(*codec, bytes)
+                        .context(InvalidPixelArraySnafu { offset })?,
+                ))
+            }
+            HeaderField::Compressed(_, _, length, _, codec) => Ok(Cow::Owned(
+                codec::decompress(*codec, bytes, *length)
+                    .context(CouldNotDecompressSnafu { tag })?,
+            )),
+            _ => Ok(Cow::Borrowed(bytes)),
+        }
+    }
+
+    /// Whether `header_field` is the top level pixel data, rather than pixel data nested in a
+    /// sequence item, which is not a safetensors object
+    fn is_pixel_field(&self, tag: Tag, header_field: &HeaderField) -> bool {
+        tag == Tag::PIXEL_DATA
+            && self
+                .header_field(Tag::PIXEL_DATA)
+                .is_ok_and(|pixel_field| pixel_field == *header_field)
+    }
+
+    /// Returns the stored bytes of a deferred field, compressed if the field is
+    pub fn field_bytes(&self, tag: Tag, header_field: &HeaderField) -> Result<&[u8]> {
+        let (offset, length) = self.field_location(tag, header_field)?;
+        self.mmap
+            .get(offset..offset + length)
+            .context(FieldOutOfBoundsSnafu {
+                tag,
+                offset,
+                length,
+                file_length: self.mmap.len(),
+            })
+    }
+
+    pub fn element(&self, tag: Tag) -> Result<Element> {
+        ensure!(tag != Tag::PIXEL_DATA, PixelDataIsNotAValueSnafu);
+        let header_field = self.header_field(tag)?;
+        self.decode(tag, &header_field)
+    }
+
+    /// Looks up a field or sequence item by path without decoding it.
+    ///
+    /// A path is a list of tags separated by `.`, where every tag but the last is a sequence
+    /// followed by the index of one of its items, e.g. `52009230[17].00289110.00280030`. The last
+    /// tag may also be indexed to select a whole item. Tags take any form [`Tag`] parses, so
+    /// `PerFrameFunctionalGroupsSequence[17].(0028,9110)` works too.
+    pub fn select(&self, path: &str) -> Result<Selection> {
+        // none until the first tag is looked up at the top level of the header
+        let mut selection = None;
+        let mut end = 0;
+        for segment in path.split('.') {
+            end += segment.len();
+            let prefix = &path[..end];
+            end += 1;
+            let (tag, index) = parse_path_segment(path, segment)?;
+            let header_field = match selection {
+                None => self.find(tag)?,
+                Some(Selection::Item(mut item)) => item.remove(&tag),
+                Some(Selection::Field(..)) => return InvalidPathSnafu { path }.fail(),
+            }
+            .context(MissingFieldSnafu { tag: prefix })?;
+            selection = Some(match (header_field, index) {
+                (header_field, None) => Selection::Field(tag, header_field),
+                (HeaderField::SQ(mut items), Some(index)) => {
+                    ensure!(
+                        index < items.len(),
+                        ItemOutOfRangeSnafu {
+                            path: prefix,
+                            length: items.len(),
+                        }
+                    );
+                    Selection::Item(items.swap_remove(index))
+                }
+                (_, Some(_)) => NotASequenceSnafu { path: prefix }.fail()?,
+            });
+        }
+        Ok(selection.expect("a path has at least one segment"))
+    }
+
+    /// Checks the header and the stored bytes of every deferred field against the checksums
+    /// written with the file. Returns the paths, in the form [`DimbleReader::select`] takes, of the
+    /// fields that do not match, which is empty if none are corrupted. A header that does not match
+    /// is an error, as none of its fields can be trusted.
+    pub fn verify(&self) -> Result<Vec<String>> {
+        ensure!(self.preamble.has_checksums(), NoChecksumsSnafu);
+        let checksums =
+            Checksums::read(&self.mmap, self.data_offset).context(InvalidChecksumsSnafu)?;
+        ensure!(
+            checksum(&self.mmap[..self.data_offset]) == checksums.header,
+            CorruptHeaderSnafu
+        );
+        let mut corrupted = Vec::new();
+        self.verify_fields(self.header()?, &checksums, "", &mut corrupted);
+        Ok(corrupted)
+    }
+
+    fn verify_fields(
+        &self,
+        header: &HeaderFieldMap,
+        checksums: &Checksums,
+        prefix: &str,
+        corrupted: &mut Vec<String>,
+    ) {
+        for (tag, header_field) in header {
+            let path = format!("{prefix}{tag}");
+            match header_field {
+                HeaderField::Deffered(offset, length, _)
+                | HeaderField::Compressed(offset, length, ..) => {
+                    let data = &self.mmap[self.data_offset..checksums.start];
+                    let location = *offset as usize..offset.saturating_add(*length) as usize;
+                    let valid = checksums
+                        .field(*offset..offset.saturating_add(*length))
+                        .zip(data.get(location))
+                        .is_some_and(|(expected, bytes)| checksum(bytes) == expected);
+                    if !valid {
+                        corrupted.push(path);
+                    }
+                }
+                HeaderField::SQ(items) => {
+                    for (index, item) in items.iter().enumerate() {
+                        let prefix = format!("{path}[{index}].");
+                        self.verify_fields(item, checksums, &prefix, corrupted);
+                    }
+                }
+                HeaderField::Empty(_) | HeaderField::BulkDataURI(..) => {}
+            }
+        }
+    }
+
+    /// Decodes every field except the pixel data
+    pub fn dataset(&self) -> Result<Dataset> {
+        self.header()?
+            .iter()
+            .filter(|(&tag, _)| tag != Tag::PIXEL_DATA)
+            .map(|(&tag, header_field)| Ok((tag, self.decode(tag, header_field)?)))
+            .collect()
+    }
+
+    fn decode_fields(&self, header: &HeaderFieldMap) -> Result<Dataset> {
+        header
+            .iter()
+            .map(|(&tag, header_field)| Ok((tag, self.decode(tag, header_field)?)))
+            .collect()
+    }
+
+    /// Decodes a single header field, which need not be at the top level of the header
+    pub fn decode(&self, tag: Tag, header_field: &HeaderField) -> Result<Element> {
+        match header_field {
+            HeaderField::Deffered(_, _, vr) | HeaderField::Compressed(_, _, _, vr, _) => {
+                let bytes = self.field_value_bytes(tag, header_field)?;
+                let (offset, _length) = self.field_location(tag, header_field)?;
+                let mut cursor = bytes.as_ref();
+                let value = read_value(&mut cursor).context(InvalidFieldSnafu { tag, offset })?;
+                let value = value_to_field_value(tag, offset, value)?;
+                Ok(Element {
+                    vr: *vr,
+                    value: if vr == b"PN" {
+                        strings_to_person_names(value)
+                    } else {
+                        value
+                    },
+                })
+            }
+            HeaderField::Empty(vr) => Ok(Element {
+                vr: *vr,
+                value: FieldValue::Empty,
+            }),
+            HeaderField::BulkDataURI(uri, vr) => Ok(Element {
+                vr: *vr,
+                value: FieldValue::BulkDataUri(uri.clone()),
+            }),
+            HeaderField::SQ(items) => Ok(Element {
+                vr: *b"SQ",
+                value: FieldValue::Sequence(
+                    items
+                        .iter()
+                        .map(|item| self.decode_fields(item))
+                        .collect::<Result<_>>()?,
+                ),
+            }),
+        }
+    }
+
+    /// Decodes a single header field and parses it according to its stored VR
+    pub fn decode_typed(&self, tag: Tag, header_field: &HeaderField) -> Result<TypedValue> {
+        self.decode(tag, header_field)?
+            .typed()
+            .context(InvalidTypedValueSnafu { tag })
+    }
+
+    /// Borrows the pixel array straight out of the memory map. Fails for chunked pixel data, which
+    /// [`DimbleReader::pixel_data`] reads.
+    pub fn pixel_array(&self) -> Result<PixelArray<'_>> {
+        let (pixel_data, offset) = self.pixel_data_at()?;
+        pixel_data
+            .into_pixel_array()
+            .context(InvalidPixelArraySnafu { offset })
+    }
+
+    /// Borrows the pixel data straight out of the memory map, in whichever layout it was written
+    pub fn pixel_data(&self) -> Result<PixelData<'_>> {
+        Ok(self.pixel_data_at()?.0)
+    }
+
+    /// Offset within the file of the tensor data of the pixel data, which writers align to the
+    /// pixel alignment they are given. `None` if the pixel data is compressed, as its tensor data
+    /// is then not in the file as it is.
+    pub fn pixel_data_offset(&self) -> Result<Option<usize>> {
+        let header_field = self.header_field(Tag::PIXEL_DATA)?;
+        if matches!(header_field, HeaderField::Compressed(..)) {
+            return Ok(None);
+        }
+        let bytes = self.field_bytes(Tag::PIXEL_DATA, &header_field)?;
+        let (offset, _length) = self.field_location(Tag::PIXEL_DATA, &header_field)?;
+        let data_start =
+            pixel_array::data_start(bytes).context(InvalidPixelArraySnafu { offset })?;
+        Ok(Some(offset + data_start))
+    }
+
+    /// The pixel data along with its offset in the file. Compressed chunked pixel data keeps its
+    /// chunks compressed until they are read, while other compressed pixel data is decompressed
+    /// once and kept for the life of the reader.
+    fn pixel_data_at(&self) -> Result<(PixelData<'_>, usize)> {
+        let header_field = self.header_field(Tag::PIXEL_DATA)?;
+        let (offset, _length) = self.field_location(Tag::PIXEL_DATA, &header_field)?;
+        let bytes = self.field_bytes(Tag::PIXEL_DATA, &header_field)?;
+        let pixel_data = match header_field {
+            HeaderField::Compressed(.., codec) => {
+                pixel_array::compressed::pixel_data(codec, bytes, &self.pixel_data)
+            }
+            _ => pixel_array::pixel_data_from_safetensors(bytes),
+        }
+        .context(InvalidPixelArraySnafu { offset })?;
+        Ok((pixel_data, offset))
+    }
+
+    /// The rescale of the pixel data, from the top level attributes or else from the shared
+    /// functional groups of an enhanced multi-frame image. The identity if the file has none.
+    pub fn rescale(&self) -> Result<Rescale> {
+        let identity = Rescale::default();
+        Ok(Rescale {
+            slope: self
+                .first_decimal("RescaleSlope", SHARED_PIXEL_VALUE_TRANSFORMATION)?
+                .unwrap_or(identity.slope),
+            intercept: self
+                .first_decimal("RescaleIntercept", SHARED_PIXEL_VALUE_TRANSFORMATION)?
+                .unwrap_or(identity.intercept),
+        })
+    }
+
+    /// The first VOI window of the pixel data, found like [`DimbleReader::rescale`]
+    pub fn window(&self) -> Result<Option<Window>> {
+        let center = self.first_decimal("WindowCenter", SHARED_FRAME_VOI_LUT)?;
+        let width = self.first_decimal("WindowWidth", SHARED_FRAME_VOI_LUT)?;
+        let (Some(center), Some(width)) = (center, width) else {
+            return Ok(None);
+        };
+        ensure!(width >= 1.0, InvalidWindowSnafu { width });
+        Ok(Some(Window { center, width }))
+    }
+
+    /// The first value of a DS attribute at the top level, or else in the `functional_group`
+    fn first_decimal(&self, keyword: &str, functional_group: &str) -> Result<Option<f64>> {
+        for path in [keyword.to_string(), format!("{functional_group}.{keyword}")] {
+            let (tag, header_field) = match self.select(&path) {
+                Ok(Selection::Field(tag, header_field)) => (tag, header_field),
+                Ok(Selection::Item(_)) => unreachable!("the path ends in a tag"),
+                Err(Error::MissingField { .. } | Error::ItemOutOfRange { .. }) => continue,
+                Err(error) => return Err(error),
+            };
+            if let TypedValue::Decimals(values) = self.decode_typed(tag, &header_field)? {
+                if let Some(&value) = values.first() {
+                    return Ok(Some(value));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Splits `52009230[17]` into its tag and item index
+pub(crate) fn parse_path_segment(path: &str, segment: &str) -> Result<(Tag, Option<usize>)> {
+    let (tag, index) = match segment.split_once('[') {
+        Some((tag, index)) => {
+            let index = index
+                .strip_suffix(']')
+                .and_then(|index| index.parse().ok())
+                .context(InvalidPathSnafu { path })?;
+            (tag, Some(index))
+        }
+        None => (segment, None),
+    };
+    let tag = tag.parse().context(InvalidPathTagSnafu { path })?;
+    Ok((tag, index))
+}
+
+/// PN values are stored in their DICOM string form
+fn strings_to_person_names(value: FieldValue) -> FieldValue {
+    match value {
+        FieldValue::String(s) => FieldValue::PersonName(PersonName::parse(&s)),
+        FieldValue::Multiple(values) => {
+            FieldValue::Multiple(values.into_iter().map(strings_to_person_names).collect())
+        }
+        value => value,
+    }
+}
+
+fn value_to_field_value(tag: Tag, offset: usize, value: Value) -> Result<FieldValue> {
+    Ok(match value {
+        Value::Nil => FieldValue::Empty,
+        Value::String(s) => {
+            FieldValue::String(s.into_str().context(InvalidUtf8Snafu { tag, offset })?)
+        }
+        Value::Integer(i) => match i.as_i64() {
+            Some(v) => FieldValue::Integer(v),
+            None => FieldValue::UnsignedInteger(i.as_u64().expect("integer is either i64 or u64")),
+        },
+        Value::F32(f) => FieldValue::Float(f.into()),
+        Value::F64(f) => FieldValue::Float(f),
+        Value::Binary(b) => FieldValue::Binary(b),
+        Value::Array(a) => FieldValue::Multiple(
+            a.into_iter()
+                .map(|v| value_to_field_value(tag, offset, v))
+                .collect::<Result<_>>()?,
+        ),
+        other => UnsupportedValueSnafu {
+            tag,
+            offset,
+            value: other,
+        }
+        .fail()?,
+    })
+}
+
+/// Returns the preamble, the header and the offset at which the field data starts
+pub(crate) fn deserialise_header(buffer: &[u8]) -> Result<(Preamble, Header, usize)> {
+    let (preamble, preamble_len) = Preamble::read(buffer).context(InvalidPreambleSnafu)?;
+    let header_start = preamble_len + usize::from(HEADER_LENGTH_LENGTH);
+    let header_len = buffer
+        .get(preamble_len..header_start)
+        .context(TruncatedSnafu {
+            length: buffer.len(),
+        })?;
+    let header_len = u64::from_le_bytes(
+        header_len
+            .try_into()
+            .expect("slice has the length of a u64"),
+    ) as usize;
+    let header_out_of_bounds = HeaderOutOfBoundsSnafu {
+        header_len,
+        offset: preamble_len,
+        length: buffer.len(),
+    };
+    let header_end = header_start
+        .checked_add(header_len)
+        .context(header_out_of_bounds)?;
+    let header_range = header_start..header_end;
+    let header = buffer
+        .get(header_range.clone())
+        .context(header_out_of_bounds)?;
+    let header = if preamble.has_header_index() {
+        Header::Index(HeaderIndex::read(buffer, header_range).context(InvalidHeaderIndexSnafu)?)
+    } else {
+        // headers written before the keys became tags may have other keys, such as NIfTI's
+        let fields: LegacyFieldMap = rmp_serde::from_slice(header).context(InvalidHeaderSnafu)?;
+        let mut header = HeaderFieldMap::new();
+        let mut legacy_fields = LegacyFieldMap::new();
+        for (key, header_field) in fields {
+            match key.parse::<Tag>() {
+                // tags are written as hex, so a key that only parses as a keyword is a legacy key
+                Ok(tag) if tag.to_string().eq_ignore_ascii_case(&key) => {
+                    header.insert(tag, header_field);
+                }
+                _ => {
+                    legacy_fields.insert(key, header_field);
+                }
+            }
+        }
+        Header::Map(header, legacy_fields)
+    };
+    Ok((preamble, header, header_end))
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Could not open {}", path.display()))]
+    CouldNotOpen {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+
+    #[snafu(display("Could not memory map {}", path.display()))]
+    CouldNotMap {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+
+    #[snafu(display("Could not read the dimble preamble"))]
+    InvalidPreamble { source: PreambleError },
+
+    #[snafu(display("File is only {length} bytes, too short for a dimble header"))]
+    Truncated { length: usize },
+
+    #[snafu(display(
+        "Header length {header_len} at byte offset {offset} exceeds the file length {length}"
+    ))]
+    HeaderOutOfBounds {
+        header_len: usize,
+        offset: usize,
+        length: usize,
+    },
+
+    #[snafu(display("Could not deserialise the dimble header"))]
+    InvalidHeader { source: rmp_serde::decode::Error },
+
+    #[snafu(display("Could not read the dimble header index"))]
+    InvalidHeaderIndex { source: HeaderIndexError },
+
+    /// `tag` is the path up to the missing field when it was looked up by path
+    #[snafu(display("Field {tag} not found"))]
+    MissingField { tag: String },
+
+    #[snafu(display(
+        "Field path {path} is not a list of tags and item indices like 52009230[17].00289110"
+    ))]
+    InvalidPath { path: String },
+
+    #[snafu(display("Field path {path} has an invalid tag"))]
+    InvalidPathTag { source: ParseTagError, path: String },
+
+    #[snafu(display("Field {path} is not a sequence, so it has no items"))]
+    NotASequence { path: String },
+
+    #[snafu(display("Sequence item {path} is out of range, the sequence has {length} items"))]
+    ItemOutOfRange { path: String, length: usize },
+
+    #[snafu(display("Field {tag} has no deferred value"))]
+    NotDeferred { tag: Tag },
+
+    #[snafu(display(
+        "Field {tag} at byte offset {offset} with length {length} extends past the end of the file ({file_length} bytes)"
+    ))]
+    FieldOutOfBounds {
+        tag: Tag,
+        offset: usize,
+        length: usize,
+        file_length: usize,
+    },
+
+    #[snafu(display("Field {tag} could not be decompressed"))]
+    CouldNotDecompress { source: CodecError, tag: Tag },
+
+    #[snafu(display("Field {tag} at byte offset {offset} is not valid MessagePack"))]
+    InvalidField {
+        source: rmpv::decode::Error,
+        tag: Tag,
+        offset: usize,
+    },
+
+    #[snafu(display("Field {tag} at byte offset {offset} is not valid UTF-8"))]
+    InvalidUtf8 { tag: Tag, offset: usize },
+
+    #[snafu(display("Field {tag} at byte offset {offset} has unsupported value {value}"))]
+    UnsupportedValue {
+        tag: Tag,
+        offset: usize,
+        value: Value,
+    },
+
+    #[snafu(display("Window width {width} is less than 1"))]
+    InvalidWindow { width: f64 },
+
+    #[snafu(display("Field {tag} does not match its VR"))]
+    InvalidTypedValue { source: ParseValueError, tag: Tag },
+
+    #[snafu(display("Pixel data is not a field value, use `pixel_array` instead"))]
+    PixelDataIsNotAValue,
+
+    #[snafu(display("File was written without checksums, so it cannot be verified"))]
+    NoChecksums,
+
+    #[snafu(display("Could not read the checksums at the end of the file"))]
+    InvalidChecksums { source: ChecksumError },
+
+    #[snafu(display("Header does not match its checksum"))]
+    CorruptHeader,
+
+    #[snafu(display("Legacy field {key} could not be decoded"))]
+    InvalidLegacyField { source: Box<Error>, key: String },
+
+    #[snafu(display("Pixel data at byte offset {offset} is not a valid safetensors object"))]
+    InvalidPixelArray {
+        source: pixel_array::Error,
+        offset: usize,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Compression;
+    use crate::pixel_array::Dtype;
+    use serde::Serialize;
+
+    fn header_map(header: Header) -> HeaderFieldMap {
+        match header {
+            Header::Map(header, _legacy_fields) => header,
+            Header::Index(_) => panic!("expected a MessagePack header map"),
+        }
+    }
+
+    #[test]
+    fn test_header_deserialisation_single_string() {
+        let buffer = {
+            let mut header_fields = HeaderFieldMap::new();
+            let vr = b"CS";
+            header_fields.insert(Tag::new(0x0008, 0x0005), HeaderField::Deffered(0, 4, *vr));
+
+            // serialise to buffer and prepend with header length
+            let mut buffer = Vec::new();
+            let header_bytes = rmp_serde::to_vec(&header_fields).unwrap();
+            let header_len = header_bytes.len() as u64;
+            buffer.extend_from_slice(&header_len.to_le_bytes());
+            buffer.extend_from_slice(&header_bytes);
+            buffer
+        };
+
+        let (preamble, header, _data_offset) = deserialise_header(&buffer).unwrap();
+        assert_eq!(preamble, Preamble::V0);
+        let header = header_map(header);
+        if let HeaderField::Deffered(offset, length, vr) =
+            *header.get(&Tag::new(0x0008, 0x0005)).unwrap()
+        {
+            assert_eq!(offset, 0);
+            assert_eq!(length, 4);
+            assert_eq!(vr, *b"CS");
+        } else {
+            panic!("expected deffered header field");
+        }
+    }
+
+    #[test]
+    fn test_header_deserialisation_no_value() {
+        let buffer = {
+            let mut header_fields = HeaderFieldMap::new();
+            let vr = b"PN";
+            header_fields.insert(Tag::new(0x0010, 0x0010), HeaderField::Empty(*vr));
+
+            // serialise to buffer and prepend with header length
+            let mut buffer = Vec::new();
+            let header_bytes = rmp_serde::to_vec(&header_fields).unwrap();
+            let header_len = header_bytes.len() as u64;
+            buffer.extend_from_slice(&header_len.to_le_bytes());
+            buffer.extend_from_slice(&header_bytes);
+            buffer
+        };
+
+        let (_preamble, header, _data_offset) = deserialise_header(&buffer).unwrap();
+        assert!(matches!(
+            header_map(header).get(&Tag::new(0x0010, 0x0010)),
+            Some(HeaderField::Empty(vr)) if vr == b"PN"
+        ));
+    }
+
+    #[test]
+    fn test_legacy_nifti_file() {
+        // as nifti_to_dimble wrote them before the header keys became tags, without a preamble
+        let pixels: Vec<u8> = [0.5f32, 1.5].iter().flat_map(|p| p.to_le_bytes()).collect();
+        let safetensors =
+            pixel_array::pixel_array_to_safetensors(Dtype::F32, &[1, 2], None, None, &pixels)
+                .unwrap();
+        let mut data_bytes = Vec::new();
+        let mut fields = LegacyFieldMap::new();
+        for (key, value) in [("descrip", "a scan"), ("dim[0]", "3")] {
+            let value = rmp_serde::to_vec(value).unwrap();
+            let offset = data_bytes.len() as u64;
+            fields.insert(
+                key.to_string(),
+                HeaderField::Deffered(offset, value.len() as u64, *b"CS"),
+            );
+            data_bytes.extend(value);
+        }
+        fields.insert(
+            "7FE00010".to_string(),
+            HeaderField::Deffered(data_bytes.len() as u64, safetensors.len() as u64, *b"OW"),
+        );
+        data_bytes.extend(safetensors);
+        let mut header_bytes = Vec::new();
+        fields
+            .serialize(&mut rmp_serde::Serializer::new(&mut header_bytes).with_struct_map())
+            .unwrap();
+        let mut buffer = (header_bytes.len() as u64).to_le_bytes().to_vec();
+        buffer.extend(header_bytes);
+        buffer.extend(data_bytes);
+        let dimble_path = "/tmp/legacy_nifti.dimble";
+        std::fs::write(dimble_path, buffer).unwrap();
+
+        let reader = DimbleReader::open(dimble_path).unwrap();
+        assert_eq!(reader.preamble(), Preamble::V0);
+        assert_eq!(
+            reader.header().unwrap().keys().collect::<Vec<_>>(),
+            [&Tag::PIXEL_DATA]
+        );
+        assert_eq!(
+            reader.legacy_fields().keys().collect::<Vec<_>>(),
+            ["descrip", "dim[0]"]
+        );
+        assert_eq!(
+            reader.legacy_element("dim[0]").unwrap(),
+            Element {
+                vr: *b"CS",
+                value: FieldValue::String("3".to_string())
+            }
+        );
+        assert!(matches!(
+            reader.legacy_element("dim[1]"),
+            Err(Error::MissingField { .. })
+        ));
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.dtype, Dtype::F32);
+        assert_eq!(pixel_array.shape, [1, 2]);
+    }
+
+    #[test]
+    fn test_header_deserialisation_versioned() {
+        let v1 = Preamble {
+            major: 1,
+            minor: 1,
+            flags: 0,
+        };
+        let buffer = {
+            let mut header_fields = HeaderFieldMap::new();
+            let vr = b"CS";
+            header_fields.insert(Tag::new(0x0008, 0x0005), HeaderField::Deffered(0, 4, *vr));
+
+            // serialise to buffer and prepend with preamble and header length
+            let mut buffer = v1.to_bytes().to_vec();
+            let header_bytes = rmp_serde::to_vec(&header_fields).unwrap();
+            let header_len = header_bytes.len() as u64;
+            buffer.extend_from_slice(&header_len.to_le_bytes());
+            buffer.extend_from_slice(&header_bytes);
+            buffer
+        };
+
+        let (preamble, header, data_offset) = deserialise_header(&buffer).unwrap();
+        assert_eq!(preamble, v1);
+        assert_eq!(data_offset, buffer.len());
+        assert!(header_map(header).contains_key(&Tag::new(0x0008, 0x0005)));
+    }
+
+    #[test]
+    fn test_header_deserialisation_indexed() {
+        let mut header_fields = HeaderFieldMap::new();
+        header_fields.insert(
+            Tag::new(0x0008, 0x0005),
+            HeaderField::Deffered(0, 1, *b"CS"),
+        );
+        header_fields.insert(Tag::new(0x0010, 0x0010), HeaderField::Empty(*b"PN"));
+        let dimble_path = "/tmp/header_index.dimble";
+        crate::ir_to_dimble::serialise_dimble_fields(
+            &header_fields,
+            &[0xC0],
+            None,
+            dimble_path,
+            Compression::default(),
+            crate::ir_to_dimble::DEFAULT_PIXEL_ALIGNMENT,
+        )
+        .unwrap();
+
+        let reader = DimbleReader::open(dimble_path).unwrap();
+        assert_eq!(reader.preamble(), Preamble::CURRENT);
+        assert!(reader.header.get().is_none());
+        assert_eq!(
+            reader.header_field(Tag::new(0x0010, 0x0010)).unwrap(),
+            HeaderField::Empty(*b"PN")
+        );
+        assert_eq!(
+            reader.element(Tag::new(0x0008, 0x0005)).unwrap().value,
+            FieldValue::Empty
+        );
+        // single field lookups leave the rest of the header undecoded
+        assert!(reader.header.get().is_none());
+        assert_eq!(reader.header().unwrap(), &header_fields);
+    }
+
+    #[test]
+    fn test_header_deserialisation_truncated() {
+        let buffer = Preamble::CURRENT.to_bytes();
+        assert!(matches!(
+            deserialise_header(&buffer),
+            Err(Error::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn test_header_deserialisation_header_beyond_file() {
+        let mut buffer = Preamble::CURRENT.to_bytes().to_vec();
+        buffer.extend_from_slice(&1024u64.to_le_bytes());
+        buffer.extend_from_slice(&[0x80]);
+        assert!(matches!(
+            deserialise_header(&buffer),
+            Err(Error::HeaderOutOfBounds {
+                header_len: 1024,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_header_deserialisation_header_length_overflows() {
+        let mut buffer = Preamble::CURRENT.to_bytes().to_vec();
+        buffer.extend_from_slice(&u64::MAX.to_le_bytes());
+        buffer.extend_from_slice(&[0x80]);
+        assert!(matches!(
+            deserialise_header(&buffer),
+            Err(Error::HeaderOutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_field_reports_tag_and_offset() {
+        let mut header_fields = HeaderFieldMap::new();
+        header_fields.insert(
+            Tag::new(0x0008, 0x0005),
+            HeaderField::Deffered(0, 1, *b"CS"),
+        );
+        header_fields.insert(
+            Tag::new(0x0008, 0x0008),
+            HeaderField::Deffered(1, 64, *b"CS"),
+        );
+        header_fields.insert(
+            Tag::new(0x0008, 0x0016),
+            HeaderField::Deffered(u64::MAX, 1, *b"UI"),
+        );
+        header_fields.insert(
+            Tag::new(0x0008, 0x0018),
+            HeaderField::Deffered(1, u64::MAX, *b"UI"),
+        );
+        // a 5 byte string with no string bytes following it
+        let data_bytes = [0xA5];
+        let dimble_path = "/tmp/corrupt_field.dimble";
+        crate::ir_to_dimble::serialise_dimble_fields(
+            &header_fields,
+            &data_bytes,
+            None,
+            dimble_path,
+            Compression::default(),
+            crate::ir_to_dimble::DEFAULT_PIXEL_ALIGNMENT,
+        )
+        .unwrap();
+
+        let reader = DimbleReader::open(dimble_path).unwrap();
+        let error = reader.element(Tag::new(0x0008, 0x0005)).unwrap_err();
+        assert!(matches!(error, Error::InvalidField { .. }));
+        let message = error.to_string();
+        assert!(message.contains("00080005"), "{message}");
+        assert!(
+            message.contains(&reader.data_offset().to_string()),
+            "{message}"
+        );
+
+        assert!(matches!(
+            reader.element(Tag::new(0x0008, 0x0008)),
+            Err(Error::FieldOutOfBounds { length: 64, .. })
+        ));
+        for tag in [Tag::new(0x0008, 0x0016), Tag::new(0x0008, 0x0018)] {
+            assert!(matches!(
+                reader.element(tag),
+                Err(Error::FieldOutOfBounds { .. })
+            ));
+        }
+        assert!(matches!(
+            reader.element(Tag::new(0x0010, 0x0010)),
+            Err(Error::MissingField { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify() {
+        let item = HeaderFieldMap::from([(
+            Tag::new(0x0028, 0x0030),
+            HeaderField::Deffered(1, 1, *b"DS"),
+        )]);
+        let mut header_fields = HeaderFieldMap::new();
+        header_fields.insert(
+            Tag::new(0x0008, 0x0060),
+            HeaderField::Deffered(0, 1, *b"CS"),
+        );
+        header_fields.insert(
+            Tag::new(0x5200, 0x9230),
+            HeaderField::SQ(vec![item.clone(), item]),
+        );
+        header_fields.insert(Tag::new(0x0010, 0x0010), HeaderField::Empty(*b"PN"));
+        let dimble_path = "/tmp/verify.dimble";
+        crate::ir_to_dimble::serialise_dimble_fields(
+            &header_fields,
+            &[0xC0, 0xC0],
+            None,
+            dimble_path,
+            Compression::default(),
+            crate::ir_to_dimble::DEFAULT_PIXEL_ALIGNMENT,
+        )
+        .unwrap();
+        let reader = DimbleReader::open(dimble_path).unwrap();
+        assert!(reader.preamble().has_checksums());
+        assert!(reader.verify().unwrap().is_empty());
+        let data_offset = reader.data_offset();
+        drop(reader);
+
+        // both items share the corrupted bytes
+        let mut file = std::fs::read(dimble_path).unwrap();
+        file[data_offset + 1] ^= 0xFF;
+        let corrupt_field_path = "/tmp/verify_corrupt_field.dimble";
+        std::fs::write(corrupt_field_path, &file).unwrap();
+        assert_eq!(
+            DimbleReader::open(corrupt_field_path)
+                .unwrap()
+                .verify()
+                .unwrap(),
+            ["52009230[0].00280030", "52009230[1].00280030"]
+        );
+
+        // the header checksum is the second to last word of the file
+        file[data_offset + 1] ^= 0xFF;
+        let header_checksum = file.len() - 16;
+        file[header_checksum] ^= 0xFF;
+        let corrupt_header_path = "/tmp/verify_corrupt_header.dimble";
+        std::fs::write(corrupt_header_path, &file).unwrap();
+        assert!(matches!(
+            DimbleReader::open(corrupt_header_path).unwrap().verify(),
+            Err(Error::CorruptHeader)
+        ));
+    }
+
+    #[test]
+    fn test_verify_without_checksums() {
+        let header_bytes = rmp_serde::to_vec(&HeaderFieldMap::new()).unwrap();
+        let mut buffer = (header_bytes.len() as u64).to_le_bytes().to_vec();
+        buffer.extend_from_slice(&header_bytes);
+        let dimble_path = "/tmp/verify_without_checksums.dimble";
+        std::fs::write(dimble_path, &buffer).unwrap();
+        assert!(matches!(
+            DimbleReader::open(dimble_path).unwrap().verify(),
+            Err(Error::NoChecksums)
+        ));
+    }
+
+    #[test]
+    fn test_select_path() {
+        let spacing = HeaderField::Deffered(0, 1, *b"DS");
+        let frame = |spacing: &HeaderField| {
+            HeaderFieldMap::from([(
+                Tag::new(0x0028, 0x9110),
+                HeaderField::SQ(vec![HeaderFieldMap::from([(
+                    Tag::new(0x0028, 0x0030),
+                    spacing.clone(),
+                )])]),
+            )])
+        };
+        let mut header_fields = HeaderFieldMap::new();
+        header_fields.insert(
+            Tag::new(0x5200, 0x9230),
+            HeaderField::SQ(vec![frame(&HeaderField::Empty(*b"DS")), frame(&spacing)]),
+        );
+        header_fields.insert(Tag::new(0x0008, 0x0060), HeaderField::Empty(*b"CS"));
+        let dimble_path = "/tmp/select_path.dimble";
+        crate::ir_to_dimble::serialise_dimble_fields(
+            &header_fields,
+            &[0xC0],
+            None,
+            dimble_path,
+            Compression::default(),
+            crate::ir_to_dimble::DEFAULT_PIXEL_ALIGNMENT,
+        )
+        .unwrap();
+        let reader = DimbleReader::open(dimble_path).unwrap();
+
+        assert_eq!(
+            reader.select("52009230[1].00289110[0].00280030").unwrap(),
+            Selection::Field(Tag::new(0x0028, 0x0030), spacing.clone())
+        );
+        assert_eq!(
+            reader
+                .select("PerFrameFunctionalGroupsSequence[1].(0028,9110)[0].PixelSpacing")
+                .unwrap(),
+            Selection::Field(Tag::new(0x0028, 0x0030), spacing)
+        );
+        assert_eq!(
+            reader.select("52009230[0]").unwrap(),
+            Selection::Item(frame(&HeaderField::Empty(*b"DS")))
+        );
+        assert!(matches!(
+            reader.select("Modality"),
+            Ok(Selection::Field(_, HeaderField::Empty(_)))
+        ));
+
+        assert!(matches!(
+            reader.select("52009230[2].00289110"),
+            Err(Error::ItemOutOfRange { length: 2, .. })
+        ));
+        assert!(matches!(
+            reader.select("52009230[1].00289110[0].00280030[0]"),
+            Err(Error::NotASequence { path }) if path == "52009230[1].00289110[0].00280030[0]"
+        ));
+        assert!(matches!(
+            reader.select("52009230[0].00080100"),
+            Err(Error::MissingField { tag }) if tag == "52009230[0].00080100"
+        ));
+        for path in ["52009230.00289110", "52009230[x]", "52009230[1"] {
+            assert!(
+                matches!(reader.select(path), Err(Error::InvalidPath { .. })),
+                "{path:?} should be invalid"
+            );
+        }
+        for path in [
+            "",
+            "[1]",
+            "dim[0]",
+            "52009230[0].0028911",
+            "Modality.pixelspacing",
+        ] {
+            assert!(
+                matches!(reader.select(path), Err(Error::InvalidPathTag { .. })),
+                "{path:?} should have an invalid tag"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rescale_and_window() {
+        use crate::writer::DimbleWriter;
+
+        let ds = |values: &[f64]| Element {
+            vr: *b"DS",
+            value: FieldValue::Multiple(values.iter().map(|&v| FieldValue::Float(v)).collect()),
+        };
+        let sequence = |items: Vec<Dataset>| Element {
+            vr: *b"SQ",
+            value: FieldValue::Sequence(items),
+        };
+        let write = |writer: DimbleWriter, name: &str| {
+            let path = format!("/tmp/{name}.dimble");
+            writer.write(&path).unwrap();
+            DimbleReader::open(path).unwrap()
+        };
+
+        let reader = write(DimbleWriter::new(), "no_rescale");
+        assert_eq!(reader.rescale().unwrap(), Rescale::default());
+        assert_eq!(reader.window().unwrap(), None);
+
+        let mut writer = DimbleWriter::new();
+        writer
+            .insert(Tag::new(0x0028, 0x1053), &ds(&[2.0]))
+            .unwrap();
+        writer
+            .insert(Tag::new(0x0028, 0x1052), &ds(&[-1024.0]))
+            .unwrap();
+        writer
+            .insert(Tag::new(0x0028, 0x1050), &ds(&[40.0, 600.0]))
+            .unwrap();
+        writer
+            .insert(Tag::new(0x0028, 0x1051), &ds(&[400.0, 1200.0]))
+            .unwrap();
+        let reader = write(writer, "top_level_rescale");
+        let rescale = Rescale {
+            slope: 2.0,
+            intercept: -1024.0,
+        };
+        assert_eq!(reader.rescale().unwrap(), rescale);
+        let window = Window {
+            center: 40.0,
+            width: 400.0,
+        };
+        assert_eq!(reader.window().unwrap(), Some(window));
+
+        // enhanced multi-frame images keep them in the shared functional groups
+        let mut writer = DimbleWriter::new();
+        let transformation = Dataset::from([
+            (Tag::new(0x0028, 0x1053), ds(&[2.0])),
+            (Tag::new(0x0028, 0x1052), ds(&[-1024.0])),
+        ]);
+        let voi_lut = Dataset::from([
+            (Tag::new(0x0028, 0x1050), ds(&[40.0])),
+            (Tag::new(0x0028, 0x1051), ds(&[0.5])),
+        ]);
+        let shared = Dataset::from([
+            (Tag::new(0x0028, 0x9145), sequence(vec![transformation])),
+            (Tag::new(0x0028, 0x9132), sequence(vec![voi_lut])),
+        ]);
+        writer
+            .insert(Tag::new(0x5200, 0x9229), &sequence(vec![shared]))
+            .unwrap();
+        let reader = write(writer, "shared_rescale");
+        assert_eq!(reader.rescale().unwrap(), rescale);
+        assert!(matches!(
+            reader.window(),
+            Err(Error::InvalidWindow { width }) if width == 0.5
+        ));
+    }
+
+    #[test]
+    fn test_header_deserialisation_unknown_major_version() {
+        let mut buffer = Preamble::CURRENT.to_bytes().to_vec();
+        buffer[crate::format::MAGIC.len()] = 0xFF;
+        buffer.extend_from_slice(&0u64.to_le_bytes());
+
+        assert!(matches!(
+            deserialise_header(&buffer),
+            Err(Error::InvalidPreamble {
+                source: PreambleError::UnsupportedVersion { .. }
+            })
+        ));
+    }
+}
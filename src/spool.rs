@@ -0,0 +1,80 @@
+//! Bytes appended to a temporary file rather than held in memory, and mapped back in to be read.
+
+use memmap2::{Mmap, MmapOptions};
+use std::{
+    fs,
+    io::{self, Write},
+    ops::Deref,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A temporary file that bytes are appended to, removed when dropped
+#[derive(Debug)]
+pub(crate) struct Spool {
+    file: fs::File,
+    path: PathBuf,
+    length: u64,
+}
+
+impl Spool {
+    /// Creates an empty spool in the temporary directory
+    pub(crate) fn new() -> io::Result<Self> {
+        static SPOOLS: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            ".dimble.{}.{}.spool",
+            process::id(),
+            SPOOLS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self {
+            file,
+            path,
+            length: 0,
+        })
+    }
+
+    /// Appends `bytes`, returning the offset they start at
+    pub(crate) fn append(&mut self, bytes: &[u8]) -> io::Result<u64> {
+        let offset = self.length;
+        self.file.write_all(bytes)?;
+        self.length += bytes.len() as u64;
+        Ok(offset)
+    }
+
+    /// Maps the bytes appended so far
+    pub(crate) fn bytes(&self) -> io::Result<SpoolBytes> {
+        if self.length == 0 {
+            // an empty file cannot be mapped
+            return Ok(SpoolBytes(None));
+        }
+        let length = usize::try_from(self.length)
+            .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "spool is too large to map"))?;
+        let mmap = unsafe { MmapOptions::new().len(length).map(&self.file) }?;
+        Ok(SpoolBytes(Some(mmap)))
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        // the file may already be gone, and there is nothing to do about it if so
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The bytes of a [`Spool`], mapped into memory
+pub(crate) struct SpoolBytes(Option<Mmap>);
+
+impl Deref for SpoolBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0.as_deref().unwrap_or_default()
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use snafu::prelude::*;
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use crate::dictionary;
+
+/// A DICOM attribute tag.
+///
+/// Tags parse from eight hex digits (`7FE00010`, in either case), the parenthesised form
+/// `(7FE0,0010)` or a keyword such as `PixelData`, and display as the upper case hex digits used
+/// as keys in the DICOM JSON model. They order by group, then element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag {
+    pub group: u16,
+    pub element: u16,
+}
+
+impl Tag {
+    pub const TRANSFER_SYNTAX_UID: Tag = Tag::new(0x0002, 0x0010);
+    pub const SOP_CLASS_UID: Tag = Tag::new(0x0008, 0x0016);
+    pub const SOP_INSTANCE_UID: Tag = Tag::new(0x0008, 0x0018);
+    pub const SAMPLES_PER_PIXEL: Tag = Tag::new(0x0028, 0x0002);
+    pub const PLANAR_CONFIGURATION: Tag = Tag::new(0x0028, 0x0006);
+    pub const NUMBER_OF_FRAMES: Tag = Tag::new(0x0028, 0x0008);
+    pub const ROWS: Tag = Tag::new(0x0028, 0x0010);
+    pub const COLUMNS: Tag = Tag::new(0x0028, 0x0011);
+    pub const BITS_ALLOCATED: Tag = Tag::new(0x0028, 0x0100);
+    pub const BITS_STORED: Tag = Tag::new(0x0028, 0x0101);
+    pub const HIGH_BIT: Tag = Tag::new(0x0028, 0x0102);
+    pub const PIXEL_REPRESENTATION: Tag = Tag::new(0x0028, 0x0103);
+    pub const PIXEL_DATA: Tag = Tag::new(0x7FE0, 0x0010);
+
+    pub const fn new(group: u16, element: u16) -> Self {
+        Self { group, element }
+    }
+
+    /// Looks up the tag of a keyword such as `PixelData` in the data dictionary
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        dictionary::lookup_keyword(keyword).map(|entry| entry.tag)
+    }
+
+    /// The keyword of a standard attribute, such as `PixelData`
+    pub fn keyword(self) -> Option<&'static str> {
+        dictionary::lookup(self).map(|entry| entry.keyword)
+    }
+
+    /// Group length elements, `(gggg,0000)`
+    pub fn is_group_length(self) -> bool {
+        self.element == 0
+    }
+}
+
+impl From<u32> for Tag {
+    fn from(tag: u32) -> Self {
+        Self::new((tag >> 16) as u16, tag as u16)
+    }
+}
+
+impl From<Tag> for u32 {
+    fn from(tag: Tag) -> Self {
+        (u32::from(tag.group) << 16) | u32::from(tag.element)
+    }
+}
+
+/// Lets a tag fill the `tag` field of error contexts
+impl From<Tag> for String {
+    fn from(tag: Tag) -> Self {
+        tag.to_string()
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04X}{:04X}", self.group, self.element)
+    }
+}
+
+fn parse_hex(digits: &str) -> Option<u16> {
+    (digits.len() == 4 && digits.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| u16::from_str_radix(digits, 16).expect("checked to be hex"))
+}
+
+impl FromStr for Tag {
+    type Err = ParseTagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = match s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            Some(parenthesised) => parenthesised
+                .split_once(',')
+                .map(|(group, element)| (group.trim(), element.trim())),
+            None if s.len() == 8 && s.is_char_boundary(4) => Some(s.split_at(4)),
+            None => None,
+        };
+        hex.and_then(|(group, element)| parse_hex(group).zip(parse_hex(element)))
+            .map(|(group, element)| Tag::new(group, element))
+            // some keywords, like Modality, are eight characters long
+            .or_else(|| Tag::from_keyword(s))
+            .context(ParseTagSnafu { tag: s })
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tag = <Cow<'de, str>>::deserialize(deserializer)?;
+        tag.parse().map_err(D::Error::custom)
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(display(
+    "{tag:?} is not a DICOM tag, expected hex like 7FE00010 or (7FE0,0010), or a keyword like PixelData"
+))]
+pub struct ParseTagError {
+    tag: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pixel_data = Tag::new(0x7FE0, 0x0010);
+        for s in [
+            "7FE00010",
+            "7fe00010",
+            "(7FE0,0010)",
+            "(7fe0, 0010)",
+            "PixelData",
+        ] {
+            assert_eq!(s.parse::<Tag>().unwrap(), pixel_data, "{s}");
+        }
+        assert_eq!("Modality".parse::<Tag>().unwrap(), Tag::new(0x0008, 0x0060));
+        assert_eq!(pixel_data.keyword(), Some("PixelData"));
+        assert_eq!(Tag::new(0x0009, 0x0010).keyword(), None);
+        assert_eq!(pixel_data.to_string(), "7FE00010");
+        assert_eq!(u32::from(pixel_data), 0x7FE0_0010);
+        assert_eq!(Tag::from(0x0008_0005), Tag::new(0x0008, 0x0005));
+
+        for s in [
+            "",
+            "7FE0001",
+            "7FE0_0010",
+            "(7FE0)",
+            "(7FE0,00100)",
+            "dim[0]",
+            "pixeldata",
+            "+7FE0010",
+        ] {
+            assert!(s.parse::<Tag>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_order() {
+        let mut tags = [
+            Tag::PIXEL_DATA,
+            Tag::new(0x0008, 0x0005),
+            Tag::new(0x0008, 0x0001),
+        ];
+        tags.sort();
+        assert_eq!(tags.map(u32::from), [0x0008_0001, 0x0008_0005, 0x7FE0_0010]);
+    }
+}
@@ -0,0 +1,442 @@
+//! Typed forms of date, time, age and numeric string values.
+//!
+//! Dimble stores DA, TM, DT, AS, DS and IS values as the strings (or, for DS and IS, the numbers)
+//! they were converted from. [`TypedValue::parse`] turns them into dates, times, durations and
+//! numbers according to the VR of the element.
+
+use snafu::prelude::*;
+use std::time::Duration;
+
+use crate::ir_to_dimble::VR;
+use crate::reader::FieldValue;
+
+/// A calendar date, from a DA value like `20230102`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+/// A time of day, from a TM value like `123456.789`. Leap seconds are not supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub microsecond: u32,
+}
+
+/// A DT value like `20230102123456.000000+0100`. Components the value leaves out are the
+/// earliest they can be, so `2023` is midnight on the first of January.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+    /// Offset from UTC in minutes, when the value has one
+    pub utc_offset: Option<i16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgeUnit {
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+/// An AS value like `042Y`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Age {
+    pub count: u16,
+    pub unit: AgeUnit,
+}
+
+/// Average length of a Gregorian year
+const SECONDS_PER_YEAR: u64 = 31_556_952;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+impl Age {
+    /// The age as a duration, taking months and years at their average Gregorian length
+    pub fn duration(&self) -> Duration {
+        let count = u64::from(self.count);
+        Duration::from_secs(match self.unit {
+            AgeUnit::Days => count * SECONDS_PER_DAY,
+            AgeUnit::Weeks => count * 7 * SECONDS_PER_DAY,
+            AgeUnit::Months => count * SECONDS_PER_YEAR / 12,
+            AgeUnit::Years => count * SECONDS_PER_YEAR,
+        })
+    }
+}
+
+/// A field value converted according to its VR
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedValue {
+    Date(Date),
+    Time(Time),
+    DateTime(DateTime),
+    Age(Age),
+    /// DS values, always a list even when there is a single value
+    Decimals(Vec<f64>),
+    /// IS values, always a list even when there is a single value
+    Integers(Vec<i64>),
+    /// Several DA, TM, DT or AS values
+    Multiple(Vec<TypedValue>),
+    /// Values of other VRs, and empty values, as they were decoded
+    Untyped(FieldValue),
+}
+
+impl TypedValue {
+    /// Converts `value` according to `vr`, failing if it is not valid for the VR
+    pub fn parse(vr: VR, value: FieldValue) -> Result<Self, ParseValueError> {
+        let parse_each = |value: FieldValue, parse: fn(&str) -> Option<TypedValue>| match value {
+            FieldValue::String(s) => parse_string(vr, &s, parse),
+            FieldValue::Multiple(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    FieldValue::String(s) => parse_string(vr, &s, parse),
+                    value => invalid(vr, &value),
+                })
+                .collect::<Result<_, _>>()
+                .map(TypedValue::Multiple),
+            value => invalid(vr, &value),
+        };
+        match (&vr, value) {
+            (_, FieldValue::Empty) => Ok(TypedValue::Untyped(FieldValue::Empty)),
+            (b"DA", value) => parse_each(value, |s| Date::parse(s).map(TypedValue::Date)),
+            (b"TM", value) => parse_each(value, |s| Time::parse(s).map(TypedValue::Time)),
+            (b"DT", value) => parse_each(value, |s| DateTime::parse(s).map(TypedValue::DateTime)),
+            (b"AS", value) => parse_each(value, |s| Age::parse(s).map(TypedValue::Age)),
+            (b"DS", value) => numbers(vr, value, decimal).map(TypedValue::Decimals),
+            (b"IS", value) => numbers(vr, value, integer).map(TypedValue::Integers),
+            (_, value) => Ok(TypedValue::Untyped(value)),
+        }
+    }
+}
+
+fn invalid<T>(vr: VR, value: &FieldValue) -> Result<T, ParseValueError> {
+    ParseValueSnafu {
+        vr: String::from_utf8_lossy(&vr),
+        value: format!("{value:?}"),
+    }
+    .fail()
+}
+
+fn parse_string(
+    vr: VR,
+    s: &str,
+    parse: fn(&str) -> Option<TypedValue>,
+) -> Result<TypedValue, ParseValueError> {
+    parse(s.trim()).with_context(|| ParseValueSnafu {
+        vr: String::from_utf8_lossy(&vr),
+        value: s,
+    })
+}
+
+fn numbers<T>(
+    vr: VR,
+    value: FieldValue,
+    convert: fn(&FieldValue) -> Option<T>,
+) -> Result<Vec<T>, ParseValueError> {
+    let values = match value {
+        FieldValue::Multiple(values) => values,
+        value => vec![value],
+    };
+    values
+        .iter()
+        .map(|value| convert(value).map_or_else(|| invalid(vr, value), Ok))
+        .collect()
+}
+
+fn decimal(value: &FieldValue) -> Option<f64> {
+    match value {
+        // rust also parses words like "inf" and "NaN", which DS does not allow
+        FieldValue::String(s) => {
+            let s = s.trim();
+            s.bytes()
+                .all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b))
+                .then(|| s.parse().ok())
+                .flatten()
+        }
+        FieldValue::Integer(i) => Some(*i as f64),
+        FieldValue::UnsignedInteger(u) => Some(*u as f64),
+        FieldValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn integer(value: &FieldValue) -> Option<i64> {
+    match value {
+        FieldValue::String(s) => s.trim().parse().ok(),
+        FieldValue::Integer(i) => Some(*i),
+        FieldValue::UnsignedInteger(u) => i64::try_from(*u).ok(),
+        _ => None,
+    }
+}
+
+/// Parses a run of exactly `length` ASCII digits
+fn digits<T: std::str::FromStr>(s: &str, length: usize) -> Option<T> {
+    (s.len() == length && s.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| s.parse().ok())
+        .flatten()
+}
+
+fn in_range<T: PartialOrd>(value: T, range: std::ops::RangeInclusive<T>) -> Option<T> {
+    range.contains(&value).then_some(value)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl Date {
+    /// Parses `YYYYMMDD`
+    pub fn parse(s: &str) -> Option<Self> {
+        if s.len() != 8 {
+            return None;
+        }
+        let year = digits(s.get(..4)?, 4)?;
+        let month = in_range(digits(s.get(4..6)?, 2)?, 1..=12)?;
+        let day = in_range(digits(s.get(6..)?, 2)?, 1..=days_in_month(year, month))?;
+        Some(Self { year, month, day })
+    }
+}
+
+impl Time {
+    /// Parses `HH[MM[SS[.FFFFFF]]]`
+    pub fn parse(s: &str) -> Option<Self> {
+        let (s, fraction) = match s.split_once('.') {
+            Some((s, fraction)) if s.len() == 6 && (1..=6).contains(&fraction.len()) => {
+                (s, Some(fraction))
+            }
+            Some(_) => return None,
+            None => (s, None),
+        };
+        if !s.is_ascii() || !matches!(s.len(), 2 | 4 | 6) {
+            return None;
+        }
+        // minutes and seconds may be left out
+        let component = |range: std::ops::Range<usize>, max| match s.get(range) {
+            Some(component) => in_range(digits(component, 2)?, 0..=max),
+            None => Some(0),
+        };
+        let microsecond = match fraction {
+            Some(fraction) => {
+                digits::<u32>(fraction, fraction.len())? * 10u32.pow(6 - fraction.len() as u32)
+            }
+            None => 0,
+        };
+        Some(Self {
+            hour: component(0..2, 23)?,
+            minute: component(2..4, 59)?,
+            second: component(4..6, 59)?,
+            microsecond,
+        })
+    }
+}
+
+impl DateTime {
+    /// Parses `YYYY[MM[DD[HH[MM[SS[.FFFFFF]]]]]][&ZZXX]`
+    pub fn parse(s: &str) -> Option<Self> {
+        if !s.is_ascii() {
+            return None;
+        }
+        let (s, utc_offset) = match s.get(4..)?.find(['+', '-']) {
+            Some(position) => {
+                let (s, offset) = s.split_at(position + 4);
+                let sign = if offset.starts_with('-') { -1 } else { 1 };
+                let hours: i16 = in_range(digits(offset.get(1..3)?, 2)?, 0..=14)?;
+                let minutes: i16 = in_range(digits(offset.get(3..)?, 2)?, 0..=59)?;
+                (s, Some(sign * (hours * 60 + minutes)))
+            }
+            None => (s, None),
+        };
+        // a time only follows a full date
+        let (date, time) = s.split_at(s.len().min(8));
+        if !matches!(date.len(), 4 | 6 | 8) || (date.len() < 8 && !time.is_empty()) {
+            return None;
+        }
+        let year = digits(&date[..4], 4)?;
+        let month = match date.get(4..6) {
+            Some(month) => in_range(digits(month, 2)?, 1..=12)?,
+            None => 1,
+        };
+        let day = match date.get(6..8) {
+            Some(day) => in_range(digits(day, 2)?, 1..=days_in_month(year, month))?,
+            None => 1,
+        };
+        let time = if time.is_empty() {
+            Time::default()
+        } else {
+            Time::parse(time)?
+        };
+        Some(Self {
+            date: Date { year, month, day },
+            time,
+            utc_offset,
+        })
+    }
+}
+
+impl Age {
+    /// Parses `nnnD`, `nnnW`, `nnnM` or `nnnY`
+    pub fn parse(s: &str) -> Option<Self> {
+        let count = digits(s.get(..3)?, 3)?;
+        let unit = match s.get(3..)? {
+            "D" => AgeUnit::Days,
+            "W" => AgeUnit::Weeks,
+            "M" => AgeUnit::Months,
+            "Y" => AgeUnit::Years,
+            _ => return None,
+        };
+        Some(Self { count, unit })
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(display("{value} is not a valid {vr} value"))]
+pub struct ParseValueError {
+    vr: String,
+    value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> FieldValue {
+        FieldValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_dates_and_times() {
+        assert_eq!(
+            Date::parse("20240229"),
+            Some(Date {
+                year: 2024,
+                month: 2,
+                day: 29
+            })
+        );
+        for s in ["20230229", "20231301", "2023010", "2023-01-01", "202301011"] {
+            assert_eq!(Date::parse(s), None, "{s}");
+        }
+
+        let time = |hour, minute, second, microsecond| Time {
+            hour,
+            minute,
+            second,
+            microsecond,
+        };
+        assert_eq!(Time::parse("07"), Some(time(7, 0, 0, 0)));
+        assert_eq!(Time::parse("0730"), Some(time(7, 30, 0, 0)));
+        assert_eq!(Time::parse("123456.789"), Some(time(12, 34, 56, 789_000)));
+        assert_eq!(Time::parse("235959.000001"), Some(time(23, 59, 59, 1)));
+        for s in [
+            "",
+            "7",
+            "24",
+            "1260",
+            "123460",
+            "1234.5",
+            "123456.",
+            "123456.1234567",
+        ] {
+            assert_eq!(Time::parse(s), None, "{s}");
+        }
+
+        assert_eq!(
+            DateTime::parse("20230102123456.5+0130"),
+            Some(DateTime {
+                date: Date {
+                    year: 2023,
+                    month: 1,
+                    day: 2
+                },
+                time: time(12, 34, 56, 500_000),
+                utc_offset: Some(90),
+            })
+        );
+        let year_only = DateTime::parse("2023-0500").unwrap();
+        assert_eq!(year_only.date.month, 1);
+        assert_eq!(year_only.date.day, 1);
+        assert_eq!(year_only.time, Time::default());
+        assert_eq!(year_only.utc_offset, Some(-300));
+        assert_eq!(
+            DateTime::parse("2023010212").map(|dt| dt.time),
+            Some(time(12, 0, 0, 0))
+        );
+        for s in [
+            "",
+            "202",
+            "20231",
+            "2023+01",
+            "20230102+1500",
+            "20230102123",
+            "202301021",
+        ] {
+            assert_eq!(DateTime::parse(s), None, "{s}");
+        }
+
+        let age = Age::parse("042Y").unwrap();
+        assert_eq!(age.duration().as_secs(), 42 * SECONDS_PER_YEAR);
+        assert_eq!(
+            Age::parse("002W").unwrap().duration().as_secs(),
+            14 * 86_400
+        );
+        for s in ["42Y", "042y", "042", "0042Y"] {
+            assert_eq!(Age::parse(s), None, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_parse_by_vr() {
+        assert_eq!(
+            TypedValue::parse(
+                *b"DS",
+                FieldValue::Multiple(vec![string(" 0.5"), FieldValue::Integer(2)])
+            )
+            .unwrap(),
+            TypedValue::Decimals(vec![0.5, 2.0])
+        );
+        assert_eq!(
+            TypedValue::parse(*b"DS", FieldValue::Float(1.5)).unwrap(),
+            TypedValue::Decimals(vec![1.5])
+        );
+        assert_eq!(
+            TypedValue::parse(*b"IS", string("+0012")).unwrap(),
+            TypedValue::Integers(vec![12])
+        );
+        assert!(matches!(
+            TypedValue::parse(*b"DA", FieldValue::Multiple(vec![string("20230102"), string("20230103 ")])),
+            Ok(TypedValue::Multiple(dates)) if dates.len() == 2
+        ));
+        assert_eq!(
+            TypedValue::parse(*b"LO", string("20230102")).unwrap(),
+            TypedValue::Untyped(string("20230102"))
+        );
+        assert_eq!(
+            TypedValue::parse(*b"DA", FieldValue::Empty).unwrap(),
+            TypedValue::Untyped(FieldValue::Empty)
+        );
+
+        for (vr, value) in [
+            (b"DS", string("NaN")),
+            (b"DS", string("inf")),
+            (b"IS", string("1.5")),
+            (b"IS", FieldValue::Float(1.0)),
+            (b"DA", string("yesterday")),
+            (b"TM", FieldValue::Integer(1200)),
+        ] {
+            let error = TypedValue::parse(*vr, value).unwrap_err();
+            assert!(error.to_string().contains("is not a valid"), "{error}");
+        }
+    }
+}
@@ -0,0 +1,586 @@
+use rmpv::Value;
+use snafu::prelude::*;
+use std::{io::Read, path::Path};
+
+use crate::codec::Compression;
+use crate::ir_to_dimble::{
+    self, HeaderField, HeaderFieldMap, PixelStream, SerialiseFieldsError, DEFAULT_PIXEL_ALIGNMENT,
+};
+use crate::pixel_array::{self, Dtype};
+use crate::reader::{Element, FieldValue};
+use crate::spool::Spool;
+use crate::tag::Tag;
+
+/// Builds the fields of a dimble file and writes them out in one go, along with pixel data that is
+/// either inserted or streamed in as the file is written. The values of the fields are spooled to a
+/// temporary file as they are inserted rather than held in memory. The file is written beside its
+/// path and only moved there once it is whole.
+#[derive(Debug)]
+pub struct DimbleWriter {
+    header: HeaderFieldMap,
+    /// The values of the fields, created with the first of them
+    data: Option<Spool>,
+    /// The inserted pixel data, as a safetensors object
+    pixel_array: Option<Vec<u8>>,
+    compression: Compression,
+    pixel_alignment: usize,
+}
+
+impl Default for DimbleWriter {
+    fn default() -> Self {
+        Self {
+            header: HeaderFieldMap::new(),
+            data: None,
+            pixel_array: None,
+            compression: Compression::default(),
+            pixel_alignment: DEFAULT_PIXEL_ALIGNMENT,
+        }
+    }
+}
+
+impl DimbleWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compresses fields as `compression` says when the file is written
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Aligns the tensor data of uncompressed pixel data within the file to `alignment` bytes, a
+    /// power of two of at most [`MAX_PIXEL_ALIGNMENT`](crate::MAX_PIXEL_ALIGNMENT). Defaults to
+    /// [`DEFAULT_PIXEL_ALIGNMENT`]; 1 stores the pixel data unaligned.
+    pub fn set_pixel_alignment(&mut self, alignment: usize) {
+        self.pixel_alignment = alignment;
+    }
+
+    /// Adds a field, replacing any previous field with the same tag. The value of the replaced field
+    /// is left out of the file.
+    pub fn insert(&mut self, tag: Tag, element: &Element) -> Result<()> {
+        ensure!(tag != Tag::PIXEL_DATA, PixelDataIsNotAValueSnafu);
+        let data = match &mut self.data {
+            Some(data) => data,
+            None => self.data.insert(Spool::new().context(CouldNotSpoolSnafu)?),
+        };
+        let header_field = prepare_element(tag, element, data)?;
+        self.header.insert(tag, header_field);
+        Ok(())
+    }
+
+    /// Adds the pixel data as a single tensor of little endian, C-order elements
+    pub fn insert_pixel_array(&mut self, dtype: Dtype, shape: &[usize], data: &[u8]) -> Result<()> {
+        self.insert_pixel_tensor(dtype, shape, None, data)
+    }
+
+    /// Adds multi-frame pixel data as a tensor of `frames` frames of `frame_shape`, each of which
+    /// can be read without reading the others
+    pub fn insert_frames(
+        &mut self,
+        dtype: Dtype,
+        frames: usize,
+        frame_shape: &[usize],
+        data: &[u8],
+    ) -> Result<()> {
+        let shape = [&[frames], frame_shape].concat();
+        self.insert_pixel_tensor(dtype, &shape, Some(frames), data)
+    }
+
+    fn insert_pixel_tensor(
+        &mut self,
+        dtype: Dtype,
+        shape: &[usize],
+        frames: Option<usize>,
+        data: &[u8],
+    ) -> Result<()> {
+        let bytes = pixel_array::pixel_array_to_safetensors(dtype, shape, frames, None, data)
+            .context(InvalidPixelArraySnafu)?;
+        self.pixel_array = Some(bytes);
+        Ok(())
+    }
+
+    /// Writes the file. Inserted pixel data is compressed if [`DimbleWriter::set_compression`]
+    /// says to, and is then stored compressed even if that does not make it smaller.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let pixel_stream = match &self.pixel_array {
+            Some(pixel_array) => {
+                let data_start =
+                    pixel_array::data_start(pixel_array).context(InvalidPixelArraySnafu)?;
+                let (header, data) = pixel_array.split_at(data_start);
+                Some(PixelStream {
+                    header: header.into(),
+                    data: Box::new(data),
+                    data_length: data.len() as u64,
+                    vr: *b"OW",
+                })
+            }
+            None => None,
+        };
+        self.write_with(path, pixel_stream)
+    }
+
+    /// Writes the file like [`DimbleWriter::write`], but streams the pixel data in from `pixels`
+    /// as it goes, so that however large the pixel data is, only a buffer's worth of it is ever in
+    /// memory. `pixels` has the little endian, C-order elements of a tensor of `dtype` and `shape`,
+    /// the first axis of which is `frames` frames long for multi-frame pixel data. Streamed pixel
+    /// data replaces any inserted.
+    pub fn write_streaming(
+        &self,
+        path: impl AsRef<Path>,
+        dtype: Dtype,
+        shape: &[usize],
+        frames: Option<usize>,
+        pixels: impl Read,
+    ) -> Result<()> {
+        let (header, data_length, data) =
+            pixel_array::pixel_array_to_safetensors_stream(dtype, shape, frames, None, pixels)
+                .context(InvalidPixelArraySnafu)?;
+        let pixel_stream = PixelStream {
+            header: header.into(),
+            data,
+            data_length,
+            vr: *b"OW",
+        };
+        self.write_with(path, Some(pixel_stream))
+    }
+
+    fn write_with(&self, path: impl AsRef<Path>, pixel_stream: Option<PixelStream>) -> Result<()> {
+        let dimble_path = path.as_ref().to_string_lossy();
+        let data = self
+            .data
+            .as_ref()
+            .map(Spool::bytes)
+            .transpose()
+            .context(CouldNotSpoolSnafu)?;
+        ir_to_dimble::serialise_dimble_fields(
+            &self.header,
+            data.as_deref().unwrap_or_default(),
+            pixel_stream,
+            &dimble_path,
+            self.compression,
+            self.pixel_alignment,
+        )
+        .context(SerialiseFieldsSnafu)
+    }
+}
+
+fn prepare_element(tag: Tag, element: &Element, data: &mut Spool) -> Result<HeaderField> {
+    match &element.value {
+        FieldValue::Empty => Ok(HeaderField::Empty(element.vr)),
+        FieldValue::BulkDataUri(uri) => Ok(HeaderField::BulkDataURI(uri.clone(), element.vr)),
+        FieldValue::Sequence(items) => {
+            let items = items
+                .iter()
+                .map(|item| {
+                    item.iter()
+                        .map(|(&tag, element)| Ok((tag, prepare_element(tag, element, data)?)))
+                        .collect::<Result<HeaderFieldMap>>()
+                })
+                .collect::<Result<_>>()?;
+            Ok(HeaderField::SQ(items))
+        }
+        value => {
+            let mut field_bytes = Vec::new();
+            rmpv::encode::write_value(&mut field_bytes, &field_value_to_value(tag, value)?)
+                .context(CouldNotEncodeSnafu { tag })?;
+            let offset = data.append(&field_bytes).context(CouldNotSpoolSnafu)?;
+            Ok(HeaderField::Deffered(
+                offset,
+                field_bytes.len() as u64,
+                element.vr,
+            ))
+        }
+    }
+}
+
+fn field_value_to_value(tag: Tag, value: &FieldValue) -> Result<Value> {
+    Ok(match value {
+        FieldValue::Empty => Value::Nil,
+        FieldValue::String(s) => Value::from(s.as_str()),
+        FieldValue::Integer(i) => Value::from(*i),
+        FieldValue::UnsignedInteger(u) => Value::from(*u),
+        FieldValue::Float(f) => Value::from(*f),
+        FieldValue::PersonName(name) => Value::from(name.to_dicom_string()),
+        FieldValue::Binary(b) => Value::Binary(b.clone()),
+        FieldValue::Multiple(values) => Value::Array(
+            values
+                .iter()
+                .map(|v| field_value_to_value(tag, v))
+                .collect::<Result<_>>()?,
+        ),
+        FieldValue::Sequence(_) => NestedSequenceSnafu { tag }.fail()?,
+        FieldValue::BulkDataUri(_) => NestedBulkDataUriSnafu { tag }.fail()?,
+    })
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Pixel data is not a field value, use `insert_pixel_array` instead"))]
+    PixelDataIsNotAValue,
+
+    #[snafu(display("Field {tag} has a sequence nested inside a multi-valued field"))]
+    NestedSequence { tag: Tag },
+
+    #[snafu(display("Field {tag} has a bulk data URI nested inside a multi-valued field"))]
+    NestedBulkDataUri { tag: Tag },
+
+    #[snafu(display("Could not encode field {tag}"))]
+    CouldNotEncode {
+        source: rmpv::encode::Error,
+        tag: Tag,
+    },
+
+    #[snafu(display("Could not build the pixel array"))]
+    InvalidPixelArray { source: pixel_array::Error },
+
+    #[snafu(display("Could not spool the fields to a temporary file"))]
+    CouldNotSpool { source: std::io::Error },
+
+    #[snafu(display("Could not serialize the fields"))]
+    SerialiseFields { source: SerialiseFieldsError },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Codec;
+    use crate::reader::{Dataset, DimbleReader};
+
+    #[test]
+    fn test_write_and_read_back() {
+        let mut writer = DimbleWriter::new();
+        let modality = Element {
+            vr: *b"CS",
+            value: FieldValue::String("CT".to_string()),
+        };
+        let image_type = Element {
+            vr: *b"CS",
+            value: FieldValue::Multiple(vec![
+                FieldValue::String("ORIGINAL".to_string()),
+                FieldValue::String("PRIMARY".to_string()),
+            ]),
+        };
+        let rows = Element {
+            vr: *b"US",
+            value: FieldValue::Integer(2),
+        };
+        let code_sequence = Element {
+            vr: *b"SQ",
+            value: FieldValue::Sequence(vec![Dataset::from([(
+                Tag::new(0x0008, 0x0100),
+                Element {
+                    vr: *b"SH",
+                    value: FieldValue::String("121327".to_string()),
+                },
+            )])]),
+        };
+        writer.insert(Tag::new(0x0008, 0x0060), &modality).unwrap();
+        writer
+            .insert(Tag::new(0x0008, 0x0008), &image_type)
+            .unwrap();
+        writer.insert(Tag::new(0x0028, 0x0010), &rows).unwrap();
+        writer
+            .insert(Tag::new(0x0008, 0x9215), &code_sequence)
+            .unwrap();
+        let pixels: Vec<u8> = [0u16, 1, 2, 3]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        writer
+            .insert_pixel_array(Dtype::U16, &[2, 2], &pixels)
+            .unwrap();
+
+        let path = "/tmp/writer_round_trip.dimble";
+        writer.write(path).unwrap();
+
+        let reader = DimbleReader::open(path).unwrap();
+        assert_eq!(reader.element(Tag::new(0x0008, 0x0060)).unwrap(), modality);
+        assert_eq!(
+            reader.element(Tag::new(0x0008, 0x0008)).unwrap(),
+            image_type
+        );
+        assert_eq!(reader.element(Tag::new(0x0028, 0x0010)).unwrap(), rows);
+        assert_eq!(
+            reader.element(Tag::new(0x0008, 0x9215)).unwrap(),
+            code_sequence
+        );
+        assert_eq!(reader.dataset().unwrap().len(), 4);
+
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.dtype, Dtype::U16);
+        assert_eq!(pixel_array.shape, [2, 2]);
+        assert_eq!(pixel_array.data, pixels);
+    }
+
+    #[test]
+    fn test_insert_replaces_field() {
+        let comments = |comments: &str| Element {
+            vr: *b"LT",
+            value: FieldValue::String(comments.to_string()),
+        };
+        let mut replaced = DimbleWriter::new();
+        replaced
+            .insert(Tag::new(0x0020, 0x4000), &comments(&"draft ".repeat(100)))
+            .unwrap();
+        replaced
+            .insert(Tag::new(0x0020, 0x4000), &comments("final"))
+            .unwrap();
+        let mut inserted_once = DimbleWriter::new();
+        inserted_once
+            .insert(Tag::new(0x0020, 0x4000), &comments("final"))
+            .unwrap();
+
+        let path = "/tmp/writer_replaced.dimble";
+        let once_path = "/tmp/writer_inserted_once.dimble";
+        replaced.write(path).unwrap();
+        inserted_once.write(once_path).unwrap();
+
+        // the first value is not written, so the files are the same
+        assert_eq!(
+            std::fs::metadata(path).unwrap().len(),
+            std::fs::metadata(once_path).unwrap().len()
+        );
+        assert_eq!(
+            std::fs::read(path).unwrap(),
+            std::fs::read(once_path).unwrap()
+        );
+        let reader = DimbleReader::open(path).unwrap();
+        assert_eq!(
+            reader.element(Tag::new(0x0020, 0x4000)).unwrap(),
+            comments("final")
+        );
+        assert!(reader.verify().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_write_frames() {
+        let mut writer = DimbleWriter::new();
+        let pixels: Vec<u8> = (0..12).collect();
+        writer
+            .insert_frames(Dtype::U8, 3, &[2, 2], &pixels)
+            .unwrap();
+
+        let path = "/tmp/writer_frames.dimble";
+        writer.write(path).unwrap();
+
+        let reader = DimbleReader::open(path).unwrap();
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.shape, [3, 2, 2]);
+        assert_eq!(pixel_array.frame_count(), 3);
+        assert_eq!(pixel_array.frame_shape(), [2, 2]);
+        assert_eq!(pixel_array.frame(1).unwrap().data, &pixels[4..8]);
+    }
+
+    #[test]
+    fn test_write_compressed() {
+        use crate::codec::Codec;
+
+        let mut writer = DimbleWriter::new();
+        writer.set_compression(Compression {
+            fields: Some(Codec::Zstd),
+            pixel_data: Some(Codec::DeltaZstd),
+        });
+        let comments = Element {
+            vr: *b"LT",
+            value: FieldValue::String("no acute findings ".repeat(20)),
+        };
+        let modality = Element {
+            vr: *b"CS",
+            value: FieldValue::String("CT".to_string()),
+        };
+        writer.insert(Tag::new(0x0020, 0x4000), &comments).unwrap();
+        writer.insert(Tag::new(0x0008, 0x0060), &modality).unwrap();
+        let pixels: Vec<u8> = (0..1024u16).flat_map(|v| v.to_le_bytes()).collect();
+        writer
+            .insert_frames(Dtype::U16, 4, &[16, 16], &pixels)
+            .unwrap();
+
+        let path = "/tmp/writer_compressed.dimble";
+        writer.write(path).unwrap();
+
+        let reader = DimbleReader::open(path).unwrap();
+        assert!(matches!(
+            reader.header_field(Tag::new(0x0020, 0x4000)).unwrap(),
+            HeaderField::Compressed(.., Codec::Zstd)
+        ));
+        // compressing a field this small would only make it bigger
+        assert!(matches!(
+            reader.header_field(Tag::new(0x0008, 0x0060)).unwrap(),
+            HeaderField::Deffered(..)
+        ));
+        assert!(matches!(
+            reader.header_field(Tag::PIXEL_DATA).unwrap(),
+            HeaderField::Compressed(.., Codec::DeltaZstd)
+        ));
+        assert_eq!(reader.element(Tag::new(0x0020, 0x4000)).unwrap(), comments);
+        assert_eq!(reader.element(Tag::new(0x0008, 0x0060)).unwrap(), modality);
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.shape, [4, 16, 16]);
+        assert_eq!(pixel_array.frames, Some(4));
+        assert_eq!(pixel_array.data, pixels);
+        assert!(std::fs::metadata(path).unwrap().len() < pixels.len() as u64);
+    }
+
+    #[test]
+    fn test_write_aligned() {
+        let modality = Element {
+            vr: *b"CS",
+            value: FieldValue::String("CT".to_string()),
+        };
+        let pixels: Vec<u8> = (0..16).collect();
+        for alignment in [1, DEFAULT_PIXEL_ALIGNMENT, crate::MAX_PIXEL_ALIGNMENT] {
+            // the pixel data is written before a field that comes after it in the data section
+            let mut writer = DimbleWriter::new();
+            writer.set_pixel_alignment(alignment);
+            writer
+                .insert_pixel_array(Dtype::U8, &[4, 4], &pixels)
+                .unwrap();
+            writer.insert(Tag::new(0x0008, 0x0060), &modality).unwrap();
+
+            let path = format!("/tmp/writer_aligned_{alignment}.dimble");
+            writer.write(&path).unwrap();
+
+            let reader = DimbleReader::open(&path).unwrap();
+            let offset = reader.pixel_data_offset().unwrap().unwrap();
+            assert!(offset.is_multiple_of(alignment), "{offset}");
+            assert_eq!(reader.pixel_array().unwrap().data, pixels);
+            assert_eq!(reader.element(Tag::new(0x0008, 0x0060)).unwrap(), modality);
+            assert!(reader.verify().unwrap().is_empty());
+        }
+
+        for alignment in [0, 3, 2 * crate::MAX_PIXEL_ALIGNMENT] {
+            let mut writer = DimbleWriter::new();
+            writer.set_pixel_alignment(alignment);
+            assert!(matches!(
+                writer.write("/tmp/writer_misaligned.dimble"),
+                Err(Error::SerialiseFields {
+                    source: SerialiseFieldsError::InvalidPixelAlignment { .. }
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_write_streaming() {
+        let modality = Element {
+            vr: *b"CS",
+            value: FieldValue::String("CT".to_string()),
+        };
+        let mut writer = DimbleWriter::new();
+        writer.insert(Tag::new(0x0008, 0x0060), &modality).unwrap();
+        // replaced by the streamed pixel data
+        writer
+            .insert_pixel_array(Dtype::U8, &[2, 2], &[0; 4])
+            .unwrap();
+
+        // far more pixel data than the copy buffer, generated as it is read
+        let frames = 4;
+        let frame_shape = [512, 1024];
+        let frame_length = frame_shape.iter().product::<usize>();
+        let pixels = std::io::repeat(7).take((frames * frame_length) as u64);
+        let path = "/tmp/writer_streaming.dimble";
+        writer
+            .write_streaming(path, Dtype::U8, &[frames, 512, 1024], Some(frames), pixels)
+            .unwrap();
+
+        let reader = DimbleReader::open(path).unwrap();
+        assert_eq!(reader.element(Tag::new(0x0008, 0x0060)).unwrap(), modality);
+        let offset = reader.pixel_data_offset().unwrap().unwrap();
+        assert!(offset.is_multiple_of(DEFAULT_PIXEL_ALIGNMENT));
+        let pixel_array = reader.pixel_array().unwrap();
+        assert_eq!(pixel_array.shape, [frames, 512, 1024]);
+        assert_eq!(pixel_array.frame_count(), frames);
+        assert!(pixel_array.frame(3).unwrap().data.iter().all(|&p| p == 7));
+        assert!(reader.verify().unwrap().is_empty());
+
+        // a stream that ends early is an error rather than a truncated tensor, and leaves the file
+        // that was there as it was
+        let written = std::fs::read(path).unwrap();
+        for codec in [None, Some(Codec::Zstd), Some(Codec::DeltaZstd)] {
+            writer.set_compression(Compression {
+                fields: None,
+                pixel_data: codec,
+            });
+            assert!(matches!(
+                writer.write_streaming(path, Dtype::U16, &[2, 2], None, &[0u8; 6][..]),
+                Err(Error::SerialiseFields {
+                    source: SerialiseFieldsError::PixelDataTooShort {
+                        expected_length: 8,
+                        length: 6
+                    }
+                })
+            ));
+        }
+        assert_eq!(std::fs::read(path).unwrap(), written);
+        let temporary_files = std::fs::read_dir("/tmp")
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy()
+                    .starts_with(".writer_streaming.dimble.")
+            })
+            .count();
+        assert_eq!(temporary_files, 0);
+
+        assert!(matches!(
+            writer.write_streaming(path, Dtype::U16, &[usize::MAX, 2], None, std::io::empty()),
+            Err(Error::InvalidPixelArray {
+                source: pixel_array::Error::TooLarge { .. }
+            })
+        ));
+    }
+
+    #[test]
+    fn test_write_streaming_compressed() {
+        // smooth rows, which delta encoding shrinks to almost nothing
+        let (frames, rows, columns) = (3, 64, 300);
+        let pixels: Vec<u8> = (0..frames * rows * columns)
+            .flat_map(|i| ((i % columns) as u16 * 3).to_le_bytes())
+            .collect();
+        for codec in [Codec::Zstd, Codec::Lz4, Codec::DeltaZstd] {
+            let mut writer = DimbleWriter::new();
+            writer.set_compression(Compression {
+                fields: None,
+                pixel_data: Some(codec),
+            });
+            let path = format!("/tmp/writer_streaming_{}.dimble", codec.name());
+            writer
+                .write_streaming(
+                    &path,
+                    Dtype::U16,
+                    &[frames, rows, columns],
+                    Some(frames),
+                    &pixels[..],
+                )
+                .unwrap();
+
+            let reader = DimbleReader::open(&path).unwrap();
+            assert!(matches!(
+                reader.header_field(Tag::PIXEL_DATA).unwrap(),
+                HeaderField::Compressed(_, length, decompressed_length, _, compressed_with)
+                    if compressed_with == codec
+                        && (length as usize) < pixels.len()
+                        && decompressed_length as usize > pixels.len()
+            ));
+            let pixel_array = reader.pixel_array().unwrap();
+            assert_eq!(pixel_array.shape, [frames, rows, columns]);
+            assert_eq!(pixel_array.data, pixels);
+            assert!(reader.verify().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_pixel_data_is_not_a_value() {
+        let mut writer = DimbleWriter::new();
+        let element = Element {
+            vr: *b"OW",
+            value: FieldValue::Empty,
+        };
+        assert!(matches!(
+            writer.insert(Tag::PIXEL_DATA, &element),
+            Err(Error::PixelDataIsNotAValue)
+        ));
+    }
+}
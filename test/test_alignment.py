@@ -0,0 +1,50 @@
+from pathlib import Path
+
+import numpy as np
+import pytest
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+TEST_DICOM_FILE = TESTFILES_DIR / "CT_small.dcm"
+
+
+def _convert(name: str, **kwargs) -> Path:
+    dimble_file = Path("/tmp") / f"{TEST_DICOM_FILE.stem}.{name}.dimble"
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file, **kwargs)
+    return dimble_file
+
+
+@pytest.mark.parametrize("pixel_alignment", [1, 4096, 2 * 1024 * 1024])
+def test_pixel_data_is_aligned(pixel_alignment):
+    unaligned = _convert("unaligned", pixel_alignment=1)
+    aligned = _convert(f"aligned_{pixel_alignment}", pixel_alignment=pixel_alignment)
+
+    info = dimble.pixel_array_info(aligned)
+    assert info["data_offset"] % pixel_alignment == 0
+    length = np.prod(info["shape"]) * np.dtype(info["dtype"]).itemsize
+    with open(aligned, "rb") as f:
+        f.seek(info["data_offset"])
+        data = f.read(length)
+
+    expected = dimble.load_dimble(unaligned, [PIXEL_ARRAY], backend="numpy")
+    np.testing.assert_array_equal(
+        np.frombuffer(data, dtype=info["dtype"]).reshape(info["shape"]),
+        expected[PIXEL_ARRAY],
+    )
+    assert dimble.verify(aligned) == []
+
+
+@pytest.mark.parametrize("pixel_alignment", [0, 3, 4 * 1024 * 1024])
+def test_invalid_alignment(pixel_alignment):
+    with pytest.raises(dimble.DimbleError):
+        _convert("misaligned", pixel_alignment=pixel_alignment)
+
+
+def test_compressed_pixel_data_has_no_offset():
+    compressed = _convert("compressed_aligned", pixel_codec="zstd")
+    assert dimble.pixel_array_info(compressed)["data_offset"] is None
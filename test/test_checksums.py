@@ -0,0 +1,39 @@
+from pathlib import Path
+
+import pytest
+
+import dimble
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+TEST_DICOM_FILE = TESTFILES_DIR / "CT_small.dcm"
+
+
+def _convert(name: str) -> Path:
+    dimble_file = Path("/tmp") / f"{TEST_DICOM_FILE.stem}.{name}.dimble"
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
+    return dimble_file
+
+
+def test_verify_intact():
+    assert dimble.verify(_convert("intact")) == []
+
+
+def test_verify_reports_corrupted_pixel_data():
+    dimble_file = _convert("corrupt_pixels")
+    data = bytearray(dimble_file.read_bytes())
+    # the pixel data is the last field, followed by the checksums
+    data[len(data) // 2] ^= 0xFF
+    dimble_file.write_bytes(data)
+    assert dimble.verify(dimble_file) == ["7FE00010"]
+
+
+def test_verify_corrupted_header():
+    dimble_file = _convert("corrupt_header")
+    data = bytearray(dimble_file.read_bytes())
+    # the header checksum is the second to last word of the file
+    data[-16] ^= 0xFF
+    dimble_file.write_bytes(data)
+    with pytest.raises(dimble.CorruptFileError):
+        dimble.verify(dimble_file)
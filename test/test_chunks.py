@@ -0,0 +1,65 @@
+from pathlib import Path
+
+import numpy as np
+import pytest
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+TEST_DICOM_FILE = TESTFILES_DIR / "693_J2KR.dcm"
+
+SLICES = [
+    None,
+    [slice(10, 42), slice(20, 30)],
+    [slice(None, None, 3), slice(-20, None)],
+    [slice(None, None, -1), slice(50, 10, -7)],
+    [slice(5, 5)],
+]
+
+
+@pytest.fixture
+def dimble_files():
+    stem = Path("/tmp") / TEST_DICOM_FILE.stem
+    contiguous = stem.with_suffix(".contiguous.dimble")
+    chunked = stem.with_suffix(".chunked.dimble")
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, contiguous)
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, chunked, chunk_shape=[16, 24])
+    return contiguous, chunked
+
+
+def _load(dimble_file: Path, slices, backend="numpy"):
+    return dimble.load_dimble(
+        dimble_file, [PIXEL_ARRAY], slices=slices, backend=backend
+    )[PIXEL_ARRAY]
+
+
+def test_chunked_info(dimble_files):
+    contiguous, chunked = dimble_files
+    info = dimble.pixel_array_info(chunked)
+    assert info["chunk_shape"] == (16, 24)
+    assert dimble.pixel_array_info(contiguous)["chunk_shape"] is None
+    assert info["shape"] == dimble.pixel_array_info(contiguous)["shape"]
+
+
+@pytest.mark.parametrize("slices", SLICES, ids=str)
+def test_chunked_slices_match_contiguous(dimble_files, slices):
+    contiguous, chunked = dimble_files
+    region = _load(chunked, slices)
+    np.testing.assert_array_equal(region, _load(contiguous, slices))
+    np.testing.assert_array_equal(np.from_dlpack(_load(chunked, slices, "dlpack")), region)
+
+
+def test_chunked_out_of_bounds_slices_raise(dimble_files):
+    _, chunked = dimble_files
+    with pytest.raises(IndexError):
+        _load(chunked, [slice(0, 100_000)])
+
+
+def test_invalid_chunk_shape_raises(dimble_files):
+    contiguous, _ = dimble_files
+    with pytest.raises(dimble.DimbleError):
+        dimble.dicom_to_dimble(TEST_DICOM_FILE, contiguous, chunk_shape=[16])
@@ -0,0 +1,53 @@
+from pathlib import Path
+
+import numpy as np
+import pytest
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+TEST_DICOM_FILE = TESTFILES_DIR / "693_J2KR.dcm"
+FIELDS = [PIXEL_ARRAY, "00080016", "00280010", "00280011"]
+
+
+def _convert(name: str, **kwargs) -> Path:
+    dimble_file = Path("/tmp") / f"{TEST_DICOM_FILE.stem}.{name}.dimble"
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file, **kwargs)
+    return dimble_file
+
+
+@pytest.mark.parametrize("pixel_codec", ["zstd", "lz4", "delta-zstd"])
+def test_compressed_matches_uncompressed(pixel_codec):
+    uncompressed = _convert("uncompressed")
+    compressed = _convert(pixel_codec, codec="zstd", pixel_codec=pixel_codec)
+    assert compressed.stat().st_size < uncompressed.stat().st_size
+
+    expected = dimble.load_dimble(uncompressed, FIELDS, backend="numpy")
+    dataset = dimble.load_dimble(compressed, FIELDS, backend="numpy")
+    for field in FIELDS[1:]:
+        assert dataset[field] == expected[field]
+    np.testing.assert_array_equal(dataset[PIXEL_ARRAY], expected[PIXEL_ARRAY])
+
+    slices = [slice(10, 42), slice(None, None, -2)]
+    np.testing.assert_array_equal(
+        dimble.load_dimble(compressed, [PIXEL_ARRAY], slices=slices, backend="numpy")[
+            PIXEL_ARRAY
+        ],
+        expected[PIXEL_ARRAY][tuple(slices)],
+    )
+
+
+def test_compressed_round_trips_to_dicom():
+    compressed = _convert("zstd", codec="zstd", pixel_codec="delta-zstd")
+    dicom_file = compressed.with_suffix(".dcm")
+    dimble.dimble_to_dicom(compressed, dicom_file)
+    assert dicom_file.stat().st_size > 0
+
+
+def test_unknown_codec_raises():
+    with pytest.raises(ValueError):
+        _convert("gzip", pixel_codec="gzip")
@@ -0,0 +1,39 @@
+from pathlib import Path
+
+import numpy as np
+import torch
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+TEST_DICOM_FILE = TESTFILES_DIR / "693_J2KR.dcm"
+
+
+def _load(dimble_file: Path, backend: str):
+    return dimble.load_dimble(dimble_file, [PIXEL_ARRAY], backend=backend)[PIXEL_ARRAY]
+
+
+def test_from_dlpack():
+    dimble_file = Path("/tmp") / TEST_DICOM_FILE.with_suffix(".dimble").name
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
+    tensor = _load(dimble_file, "torch")
+
+    array = _load(dimble_file, "dlpack")
+    assert array.shape == tuple(tensor.shape)
+    torch.testing.assert_close(torch.from_dlpack(array), tensor)
+    np.testing.assert_array_equal(np.from_dlpack(array), tensor.numpy())
+
+
+def test_from_dlpack_writes_are_private():
+    dimble_file = Path("/tmp") / TEST_DICOM_FILE.with_suffix(".dimble").name
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
+    before = dimble_file.read_bytes()
+
+    tensor = torch.from_dlpack(_load(dimble_file, "dlpack"))
+    tensor += 1
+
+    assert dimble_file.read_bytes() == before
@@ -51,3 +51,37 @@ def test_load_and_convert_dtype(dtype):
         dimble.dicom_to_dimble(dicom_file, dimble_file, dtype=dtype)
         ds = dimble.load_dimble(dimble_file, ["7FE00010"])
         assert str(ds["7FE00010"].dtype) == "torch." + dtype
+
+
+# torch only has some of these in recent versions, and widens or returns bytes otherwise
+UNSIGNED_DTYPES = ["bool", "uint16", "uint32", "uint64"]
+WIDENED = {"uint16": "int32", "uint32": "int64"}
+ITEMSIZES = {"bool": 1, "uint16": 2, "uint32": 4, "uint64": 8}
+
+
+@pytest.mark.parametrize("dtype", UNSIGNED_DTYPES)
+def test_load_unsigned_dtype(dtype):
+    dicom_file = TEST_DICOM_FILE
+    dimble_file = Path("/tmp") / dicom_file.with_suffix(f".{dtype}.dimble").name
+    dimble.dicom_to_dimble(dicom_file, dimble_file, dtype=dtype)
+    array = dimble.load_dimble(dimble_file, ["7FE00010"], backend="numpy")["7FE00010"]
+    assert str(array.dtype) == dtype
+
+    if hasattr(torch, dtype):
+        tensor = dimble.load_dimble(dimble_file, ["7FE00010"])["7FE00010"]
+        assert str(tensor.dtype) == "torch." + dtype
+    elif dtype in WIDENED:
+        tensor = dimble.load_dimble(dimble_file, ["7FE00010"])["7FE00010"]
+        assert str(tensor.dtype) == "torch." + WIDENED[dtype]
+        assert (tensor.numpy() == array).all()
+    else:
+        with pytest.raises(dimble.UnsupportedValueError):
+            dimble.load_dimble(dimble_file, ["7FE00010"])
+
+    raw = dimble.load_dimble(dimble_file, ["7FE00010"], dtype_fallback="bytes")
+    if not hasattr(torch, dtype):
+        assert raw["7FE00010"]["dtype"] == dtype
+        raw_bytes = raw["7FE00010"]["bytes"]
+        assert raw_bytes.dtype == torch.uint8
+        assert tuple(raw_bytes.shape) == array.shape + (ITEMSIZES[dtype],)
+        assert raw_bytes.numpy().tobytes() == array.tobytes()
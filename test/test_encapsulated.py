@@ -0,0 +1,60 @@
+from pathlib import Path
+
+import numpy as np
+import pydicom
+import pytest
+from pydicom.data import get_testdata_file
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+JPEG_2000_FILE = TESTFILES_DIR / "693_J2KR.dcm"
+RLE_FILE = Path(get_testdata_file("MR_small_RLE.dcm"))
+
+
+def _convert(dicom_file: Path, name: str, **kwargs) -> Path:
+    dimble_file = Path("/tmp") / f"{dicom_file.stem}.{name}.dimble"
+    dimble.dicom_to_dimble(dicom_file, dimble_file, **kwargs)
+    return dimble_file
+
+
+@pytest.mark.parametrize("dicom_file", [JPEG_2000_FILE, RLE_FILE])
+def test_kept_encapsulated_round_trips_bit_for_bit(dicom_file):
+    dimble_file = _convert(dicom_file, "kept", keep_encapsulated=True)
+    info = dimble.pixel_array_info(dimble_file)
+    original = pydicom.dcmread(dicom_file)
+    assert info["transfer_syntax"] == original.file_meta.TransferSyntaxUID
+
+    dicom_file = dimble_file.with_suffix(".dcm")
+    dimble.dimble_to_dicom(dimble_file, dicom_file)
+    reconstructed = pydicom.dcmread(dicom_file)
+    assert reconstructed.file_meta.TransferSyntaxUID == original.file_meta.TransferSyntaxUID
+    assert reconstructed.PixelData == original.PixelData
+
+
+@pytest.mark.parametrize("keep_encapsulated", [False, True])
+def test_rle_lossless_decodes(keep_encapsulated):
+    dimble_file = _convert(
+        RLE_FILE, f"keep_{keep_encapsulated}", keep_encapsulated=keep_encapsulated
+    )
+    expected = pydicom.dcmread(RLE_FILE).pixel_array
+    pixels = dimble.load_dimble(dimble_file, [PIXEL_ARRAY], backend="numpy")[PIXEL_ARRAY]
+    np.testing.assert_array_equal(pixels, expected)
+
+    slices = [slice(10, 42), slice(None, None, -3)]
+    np.testing.assert_array_equal(
+        dimble.load_dimble(dimble_file, [PIXEL_ARRAY], slices=slices, backend="numpy")[
+            PIXEL_ARRAY
+        ],
+        expected[tuple(slices)],
+    )
+
+
+def test_kept_jpeg_2000_cannot_load():
+    dimble_file = _convert(JPEG_2000_FILE, "kept", keep_encapsulated=True)
+    with pytest.raises(ValueError):
+        dimble.load_dimble(dimble_file, [PIXEL_ARRAY], backend="numpy")
@@ -0,0 +1,50 @@
+from pathlib import Path
+
+import numpy as np
+import pydicom
+import pytest
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+
+def _multi_frame_files() -> list[Path]:
+    files = []
+    for path in sorted(TESTFILES_DIR.iterdir()):
+        try:
+            ds = pydicom.dcmread(path, stop_before_pixels=True)
+        except Exception:
+            continue
+        if int(ds.get("NumberOfFrames", 1) or 1) > 1:
+            files.append(path)
+    return files[:3]
+
+
+@pytest.mark.parametrize("dicom_file", _multi_frame_files(), ids=lambda p: p.name)
+def test_load_frames(dicom_file):
+    dimble_file = Path("/tmp") / dicom_file.with_suffix(".frames.dimble").name
+    try:
+        dimble.dicom_to_dimble(dicom_file, dimble_file)
+    except Exception:
+        pytest.skip("pydicom cannot decode the pixel data")
+    pixel_array = pydicom.dcmread(dicom_file).pixel_array
+    frames = pixel_array.shape[0]
+
+    info = dimble.pixel_array_info(dimble_file)
+    assert info["frames"] == frames
+    assert info["frame_shape"] == pixel_array.shape[1:]
+
+    def load(selected):
+        return dimble.load_dimble(
+            dimble_file, [PIXEL_ARRAY], backend="numpy", frames=selected
+        )[PIXEL_ARRAY]
+
+    np.testing.assert_array_equal(load([frames - 1]), pixel_array[[frames - 1]])
+    np.testing.assert_array_equal(load([0, 1]), pixel_array[:2])
+    np.testing.assert_array_equal(load([1, 0]), pixel_array[[1, 0]])
+    with pytest.raises(IndexError):
+        load([frames])
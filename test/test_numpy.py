@@ -0,0 +1,51 @@
+from pathlib import Path
+
+import numpy as np
+import pytest
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+TEST_DICOM_FILE = TESTFILES_DIR / "693_J2KR.dcm"
+
+
+@pytest.fixture
+def dimble_file():
+    dimble_file = Path("/tmp") / TEST_DICOM_FILE.with_suffix(".dimble").name
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
+    return dimble_file
+
+
+def test_load_numpy_matches_torch(dimble_file):
+    array = dimble.load_dimble(dimble_file, [PIXEL_ARRAY], backend="numpy")[PIXEL_ARRAY]
+    tensor = dimble.load_dimble(dimble_file, [PIXEL_ARRAY])[PIXEL_ARRAY]
+    assert isinstance(array, np.ndarray)
+    assert str(array.dtype) == str(tensor.dtype).removeprefix("torch.")
+    np.testing.assert_array_equal(array, tensor.numpy())
+
+
+def test_load_numpy_is_read_only(dimble_file):
+    array = dimble.load_dimble(dimble_file, [PIXEL_ARRAY], backend="numpy")[PIXEL_ARRAY]
+    assert not array.flags.writeable
+    assert not array.flags.owndata
+    with pytest.raises(ValueError):
+        array[0, 0] = 0
+
+
+def test_load_numpy_slices(dimble_file):
+    slices = [slice(10, 42), slice(20, 30)]
+    array = dimble.load_dimble(
+        dimble_file, [PIXEL_ARRAY], slices=slices, backend="numpy"
+    )[PIXEL_ARRAY]
+    tensor = dimble.load_dimble(dimble_file, [PIXEL_ARRAY], slices=slices)[PIXEL_ARRAY]
+    assert array.shape == (32, 10)
+    np.testing.assert_array_equal(array, tensor.numpy())
+
+
+def test_load_numpy_cpu_only(dimble_file):
+    with pytest.raises(ValueError):
+        dimble.load_dimble(dimble_file, [PIXEL_ARRAY], device="cuda", backend="numpy")
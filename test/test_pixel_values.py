@@ -0,0 +1,54 @@
+from pathlib import Path
+
+import numpy as np
+import pydicom
+import pytest
+from pydicom.pixel_data_handlers.util import apply_modality_lut
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+TEST_DICOM_FILE = TESTFILES_DIR / "693_J2KR.dcm"
+
+
+@pytest.fixture
+def dimble_file():
+    dimble_file = Path("/tmp") / TEST_DICOM_FILE.with_suffix(".values.dimble").name
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
+    return dimble_file
+
+
+def _load(dimble_file: Path, values: str):
+    return dimble.load_dimble(
+        dimble_file, [PIXEL_ARRAY], backend="numpy", values=values
+    )[PIXEL_ARRAY]
+
+
+def test_stored_dtype_is_kept(dimble_file):
+    ds = pydicom.dcmread(TEST_DICOM_FILE)
+    stored = _load(dimble_file, "stored")
+    assert stored.dtype == ds.pixel_array.dtype
+    np.testing.assert_array_equal(stored, ds.pixel_array)
+
+
+def test_rescaled(dimble_file):
+    ds = pydicom.dcmread(TEST_DICOM_FILE)
+    rescaled = _load(dimble_file, "rescaled")
+    assert rescaled.dtype == np.float32
+    expected = apply_modality_lut(ds.pixel_array, ds).astype(np.float32)
+    np.testing.assert_allclose(rescaled, expected)
+
+
+def test_windowed(dimble_file):
+    ds = pydicom.dcmread(TEST_DICOM_FILE)
+    if "WindowCenter" not in ds:
+        with pytest.raises(dimble.MissingFieldError):
+            _load(dimble_file, "windowed")
+        return
+    windowed = _load(dimble_file, "windowed")
+    assert windowed.dtype == np.float32
+    assert windowed.min() >= 0 and windowed.max() <= 1
@@ -0,0 +1,57 @@
+from pathlib import Path
+
+import numpy as np
+import pytest
+
+import dimble
+
+PIXEL_ARRAY = "7FE00010"
+
+TESTFILES_DIR = Path(__file__).parent.parent / "pydicom-data" / "data"
+assert TESTFILES_DIR.exists()
+
+TEST_DICOM_FILE = TESTFILES_DIR / "693_J2KR.dcm"
+
+SLICES = [
+    [slice(10, 42), slice(20, 30)],
+    [slice(None, None, 3), slice(-20, None)],
+    [slice(None, None, -1), slice(50, 10, -7)],
+    [slice(5, 5)],
+]
+
+
+@pytest.fixture
+def dimble_file():
+    dimble_file = Path("/tmp") / TEST_DICOM_FILE.with_suffix(".slices.dimble").name
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
+    return dimble_file
+
+
+def _load(dimble_file: Path, slices, backend="numpy"):
+    return dimble.load_dimble(
+        dimble_file, [PIXEL_ARRAY], slices=slices, backend=backend
+    )[PIXEL_ARRAY]
+
+
+@pytest.mark.parametrize("slices", SLICES, ids=str)
+def test_slices_match_numpy(dimble_file, slices):
+    array = _load(dimble_file, None)
+    region = _load(dimble_file, slices)
+    np.testing.assert_array_equal(region, array[tuple(slices)])
+    assert region.flags.c_contiguous
+    np.testing.assert_array_equal(np.from_dlpack(_load(dimble_file, slices, "dlpack")), region)
+
+
+@pytest.mark.parametrize(
+    "slices",
+    [[slice(0, 100_000)], [slice(None), slice(-100_000, None)], [slice(None)] * 4],
+    ids=str,
+)
+def test_out_of_bounds_slices_raise(dimble_file, slices):
+    with pytest.raises(IndexError):
+        _load(dimble_file, slices)
+
+
+def test_zero_step_raises(dimble_file):
+    with pytest.raises(ValueError):
+        _load(dimble_file, [slice(None, None, 0)])
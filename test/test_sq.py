@@ -3,6 +3,7 @@ import json
 from pathlib import Path
 
 import pydicom
+import pytest
 
 import dimble
 from dimble.dimble import _dicom_to_ir
@@ -36,14 +37,34 @@ def test_sq_dimble():
     dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
     ds = dimble.load_dimble(dimble_file, [SQ_TAG])
     assert ds == {
-        "00089215": {
-            "00080102": "DCM",
-            "00080104": "Full fidelity image",
-            "00080100": "121327",
-        }
+        "00089215": [
+            {
+                "00080102": "DCM",
+                "00080104": "Full fidelity image",
+                "00080100": "121327",
+            }
+        ]
     }
 
 
+def test_sq_lazy_items():
+    dimble_file = "/tmp/693_J2KR.dimble"
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
+    ds = dimble.load_dimble(dimble_file, [SQ_TAG, SQ_TAG + "[0].00080100"])
+    sq = ds[SQ_TAG]
+    assert isinstance(sq, dimble.DimbleSequence)
+    assert len(sq) == 1
+    assert [item["00080102"] for item in sq] == ["DCM"]
+    assert ds[SQ_TAG + "[0].00080100"] == "121327"
+
+
+def test_sq_invalid_path():
+    dimble_file = "/tmp/693_J2KR.dimble"
+    dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)
+    with pytest.raises(dimble.InvalidPathError):
+        dimble.load_dimble(dimble_file, [SQ_TAG + "[1].00080100"])
+
+
 def test_sq_recon():
     dimble_file = "/tmp/693_J2KR.dimble"
     dimble.dicom_to_dimble(TEST_DICOM_FILE, dimble_file)